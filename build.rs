@@ -0,0 +1,19 @@
+// Compila las definiciones proto del servicio gRPC. Se usa protox para no
+// depender de tener protoc instalado
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto/simrng.proto");
+    // Sólo el servidor usa el servicio gRPC
+    if std::env::var_os("CARGO_FEATURE_SERVER").is_none() {
+        return Ok(());
+    }
+    let fds = protox::compile(["proto/simrng.proto"], ["proto"])?;
+    let out = std::path::PathBuf::from(std::env::var("OUT_DIR")?);
+    let fds_path = out.join("simrng.bin");
+    std::fs::write(&fds_path, prost::Message::encode_to_vec(&fds))?;
+    let mut config = prost_build::Config::new();
+    config.file_descriptor_set_path(&fds_path).skip_protoc_run();
+    tonic_build::configure()
+        .build_client(false)
+        .compile_with_config(config, &["proto/simrng.proto"], &["proto"])?;
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequestParts, State};
+use axum::http::request::Parts;
+use axum::http::{header, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::error::ApiError;
+
+/// Cliente que realiza la petición. Si no hay claves configuradas todas las
+/// peticiones son del cliente anónimo, y comparten los conjuntos de datos
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Client(pub String);
+
+impl Client {
+    /// Cliente usado cuando la autenticación está deshabilitada
+    pub fn anonymous() -> Self {
+        Client(String::new())
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Busca el cliente correspondiente a la clave recibida. Devuelve el cliente
+/// anónimo si no hay claves configuradas, y None si la clave no es válida.
+/// Se compara contra todas las claves, en tiempo constante, para no revelar
+/// por el tiempo de respuesta si una clave coincide en parte con otra
+///
+/// # Argumentos
+///
+/// * `config` configuración del servidor, con las claves de cada cliente
+/// * `key` clave recibida en la petición
+pub fn authenticate(config: &Config, key: Option<&str>) -> Option<Client> {
+    if config.api_keys.is_empty() {
+        return Some(Client::anonymous());
+    }
+    let key = key?;
+    config.api_keys.iter().fold(None, |found, (name, k)| {
+        if keys_match(k, key) { Some(Client(name.clone())) } else { found }
+    })
+}
+
+// Función privada, compara dos claves sin cortar en el primer byte
+// distinto, así el tiempo sólo depende de la longitud
+fn keys_match(expected: &str, key: &str) -> bool {
+    let (expected, key) = (expected.as_bytes(), key.as_bytes());
+    if expected.len() != key.len() {
+        return false;
+    }
+    let diff = expected.iter().zip(key).fold(0u8, |acc, (a, b)| acc | (a ^ b));
+    std::hint::black_box(diff) == 0
+}
+
+/// Extrae la clave de los headers X-Api-Key o Authorization: Bearer
+///
+/// # Argumentos
+///
+/// * `api_key` valor del header X-Api-Key
+/// * `authorization` valor del header Authorization
+pub fn key_from_headers<'a>(api_key: Option<&'a str>, authorization: Option<&'a str>) -> Option<&'a str> {
+    api_key.or_else(|| authorization.and_then(|v| v.strip_prefix("Bearer ")))
+}
+
+/// Middleware que exige una clave válida cuando hay claves configuradas, y
+/// guarda el cliente autenticado en las extensiones de la petición
+pub async fn require_api_key<B>(
+    State(config): State<Arc<Config>>,
+    mut req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let headers = req.headers();
+    let key = key_from_headers(
+        headers.get("x-api-key").and_then(|v| v.to_str().ok()),
+        headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()),
+    );
+    match authenticate(&config, key) {
+        Some(client) => {
+            req.extensions_mut().insert(client);
+            next.run(req).await
+        }
+        None => ApiError::Unauthorized("clave de API inválida o ausente".into()).into_response(),
+    }
+}
+
+/// Extractor del cliente autenticado por `require_api_key`. Si la ruta no
+/// pasó por el middleware sólo puede ser el cliente anónimo, así que con
+/// claves configuradas responde 401 en vez de compartir sus datos
+#[async_trait]
+impl<S> FromRequestParts<S> for Client
+where
+    S: Send + Sync,
+    Arc<Config>: FromRef<S>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(client) = parts.extensions.get::<Client>() {
+            return Ok(client.clone());
+        }
+        if Arc::<Config>::from_ref(state).api_keys.is_empty() {
+            Ok(Client::anonymous())
+        } else {
+            Err(ApiError::Unauthorized("clave de API inválida o ausente".into()))
+        }
+    }
+}
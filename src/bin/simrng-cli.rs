@@ -0,0 +1,331 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use simrng::dist::exponential::Exponential;
+use simrng::dist::normal::{Algorithm, Normal};
+use simrng::dist::poisson::Poisson;
+use simrng::dist::uniform::Uniform;
+use simrng::dist::Distribution;
+use simrng::rng::fill_chunked;
+use simrng::stats::descriptive::summary;
+use simrng::stats::{
+    ks, significance_for_alpha, statistics, Binning, FinalBin, HistogramData, Scale,
+    StatisticsInput, Trim,
+};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+/// Ancho máximo de las barras del histograma, en caracteres
+const BAR_WIDTH: u64 = 50;
+
+/// Generación de números aleatorios y pruebas de bondad de ajuste, sin
+/// necesidad de iniciar el servidor
+#[derive(Parser)]
+#[command(version, about = "Generación de números aleatorios desde la línea de comandos")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Genera valores y los escribe, uno por línea
+    Generate {
+        #[command(flatten)]
+        sample: SampleArgs,
+        /// Archivo CSV en el que escribir los valores, con encabezado. Si no
+        /// se indica se escriben en la salida estándar
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        #[command(subcommand)]
+        distribution: DistributionArgs,
+    },
+    /// Genera valores, muestra su histograma y aplica las pruebas de chi
+    /// cuadrado y de Kolmogorov-Smirnov contra la distribución
+    Test {
+        #[command(flatten)]
+        sample: SampleArgs,
+        /// Cantidad de intervalos del histograma y de la prueba de chi cuadrado
+        #[arg(short, long, default_value_t = 10)]
+        intervals: usize,
+        /// Nivel de significancia, uno de los tabulados
+        #[arg(short, long, default_value_t = 0.05)]
+        alpha: f64,
+        /// Usar como intervalo final la clase abierta [lower, ∞)
+        #[arg(long)]
+        open_final_bin: bool,
+        /// Calcular el histograma y la prueba de chi cuadrado sobre el
+        /// logaritmo de los valores
+        #[arg(long)]
+        log_scale: bool,
+        /// Excluir los valores fuera de [Q1 - k·IQR, Q3 + k·IQR] antes de
+        /// armar los intervalos
+        #[arg(long, value_name = "K")]
+        trim_iqr: Option<f64>,
+        /// Armar los intervalos con los cuantiles de la muestra, con
+        /// aproximadamente la misma cantidad de valores cada uno
+        #[arg(long)]
+        quantile_bins: bool,
+        /// Restar un grado de libertad por parámetro de la distribución, como
+        /// si se hubieran estimado a partir de la muestra
+        #[arg(long)]
+        parameters_estimated: bool,
+        /// Mostrar las distancias entre la distribución empírica y la teórica
+        #[arg(long)]
+        extended: bool,
+        #[command(subcommand)]
+        distribution: DistributionArgs,
+    },
+}
+
+/// Parámetros comunes de la generación
+#[derive(Args)]
+struct SampleArgs {
+    /// Semilla del generador de números aleatorios
+    #[arg(short, long, default_value_t = 0)]
+    seed: u64,
+    /// Cantidad de valores a generar
+    #[arg(short, long, default_value_t = 1000)]
+    number: u64,
+}
+
+/// Distribución a generar, con sus parámetros
+#[derive(Subcommand)]
+#[command(allow_negative_numbers = true)]
+enum DistributionArgs {
+    Uniform {
+        #[arg(long, allow_negative_numbers = true)]
+        lower: f64,
+        #[arg(long, allow_negative_numbers = true)]
+        upper: f64,
+    },
+    Normal {
+        #[arg(long, allow_negative_numbers = true)]
+        mean: f64,
+        #[arg(long)]
+        sd: f64,
+        #[arg(long, value_enum, default_value_t = AlgorithmArg::BoxMuller)]
+        algorithm: AlgorithmArg,
+    },
+    Exponential {
+        #[arg(long)]
+        lambda: f64,
+        #[arg(long, allow_negative_numbers = true, default_value_t = 0f64)]
+        shift: f64,
+    },
+    Poisson {
+        #[arg(long)]
+        lambda: f64,
+    },
+}
+
+/// Algoritmo de generación de la distribución Normal
+#[derive(ValueEnum, Clone, Copy)]
+enum AlgorithmArg {
+    BoxMuller,
+    Convolution,
+}
+
+/// Genera los valores de la distribución con la semilla indicada, con la
+/// misma división en bloques que el servidor. Devuelve los valores y la
+/// distribución, para las pruebas, o el error si los parámetros no son válidos
+///
+/// # Argumentos
+///
+/// * `sample` semilla y cantidad de valores
+/// * `distribution` distribución con sus parámetros
+fn generate(
+    sample: &SampleArgs,
+    distribution: &DistributionArgs,
+) -> Result<(Vec<f64>, Box<dyn Distribution>), String> {
+    let mut res = vec![0f64; sample.number as usize];
+    match *distribution {
+        DistributionArgs::Uniform { lower, upper } => {
+            let dist = Uniform::builder().lower(lower).upper(upper).build()?;
+            fill_chunked(sample.seed, &mut res, |rng, chunk| {
+                chunk.iter_mut().for_each(|x| *x = dist.next(rng));
+            });
+            Ok((res, Box::new(dist)))
+        }
+        DistributionArgs::Normal { mean, sd, algorithm } => {
+            let algorithm = match algorithm {
+                AlgorithmArg::BoxMuller => Algorithm::BoxMuller,
+                AlgorithmArg::Convolution => Algorithm::Convolution,
+            };
+            let dist = Normal::builder().algorithm(algorithm).mean(mean).sd(sd).build()?;
+            fill_chunked(sample.seed, &mut res, |rng, chunk| {
+                let mut sampler = dist.sampler();
+                chunk.iter_mut().for_each(|x| *x = sampler.next(rng));
+            });
+            Ok((res, Box::new(dist)))
+        }
+        DistributionArgs::Exponential { lambda, shift } => {
+            let dist = Exponential::builder().lambda(lambda).shift(shift).build()?;
+            fill_chunked(sample.seed, &mut res, |rng, chunk| {
+                chunk.iter_mut().for_each(|x| *x = dist.next(rng));
+            });
+            Ok((res, Box::new(dist)))
+        }
+        DistributionArgs::Poisson { lambda } => {
+            let dist = Poisson::builder().lambda(lambda).build()?;
+            fill_chunked(sample.seed, &mut res, |rng, chunk| {
+                chunk.iter_mut().for_each(|x| *x = dist.next(rng));
+            });
+            Ok((res, Box::new(dist)))
+        }
+    }
+}
+
+/// Escribe los valores, uno por línea
+///
+/// # Argumentos
+///
+/// * `out` destino de la escritura
+/// * `values` valores a escribir
+/// * `header` encabezado de la columna, para CSV
+fn write_values(out: &mut dyn Write, values: &[f64], header: Option<&str>) -> io::Result<()> {
+    if let Some(header) = header {
+        writeln!(out, "{}", header)?;
+    }
+    for v in values {
+        writeln!(out, "{}", v)?;
+    }
+    out.flush()
+}
+
+/// Imprime el histograma con barras de caracteres, una por intervalo
+///
+/// # Argumentos
+///
+/// * `histogram` frecuencias observadas por intervalo
+fn print_histogram(histogram: &HistogramData) {
+    let max = histogram.y.iter().copied().max().unwrap_or(0).max(1);
+    let last = histogram.y.len().saturating_sub(1);
+    // En escala logarítmica los límites se muestran en la escala de los valores
+    let value = |x: f64| if histogram.scale == Scale::Log { x.exp() } else { x };
+    for (i, (x, &count)) in histogram.x.iter().zip(&histogram.y).enumerate() {
+        // Con intervalos por cuantiles cada uno tiene su ancho
+        let size = histogram.widths.as_ref().map_or(histogram.size, |widths| widths[i]);
+        let lower = value(x - size / 2f64);
+        let upper = value(x + size / 2f64);
+        let bar = "#".repeat((count * BAR_WIDTH / max) as usize);
+        // Los intervalos son [lower, upper), salvo el final (ver FinalBin)
+        let upper = match histogram.final_bin {
+            FinalBin::Open if i == last => format!("{:>10})", "∞"),
+            FinalBin::Closed if i == last => format!("{:>10.4}]", upper),
+            _ => format!("{:>10.4})", upper),
+        };
+        println!("[{:>10.4}, {} {:<width$} {}", lower, upper, bar, count, width = BAR_WIDTH as usize);
+    }
+}
+
+/// Texto del resultado de una prueba
+fn verdict(reject: bool) -> &'static str {
+    if reject { "se rechaza" } else { "no se rechaza" }
+}
+
+/// Genera los valores, o termina con un error si los parámetros de la
+/// distribución no son válidos
+fn generate_or_exit(
+    sample: &SampleArgs,
+    distribution: &DistributionArgs,
+) -> (Vec<f64>, Box<dyn Distribution>) {
+    generate(sample, distribution).unwrap_or_else(|e| {
+        eprintln!("parámetros no válidos: {}", e);
+        std::process::exit(2);
+    })
+}
+
+fn main() {
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Generate { sample, output, distribution } => {
+            let (values, _) = generate_or_exit(&sample, &distribution);
+            let res = match output {
+                Some(path) => File::create(&path).and_then(|file| {
+                    write_values(&mut BufWriter::new(file), &values, Some("value"))
+                }),
+                None => write_values(&mut BufWriter::new(io::stdout().lock()), &values, None),
+            };
+            if let Err(e) = res {
+                eprintln!("error al escribir los valores: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Command::Test {
+            sample,
+            intervals,
+            alpha,
+            open_final_bin,
+            log_scale,
+            trim_iqr,
+            quantile_bins,
+            parameters_estimated,
+            extended,
+            distribution,
+        } => {
+            let Some(significance) = significance_for_alpha(alpha) else {
+                eprintln!("el nivel de significancia {} no está tabulado", alpha);
+                std::process::exit(2);
+            };
+            if intervals == 0 || sample.number == 0 {
+                eprintln!("se necesitan al menos un valor y un intervalo");
+                std::process::exit(2);
+            }
+            let (values, dist) = generate_or_exit(&sample, &distribution);
+            let s = summary(&values);
+            println!("n = {}, media = {:.4}, varianza = {:.4}, mín = {:.4}, máx = {:.4}", s.count, s.mean, s.variance, s.min, s.max);
+            let ks = ks::one_sample(&values, dist.as_ref(), alpha);
+            let final_bin = if open_final_bin { FinalBin::Open } else { FinalBin::Closed };
+            let input = StatisticsInput {
+                intervals,
+                significance: Some(significance),
+                final_bin,
+                from: None,
+                to: None,
+                scale: if log_scale { Scale::Log } else { Scale::Linear },
+                trim: trim_iqr.map(|k| Trim::Iqr { k }),
+                binning: if quantile_bins { Binning::Quantile } else { Binning::EqualWidth },
+                alphas: None,
+                parameters_estimated,
+                extended,
+            };
+            let check = input.check_trim(dist.as_ref());
+            if let Err(e) = check.and_then(|_| input.check_scale(&values, dist.as_ref())) {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+            let stats = statistics(input, &values, dist.as_ref());
+            println!();
+            print_histogram(&stats.histogram);
+            if let Some(trimmed) = &stats.trimmed {
+                println!(
+                    "Recorte: {} valores excluidos fuera de [{:.4}, {:.4}]",
+                    trimmed.excluded,
+                    trimmed.lower.unwrap_or(f64::NEG_INFINITY),
+                    trimmed.upper.unwrap_or(f64::INFINITY)
+                );
+            }
+            println!();
+            println!(
+                "Chi cuadrado: calculado = {:.4}, crítico = {:.4} (α = {}): {}",
+                stats.test.calculated,
+                stats.test.critical,
+                alpha,
+                verdict(stats.test.reject)
+            );
+            println!(
+                "Kolmogorov-Smirnov: D = {:.4}, crítico = {:.4} (α = {}): {}",
+                ks.statistic,
+                ks.critical,
+                alpha,
+                verdict(ks.reject)
+            );
+            if let Some(distances) = &stats.extended {
+                println!(
+                    "Distancias: variación total = {:.4}, W1 = {:.4}, máx |Fn - F| = {:.4}",
+                    distances.total_variation, distances.wasserstein, distances.max_cdf_gap
+                );
+            }
+        }
+    }
+}
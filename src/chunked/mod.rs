@@ -0,0 +1,553 @@
+use std::any::{Any, TypeId};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use memmap2::Mmap;
+
+use crate::float::Float;
+use crate::rng::{fill_blocks, fill_chunked, UniformGenerator, PARALLEL_CHUNK, PARALLEL_THRESHOLD};
+
+/// Cantidad de valores de cada bloque. Coincide con el umbral de la
+/// generación por subflujos, para que cada bloque se genere con subflujos
+/// enteros (ver `rng::fill_chunked`)
+pub const CHUNK_LEN: usize = PARALLEL_THRESHOLD;
+
+/// Muestra que se puede leer por bloques, sin necesidad de tener todos los
+/// valores contiguos en memoria. La implementan los slices, los vectores y
+/// ChunkedVec
+pub trait SampleChunks<T: Float>: Sync {
+    /// Cantidad de valores
+    fn len(&self) -> usize;
+
+    /// Cantidad de bloques
+    fn chunk_count(&self) -> usize;
+
+    /// Valores del bloque i, todos de CHUNK_LEN valores salvo el último
+    fn chunk(&self, i: usize) -> &[T];
+
+    /// Si la muestra no tiene valores
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Copia los valores entre las posiciones `start` y `end`, limitadas a
+    /// la cantidad de valores
+    ///
+    /// # Argumentos
+    ///
+    /// * `start` posición del primer valor
+    /// * `end` posición siguiente al último valor
+    fn range(&self, start: usize, end: usize) -> Vec<T> {
+        let end = end.min(self.len());
+        let mut out = Vec::with_capacity(end.saturating_sub(start));
+        slices(self, start, end).for_each(|slice| out.extend_from_slice(slice));
+        out
+    }
+}
+
+impl<T: Float> SampleChunks<T> for [T] {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn chunk_count(&self) -> usize {
+        <[T]>::len(self).div_ceil(CHUNK_LEN)
+    }
+
+    fn chunk(&self, i: usize) -> &[T] {
+        let start = i * CHUNK_LEN;
+        &self[start..(start + CHUNK_LEN).min(<[T]>::len(self))]
+    }
+}
+
+impl<T: Float> SampleChunks<T> for Vec<T> {
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn chunk_count(&self) -> usize {
+        self.as_slice().chunk_count()
+    }
+
+    fn chunk(&self, i: usize) -> &[T] {
+        self.as_slice().chunk(i)
+    }
+}
+
+impl<T: Float, const N: usize> SampleChunks<T> for [T; N] {
+    fn len(&self) -> usize {
+        N
+    }
+
+    fn chunk_count(&self) -> usize {
+        self.as_slice().chunk_count()
+    }
+
+    fn chunk(&self, i: usize) -> &[T] {
+        self.as_slice().chunk(i)
+    }
+}
+
+/// Recorre los valores de la muestra en orden, bloque por bloque
+///
+/// # Argumentos
+///
+/// * `nums` muestra a recorrer
+pub fn values<T: Float, S: SampleChunks<T> + ?Sized>(nums: &S) -> impl Iterator<Item = T> + '_ {
+    (0..nums.chunk_count()).flat_map(move |i| nums.chunk(i).iter().copied())
+}
+
+/// Recorre los valores entre las posiciones `start` y `end`, limitadas a la
+/// cantidad de valores, como partes de los bloques, sin copiarlos. Un rango
+/// dentro de un bloque se devuelve en una sola parte
+///
+/// # Argumentos
+///
+/// * `nums` muestra a recorrer
+/// * `start` posición del primer valor
+/// * `end` posición siguiente al último valor
+pub fn slices<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    start: usize,
+    end: usize,
+) -> impl Iterator<Item = &[T]> + '_ {
+    let end = end.min(nums.len());
+    let start = start.min(end);
+    // Un rango vacío no recorre ningún bloque
+    let last = if start < end { end.div_ceil(CHUNK_LEN) } else { 0 };
+    (start / CHUNK_LEN..last).map(move |i| {
+        let base = i * CHUNK_LEN;
+        let chunk = nums.chunk(i);
+        &chunk[start.max(base) - base..end.min(base + chunk.len()) - base]
+    })
+}
+
+/// Dónde y a partir de cuántos bloques se escriben los bloques en disco
+#[derive(Clone, Debug)]
+pub struct SpillConfig {
+    /// Directorio de los archivos temporales, uno por bloque
+    pub dir: PathBuf,
+    /// Cantidad de bloques de cada muestra que se mantienen en memoria; los
+    /// siguientes se escriben en disco y se leen mapeados en memoria
+    pub memory_chunks: usize,
+}
+
+/// Vector de valores guardado por bloques de CHUNK_LEN valores. Con un
+/// SpillConfig, los bloques que superan el límite en memoria se escriben en
+/// archivos temporales y se mapean, de modo que el sistema operativo los
+/// carga y descarga según se leen. Los archivos se borran con el vector
+pub struct ChunkedVec<T: Float> {
+    chunks: Vec<Chunk<T>>,
+    len: usize,
+    spill: Option<SpillConfig>,
+}
+
+enum Chunk<T> {
+    Memory(Vec<T>),
+    Mapped(MappedChunk),
+}
+
+/// Bloque escrito en disco, con su archivo, que se borra al descartarlo
+struct MappedChunk {
+    map: Mmap,
+    path: PathBuf,
+    len: usize,
+}
+
+impl Drop for MappedChunk {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Contador para los nombres de los archivos temporales
+static SPILL_FILES: AtomicU64 = AtomicU64::new(0);
+
+/// Cantidad de bloques libres de cada tipo que se guardan por defecto para
+/// reusarlos (ver `set_pool_limit`)
+pub const DEFAULT_POOL_CHUNKS: usize = 16;
+
+/// Cantidad máxima de bloques libres de cada tipo guardados para reusar
+static POOL_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_POOL_CHUNKS);
+
+/// Bloques de CHUNK_LEN valores de los vectores descartados, que las
+/// siguientes generaciones reusan en lugar de pedir memoria nueva, que
+/// además el sistema operativo tendría que asignar página por página
+struct ChunkPool<T> {
+    free: Mutex<Vec<Vec<T>>>,
+}
+
+static F64_POOL: ChunkPool<f64> = ChunkPool::new();
+static F32_POOL: ChunkPool<f32> = ChunkPool::new();
+
+impl<T> ChunkPool<T> {
+    const fn new() -> Self {
+        ChunkPool { free: Mutex::new(Vec::new()) }
+    }
+
+    // Vector vacío con lugar para `capacity` valores: un bloque libre si se
+    // pide un bloque entero y hay alguno, o uno nuevo
+    fn take(&self, capacity: usize) -> Vec<T> {
+        if capacity == CHUNK_LEN {
+            if let Some(mut values) = self.free.lock().unwrap().pop() {
+                values.clear();
+                return values;
+            }
+        }
+        Vec::with_capacity(capacity)
+    }
+
+    // Guarda el vector para reusarlo si tiene lugar para un bloque entero
+    // y no se superó el límite; si no, lo libera
+    fn give(&self, values: Vec<T>) {
+        if values.capacity() != CHUNK_LEN {
+            return;
+        }
+        let mut free = self.free.lock().unwrap();
+        if free.len() < POOL_LIMIT.load(Ordering::Relaxed) {
+            free.push(values);
+        }
+    }
+
+    fn bytes(&self) -> usize {
+        self.free.lock().unwrap().len() * CHUNK_LEN * std::mem::size_of::<T>()
+    }
+
+    fn truncate(&self, chunks: usize) {
+        self.free.lock().unwrap().truncate(chunks);
+    }
+}
+
+// Función privada, bloques libres del tipo de los valores
+fn pool<T: Float>() -> &'static ChunkPool<T> {
+    let pool: &'static dyn Any = if TypeId::of::<T>() == TypeId::of::<f64>() {
+        &F64_POOL
+    } else {
+        &F32_POOL
+    };
+    pool.downcast_ref().expect("Float sólo se implementa para f32 y f64")
+}
+
+/// Fija la cantidad máxima de bloques libres de cada tipo que se guardan
+/// para reusarlos en las siguientes generaciones, y libera los que sobran.
+/// Con 0 no se reusan
+///
+/// # Argumentos
+///
+/// * `chunks` cantidad máxima de bloques de f64, y también de f32
+pub fn set_pool_limit(chunks: usize) {
+    POOL_LIMIT.store(chunks, Ordering::Relaxed);
+    F64_POOL.truncate(chunks);
+    F32_POOL.truncate(chunks);
+}
+
+/// Bytes de los bloques libres guardados para reusar, que no pertenecen a
+/// ningún vector
+pub fn pooled_bytes() -> usize {
+    F64_POOL.bytes() + F32_POOL.bytes()
+}
+
+impl<T: Float> Default for ChunkedVec<T> {
+    fn default() -> Self {
+        ChunkedVec { chunks: Vec::new(), len: 0, spill: None }
+    }
+}
+
+impl<T: Float> Drop for ChunkedVec<T> {
+    /// Los bloques en memoria vuelven a los bloques libres, para reusarlos
+    fn drop(&mut self) {
+        let pool = pool::<T>();
+        for chunk in self.chunks.drain(..) {
+            if let Chunk::Memory(values) = chunk {
+                pool.give(values);
+            }
+        }
+    }
+}
+
+impl<T: Float> From<Vec<T>> for ChunkedVec<T> {
+    fn from(values: Vec<T>) -> Self {
+        let mut vec = ChunkedVec::default();
+        if values.len() <= CHUNK_LEN {
+            vec.len = values.len();
+            if !values.is_empty() {
+                vec.chunks.push(Chunk::Memory(values));
+            }
+        } else {
+            for chunk in values.chunks(CHUNK_LEN) {
+                vec.len += chunk.len();
+                vec.chunks.push(Chunk::Memory(chunk.to_vec()));
+            }
+        }
+        vec
+    }
+}
+
+impl<T: Float> ChunkedVec<T> {
+    /// Crea un vector vacío, que escribe en disco los bloques según `spill`,
+    /// o los mantiene todos en memoria si es None
+    ///
+    /// # Argumentos
+    ///
+    /// * `spill` directorio y límite de bloques en memoria
+    pub fn new(spill: Option<SpillConfig>) -> Self {
+        ChunkedVec { chunks: Vec::new(), len: 0, spill }
+    }
+
+    /// Agrega un bloque al final. Todos los bloques salvo el último deben
+    /// tener CHUNK_LEN valores
+    ///
+    /// # Argumentos
+    ///
+    /// * `values` valores del bloque
+    pub fn push_chunk(&mut self, values: Vec<T>) -> io::Result<()> {
+        assert!(values.len() <= CHUNK_LEN, "el bloque supera CHUNK_LEN valores");
+        assert!(
+            self.len.is_multiple_of(CHUNK_LEN),
+            "sólo el último bloque puede tener menos de CHUNK_LEN valores"
+        );
+        if values.is_empty() {
+            return Ok(());
+        }
+        self.len += values.len();
+        let chunk = match &self.spill {
+            Some(spill) if self.chunks.len() >= spill.memory_chunks => {
+                let mapped = spill_chunk(spill, &values)?;
+                pool::<T>().give(values);
+                Chunk::Mapped(mapped)
+            }
+            _ => Chunk::Memory(values),
+        };
+        self.chunks.push(chunk);
+        Ok(())
+    }
+
+    /// Cantidad de bloques escritos en disco
+    pub fn spilled_chunks(&self) -> usize {
+        self.chunks.iter().filter(|c| matches!(c, Chunk::Mapped(_))).count()
+    }
+
+    /// Bytes que ocupan los bloques en memoria. Los bloques escritos en disco
+    /// no se cuentan: el sistema operativo los descarga cuando hace falta
+    pub fn memory_bytes(&self) -> usize {
+        self.chunks
+            .iter()
+            .map(|c| match c {
+                Chunk::Memory(values) => std::mem::size_of_val(values.as_slice()),
+                Chunk::Mapped(_) => 0,
+            })
+            .sum()
+    }
+
+    /// Devuelve todos los valores en un único vector en memoria
+    pub fn into_vec(mut self) -> Vec<T> {
+        if let [Chunk::Memory(_)] = self.chunks.as_slice() {
+            if let Some(Chunk::Memory(values)) = self.chunks.pop() {
+                return values;
+            }
+        }
+        self.range(0, self.len)
+    }
+
+    /// Genera `number` valores en bloques, sin tener más de un bloque de
+    /// valores f64 en memoria a la vez. El resultado es el mismo que el de
+    /// `rng::fill_chunked` con la misma semilla: cada bloque se completa con
+    /// sus subflujos, en paralelo con el feature `parallel`. Con `skip` se
+    /// generan skip + number valores y se descartan los primeros skip. Los
+    /// bloques, y el de f64 en el que se generan, se toman de los bloques
+    /// libres de los vectores descartados si los hay (ver `set_pool_limit`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `seed` semilla del generador
+    /// * `skip` cantidad de valores iniciales a descartar
+    /// * `number` cantidad de valores a guardar
+    /// * `spill` directorio y límite de bloques en memoria
+    /// * `fill` completa un bloque de subflujo con el generador indicado
+    /// * `transform` transforma cada bloque generado, en orden, antes de guardarlo
+    pub fn generate<F, G>(
+        seed: u64,
+        skip: usize,
+        number: usize,
+        spill: Option<SpillConfig>,
+        fill: F,
+        mut transform: G,
+    ) -> io::Result<Self>
+    where
+        F: Fn(&mut UniformGenerator, &mut [f64]) + Sync,
+        G: FnMut(&mut [f64]),
+    {
+        let mut out = ChunkedWriter::new(number, spill);
+        generate_blocks(seed, skip + number, fill, |start, values| {
+            transform(values);
+            out.write(&values[skip.saturating_sub(start).min(values.len())..])?;
+            Ok(true)
+        })?;
+        out.finish()
+    }
+}
+
+/// Escribe un ChunkedVec de a tramos de valores f64 de cualquier largo, que
+/// se convierten y se juntan en bloques de CHUNK_LEN valores antes de
+/// guardarlos
+pub struct ChunkedWriter<T: Float> {
+    out: ChunkedVec<T>,
+    /// Valores que todavía no completan un bloque
+    pending: Vec<T>,
+    /// Cantidad de valores que se espera escribir, para pedir los bloques
+    /// con el lugar justo
+    number: usize,
+}
+
+impl<T: Float> ChunkedWriter<T> {
+    /// Crea un escritor de un vector vacío
+    ///
+    /// # Argumentos
+    ///
+    /// * `number` cantidad de valores que se espera escribir
+    /// * `spill` directorio y límite de bloques en memoria
+    pub fn new(number: usize, spill: Option<SpillConfig>) -> Self {
+        let pending = pool::<T>().take(number.min(CHUNK_LEN));
+        ChunkedWriter { out: ChunkedVec::new(spill), pending, number }
+    }
+
+    /// Cantidad de valores escritos
+    pub fn written(&self) -> usize {
+        self.out.len + self.pending.len()
+    }
+
+    /// Agrega los valores al final, guardando cada bloque que se completa
+    ///
+    /// # Argumentos
+    ///
+    /// * `values` valores a agregar
+    pub fn write(&mut self, mut values: &[f64]) -> io::Result<()> {
+        let pool = pool::<T>();
+        while !values.is_empty() {
+            let count = (CHUNK_LEN - self.pending.len()).min(values.len());
+            extend_converted(&mut self.pending, &values[..count]);
+            values = &values[count..];
+            if self.pending.len() == CHUNK_LEN {
+                let rest = self.number.saturating_sub(self.out.len + CHUNK_LEN);
+                let next = pool.take(rest.min(CHUNK_LEN));
+                self.out.push_chunk(std::mem::replace(&mut self.pending, next))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Guarda el último bloque, incompleto, y devuelve el vector
+    pub fn finish(self) -> io::Result<ChunkedVec<T>> {
+        let ChunkedWriter { mut out, pending, .. } = self;
+        out.push_chunk(pending)?;
+        Ok(out)
+    }
+}
+
+/// Genera `total` valores por bloques de hasta CHUNK_LEN, los mismos que
+/// `rng::fill_chunked` con la misma semilla, y pasa cada bloque en orden a
+/// `each`, con la posición de su primer valor, sin tener más de un bloque
+/// en memoria a la vez. Se detiene antes si `each` devuelve false
+///
+/// # Argumentos
+///
+/// * `seed` semilla del generador
+/// * `total` cantidad de valores a generar
+/// * `fill` completa un bloque de subflujo con el generador indicado
+/// * `each` recibe cada bloque generado y devuelve si se sigue generando
+pub fn generate_blocks<F, G>(seed: u64, total: usize, fill: F, mut each: G) -> io::Result<()>
+where
+    F: Fn(&mut UniformGenerator, &mut [f64]) + Sync,
+    G: FnMut(usize, &mut [f64]) -> io::Result<bool>,
+{
+    if total <= PARALLEL_THRESHOLD {
+        let mut values = vec![0f64; total];
+        fill_chunked(seed, &mut values, fill);
+        return each(0, &mut values).map(|_| ());
+    }
+    let streams = UniformGenerator::substreams(seed, total.div_ceil(PARALLEL_CHUNK));
+    let per_chunk = CHUNK_LEN / PARALLEL_CHUNK;
+    let mut values = F64_POOL.take(CHUNK_LEN);
+    values.resize(CHUNK_LEN, 0f64);
+    let mut res = Ok(());
+    for (i, start) in (0..total).step_by(CHUNK_LEN).enumerate() {
+        let len = (total - start).min(CHUNK_LEN);
+        values.truncate(len);
+        fill_blocks(&streams[i * per_chunk..], &mut values, &fill);
+        match each(start, &mut values) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => {
+                res = Err(e);
+                break;
+            }
+        }
+    }
+    F64_POOL.give(values);
+    res
+}
+
+impl<T: Float> SampleChunks<T> for ChunkedVec<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    fn chunk(&self, i: usize) -> &[T] {
+        match &self.chunks[i] {
+            Chunk::Memory(values) => values,
+            // SAFETY: el mapa contiene los bytes de `len` valores de T
+            // escritos por spill_chunk, y está alineado a página. Float sólo
+            // se implementa para f32 y f64, sin relleno ni valores inválidos
+            Chunk::Mapped(mapped) => unsafe {
+                std::slice::from_raw_parts(mapped.map.as_ptr() as *const T, mapped.len)
+            },
+        }
+    }
+}
+
+// Función privada, agrega los valores al final del vector, convertidos al
+// tipo de almacenamiento. Se escriben directamente en la capacidad libre y
+// después se fija el largo, sin verificar la capacidad por cada valor como
+// push, que en las generaciones de cientos de millones de valores pesa
+fn extend_converted<T: Float>(out: &mut Vec<T>, values: &[f64]) {
+    out.reserve(values.len());
+    let spare = &mut out.spare_capacity_mut()[..values.len()];
+    for (slot, &x) in spare.iter_mut().zip(values) {
+        slot.write(T::from_f64(x));
+    }
+    // SAFETY: reserve dejó lugar para values.len() valores más después de
+    // los actuales, y el ciclo los inicializó todos, ya que spare tiene el
+    // mismo largo que values
+    unsafe { out.set_len(out.len() + values.len()) };
+}
+
+// Función privada, escribe el bloque en un archivo temporal y lo mapea
+fn spill_chunk<T: Float>(spill: &SpillConfig, values: &[T]) -> io::Result<MappedChunk> {
+    let id = SPILL_FILES.fetch_add(1, Ordering::Relaxed);
+    let path = spill.dir.join(format!("simrng-{}-{}.chunk", std::process::id(), id));
+    // SAFETY: los valores de f32 y f64 no tienen relleno, se pueden ver como bytes
+    let bytes = unsafe {
+        std::slice::from_raw_parts(values.as_ptr() as *const u8, std::mem::size_of_val(values))
+    };
+    // Se abre también para lectura, que necesita el mapa
+    let mut file = OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+    let mapped = file
+        .write_all(bytes)
+        // SAFETY: el archivo es propio del proceso y no se modifica mientras
+        // está mapeado
+        .and_then(|_| unsafe { Mmap::map(&file) });
+    match mapped {
+        Ok(map) => Ok(MappedChunk { map, path, len: values.len() }),
+        Err(e) => {
+            let _ = fs::remove_file(&path);
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use simrng::chunked::SpillConfig;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+use crate::store::StoreKind;
+
+/// Argumentos de línea de comandos del servidor
+///
+/// Todos son opcionales y, si se indican, tienen prioridad sobre los valores
+/// del archivo de configuración
+#[derive(Parser)]
+#[command(version, about = "Servidor de generación de números aleatorios")]
+pub struct Cli {
+    /// Archivo de configuración TOML
+    #[arg(short, long, env = "SIMRNG_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Dirección en la que escucha el servidor
+    #[arg(long)]
+    pub bind: Option<IpAddr>,
+    /// Puerto en el que escucha el servidor
+    #[arg(short, long, env = "SIMRNG_PORT")]
+    pub port: Option<u16>,
+    /// Orígenes permitidos por CORS, "*" para permitir cualquiera
+    #[arg(long = "allow-origin")]
+    pub allowed_origins: Vec<String>,
+    /// Cantidad máxima de valores a generar por petición
+    #[arg(long)]
+    pub max_numbers: Option<u64>,
+    /// Nivel de significancia por defecto para la prueba de chi cuadrado
+    #[arg(long)]
+    pub default_alpha: Option<f64>,
+    /// Archivo donde persistir el último experimento generado
+    #[arg(long)]
+    pub data_file: Option<PathBuf>,
+    /// Dónde persistir los experimentos: memory, file o sqlite
+    #[arg(long, value_enum)]
+    pub store: Option<StoreKind>,
+    /// Base de datos SQLite del historial de experimentos
+    #[arg(long)]
+    pub history_db: Option<PathBuf>,
+    /// Puerto del servicio gRPC
+    #[arg(long)]
+    pub grpc_port: Option<u16>,
+    /// Certificado TLS en formato PEM, para servir HTTPS
+    #[arg(long)]
+    pub tls_cert: Option<PathBuf>,
+    /// Clave privada TLS en formato PEM
+    #[arg(long)]
+    pub tls_key: Option<PathBuf>,
+    /// Directorio donde escribir los bloques de los conjuntos de datos grandes
+    #[arg(long)]
+    pub spill_dir: Option<PathBuf>,
+    /// Bytes máximos que pueden ocupar en memoria los conjuntos de datos
+    #[arg(long)]
+    pub memory_budget: Option<u64>,
+    /// Bloques libres de cada precisión que se guardan para reusar
+    #[arg(long)]
+    pub pool_chunks: Option<usize>,
+}
+
+/// Qué hacer cuando un conjunto de datos nuevo no entra en el presupuesto
+/// de memoria
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryPolicy {
+    /// Descartar los conjuntos de datos usados hace más tiempo, de cualquier
+    /// cliente, hasta que haya lugar
+    #[default]
+    Evict,
+    /// Rechazar la generación con 507 Insufficient Storage
+    Reject,
+}
+
+/// Configuración del servidor, leída del archivo TOML y de la línea de comandos
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Dirección en la que escucha el servidor
+    pub bind: IpAddr,
+    /// Puerto en el que escucha el servidor
+    pub port: u16,
+    /// Orígenes permitidos por CORS, "*" para permitir cualquiera
+    pub allowed_origins: Vec<String>,
+    /// Cantidad máxima de valores a generar por petición
+    pub max_numbers: u64,
+    /// Nivel de significancia por defecto para la prueba de chi cuadrado,
+    /// debe ser uno de los tabulados
+    pub default_alpha: f64,
+    /// Archivo donde persistir el último experimento generado, para
+    /// recuperarlo al reiniciar. Si no se indica no se persiste
+    pub data_file: Option<PathBuf>,
+    /// Almacenamiento de los experimentos, con `data_file` como archivo o
+    /// base de datos. Si no se indica, `file` con `data_file` y `memory` sin él
+    pub store: Option<StoreKind>,
+    /// Base de datos SQLite del historial de experimentos. Si no se indica
+    /// el historial se mantiene en memoria
+    pub history_db: Option<PathBuf>,
+    /// Puerto del servicio gRPC, en la misma dirección que el HTTP. Si no
+    /// se indica el servicio no se inicia
+    pub grpc_port: Option<u16>,
+    /// Claves de API de cada cliente, por nombre de cliente. Si no hay
+    /// ninguna, la autenticación está deshabilitada
+    pub api_keys: HashMap<String, String>,
+    /// Certificado TLS en formato PEM. Si se indica junto con `tls_key` el
+    /// servidor usa HTTPS
+    pub tls_cert: Option<PathBuf>,
+    /// Clave privada TLS en formato PEM
+    pub tls_key: Option<PathBuf>,
+    /// Directorio donde escribir los bloques de los conjuntos de datos que
+    /// superan `memory_chunks` bloques, que se leen mapeados en memoria. Si
+    /// no se indica, todos los valores se mantienen en memoria
+    pub spill_dir: Option<PathBuf>,
+    /// Cantidad de bloques de cada conjunto de datos que se mantienen en
+    /// memoria antes de escribirlos en `spill_dir`
+    pub memory_chunks: usize,
+    /// Bytes máximos que pueden ocupar en memoria los conjuntos de datos de
+    /// todos los clientes, sin contar los bloques en disco. Si no se indica
+    /// no hay límite
+    pub memory_budget: Option<u64>,
+    /// Qué hacer cuando una generación superaría `memory_budget`
+    pub memory_policy: MemoryPolicy,
+    /// Cantidad de bloques libres de cada precisión, de los conjuntos de
+    /// datos descartados, que se guardan para reusar en las siguientes
+    /// generaciones (ver `chunked::set_pool_limit`). 0 para no reusarlos
+    pub pool_chunks: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bind: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: 3000,
+            allowed_origins: vec!["*".into()],
+            max_numbers: 1_000_000_000,
+            default_alpha: 0.05,
+            data_file: None,
+            store: None,
+            history_db: None,
+            grpc_port: None,
+            api_keys: HashMap::new(),
+            tls_cert: None,
+            tls_key: None,
+            spill_dir: None,
+            memory_chunks: 64,
+            memory_budget: None,
+            memory_policy: MemoryPolicy::default(),
+            pool_chunks: simrng::chunked::DEFAULT_POOL_CHUNKS,
+        }
+    }
+}
+
+impl Config {
+    /// Construye la configuración a partir de los argumentos, leyendo el
+    /// archivo TOML indicado si lo hubiera
+    ///
+    /// # Argumentos
+    ///
+    /// * `cli` argumentos de línea de comandos ya interpretados
+    pub fn load(cli: Cli) -> Result<Self, String> {
+        let mut config = match &cli.config {
+            Some(path) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| format!("{}: {}", path.display(), e))?;
+                toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?
+            }
+            None => Config::default(),
+        };
+        if let Some(bind) = cli.bind {
+            config.bind = bind;
+        }
+        if let Some(port) = cli.port {
+            config.port = port;
+        }
+        if !cli.allowed_origins.is_empty() {
+            config.allowed_origins = cli.allowed_origins;
+        }
+        if let Some(max_numbers) = cli.max_numbers {
+            config.max_numbers = max_numbers;
+        }
+        if let Some(alpha) = cli.default_alpha {
+            config.default_alpha = alpha;
+        }
+        if cli.data_file.is_some() {
+            config.data_file = cli.data_file;
+        }
+        if cli.store.is_some() {
+            config.store = cli.store;
+        }
+        if cli.history_db.is_some() {
+            config.history_db = cli.history_db;
+        }
+        if cli.grpc_port.is_some() {
+            config.grpc_port = cli.grpc_port;
+        }
+        if cli.tls_cert.is_some() {
+            config.tls_cert = cli.tls_cert;
+        }
+        if cli.tls_key.is_some() {
+            config.tls_key = cli.tls_key;
+        }
+        if cli.spill_dir.is_some() {
+            config.spill_dir = cli.spill_dir;
+        }
+        if cli.memory_budget.is_some() {
+            config.memory_budget = cli.memory_budget;
+        }
+        if let Some(pool_chunks) = cli.pool_chunks {
+            config.pool_chunks = pool_chunks;
+        }
+        if config.store() != StoreKind::Memory && config.data_file.is_none() {
+            return Err("store file y sqlite necesitan data_file".into());
+        }
+        // El archivo guarda un único experimento, el del cliente anónimo: con
+        // claves los datos de los clientes se perderían sin avisar
+        if config.store() == StoreKind::File && !config.api_keys.is_empty() {
+            return Err("store file sólo persiste al cliente anónimo: con api_keys use sqlite".into());
+        }
+        if config.tls_cert.is_some() != config.tls_key.is_some() {
+            return Err("tls_cert y tls_key deben indicarse juntos".into());
+        }
+        if simrng::stats::significance_for_alpha(config.default_alpha).is_none() {
+            return Err(format!(
+                "default_alpha {} no es un nivel tabulado ({:?})",
+                config.default_alpha,
+                simrng::stats::ALPHAS
+            ));
+        }
+        Ok(config)
+    }
+
+    /// Almacenamiento de los experimentos: el indicado, o `file` si sólo se
+    /// indica `data_file`, como antes de poder elegirlo
+    pub fn store(&self) -> StoreKind {
+        match (self.store, &self.data_file) {
+            (Some(store), _) => store,
+            (None, Some(_)) => StoreKind::File,
+            (None, None) => StoreKind::Memory,
+        }
+    }
+
+    /// Dirección completa (IP y puerto) en la que escucha el servidor
+    pub fn addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind, self.port)
+    }
+
+    /// Rutas del certificado y la clave TLS, si HTTPS está habilitado
+    pub fn tls(&self) -> Option<(&Path, &Path)> {
+        Some((self.tls_cert.as_deref()?, self.tls_key.as_deref()?))
+    }
+
+    /// Directorio y límite de bloques en memoria de los conjuntos de datos,
+    /// si está configurado el directorio
+    pub fn spill(&self) -> Option<SpillConfig> {
+        Some(SpillConfig { dir: self.spill_dir.clone()?, memory_chunks: self.memory_chunks })
+    }
+
+    /// Índice de la tabla de chi cuadrado correspondiente a `default_alpha`
+    pub fn default_significance(&self) -> usize {
+        // Validado en load()
+        simrng::stats::significance_for_alpha(self.default_alpha)
+            .unwrap_or(simrng::stats::DEFAULT_SIGNIFICANCE)
+    }
+}
@@ -1,26 +1,98 @@
-use axum::extract::{State, Query};
-use axum::{extract, Json};
-use rand::SeedableRng;
-use rand::rngs::SmallRng;
-use serde::Deserialize;
+use axum::extract::{self, BodyStream, FromRef, Path, State, Query};
+use axum::http::{header, StatusCode};
+use axum::body::StreamBody;
+use axum::response::{IntoResponse, Response};
+use futures_util::StreamExt;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use simrng::copula::{Copula, KendallTau};
 use simrng::dist::exponential::Exponential;
-use simrng::dist::normal::Normal;
+use simrng::dist::gamma::Gamma;
+use simrng::dist::normal::{Algorithm, Normal};
 use simrng::dist::poisson::Poisson;
 use simrng::dist::uniform::Uniform;
 use simrng::dist::Distribution;
-use simrng::list::get_page;
-use simrng::rng::UniformGenerator;
-use simrng::stats::{full_statistics, StatisticsInput, StatisticsResponse};
+use simrng::list;
+use simrng::montecarlo::{self, expr::Expr, ControlVariateEstimate, Estimate};
+use simrng::chunked::{self, ChunkedVec, ChunkedWriter, SpillConfig, CHUNK_LEN};
+use simrng::float::Float;
+use simrng::rng::{
+    fill_chunked, fill_chunked_recorded, LatticePlanes, LinearCongruentialGenerator, Random,
+    Recorder, SeedWarning, SequenceGenerator, UniformGenerator, LCG_A, LCG_C, LCG_M,
+};
+use simrng::sampling::{self, Stratum, VarianceComparison};
+use simrng::sim::inventory::{self, InventoryParameters, InventoryReport};
+use simrng::sim::ConfidenceInterval;
+use simrng::sim::markov::{self, MarkovParameters, MarkovReport};
+use simrng::sim::queue::{self, QueueParameters, QueueReport};
+use simrng::stats::descriptive::{
+    check_weights, effective_size, summary, RollingPoint, RunningMean, Summary,
+};
+use simrng::stats::battery::{Battery, BatteryOptions};
+use simrng::stats::correlation::Correlation;
+use simrng::stats::discrete::DiscreteFrequencies;
+use simrng::stats::ks::{self, OneSampleTest, TwoSampleTest};
+use simrng::stats::normality::{jarque_bera, JarqueBera};
+use simrng::stats::suggest::{suggest, Candidate, Shape};
+use simrng::stats::streaming::{
+    CsvValues, StreamingAccumulator, StreamingBins, StreamingStatistics, PARSE_BATCH,
+};
+use simrng::transform::{Operation, TransformedDistribution};
+use simrng::stats::{
+    full_statistics, significance_for_alpha, statistics, Binning, ChiSquaredTrace, FinalBin,
+    HistogramData, IntervalSensitivity, Scale, StatisticsInput, StatisticsResponse,
+};
+use std::io;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::RwLock;
+use tracing::Instrument;
+
+use crate::auth::Client;
+use crate::config::{Config, MemoryPolicy};
+use crate::datasets::{
+    ClientDatasets, DatasetInfo, Datasets, Page, Precision, Reservation, Samples, Workspaces,
+};
+use crate::error::ApiError;
+use crate::history::{History, HistoryEntry, Metadata};
+use crate::locale::{Language, Localized};
+use crate::negotiate::{Format, Negotiated};
+use crate::plot;
+use crate::store::Persistence;
+use crate::report;
+use crate::storage::{
+    self, Bounds, Experiment, GenerationMode, Generator, Reproducibility, Snapshot,
+    StoredDistribution,
+};
+use crate::trace::{ActiveSpan, Progress, SpanLog, SpanRecord};
+use crate::version::ApiVersion;
+
+/// Estado compartido por todos los handlers
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    /// Conjuntos de datos generados por cada cliente
+    pub datasets: Arc<Workspaces>,
+    /// Configuración del servidor
+    pub config: Arc<Config>,
+    /// Historial de experimentos generados
+    pub history: Arc<History>,
+    /// Dónde se persiste el último experimento de cada cliente
+    pub store: Arc<Persistence>,
+    /// Últimos spans de generación y estadísticas, para `/last-run`
+    pub spans: Arc<SpanLog>,
+}
 
 /// Tipo de distribución: parámetro para la generación de números
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Copy)]
 pub enum DistributionType {
     Normal,
     Uniform,
     Exponential,
     Poisson,
+    /// Gamma o Erlang, por su forma y escala, su media y varianza, o k y lambda
+    Gamma,
+    /// Distribución definida por una tabla de su función acumulada
+    Tabulated,
 }
 
 /// Parámetros para la generación de valores
@@ -34,26 +106,982 @@ pub struct GenerationParameters {
     pub distribution: DistributionType,
     /// Parámetros para la distribución, de tipo Distribution
     pub data: serde_json::Value,
+    /// Etiqueta, descripción y etiquetas opcionales del conjunto de datos
+    /// generado, que se guardan también en el historial
+    #[serde(flatten)]
+    pub metadata: Metadata,
+    /// Modo de generación, valores independientes si no se indica
+    #[serde(default)]
+    pub mode: GenerationMode,
+    /// Precisión con la que se guardan los valores, f64 si no se indica
+    #[serde(default)]
+    pub precision: Precision,
+    /// Cantidad de valores iniciales a generar y descartar antes de guardar
+    /// los datos, para omitir el transitorio inicial
+    #[serde(default)]
+    pub discard: u64,
+    /// Generador de números aleatorios uniformes, xoshiro256++ si no se indica
+    #[serde(default)]
+    pub generator: Generator,
+    /// Si se guardan también los números uniformes crudos del generador,
+    /// para inspeccionarlos en `/uniforms/page`
+    #[serde(default)]
+    pub keep_uniforms: bool,
+    /// Cantidad de decimales a los que se redondean los valores guardados,
+    /// sin redondear si no se indica
+    #[serde(default)]
+    pub decimals: Option<u8>,
+    /// Límites de los valores guardados y qué hacer con los que caen
+    /// fuera, sin limitar si no se indican
+    #[serde(default)]
+    pub bounds: Option<Bounds>,
+}
+
+impl GenerationParameters {
+    /// Interpreta los parámetros de la distribución y valida el modo y los
+    /// metadatos
+    pub fn experiment(&self) -> Result<Experiment, ApiError> {
+        self.metadata.validate().map_err(ApiError::BadRequest)?;
+        let experiment = Experiment {
+            seed: self.seed,
+            distribution: StoredDistribution::from_parameters(&self.distribution, &self.data)?,
+            mode: self.mode,
+            discard: self.discard,
+            generator: self.generator,
+            decimals: self.decimals,
+            bounds: self.bounds,
+        };
+        experiment.validate().map_err(ApiError::BadRequest)?;
+        Ok(experiment)
+    }
+
+    /// Cantidad total de valores a generar, contando los descartados
+    pub fn total(&self) -> u64 {
+        self.number.saturating_add(self.discard)
+    }
+
+    /// Bytes que ocuparán en memoria los valores generados, y los uniformes
+    /// crudos si se guardan, sin contar los bloques que se escriban en disco.
+    /// Al descartar los valores fuera de los límites cuenta también los que se
+    /// generan de más: un bloque, o todos si se guardan los uniformes. Es
+    /// aproximado: los métodos de rechazo usan más de un uniforme por valor
+    ///
+    /// # Argumentos
+    ///
+    /// * `experiment` experimento de los parámetros (ver `experiment`)
+    pub fn estimated_bytes(&self, experiment: &Experiment) -> u64 {
+        let values = self.number.saturating_mul(self.precision.size());
+        let draws = (self.total() as f64 / experiment.acceptance()).ceil() as u64;
+        let extra = match (self.keep_uniforms, experiment.rejection()) {
+            (true, Some(_)) => draws.saturating_mul(16),
+            (true, None) => draws.saturating_mul(8),
+            (false, Some(_)) => draws.min(CHUNK_LEN as u64) * 8,
+            (false, None) => 0,
+        };
+        values.saturating_add(extra)
+    }
+}
+
+/// Petición de generación: un único conjunto de datos, o varios a la vez
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum GenerationRequest {
+    Batch(Vec<GenerationParameters>),
+    Single(GenerationParameters),
+}
+
+/// Identificador de un conjunto de datos generado
+#[derive(Serialize)]
+pub struct DatasetId {
+    pub id: u64,
+    pub label: Option<String>,
+    /// Cantidad de valores de cada estrato, en el muestreo estratificado
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strata: Option<Vec<Stratum>>,
+    /// Manifiesto para repetir la generación
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reproducibility: Option<Reproducibility>,
+    /// Experimento reciente del historial con la misma configuración, si lo hay
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate: Option<DuplicateSeed>,
+}
+
+/// Advertencia de que un experimento reciente del historial usó la misma
+/// semilla, generador y distribución, por lo que los valores generados son
+/// los mismos
+#[derive(Serialize)]
+pub struct DuplicateSeed {
+    /// Identificador del experimento anterior en el historial
+    pub id: i64,
+    /// Momento de la generación anterior, en segundos desde UNIX_EPOCH
+    pub timestamp: u64,
+    /// Etiqueta del experimento anterior
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Descripción de la advertencia
+    pub message: String,
+}
+
+/// Mayor cantidad de valores de la vista previa de una generación: para
+/// más, se generan con `/generate` y se piden las estadísticas aparte
+pub const MAX_PREVIEW_NUMBERS: u64 = 100_000;
+
+/// Parámetros de la vista previa de una generación
+#[derive(Deserialize)]
+pub struct PreviewParameters {
+    /// Parámetros de la generación, los de `/generate`
+    #[serde(flatten)]
+    pub generation: GenerationParameters,
+    /// Parámetros de las estadísticas, los de `/statistics`
+    pub statistics: StatisticsInput,
+}
+
+/// Conjunto de datos generado con su primera página y sus estadísticas
+#[derive(Serialize)]
+pub struct PreviewResponse {
+    /// Identificador, etiqueta y manifiesto del conjunto de datos
+    #[serde(flatten)]
+    pub dataset: DatasetId,
+    /// Primera página de los valores, la de `/page?page=1`
+    pub page: Page,
+    /// Histograma y pruebas, los de `/statistics`
+    pub statistics: StatisticsResponse,
+}
+
+/// Formato de la tabla de cálculo de la prueba de chi cuadrado
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum TableFormat {
+    /// Valores separados por comas, con todos los decimales
+    Csv,
+    /// Entorno tabular de LaTeX, con 4 decimales
+    Latex,
+}
+
+/// Parámetros del informe de un conjunto de datos
+#[derive(Deserialize)]
+pub struct ReportQuery {
+    /// Cantidad de intervalos del histograma y de la prueba, 10 si no se indica
+    pub intervals: Option<usize>,
+    /// Intervalo final, cerrado si no se indica
+    #[serde(default)]
+    pub final_bin: FinalBin,
+    /// Forma de armar los intervalos, de igual ancho si no se indica
+    #[serde(default)]
+    pub binning: Binning,
+    /// Conjunto de datos a utilizar, el actual si no se indica
+    pub dataset: Option<u64>,
+}
+
+/// Parámetros de la exportación de la tabla de la prueba de chi cuadrado
+#[derive(Deserialize)]
+pub struct TableQuery {
+    /// Formato de la tabla
+    pub format: TableFormat,
+    /// Conjunto de datos a utilizar, el actual si no se indica
+    pub dataset: Option<u64>,
+}
+
+/// Filtro del historial de experimentos
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    /// Etiqueta que deben tener los experimentos, todos si no se indica
+    pub tag: Option<String>,
+}
+
+/// Conjunto de datos sobre el que opera una petición, el actual si no se indica
+#[derive(Deserialize)]
+pub struct DatasetQuery {
+    pub dataset: Option<u64>,
+}
+
+/// Conjunto de datos a copiar y etiqueta de la copia
+#[derive(Deserialize)]
+pub struct SnapshotQuery {
+    /// Conjunto a copiar, el actual si no se indica
+    pub dataset: Option<u64>,
+    /// Etiqueta de la copia, la del original si no se indica
+    pub label: Option<String>,
 }
 
 #[derive(Deserialize)]
 pub struct Pagination {
     pub page: usize,
+    /// Conjunto de datos a paginar, el actual si no se indica
+    pub dataset: Option<u64>,
+}
+
+/// Experimento a comparar: uno del historial, por su identificador, o uno
+/// nuevo con sus parámetros de generación
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum CompareRun {
+    Stored { id: i64 },
+    Generate(Box<GenerationParameters>),
+}
+
+/// Parámetros para la comparación de dos experimentos
+#[derive(Deserialize)]
+pub struct CompareParameters {
+    pub first: CompareRun,
+    pub second: CompareRun,
+    /// Cantidad de intervalos para los histogramas
+    pub intervals: usize,
+    /// Nivel de significancia de la prueba de dos muestras, si no se indica
+    /// se usa el de la configuración
+    pub alpha: Option<f64>,
+}
+
+/// Resultados de uno de los experimentos comparados
+#[derive(Serialize)]
+pub struct CompareSide {
+    pub summary: Summary,
+    pub statistics: StatisticsResponse,
+}
+
+/// Respuesta de la comparación de dos experimentos
+#[derive(Serialize)]
+pub struct CompareResponse {
+    pub first: CompareSide,
+    pub second: CompareSide,
+    /// Prueba de Kolmogorov-Smirnov de dos muestras
+    pub test: TwoSampleTest,
+}
+
+/// Parámetros para la integración por Monte Carlo
+#[derive(Deserialize)]
+pub struct MonteCarloParameters {
+    /// Semilla del generador de números aleatorios
+    pub seed: u64,
+    /// Cantidad de puntos a muestrear
+    pub samples: u64,
+    /// Función a integrar, en la variable x, por ejemplo "x^2 + sin(x)"
+    pub function: String,
+    /// Límite inferior de integración
+    pub lower: f64,
+    /// Límite superior de integración
+    pub upper: f64,
+    /// Cantidad de estimaciones parciales a devolver
+    #[serde(default = "default_trace_points")]
+    pub trace_points: usize,
+}
+
+fn default_trace_points() -> usize {
+    100
+}
+
+/// Parámetros para la integración por Monte Carlo con variable de control
+#[derive(Deserialize)]
+pub struct ControlVariateParameters {
+    /// Semilla del generador de números aleatorios
+    pub seed: u64,
+    /// Cantidad de puntos a muestrear
+    pub samples: u64,
+    /// Función a integrar, en la variable x
+    pub function: String,
+    /// Límite inferior de integración
+    pub lower: f64,
+    /// Límite superior de integración
+    pub upper: f64,
+    /// Función de control, en la variable x. Si no se indica se usa el
+    /// propio uniforme, x
+    pub control: Option<String>,
+    /// Media conocida de la función de control, obligatoria si se indica
+    /// la función
+    pub control_mean: Option<f64>,
+}
+
+/// Parámetros para la simulación de una cola M/M/c
+#[derive(Deserialize)]
+pub struct QueueRequest {
+    /// Semilla del generador de números aleatorios
+    pub seed: u64,
+    #[serde(flatten)]
+    pub queue: QueueParameters,
+}
+
+/// Parámetros para la simulación de un inventario (s, S)
+#[derive(Deserialize)]
+pub struct InventoryRequest {
+    /// Semilla del generador de números aleatorios
+    pub seed: u64,
+    #[serde(flatten)]
+    pub inventory: InventoryParameters,
+}
+
+/// Parámetros para la simulación de una cadena de Markov
+#[derive(Deserialize)]
+pub struct MarkovRequest {
+    /// Semilla del generador de números aleatorios
+    pub seed: u64,
+    #[serde(flatten)]
+    pub chain: MarkovParameters,
+}
+
+/// Parámetros para el remuestreo bootstrap de un conjunto de datos
+#[derive(Deserialize)]
+pub struct ResampleParameters {
+    /// Semilla del generador de números aleatorios
+    pub seed: u64,
+    /// Cantidad de valores a elegir, tantos como la muestra si no se indica
+    pub number: Option<u64>,
+    /// Conjunto de datos a remuestrear, el actual si no se indica
+    pub dataset: Option<u64>,
+    /// Muestra subida por el cliente, se usa en lugar de un conjunto guardado
+    pub values: Option<Vec<f64>>,
+    /// Distribución contra la que se prueba el remuestreo de una muestra
+    /// subida
+    pub distribution: Option<DistributionType>,
+    /// Parámetros de la distribución, de tipo Distribution
+    pub data: Option<serde_json::Value>,
+    /// Etiqueta opcional del nuevo conjunto de datos
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Parámetros para la generación por replicaciones independientes
+#[derive(Deserialize)]
+pub struct ReplicationParameters {
+    /// Semilla, distribución, modo y cantidad de valores de cada replicación
+    #[serde(flatten)]
+    pub generation: GenerationParameters,
+    /// Cantidad de replicaciones
+    pub replications: usize,
+    /// Cantidad de intervalos de la prueba de chi cuadrado de cada
+    /// replicación. Si no se indica no se hace la prueba
+    pub intervals: Option<usize>,
+}
+
+/// Resultado de la prueba de chi cuadrado de una replicación
+#[derive(Serialize)]
+pub struct ReplicationTest {
+    /// Chi cuadrado calculado
+    pub calculated: f64,
+    /// Valor crítico
+    pub critical: f64,
+    /// Si se rechaza que los valores sigan la distribución
+    pub reject: bool,
+}
+
+/// Resultados de una replicación
+#[derive(Serialize)]
+pub struct Replication {
+    /// Estadísticos descriptivos de los valores de la replicación
+    pub summary: Summary,
+    /// Prueba de chi cuadrado, si se pidió
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test: Option<ReplicationTest>,
+}
+
+/// Respuesta de la generación por replicaciones
+#[derive(Serialize)]
+pub struct ReplicationsResponse {
+    /// Resultados de cada replicación, en orden
+    pub replications: Vec<Replication>,
+    /// Intervalo de confianza del 95% de la media, entre replicaciones
+    pub mean: ConfidenceInterval,
+    /// Intervalo de confianza del 95% de la varianza, entre replicaciones
+    pub variance: ConfidenceInterval,
+    /// Media teórica de los valores generados
+    pub expected_mean: f64,
+    /// Varianza teórica de los valores generados
+    pub expected_variance: f64,
+    /// Proporción de replicaciones en las que se rechaza la prueba de chi
+    /// cuadrado, cercana a la significancia si los valores siguen la
+    /// distribución
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejection_rate: Option<f64>,
+}
+
+/// Parámetros de la generación a partir de números uniformes dados
+#[derive(Deserialize)]
+pub struct TransformParameters {
+    /// Cantidad de valores a generar
+    pub number: u64,
+    /// Tipo de distribución a generar
+    pub distribution: DistributionType,
+    /// Parámetros para la distribución, de tipo Distribution
+    pub data: serde_json::Value,
+    /// Modo de generación, valores independientes si no se indica
+    #[serde(default)]
+    pub mode: GenerationMode,
+    /// Números uniformes en [0, 1) a transformar, en orden
+    pub uniforms: Vec<f64>,
+    /// Etiqueta opcional del conjunto de datos
+    pub label: Option<String>,
+    /// Precisión con la que se guardan los valores, f64 si no se indica
+    #[serde(default)]
+    pub precision: Precision,
+}
+
+/// Resultado de la generación a partir de números uniformes dados
+#[derive(Serialize)]
+pub struct TransformResponse {
+    /// Identificador y etiqueta del conjunto de datos
+    #[serde(flatten)]
+    pub dataset: DatasetId,
+    /// Valores generados, en orden
+    pub values: Vec<f64>,
+    /// Cantidad de números uniformes usados, desde el primero
+    pub used: usize,
+}
+
+/// Parámetros de la distribución de las medias muestrales
+#[derive(Deserialize)]
+pub struct SampleMeansParameters {
+    /// Semilla, distribución, modo y tamaño `number` de cada muestra
+    #[serde(flatten)]
+    pub generation: GenerationParameters,
+    /// Cantidad de muestras, M, cuyas medias se guardan
+    pub samples: usize,
+    /// Cantidad de intervalos de la prueba de chi cuadrado de las medias
+    pub intervals: usize,
+}
+
+/// Distribución de las medias muestrales, con las pruebas de normalidad
+#[derive(Serialize)]
+pub struct SampleMeansResponse {
+    /// Identificador y etiqueta del conjunto de datos de las medias
+    #[serde(flatten)]
+    pub dataset: DatasetId,
+    /// Cantidad de muestras
+    pub samples: usize,
+    /// Tamaño de cada muestra
+    pub size: u64,
+    /// Media teórica de las medias, la de la distribución
+    pub expected_mean: f64,
+    /// Desviación estándar teórica de las medias, σ/√n
+    pub expected_sd: f64,
+    /// Estadísticos descriptivos de las medias
+    pub summary: Summary,
+    /// Histograma y prueba de chi cuadrado contra la Normal teórica
+    pub statistics: StatisticsResponse,
+    /// Prueba de Kolmogorov-Smirnov contra la Normal teórica
+    pub kolmogorov_smirnov: OneSampleTest,
+    /// Prueba de Jarque-Bera, con la media y la varianza de las medias
+    pub jarque_bera: Option<JarqueBera>,
+}
+
+/// Parámetros de un generador congruencial lineal a verificar. Los que no
+/// se indican son los de `LinearCongruentialGenerator::with_seed`
+#[derive(Deserialize)]
+pub struct LcgParameters {
+    /// Semilla del generador, x0
+    pub seed: u64,
+    /// Módulo
+    pub m: Option<u64>,
+    /// Multiplicador
+    pub a: Option<u64>,
+    /// Incremento
+    pub c: Option<u64>,
+    /// Cantidad de valores a generar
+    pub number: u64,
+}
+
+/// Diagnóstico de los parámetros de un generador congruencial lineal
+#[derive(Serialize)]
+pub struct LcgReport {
+    /// Longitud del ciclo de la secuencia, None si no se encontró por ser
+    /// mucho mayor a la cantidad de valores pedida
+    pub period: Option<u64>,
+    /// Si el período es m para cualquier semilla (teorema de Hull-Dobell)
+    pub full_period: bool,
+    /// Problemas que hacen que la secuencia sea degenerada
+    pub warnings: Vec<SeedWarning>,
+    /// Descripción de cada problema
+    pub messages: Vec<String>,
+}
+
+/// Cantidad de valores que se generan en cada combinación de `/benchmark`,
+/// menor a `rng::PARALLEL_THRESHOLD` para que todas usen un solo hilo
+pub const BENCHMARK_SAMPLES: u64 = 1_000_000;
+
+/// Rendimiento de la generación con un generador y una distribución
+#[derive(Serialize)]
+pub struct BenchmarkResult {
+    /// Generador de números aleatorios uniformes
+    pub generator: Generator,
+    /// Distribución con sus parámetros
+    pub distribution: StoredDistribution,
+    /// Tiempo de generación, en segundos
+    pub seconds: f64,
+    /// Valores generados por segundo
+    pub samples_per_second: f64,
+}
+
+/// Resultado de la medición del rendimiento de la generación
+#[derive(Serialize)]
+pub struct BenchmarkResponse {
+    /// Cantidad de valores generados en cada combinación
+    pub samples: u64,
+    /// Rendimiento de cada combinación de generador y distribución
+    pub results: Vec<BenchmarkResult>,
+}
+
+/// Parámetros para importar una muestra obtenida fuera del servidor
+#[derive(Deserialize)]
+pub struct ImportParameters {
+    /// Valores de la muestra
+    pub values: Vec<f64>,
+    /// Peso de cada valor, por ejemplo del muestreo por importancia. Si no
+    /// se indica, todos los valores pesan lo mismo
+    pub weights: Option<Vec<f64>>,
+    /// Distribución contra la que se prueba la muestra
+    pub distribution: DistributionType,
+    /// Parámetros de la distribución, de tipo Distribution
+    pub data: serde_json::Value,
+    /// Etiqueta opcional del conjunto de datos
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Precisión con la que se guardan los valores, f64 si no se indica
+    #[serde(default)]
+    pub precision: Precision,
+}
+
+/// Respuesta de la importación de una muestra
+#[derive(Serialize)]
+pub struct ImportResponse {
+    #[serde(flatten)]
+    pub dataset: DatasetId,
+    /// Estadísticos descriptivos, con los pesos si los hay
+    pub summary: Summary,
+    /// Tamaño efectivo de la muestra, si tiene pesos
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_size: Option<f64>,
+}
+
+/// Parámetros para derivar un conjunto de datos de otro con una operación
+#[derive(Deserialize)]
+pub struct DataTransformParameters {
+    /// Operación a aplicar a cada valor
+    pub operation: Operation,
+    /// Conjunto de datos a transformar, el actual si no se indica
+    pub dataset: Option<u64>,
+    /// Etiqueta opcional del nuevo conjunto de datos
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Respuesta de la transformación de un conjunto de datos
+#[derive(Serialize)]
+pub struct DataTransformResponse {
+    #[serde(flatten)]
+    pub dataset: DatasetId,
+    /// Operación aplicada, con la media y el desvío calculados si se
+    /// estandarizó
+    pub operation: Operation,
+    /// Estadísticos descriptivos de los valores transformados
+    pub summary: Summary,
+}
+
+/// Parámetros de la importación en una sola pasada de un CSV de valores,
+/// en la consulta porque el cuerpo es el archivo
+#[derive(Deserialize)]
+pub struct StreamImportQuery {
+    /// Límite inferior de los intervalos acordados
+    pub lower: f64,
+    /// Límite superior de los intervalos acordados
+    pub upper: f64,
+    /// Cantidad de intervalos
+    pub intervals: usize,
+    /// Distribución contra la que se prueba la muestra, sin prueba si no se
+    /// indica
+    pub distribution: Option<DistributionType>,
+    /// Parámetros de la distribución, en Json
+    pub data: Option<String>,
+    /// Nivel de significancia de la prueba, uno de los tabulados; el de la
+    /// configuración si no se indica
+    pub alpha: Option<f64>,
+    /// Si los parámetros de la distribución se estimaron a partir de la muestra
+    #[serde(default)]
+    pub parameters_estimated: bool,
+}
+
+/// Distribución de una dimensión del muestreo
+#[derive(Deserialize)]
+pub struct DimensionParameters {
+    pub distribution: DistributionType,
+    /// Parámetros para la distribución, de tipo Distribution
+    pub data: serde_json::Value,
+}
+
+/// Parámetros para la generación de una muestra multivariada con cópula
+#[derive(Deserialize)]
+pub struct CopulaParameters {
+    /// Semilla del generador de números aleatorios
+    pub seed: u64,
+    /// Cantidad de valores de cada dimensión
+    pub number: u64,
+    /// Cópula que define la dependencia entre las dimensiones
+    pub copula: Copula,
+    /// Distribución marginal de cada dimensión
+    pub marginals: Vec<DimensionParameters>,
+    /// Etiqueta opcional, se le agrega el número de dimensión a cada conjunto
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Respuesta de la generación con cópula
+#[derive(Serialize)]
+pub struct CopulaResponse {
+    /// Conjunto de datos guardado para cada dimensión
+    pub datasets: Vec<DatasetId>,
+    /// Tau de Kendall de cada par de dimensiones
+    pub kendall: Vec<KendallTau>,
+}
+
+/// Parámetros para la comparación del hipercubo latino con el muestreo
+/// aleatorio simple
+#[derive(Deserialize)]
+pub struct LatinHypercubeParameters {
+    /// Semilla del generador de números aleatorios
+    pub seed: u64,
+    /// Cantidad de puntos de cada muestra
+    pub samples: usize,
+    /// Cantidad de estimaciones con cada método
+    pub replications: usize,
+    /// Distribución de cada dimensión
+    pub dimensions: Vec<DimensionParameters>,
+}
+
+/// Parámetros para el gráfico del histograma
+#[derive(Deserialize)]
+pub struct PlotQuery {
+    /// Cantidad de intervalos del histograma
+    pub intervals: usize,
+    /// Superponer la curva de frecuencias esperadas de la distribución
+    #[serde(default)]
+    pub overlay: bool,
+    /// Intervalo final, cerrado si no se indica
+    #[serde(default)]
+    pub final_bin: FinalBin,
+    /// Forma de armar los intervalos, de igual ancho si no se indica; con
+    /// intervalos por cuantiles las barras muestran la densidad de frecuencia
+    #[serde(default)]
+    pub binning: Binning,
+    /// Cantidad máxima de barras; con más intervalos, el histograma se arma
+    /// con `max_points` intervalos
+    pub max_points: Option<usize>,
+    /// Conjunto de datos a graficar, el actual si no se indica
+    pub dataset: Option<u64>,
+}
+
+/// Cantidad máxima de posiciones de las estadísticas móviles
+pub const MAX_ROLLING_POINTS: usize = 10_000;
+
+/// Parámetros de las estadísticas móviles
+#[derive(Deserialize)]
+pub struct RollingQuery {
+    /// Cantidad de valores de la ventana, todos los recorridos si no se indica
+    pub window: Option<usize>,
+    /// Cantidad de posiciones a devolver, 100 si no se indica
+    pub points: Option<usize>,
+    /// Conjunto de datos a utilizar, el actual si no se indica
+    pub dataset: Option<u64>,
+}
+
+/// Media y varianza a lo largo de la secuencia, con los valores teóricos
+#[derive(Serialize)]
+pub struct RollingResponse {
+    /// Cantidad de valores de la ventana, None si se usan todos los recorridos
+    pub window: Option<usize>,
+    /// Media y varianza en cada posición
+    pub points: Vec<RollingPoint>,
+    /// Media teórica de la distribución, si los datos se generaron
+    pub expected_mean: Option<f64>,
+    /// Varianza teórica de la distribución, si los datos se generaron
+    pub expected_variance: Option<f64>,
+}
+
+/// Parámetros de la traza de la ley de los grandes números
+#[derive(Deserialize)]
+pub struct LlnQuery {
+    /// Cantidad máxima de posiciones a devolver, 1000 si no se indica
+    pub points: Option<usize>,
+    /// Límite de posiciones, por debajo de `points`, para acotar la respuesta
+    /// sin cambiar el valor por defecto de `points`
+    pub max_points: Option<usize>,
+    /// Conjunto de datos a utilizar, el actual si no se indica
+    pub dataset: Option<u64>,
+}
+
+/// Promedio acumulado en una posición, comparado con la media teórica
+#[derive(Serialize)]
+pub struct LlnPoint {
+    /// Cantidad de valores recorridos y su media
+    #[serde(flatten)]
+    pub running: RunningMean,
+    /// Diferencia entre la media de los n valores y la teórica
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<f64>,
+    /// Semiancho de la banda del 95% de la media, 1.96·σ/√n
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bound: Option<f64>,
+}
+
+/// Traza de la convergencia del promedio acumulado a la media teórica
+#[derive(Serialize)]
+pub struct LlnResponse {
+    /// Promedio acumulado en cada posición
+    pub points: Vec<LlnPoint>,
+    /// Media teórica de la distribución, si los datos se generaron
+    pub expected_mean: Option<f64>,
+    /// Desviación estándar teórica de la distribución, si los datos se generaron
+    pub expected_sd: Option<f64>,
+}
+
+/// Mayor cantidad de intervalos de la sensibilidad de la prueba
+pub const MAX_SENSITIVITY_INTERVALS: usize = 200;
+
+/// Rango de cantidades de intervalos de la sensibilidad de la prueba
+#[derive(Deserialize)]
+pub struct SensitivityQuery {
+    /// Menor cantidad de intervalos, 5 si no se indica
+    pub min_intervals: Option<usize>,
+    /// Mayor cantidad de intervalos, 30 si no se indica
+    pub max_intervals: Option<usize>,
+    /// Conjunto de datos a utilizar, el actual si no se indica
+    pub dataset: Option<u64>,
+}
+
+/// Cantidad máxima de probabilidades de la tabla de cuantiles
+pub const MAX_QUANTILES: usize = 1000;
+
+/// Probabilidades de la tabla de cuantiles si no se indican
+pub const DEFAULT_QUANTILES: &[f64] = &[0.01, 0.05, 0.1, 0.25, 0.5, 0.75, 0.9, 0.95, 0.99];
+
+/// Parámetros de la tabla de cuantiles
+#[derive(Deserialize)]
+pub struct QuantilesQuery {
+    /// Probabilidades separadas por comas, por ejemplo "0.1,0.5,0.9";
+    /// `DEFAULT_QUANTILES` si no se indican
+    pub probabilities: Option<String>,
+    /// Conjunto de datos a utilizar, el actual si no se indica
+    pub dataset: Option<u64>,
+}
+
+/// Cuantil empírico y teórico en una probabilidad
+#[derive(Serialize)]
+pub struct QuantileRow {
+    /// Probabilidad del cuantil
+    pub probability: f64,
+    /// Cuantil de los valores del conjunto de datos
+    pub empirical: f64,
+    /// Cuantil de la distribución del conjunto de datos, null si es
+    /// infinito, como el cuantil 0 de la Normal
+    pub theoretical: f64,
+}
+
+/// Tabla de cuantiles de un conjunto de datos
+#[derive(Serialize)]
+pub struct QuantilesResponse {
+    /// Cantidad de valores del conjunto de datos
+    pub count: u64,
+    /// Cuantiles, en el orden de las probabilidades pedidas
+    pub quantiles: Vec<QuantileRow>,
+}
+
+/// Familias de distribuciones sugeridas para un conjunto de datos
+#[derive(Serialize)]
+pub struct SuggestResponse {
+    /// Forma de los valores: estadísticos descriptivos, asimetría, curtosis,
+    /// coeficiente de variación y si son enteros
+    pub shape: Shape,
+    /// Familias candidatas, la más parecida primero
+    pub candidates: Vec<Candidate>,
+}
+
+/// Cantidad máxima de pares del gráfico de valores sucesivos
+pub const MAX_PAIRS: usize = 100_000;
+
+/// Cantidad máxima de valores uniformes con los que se cuentan los planos
+/// de las ternas
+pub const MAX_PLANE_VALUES: u64 = 1 << 22;
+
+/// Parámetros del gráfico de pares o ternas de valores uniformes sucesivos
+#[derive(Deserialize)]
+pub struct PairsQuery {
+    /// Distancia entre el primer valor de un par o terna y el del siguiente,
+    /// 1 si no se indica
+    pub step: Option<usize>,
+    /// Cantidad máxima de pares o ternas, 10000 si no se indica
+    pub limit: Option<usize>,
+    /// Cantidad máxima de pares o ternas repartidos a lo largo de todos los
+    /// valores: si hay más, se agranda `step` (ver `list::spread_step`)
+    pub max_points: Option<usize>,
+    /// Conjunto de datos a utilizar, el actual si no se indica
+    pub dataset: Option<u64>,
+}
+
+/// Mayor cantidad de valores de una submuestra
+pub const MAX_SUBSAMPLE: usize = 100_000;
+
+/// Parámetros de la submuestra al azar de un conjunto de datos
+#[derive(Deserialize)]
+pub struct SubsampleQuery {
+    /// Cantidad de valores a elegir, 1000 si no se indica
+    pub k: Option<usize>,
+    /// Semilla del generador con el que se eligen, 0 si no se indica
+    #[serde(default)]
+    pub seed: u64,
+    /// Conjunto de datos a utilizar, el actual si no se indica
+    pub dataset: Option<u64>,
+}
+
+/// Valor de una submuestra, con su posición en el conjunto de datos
+#[derive(Serialize)]
+pub struct SubsampleValue {
+    /// Posición del valor, desde 0
+    pub position: u64,
+    /// Valor
+    pub value: f64,
+}
+
+/// Submuestra al azar, sin reposición, de un conjunto de datos
+#[derive(Serialize)]
+pub struct SubsampleResponse {
+    /// Semilla con la que se eligieron los valores
+    pub seed: u64,
+    /// Cantidad de valores del conjunto de datos
+    pub total: u64,
+    /// Valores elegidos, en el orden del conjunto de datos
+    pub values: Vec<SubsampleValue>,
+}
+
+/// Pares de valores uniformes sucesivos, (uᵢ, uᵢ₊₁)
+#[derive(Serialize)]
+pub struct PairsResponse {
+    /// Distancia entre el primer valor de un par y el del siguiente
+    pub step: usize,
+    /// Cantidad de pares disponibles con esa distancia, aunque se devuelvan menos
+    pub available: u64,
+    /// Pares devueltos
+    pub pairs: Vec<(f64, f64)>,
+}
+
+/// Ternas de valores uniformes sucesivos, (uᵢ, uᵢ₊₁, uᵢ₊₂)
+#[derive(Serialize)]
+pub struct TriplesResponse {
+    /// Distancia entre el primer valor de una terna y el de la siguiente
+    pub step: usize,
+    /// Cantidad de ternas disponibles con esa distancia, aunque se devuelvan menos
+    pub available: u64,
+    /// Ternas devueltas
+    pub triples: Vec<(f64, f64, f64)>,
+    /// Planos que ocupan todas las ternas solapadas, si el conjunto de datos
+    /// se generó con un congruencial y se encuentra una familia de planos
+    pub planes: Option<LatticePlanes>,
+}
+
+/// Parámetros de la batería de pruebas del flujo uniforme
+#[derive(Deserialize)]
+pub struct BatteryQuery {
+    /// Conjunto de datos a utilizar, el actual si no se indica
+    pub dataset: Option<u64>,
+    /// Nivel de significancia, el de la configuración si no se indica
+    pub alpha: Option<f64>,
+    /// Cantidad de dígitos de la prueba de frecuencia, 1 si no se indica
+    pub digits: Option<u32>,
+    /// Cantidad de valores de cada punto de la prueba de colisiones, 2 si
+    /// no se indica
+    pub collision_dimension: Option<u32>,
+    /// Divisiones de cada coordenada de la prueba de colisiones, 1024 si no
+    /// se indica
+    pub collision_divisions: Option<u64>,
+    /// Cantidad de valores de cada grupo de la prueba de permutaciones, 3 si
+    /// no se indica
+    pub permutation_length: Option<usize>,
+    /// Cantidad de intervalos de la estimación de la entropía, 16 si no se
+    /// indica
+    pub entropy_bins: Option<u64>,
 }
 
-/// Últimos datos generados, con los parámetros de su distribución
+/// Conjuntos de datos a correlacionar
+#[derive(Deserialize)]
+pub struct CorrelationQuery {
+    /// Primer conjunto de datos
+    pub first: u64,
+    /// Segundo conjunto de datos
+    pub second: u64,
+    /// Nivel de significancia, el configurado por defecto si no se indica
+    pub alpha: Option<f64>,
+}
+
+/// Datos generados, con los parámetros de su distribución
+#[derive(Clone)]
 pub struct Generated {
-    /// Vector de números generados
-    pub data: Arc<Vec<f64>>,
+    /// Números generados, en la precisión con la que se guardan
+    pub data: Samples,
     /// Parámetros de la distribución
     pub dist: Arc<Box<dyn Distribution + Send + Sync>>,
+    /// Etiqueta, descripción y etiquetas del conjunto de datos
+    pub metadata: Metadata,
+    /// Manifiesto para repetir la generación, si los datos se generaron a
+    /// partir de una semilla y una distribución
+    pub reproducibility: Option<Reproducibility>,
+    /// Peso de cada valor, si la muestra se importó con pesos
+    pub weights: Option<Arc<Vec<f64>>>,
+    /// Flujo uniforme crudo del generador, si se pidió guardarlo
+    pub uniforms: Option<Samples>,
 }
 
 impl Generated {
-    pub fn new(data: Vec<f64>, dist: Box<dyn Distribution + Send + Sync>) -> Self {
+    pub fn new(data: impl Into<Samples>, dist: Box<dyn Distribution + Send + Sync>) -> Self {
         let dist = Arc::new(dist);
-        let data = Arc::new(data);
-        Self { data, dist }
+        let data = data.into();
+        let metadata = Metadata::default();
+        Self { data, dist, metadata, reproducibility: None, weights: None, uniforms: None }
+    }
+
+    /// Bytes que ocupan en memoria los valores, los uniformes y los pesos,
+    /// sin los bloques en disco
+    pub fn memory_bytes(&self) -> u64 {
+        let uniforms = self.uniforms.as_ref().map_or(0, Samples::memory_bytes);
+        let weights = self.weights.as_ref().map_or(0, |w| w.len() as u64 * 8);
+        self.data.memory_bytes() + uniforms + weights
+    }
+
+    /// Asigna la etiqueta del conjunto de datos
+    pub fn with_label(mut self, label: Option<String>) -> Self {
+        self.metadata.label = label;
+        self
+    }
+
+    /// Asigna la etiqueta, la descripción y las etiquetas del conjunto de datos
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Asigna el manifiesto de reproducibilidad del conjunto de datos
+    pub fn with_reproducibility(mut self, reproducibility: Reproducibility) -> Self {
+        self.reproducibility = Some(reproducibility);
+        self
+    }
+
+    /// Asigna los pesos de los valores
+    pub fn with_weights(mut self, weights: Option<Vec<f64>>) -> Self {
+        self.weights = weights.map(Arc::new);
+        self
+    }
+
+    /// Asigna el flujo uniforme crudo con el que se generaron los valores
+    pub fn with_uniforms(mut self, uniforms: Option<Samples>) -> Self {
+        self.uniforms = uniforms;
+        self
+    }
+}
+
+impl From<Snapshot> for Generated {
+    // Con el manifiesto se prueba contra la distribución con los límites y
+    // el redondeo, como antes de persistirlo
+    fn from(snapshot: Snapshot) -> Self {
+        let tested = snapshot.tested();
+        let generated = Generated::new(snapshot.data, tested).with_metadata(snapshot.metadata);
+        match snapshot.manifest {
+            Some(manifest) => generated.with_reproducibility(manifest),
+            None => generated,
+        }
     }
 }
 
@@ -69,99 +1097,2985 @@ impl Default for Generated {
     }
 }
 
-/// Método handler de las peticiones de generación de valores
+/// Crea una instancia de generador de números aleatorios, con la semilla
+/// de los parámetros de la generación
 ///
 /// # Argumentos
 ///
-/// * `State(arc)` Un wrapper state al Arc que contiene el RwLock del estado
-/// * `data` Datos en Json recibidos del front end
-pub async fn get_unified(
-    State(arc): State<Arc<RwLock<Generated>>>,
-    data: extract::Json<GenerationParameters>,
-) {
-    // Asegurarse de que ningún otro hilo pueda acceder al estado
-    let mut arc = arc.write().await;
-    arc.data = Arc::new(vec![]);
-    // Crear una instancia de generador de números aleatorios, con la semilla
-    // de los parámetros de la generación
-    let distro: rand::distributions::Uniform<f64> = rand::distributions::Uniform::new(0.0,1.0);
-    let mut rng: UniformGenerator = UniformGenerator { dist: distro, rng: SmallRng::from_seed(rand::random()) };
-    //let mut rng: LinearCongruentialGenerator = LinearCongruentialGenerator::with_seed(1021218219);
-    // Crear el vector en el que se guardan los datos, con capacidad
-    // suficiente para la cantidad de valores a generar
-    let mut res = Vec::with_capacity(data.number as usize);
-    // Distribución a almacenar posteriormente en el estado
-    let dist: Box<dyn Distribution + Send + Sync>;
-    // Según la distribución, llamar al método correcto
-    // No se usa método de interfaz por rendimiento al usar dynamic dispatch
-    match data.distribution {
-        DistributionType::Normal => {
-            let mut distribution = serde_json::from_value::<Normal>(data.data.clone()).unwrap();
-            for _ in 0..data.number {
-                res.push(distribution.next(&mut rng));
-            }
-            dist = Box::new(distribution);
-        }
-        DistributionType::Uniform => {
-            let distribution = serde_json::from_value::<Uniform>(data.data.clone()).unwrap();
-            for _ in 0..data.number {
-                res.push(distribution.next(&mut rng));
-            }
-            dist = Box::new(distribution);
-        }
-        DistributionType::Exponential => {
-            let distribution = serde_json::from_value::<Exponential>(data.data.clone()).unwrap();
-            for _ in 0..data.number {
-                res.push(distribution.next(&mut rng));
-            }
-            dist = Box::new(distribution);
-        }
-        DistributionType::Poisson => {
-            let distribution = serde_json::from_value::<Poisson>(data.data.clone()).unwrap();
-            for _ in 0..data.number {
-                res.push(distribution.next(&mut rng));
-            }
-            dist = Box::new(distribution);
+/// * `seed` semilla del generador de números aleatorios
+pub fn seeded_rng(seed: u64) -> UniformGenerator {
+    UniformGenerator::with_seed(seed)
+}
+
+/// Genera los valores del experimento indicado, con un generador de
+/// números aleatorios inicializado con su semilla. Los pedidos grandes se
+/// generan por bloques, en paralelo, con un subflujo del generador por bloque.
+/// Se generan también los valores a descartar del experimento, que se
+/// quitan del principio después de aplicar el modo
+///
+/// # Argumentos
+///
+/// * `number` cantidad de valores a devolver
+/// * `experiment` semilla, distribución con sus parámetros y modo de generación
+pub fn generate(number: u64, experiment: &Experiment) -> Vec<f64> {
+    // El congruencial genera un único flujo, en orden
+    if let Some(mut lcg) = experiment.generator.lcg(experiment.seed) {
+        // Con un uniforme por valor y sin modo, los valores descartados se
+        // saltan sin generarlos
+        if matches!(experiment.mode, GenerationMode::Independent)
+            && experiment.distribution.inverse_transform()
+        {
+            lcg.advance(experiment.discard);
+            let experiment = Experiment { discard: 0, ..experiment.clone() };
+            return generate_with(number, &experiment, &mut lcg);
         }
+        return generate_with(number, experiment, &mut lcg);
+    }
+    let distribution = &experiment.distribution;
+    // El muestreo estratificado aplica directamente la transformada inversa
+    if let GenerationMode::Stratified { strata } = experiment.mode {
+        let dist = distribution.clone().boxed();
+        let mut rng = seeded_rng(experiment.seed);
+        return sampling::stratified(number as usize, strata, dist.as_ref(), &mut rng);
     }
-    // Guardar el vector generado y la distribución utilizada
-    *arc = Generated::new(res, dist);
+    // Crear el vector en el que se guardan los datos, que cada bloque
+    // completa directamente
+    let discard = experiment.discard as usize;
+    let mut res = vec![0f64; discard + number as usize];
+    let fill = chunk_filler(distribution);
+    fill_chunked(experiment.seed, &mut res, |rng: &mut UniformGenerator, chunk: &mut [f64]| {
+        fill(rng, chunk)
+    });
+    experiment.mode.apply(distribution, &mut res, None);
+    res.drain(..discard);
+    res
 }
 
-/// Método handler de las peticiones de cálculo de estadísticas
-/// Devuelve Json con histogram, de tipo HistogramData, y test, de tipo
-/// TestResult
+/// Genera los valores del experimento como un conjunto de datos en la
+/// precisión indicada. Salvo en el muestreo estratificado y con el generador
+/// congruencial, los valores se generan y guardan por bloques, sin armar el
+/// vector completo en f64, y con `spill` los bloques que superan el límite en
+/// memoria se escriben en disco. Los valores son los mismos que los de `generate`,
+/// limitados y redondeados si el experimento lo indica (ver `Experiment::finish`).
+/// Al descartar los valores fuera de los límites, se generan por bloques como
+/// en `generate_bounded`
 ///
 /// # Argumentos
 ///
-/// * `State(arc)` Un wrapper state al Arc que contiene el RwLock del estado
-/// * `data` Datos en Json recibidos del front end
-pub async fn get_statistics(
-    State(arc): State<Arc<RwLock<Generated>>>,
-    data: extract::Json<StatisticsInput>,
-) -> Json<StatisticsResponse> {
-    // Extraer el Input del body Json
-    let data = data.0;
-    // Bloquear el estado para lectura
-    let arc = arc.read().await;
-    // Clonar la distribución (se podría pasar una referencia?)
-    let dist = arc.dist.clone();
-    // Guardar la respuesta del método y devolverla como Json
-    let res = full_statistics(data, arc.data.clone(), dist).await;
-    Json(res)
+/// * `number` cantidad de valores a generar
+/// * `experiment` semilla, distribución con sus parámetros y modo de generación
+/// * `precision` precisión con la que se guardan los valores
+/// * `spill` directorio y límite de bloques en memoria, None para no usar el disco
+pub fn generate_samples(
+    number: u64,
+    experiment: &Experiment,
+    precision: Precision,
+    spill: Option<SpillConfig>,
+) -> io::Result<Samples> {
+    let _span = generation_span(number, experiment).entered();
+    if let Some(bounds) = experiment.rejection() {
+        let draw = |draws, each: &mut BlockSink| experiment_blocks(draws, experiment, each);
+        return Ok(match precision {
+            Precision::F64 => {
+                Samples::F64(Arc::new(generate_bounded(number, experiment, bounds, spill, draw)?))
+            }
+            Precision::F32 => {
+                Samples::F32(Arc::new(generate_bounded(number, experiment, bounds, spill, draw)?))
+            }
+        });
+    }
+    if matches!(experiment.mode, GenerationMode::Stratified { .. })
+        || matches!(experiment.generator, Generator::Lcg { .. })
+    {
+        let mut values = generate(number, experiment);
+        experiment.finish(&mut values);
+        return Ok(Samples::new(precision, values));
+    }
+    Ok(match precision {
+        Precision::F64 => Samples::F64(Arc::new(generate_chunks(number, experiment, spill)?)),
+        Precision::F32 => Samples::F32(Arc::new(generate_chunks(number, experiment, spill)?)),
+    })
 }
 
-/// Método handler de petición para mostrar números de una página
+// Función privada, genera los valores por bloques, aplicando el modo a cada
+// bloque con el último valor del anterior e informando el avance (ver
+// `trace::Progress`)
+fn generate_chunks<T: Float>(
+    number: u64,
+    experiment: &Experiment,
+    spill: Option<SpillConfig>,
+) -> io::Result<ChunkedVec<T>> {
+    let distribution = &experiment.distribution;
+    let fill = chunk_filler(distribution);
+    let progress = Progress::new(experiment.discard + number);
+    let mut previous = None;
+    ChunkedVec::generate(
+        experiment.seed,
+        experiment.discard as usize,
+        number as usize,
+        spill,
+        |rng: &mut UniformGenerator, chunk: &mut [f64]| {
+            fill(rng, chunk);
+            progress.advance(chunk.len() as u64);
+        },
+        |chunk| {
+            experiment.mode.apply(distribution, chunk, previous);
+            previous = chunk.last().copied().or(previous);
+            experiment.finish(chunk);
+        },
+    )
+}
+
+/// Genera los valores del experimento como `generate` y devuelve además el
+/// flujo uniforme crudo del generador: los números uniformes que usó la
+/// distribución, en orden, incluidos los de los valores descartados. Con
+/// subflujos, son los de cada bloque en el orden de los bloques
 ///
 /// # Argumentos
 ///
-/// * `State(arc)` Un wrapper state al Arc que contiene el RwLock del estado
-/// * `data` número de página a devolver
-pub async fn get_page_numbers(
-    Query(page): Query<Pagination>,
-    State(arc): State<Arc<RwLock<Generated>>>
-) -> Json<Vec<f64>> {
-    let arc = arc.read().await;
-    Json(get_page(arc.data.clone(), page.page))
+/// * `number` cantidad de valores a devolver
+/// * `experiment` semilla, distribución con sus parámetros y modo de generación
+pub fn generate_recorded(number: u64, experiment: &Experiment) -> (Vec<f64>, Vec<f64>) {
+    let _span = generation_span(number, experiment).entered();
+    // El congruencial y el muestreo estratificado usan un único flujo
+    let single: Option<Box<dyn Random>> = match experiment.generator.lcg(experiment.seed) {
+        Some(lcg) => Some(Box::new(lcg)),
+        None if matches!(experiment.mode, GenerationMode::Stratified { .. }) => {
+            Some(Box::new(seeded_rng(experiment.seed)))
+        }
+        None => None,
+    };
+    if let Some(mut rng) = single {
+        let mut recorder = Recorder::new(rng.as_mut());
+        let values = generate_with(number, experiment, &mut recorder);
+        return (values, recorder.into_values());
+    }
+    let distribution = &experiment.distribution;
+    let discard = experiment.discard as usize;
+    let mut res = vec![0f64; discard + number as usize];
+    let fill = chunk_filler(distribution);
+    let progress = Progress::new(res.len() as u64);
+    let uniforms = fill_chunked_recorded(experiment.seed, &mut res, |rng, chunk| {
+        fill(rng, chunk);
+        progress.advance(chunk.len() as u64);
+    });
+    experiment.mode.apply(distribution, &mut res, None);
+    res.drain(..discard);
+    (res, uniforms)
 }
 
+// Función privada, span de tracing de una generación, con la distribución,
+// la cantidad de valores y el generador (ver `trace::RecentSpans`)
+fn generation_span(number: u64, experiment: &Experiment) -> tracing::Span {
+    tracing::info_span!(
+        "generation",
+        distribution = experiment.distribution.name(),
+        n = number,
+        generator = ?experiment.generator,
+    )
+}
+
+// Función privada, genera los valores de un pedido en la precisión indicada
+// y, si se pide, también el flujo uniforme crudo, que se guarda en memoria
+fn generate_request(
+    number: u64,
+    experiment: &Experiment,
+    precision: Precision,
+    keep_uniforms: bool,
+    spill: Option<SpillConfig>,
+) -> io::Result<(Samples, Option<Samples>)> {
+    if keep_uniforms {
+        let (mut values, uniforms) = match experiment.rejection() {
+            Some(bounds) => generate_bounded_recorded(number, experiment, bounds),
+            None => generate_recorded(number, experiment),
+        };
+        experiment.finish(&mut values);
+        return Ok((Samples::new(precision, values), Some(uniforms.into())));
+    }
+    Ok((generate_samples(number, experiment, precision, spill)?, None))
+}
+
+/// Recibe cada bloque de valores generados, en orden, y devuelve si se
+/// sigue generando (ver `generate_bounded`)
+pub type BlockSink<'a> = dyn FnMut(&mut [f64]) -> io::Result<bool> + 'a;
+
+/// Genera valores del experimento hasta que `number` caen dentro de los
+/// límites, descartando los que caen fuera, y guarda, terminados (ver
+/// `Experiment::finish`), los primeros `number` de ellos. Se generan los
+/// valores que se esperan necesarios según la probabilidad de caer dentro
+/// (ver `Experiment::acceptance`), con un margen, y si no alcanzan se vuelve
+/// a generar el doble. Los valores se generan y filtran de a un bloque, y
+/// se deja de generar al completar `number`; dependen sólo del experimento
+///
+/// # Argumentos
+///
+/// * `number` cantidad de valores a devolver
+/// * `experiment` experimento, cuya probabilidad de caer dentro fija cuántos generar
+/// * `bounds` límites de los valores
+/// * `spill` directorio y límite de bloques en memoria, None para no usar el disco
+/// * `draw` genera la cantidad de valores indicada, de a bloques, y pasa
+///   cada uno a `BlockSink` (ver `experiment_blocks`)
+pub fn generate_bounded<T: Float>(
+    number: u64,
+    experiment: &Experiment,
+    bounds: Bounds,
+    spill: Option<SpillConfig>,
+    mut draw: impl FnMut(u64, &mut BlockSink) -> io::Result<()>,
+) -> io::Result<ChunkedVec<T>> {
+    let number = number as usize;
+    let mut draws = (number as f64 / experiment.acceptance() * 1.05).ceil() as u64 + 16;
+    loop {
+        let mut out = ChunkedWriter::new(number, spill.clone());
+        draw(draws, &mut |block| {
+            // Dejar al principio del bloque los valores dentro de los límites
+            let mut kept = 0;
+            for i in 0..block.len() {
+                if bounds.contains(block[i]) {
+                    block.swap(kept, i);
+                    kept += 1;
+                }
+            }
+            let block = &mut block[..kept.min(number - out.written())];
+            experiment.finish(block);
+            out.write(block)?;
+            Ok(out.written() < number)
+        })?;
+        if out.written() == number {
+            return out.finish();
+        }
+        draws *= 2;
+    }
+}
+
+// Función privada, como `generate_bounded`, pero con todos los valores en
+// memoria y el flujo uniforme crudo del último intento (ver
+// `generate_recorded`), que se guarda entero. Los valores no se terminan
+fn generate_bounded_recorded(
+    number: u64,
+    experiment: &Experiment,
+    bounds: Bounds,
+) -> (Vec<f64>, Vec<f64>) {
+    let mut draws = (number as f64 / experiment.acceptance() * 1.05).ceil() as u64 + 16;
+    loop {
+        let (values, uniforms) = generate_recorded(draws, experiment);
+        let mut kept: Vec<f64> = values.into_iter().filter(|&x| bounds.contains(x)).collect();
+        if kept.len() as u64 >= number {
+            kept.truncate(number as usize);
+            return (kept, uniforms);
+        }
+        draws *= 2;
+    }
+}
+
+/// Genera `draws` valores del experimento, los mismos que `generate`, de a
+/// bloques de hasta CHUNK_LEN, y pasa cada uno a `each` hasta que devuelva
+/// false. No admite el muestreo estratificado
+///
+/// # Argumentos
+///
+/// * `draws` cantidad de valores a generar, sin contar los descartados
+/// * `experiment` semilla, distribución con sus parámetros y modo de generación
+/// * `each` recibe cada bloque generado y devuelve si se sigue generando
+pub fn experiment_blocks(draws: u64, experiment: &Experiment, each: &mut BlockSink) -> io::Result<()> {
+    if let Some(mut lcg) = experiment.generator.lcg(experiment.seed) {
+        return stream_blocks(draws, experiment, &mut lcg, each);
+    }
+    let distribution = &experiment.distribution;
+    let fill = chunk_filler(distribution);
+    let skip = experiment.discard as usize;
+    let mut previous = None;
+    chunked::generate_blocks(
+        experiment.seed,
+        skip + draws as usize,
+        |rng: &mut UniformGenerator, chunk: &mut [f64]| fill(rng, chunk),
+        |start, block| {
+            experiment.mode.apply(distribution, block, previous);
+            previous = block.last().copied().or(previous);
+            let len = block.len();
+            each(&mut block[skip.saturating_sub(start).min(len)..])
+        },
+    )
+}
+
+/// Genera `draws` valores del experimento con el generador indicado, los
+/// mismos que `generate_with`, de a bloques de hasta CHUNK_LEN, y pasa cada
+/// uno a `each` hasta que devuelva false. No admite el muestreo estratificado
+///
+/// # Argumentos
+///
+/// * `draws` cantidad de valores a generar, sin contar los descartados
+/// * `experiment` distribución con sus parámetros y modo de generación
+/// * `rng` generador de números aleatorios, en lugar del de la semilla
+/// * `each` recibe cada bloque generado y devuelve si se sigue generando
+pub fn stream_blocks(
+    draws: u64,
+    experiment: &Experiment,
+    rng: &mut dyn Random,
+    each: &mut BlockSink,
+) -> io::Result<()> {
+    let distribution = &experiment.distribution;
+    let mut fill = stream_filler(distribution);
+    let total = experiment.discard.saturating_add(draws);
+    let mut block = vec![0f64; total.min(CHUNK_LEN as u64) as usize];
+    let mut previous = None;
+    let mut start = 0u64;
+    while start < total {
+        let len = (total - start).min(CHUNK_LEN as u64) as usize;
+        block.truncate(len);
+        fill(rng, &mut block);
+        experiment.mode.apply(distribution, &mut block, previous);
+        previous = block.last().copied().or(previous);
+        let skip = experiment.discard.saturating_sub(start).min(len as u64) as usize;
+        if !each(&mut block[skip..])? {
+            break;
+        }
+        start += len as u64;
+    }
+    Ok(())
+}
+
+/// Genera los valores del experimento como `generate`, limitados y
+/// redondeados como en `generate_samples`, en un único vector f64, para los
+/// cálculos que necesitan todos los valores juntos
+///
+/// # Argumentos
+///
+/// * `number` cantidad de valores a devolver
+/// * `experiment` semilla, distribución con sus parámetros y modo de generación
+pub fn generate_finished(number: u64, experiment: &Experiment) -> io::Result<Vec<f64>> {
+    if let Some(bounds) = experiment.rejection() {
+        let draw = |draws, each: &mut BlockSink| experiment_blocks(draws, experiment, each);
+        let values: ChunkedVec<f64> = generate_bounded(number, experiment, bounds, None, draw)?;
+        return Ok(values.into_vec());
+    }
+    let mut values = generate(number, experiment);
+    experiment.finish(&mut values);
+    Ok(values)
+}
+
+/// Genera los valores del experimento como `generate_with`, limitados y
+/// redondeados como en `generate_samples`, en un único vector f64
+///
+/// # Argumentos
+///
+/// * `number` cantidad de valores a devolver
+/// * `experiment` distribución con sus parámetros y modo de generación
+/// * `rng` generador de números aleatorios, en lugar del de la semilla; al
+///   descartar valores fuera de los límites, cada intento empieza de nuevo
+///   desde su estado
+pub fn generate_finished_with(
+    number: u64,
+    experiment: &Experiment,
+    rng: &UniformGenerator,
+) -> io::Result<Vec<f64>> {
+    if let Some(bounds) = experiment.rejection() {
+        let draw = |draws, each: &mut BlockSink| {
+            stream_blocks(draws, experiment, &mut rng.clone(), each)
+        };
+        let values: ChunkedVec<f64> = generate_bounded(number, experiment, bounds, None, draw)?;
+        return Ok(values.into_vec());
+    }
+    let mut values = generate_with(number, experiment, &mut rng.clone());
+    experiment.finish(&mut values);
+    Ok(values)
+}
+
+/// Función que completa un bloque de subflujo con el generador indicado
+type ChunkFiller<'a> = Box<dyn Fn(&mut dyn Random, &mut [f64]) + Sync + 'a>;
+
+// Función privada, completa un bloque de subflujo con valores de la
+// distribución. Según la distribución, se llama al método correcto; la
+// interfaz se usa una vez por bloque, no por valor, por rendimiento
+fn chunk_filler(distribution: &StoredDistribution) -> ChunkFiller<'_> {
+    match distribution {
+        StoredDistribution::Normal(distribution) => Box::new(|rng, chunk| {
+            // Cada bloque comienza sin el par guardado de Box-Muller
+            let mut sampler = distribution.sampler();
+            chunk.iter_mut().for_each(|x| *x = sampler.next(rng));
+        }),
+        StoredDistribution::Uniform(distribution) => Box::new(|rng, chunk| {
+            chunk.iter_mut().for_each(|x| *x = distribution.next(rng));
+        }),
+        StoredDistribution::Exponential(distribution) => Box::new(|rng, chunk| {
+            chunk.iter_mut().for_each(|x| *x = distribution.next(rng));
+        }),
+        StoredDistribution::Poisson(distribution) => Box::new(|rng, chunk| {
+            chunk.iter_mut().for_each(|x| *x = distribution.next(rng));
+        }),
+        StoredDistribution::Gamma(distribution) => Box::new(|rng, chunk| {
+            let mut sampler = distribution.sampler();
+            chunk.iter_mut().for_each(|x| *x = sampler.next(rng));
+        }),
+        StoredDistribution::Tabulated(distribution) => Box::new(|rng, chunk| {
+            chunk.iter_mut().for_each(|x| *x = distribution.next(rng));
+        }),
+    }
+}
+
+/// Función que completa partes sucesivas de un único flujo de valores
+type StreamFiller<'a> = Box<dyn FnMut(&mut dyn Random, &mut [f64]) + 'a>;
+
+// Función privada, como `chunk_filler`, pero conserva el estado de la
+// distribución entre una parte y la siguiente, como el valor pendiente del
+// par de Box-Müller, para que el flujo sea el mismo que completado de una vez
+fn stream_filler(distribution: &StoredDistribution) -> StreamFiller<'_> {
+    match distribution {
+        StoredDistribution::Normal(distribution) => {
+            let mut sampler = distribution.sampler();
+            Box::new(move |rng, chunk| chunk.iter_mut().for_each(|x| *x = sampler.next(rng)))
+        }
+        StoredDistribution::Gamma(distribution) => {
+            let mut sampler = distribution.sampler();
+            Box::new(move |rng, chunk| chunk.iter_mut().for_each(|x| *x = sampler.next(rng)))
+        }
+        distribution => {
+            let fill = chunk_filler(distribution);
+            Box::new(move |rng, chunk| fill(rng, chunk))
+        }
+    }
+}
+
+/// Genera los valores del experimento con el generador indicado, en un solo
+/// bloque, por ejemplo con un subflujo por replicación. Como en `generate`,
+/// primero se generan y descartan los valores iniciales del experimento
+///
+/// # Argumentos
+///
+/// * `number` cantidad de valores a devolver
+/// * `experiment` distribución con sus parámetros y modo de generación
+/// * `rng` generador de números aleatorios, en lugar del de la semilla
+pub fn generate_with(number: u64, experiment: &Experiment, rng: &mut dyn Random) -> Vec<f64> {
+    let distribution = &experiment.distribution;
+    if let GenerationMode::Stratified { strata } = experiment.mode {
+        let dist = distribution.clone().boxed();
+        return sampling::stratified(number as usize, strata, dist.as_ref(), rng);
+    }
+    let discard = experiment.discard as usize;
+    let mut res = vec![0f64; discard + number as usize];
+    chunk_filler(distribution)(rng, &mut res);
+    experiment.mode.apply(distribution, &mut res, None);
+    res.drain(..discard);
+    res
+}
+
+/// Verifica que la cantidad de valores a generar no supere el límite configurado
+pub fn check_number(config: &Config, number: u64) -> Result<(), ApiError> {
+    if number > config.max_numbers {
+        return Err(ApiError::TooLarge(format!(
+            "no se pueden generar más de {} valores",
+            config.max_numbers
+        )));
+    }
+    Ok(())
+}
+
+/// Hace lugar en el presupuesto de memoria para guardar `bytes` más,
+/// descartando conjuntos de datos o devolviendo un error 507 según la
+/// configuración, y los reserva mientras viva la reserva devuelta (ver
+/// `Workspaces::make_room`)
+pub async fn make_room(state: &AppState, bytes: u64) -> Result<Reservation, ApiError> {
+    let config = &state.config;
+    state.datasets.make_room(bytes, config.memory_budget, config.memory_policy).await
+}
+
+/// Devuelve los conjuntos de datos del cliente. Si no tiene ninguno en
+/// memoria, porque se descartaron para respetar el presupuesto de memoria o
+/// no entraron al reiniciar el servidor, recupera su último experimento a
+/// través del almacenamiento (ver `DatasetStore::get`) y lo deja como
+/// conjunto actual
+///
+/// # Argumentos
+///
+/// * `state` estado compartido, con los datos y el almacenamiento
+/// * `client` cliente que realiza la petición
+pub async fn stored_datasets(
+    state: &AppState,
+    client: &Client,
+) -> Result<Arc<RwLock<Datasets>>, ApiError> {
+    let datasets = state.datasets.get(client);
+    if !datasets.read().await.is_empty() {
+        return Ok(datasets);
+    }
+    let store = Arc::clone(&state.store);
+    let owner = client.clone();
+    let snapshot = tokio::task::spawn_blocking(move || store.get(&owner))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let Some(snapshot) = snapshot else { return Ok(datasets) };
+    let generated = Generated::from(snapshot);
+    let _reservation = make_room(state, generated.memory_bytes()).await?;
+    // Otra petición pudo guardar un conjunto mientras se leía
+    let mut guard = datasets.write().await;
+    if guard.is_empty() {
+        guard.insert(generated);
+    }
+    drop(guard);
+    Ok(datasets)
+}
+
+/// Busca el conjunto de datos indicado, o el actual si no se indica ninguno
+pub async fn find_dataset(
+    datasets: &RwLock<Datasets>,
+    id: Option<u64>,
+) -> Result<Generated, ApiError> {
+    datasets
+        .read()
+        .await
+        .get(id)
+        .ok_or_else(|| ApiError::NotFound(format!("no existe el conjunto de datos {}", id.unwrap_or(0))))
+}
+
+/// Span de tracing del cálculo de estadísticas, con la distribución, la
+/// cantidad de valores a usar y la de intervalos (ver `trace::RecentSpans`)
+///
+/// # Argumentos
+///
+/// * `distribution` tipo de distribución, None si el conjunto de datos no
+///   tiene manifiesto de reproducibilidad
+/// * `input` intervalos y parte de los valores a usar
+/// * `len` cantidad de valores de la muestra
+pub fn statistics_span(
+    distribution: Option<&'static str>,
+    input: &StatisticsInput,
+    len: usize,
+) -> tracing::Span {
+    let (from, to) = input.bounds(len);
+    tracing::info_span!(
+        "statistics",
+        distribution,
+        n = to - from,
+        intervals = input.intervals,
+    )
+}
+
+/// Verifica que los valores admitan la escala pedida (ver
+/// `StatisticsInput::check_scale`). Con la escala logarítmica los valores se
+/// recorren en un hilo bloqueante; devuelve los datos de entrada para usarlos
+pub async fn check_scale(
+    input: StatisticsInput,
+    data: &Samples,
+    dist: &Arc<Box<dyn Distribution + Send + Sync>>,
+) -> Result<StatisticsInput, ApiError> {
+    if input.scale == Scale::Linear {
+        return Ok(input);
+    }
+    let (data, dist) = (data.clone(), Arc::clone(dist));
+    tokio::task::spawn_blocking(move || {
+        data.check_scale(&input, dist.as_ref().as_ref()).map(|_| input)
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?
+    .map_err(ApiError::BadRequest)
+}
+
+/// Guarda los valores generados como un nuevo conjunto de datos del cliente,
+/// los registra en el historial y los persiste en disco si está configurado.
+/// En disco los valores se guardan siempre en f64. Quien llama reserva la
+/// memoria de los valores antes de generarlos (ver `make_room`) y mantiene
+/// la reserva hasta que esta función devuelva, cuando el conjunto ya cuenta
+/// en la memoria ocupada. Devuelve el identificador del conjunto de datos y
+/// el manifiesto para repetir la generación
+pub async fn store(
+    state: &AppState,
+    client: &Client,
+    experiment: Experiment,
+    data: Samples,
+    uniforms: Option<Samples>,
+    metadata: Metadata,
+    record: bool,
+) -> Result<(u64, Reproducibility), ApiError> {
+    let manifest = Reproducibility::new(&experiment, data.len() as u64, data.precision());
+    let tested = experiment.tested();
+    // Registrar la generación en el historial
+    if record {
+        state.history.record(client, &manifest, &data.summary(), &metadata)?;
+    }
+    // Guardar los valores generados y la distribución de los valores, contra
+    // la que se hace la prueba de chi cuadrado. Se persiste el manifiesto,
+    // para volver a probarlos contra la misma distribución al recuperarlos
+    let generated = Generated::new(data, tested)
+        .with_metadata(metadata.clone())
+        .with_reproducibility(manifest.clone())
+        .with_uniforms(uniforms);
+    let nums = generated.data.clone();
+    // El turno del guardado se pide mientras se retienen los datos, para
+    // que quede ordenado con los borrados (ver `delete_data`)
+    let workspace = state.datasets.get(client);
+    let (id, ticket) = {
+        let mut datasets = workspace.write().await;
+        (datasets.insert(generated), state.store.ticket(client))
+    };
+    // Persistir el experimento en segundo plano, sin bloquear la respuesta
+    let (store, client, stored) = (Arc::clone(&state.store), client.clone(), manifest.clone());
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = store.save(ticket, &client, &stored, &metadata, &nums) {
+            tracing::error!("failed to save data of client {}: {}", client.0, e);
+        }
+    });
+    Ok((id, manifest))
+}
+
+// Función privada, advertencia si un experimento reciente del cliente usó
+// la misma semilla, generador y distribución, para que se note por qué dos
+// generaciones "distintas" dieron los mismos valores. Se busca antes de
+// registrar la nueva generación, que si no se encontraría a sí misma
+fn duplicate_seed(
+    state: &AppState,
+    client: &Client,
+    experiment: &Experiment,
+) -> Result<Option<DuplicateSeed>, ApiError> {
+    let Some(entry) = state.history.find_duplicate(client, experiment)? else {
+        return Ok(None);
+    };
+    let message = format!(
+        "el experimento {} ya usó la semilla {} con el mismo generador y distribución, \
+         los valores generados son los mismos",
+        entry.id, entry.seed
+    );
+    Ok(Some(DuplicateSeed {
+        id: entry.id,
+        timestamp: entry.timestamp,
+        label: entry.metadata.label,
+        message,
+    }))
+}
+
+/// Método handler de las peticiones de generación de valores. Si se recibe
+/// una lista de parámetros, genera cada conjunto de datos en paralelo y
+/// devuelve sus identificadores. En la API versionada también devuelve el
+/// identificador de un único conjunto; sin versión responde sin cuerpo
+///
+/// # Argumentos
+///
+/// * `State(state)` Estado compartido, con los datos, la configuración y el historial
+/// * `client` cliente que realiza la petición
+/// * `version` versión de la API de la petición
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn get_unified(
+    State(state): State<AppState>,
+    client: Client,
+    version: ApiVersion,
+    format: Format,
+    data: extract::Json<GenerationRequest>,
+) -> Result<Response, ApiError> {
+    match data.0 {
+        GenerationRequest::Single(data) => {
+            let dataset = generate_dataset(&state, &client, data).await?;
+            match version {
+                ApiVersion::Legacy => Ok(StatusCode::OK.into_response()),
+                ApiVersion::V1 => Ok(Negotiated(format, dataset).into_response()),
+            }
+        }
+        GenerationRequest::Batch(batch) => {
+            // Validar todos los parámetros antes de generar. Los conjuntos se
+            // generan a la vez, así que el límite es para la suma
+            let total = batch.iter().fold(0u64, |acc, data| acc.saturating_add(data.total()));
+            check_number(&state.config, total)?;
+            let mut specs = Vec::with_capacity(batch.len());
+            let mut bytes = 0u64;
+            for data in batch {
+                let experiment = data.experiment()?;
+                bytes = bytes.saturating_add(data.estimated_bytes(&experiment));
+                let keep_uniforms = data.keep_uniforms;
+                specs.push((data.number, experiment, data.precision, keep_uniforms, data.metadata));
+            }
+            // La reserva vive hasta guardar todos los conjuntos
+            let _reservation = make_room(&state, bytes).await?;
+            // Generar cada conjunto en una tarea bloqueante
+            let tasks: Vec<_> = specs
+                .into_iter()
+                .map(|(number, experiment, precision, keep_uniforms, metadata)| {
+                    let spill = state.config.spill();
+                    tokio::task::spawn_blocking(move || {
+                        let res =
+                            generate_request(number, &experiment, precision, keep_uniforms, spill);
+                        (experiment, res, metadata)
+                    })
+                })
+                .collect();
+            let mut ids = Vec::with_capacity(tasks.len());
+            for task in tasks {
+                let (experiment, res, metadata) =
+                    task.await.map_err(|e| ApiError::Internal(e.to_string()))?;
+                let (res, uniforms) = res.map_err(|e| ApiError::Internal(e.to_string()))?;
+                let strata = experiment.mode.strata(res.len() as u64, &experiment.distribution);
+                let label = metadata.label.clone();
+                let duplicate = duplicate_seed(&state, &client, &experiment)?;
+                let (id, manifest) =
+                    store(&state, &client, experiment, res, uniforms, metadata, true).await?;
+                ids.push(DatasetId {
+                    id,
+                    label,
+                    strata,
+                    reproducibility: Some(manifest),
+                    duplicate,
+                });
+            }
+            Ok(Negotiated(format, ids).into_response())
+        }
+    }
+}
+
+// Función privada, genera y guarda un único conjunto de datos con los
+// parámetros recibidos, como en `/generate`, y devuelve su identificador
+async fn generate_dataset(
+    state: &AppState,
+    client: &Client,
+    data: GenerationParameters,
+) -> Result<DatasetId, ApiError> {
+    check_number(&state.config, data.total())?;
+    // Parámetros de la distribución, se guardan en el historial y en disco
+    let experiment = data.experiment()?;
+    let (_reservation, res, uniforms) = generate_values(state, &data, &experiment).await?;
+    store_dataset(state, client, data, experiment, res, uniforms).await
+}
+
+// Función privada, genera los valores de un conjunto de datos, y los
+// uniformes si se piden, sin guardarlos. Devuelve también la reserva de su
+// memoria, que debe vivir hasta guardarlos
+async fn generate_values(
+    state: &AppState,
+    data: &GenerationParameters,
+    experiment: &Experiment,
+) -> Result<(Reservation, Samples, Option<Samples>), ApiError> {
+    // Hacer lugar antes de generar, no después de reservar la memoria
+    let reservation = make_room(state, data.estimated_bytes(experiment)).await?;
+    let spill = state.config.spill();
+    let keep_uniforms = data.keep_uniforms;
+    // Generar en una tarea bloqueante, para que el servidor siga
+    // respondiendo, por ejemplo el progreso en /last-run/active
+    let (number, precision) = (data.number, data.precision);
+    let task_experiment = experiment.clone();
+    let (res, uniforms) = tokio::task::spawn_blocking(move || {
+        generate_request(number, &task_experiment, precision, keep_uniforms, spill)
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok((reservation, res, uniforms))
+}
+
+// Función privada, guarda los valores generados como un nuevo conjunto de
+// datos, con la advertencia si repiten un experimento reciente, y devuelve
+// su identificador
+async fn store_dataset(
+    state: &AppState,
+    client: &Client,
+    data: GenerationParameters,
+    experiment: Experiment,
+    res: Samples,
+    uniforms: Option<Samples>,
+) -> Result<DatasetId, ApiError> {
+    let label = data.metadata.label.clone();
+    let strata = experiment.mode.strata(data.number, &experiment.distribution);
+    let duplicate = duplicate_seed(state, client, &experiment)?;
+    let (id, manifest) =
+        store(state, client, experiment, res, uniforms, data.metadata, true).await?;
+    Ok(DatasetId { id, label, strata, reproducibility: Some(manifest), duplicate })
+}
+
+/// Método handler que genera y guarda un conjunto de datos y devuelve en la
+/// misma respuesta su identificador, la primera página de valores y sus
+/// estadísticas, en lugar de pedirlos a `/generate`, `/page` y
+/// `/statistics`. Sólo para hasta `MAX_PREVIEW_NUMBERS` valores
+///
+/// # Argumentos
+///
+/// * `State(state)` Estado compartido, con los datos, la configuración y el historial
+/// * `client` cliente que realiza la petición
+/// * `format` formato de la respuesta, según el header Accept
+/// * `language` idioma de los nombres de los campos de la respuesta
+/// * `data` Datos en Json recibidos del front end
+pub async fn generate_preview(
+    State(state): State<AppState>,
+    client: Client,
+    format: Format,
+    language: Language,
+    data: extract::Json<PreviewParameters>,
+) -> Result<Localized<PreviewResponse>, ApiError> {
+    let PreviewParameters { generation, mut statistics } = data.0;
+    if generation.number > MAX_PREVIEW_NUMBERS {
+        return Err(ApiError::TooLarge(format!(
+            "la vista previa admite hasta {} valores, para más se usa /generate",
+            MAX_PREVIEW_NUMBERS
+        )));
+    }
+    check_number(&state.config, generation.total())?;
+    let experiment = generation.experiment()?;
+    // Validar las estadísticas antes de generar, para no guardar un
+    // conjunto de datos sin responder con él. La distribución contra la
+    // que se prueba ya se conoce
+    let tested = Arc::new(experiment.tested());
+    statistics.significance.get_or_insert(state.config.default_significance());
+    statistics.check_intervals().map_err(ApiError::BadRequest)?;
+    statistics.check_range(generation.number as usize).map_err(ApiError::BadRequest)?;
+    statistics.check_significance().map_err(ApiError::BadRequest)?;
+    statistics.check_alphas().map_err(ApiError::BadRequest)?;
+    statistics.check_trim(tested.as_ref().as_ref()).map_err(ApiError::BadRequest)?;
+    let (_reservation, res, uniforms) = generate_values(&state, &generation, &experiment).await?;
+    // Con la escala logarítmica se verifican también los valores, todavía
+    // sin guardarlos
+    let statistics = check_scale(statistics, &res, &tested).await?;
+    let dataset = store_dataset(&state, &client, generation, experiment, res, uniforms).await?;
+    let datasets = state.datasets.get(&client);
+    let page = find_dataset(&datasets, Some(dataset.id)).await?.data.page(1);
+    let statistics =
+        dataset_statistics(&datasets, &state.config, Some(dataset.id), statistics).await?;
+    Ok(Localized(format, language, PreviewResponse { dataset, page, statistics }))
+}
+
+/// Método handler que genera valores de la distribución transformando los
+/// números uniformes recibidos, en lugar de los de un generador, para
+/// verificar ejercicios resueltos a mano. Guarda los valores como un nuevo
+/// conjunto de datos, con los uniformes usados como flujo crudo, y los
+/// devuelve. Devuelve un error si los uniformes no alcanzan
+///
+/// # Argumentos
+///
+/// * `State(state)` Estado compartido, con los datos y la configuración
+/// * `client` cliente que realiza la petición
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn transform_uniforms(
+    State(state): State<AppState>,
+    client: Client,
+    format: Format,
+    data: extract::Json<TransformParameters>,
+) -> Result<Negotiated<TransformResponse>, ApiError> {
+    let data = data.0;
+    check_number(&state.config, data.number)?;
+    let experiment = Experiment {
+        seed: 0,
+        distribution: StoredDistribution::from_parameters(&data.distribution, &data.data)?,
+        mode: data.mode,
+        discard: 0,
+        generator: Generator::default(),
+        decimals: None,
+        bounds: None,
+    };
+    experiment.validate().map_err(ApiError::BadRequest)?;
+    let available = data.uniforms.len();
+    let mut rng = SequenceGenerator::new(data.uniforms).map_err(ApiError::BadRequest)?;
+    let values = generate_with(data.number, &experiment, &mut rng);
+    if rng.exhausted() {
+        return Err(ApiError::BadRequest(format!(
+            "los {} números uniformes recibidos no alcanzan para generar {} valores",
+            available,
+            data.number
+        )));
+    }
+    let used = rng.used();
+    let mut uniforms = rng.into_values();
+    uniforms.truncate(used);
+    let samples = Samples::new(data.precision, values.clone());
+    let uniforms = Samples::from(uniforms);
+    let _reservation = make_room(&state, samples.memory_bytes() + uniforms.memory_bytes()).await?;
+    let tested = experiment.mode.tested_distribution(&experiment.distribution);
+    let generated = Generated::new(samples, tested.boxed())
+        .with_label(data.label.clone())
+        .with_uniforms(Some(uniforms));
+    let id = state.datasets.get(&client).write().await.insert(generated);
+    let dataset = DatasetId {
+        id,
+        label: data.label,
+        strata: None,
+        reproducibility: None,
+        duplicate: None,
+    };
+    Ok(Negotiated(format, TransformResponse { dataset, values, used }))
+}
+
+/// Método handler que aplica una operación a cada valor de un conjunto de
+/// datos y guarda el resultado como un nuevo conjunto, que pasa a ser el
+/// actual, con los mismos pesos y la precisión del original. El nuevo
+/// conjunto se prueba contra la distribución del original transformada
+/// por la operación (ver `TransformedDistribution`)
+///
+/// # Argumentos
+///
+/// * `State(state)` Estado compartido, con los datos y la configuración
+/// * `client` cliente que realiza la petición
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn transform_dataset(
+    State(state): State<AppState>,
+    client: Client,
+    format: Format,
+    data: extract::Json<DataTransformParameters>,
+) -> Result<Negotiated<DataTransformResponse>, ApiError> {
+    let data = data.0;
+    data.operation.validate().map_err(ApiError::BadRequest)?;
+    let datasets = state.datasets.get(&client);
+    let generated = find_dataset(&datasets, data.dataset).await?;
+    if generated.data.len() == 0 {
+        return Err(ApiError::BadRequest("no hay valores para transformar".into()));
+    }
+    let _reservation = make_room(&state, generated.data.memory_bytes()).await?;
+    let samples = generated.data.clone();
+    let operation = data.operation;
+    let (samples, operation, summary) = tokio::task::spawn_blocking(move || {
+        let transformation = samples.transform(operation)?;
+        let samples = Samples::new(samples.precision(), transformation.values);
+        let summary = samples.summary();
+        Ok::<_, String>((samples, transformation.operation, summary))
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?
+    .map_err(ApiError::BadRequest)?;
+    let dist = TransformedDistribution::new(generated.dist, operation);
+    let mut derived = Generated::new(samples, Box::new(dist)).with_label(data.label.clone());
+    derived.weights = generated.weights;
+    let id = datasets.write().await.insert(derived);
+    let dataset = DatasetId {
+        id,
+        label: data.label,
+        strata: None,
+        reproducibility: None,
+        duplicate: None,
+    };
+    Ok(Negotiated(format, DataTransformResponse { dataset, operation, summary }))
+}
+
+/// Método handler que lista los conjuntos de datos guardados
+///
+/// # Argumentos
+///
+/// * `State(state)` Estado compartido, con los datos y el almacenamiento
+/// * `client` cliente que realiza la petición
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn list_datasets(
+    State(state): State<AppState>,
+    client: Client,
+    format: Format,
+) -> Result<Negotiated<Vec<DatasetInfo>>, ApiError> {
+    let datasets = stored_datasets(&state, &client).await?;
+    let list = datasets.read().await.list();
+    Ok(Negotiated(format, list))
+}
+
+/// Método handler que descarta todos los conjuntos de datos del cliente,
+/// liberando la memoria, y borra el experimento persistido si lo hubiera
+///
+/// # Argumentos
+///
+/// * `State(state)` Estado compartido, con los datos y la configuración
+/// * `client` cliente que realiza la petición
+pub async fn delete_data(
+    State(state): State<AppState>,
+    client: Client,
+) -> Result<StatusCode, ApiError> {
+    let workspace = state.datasets.get(&client);
+    let mut datasets = workspace.write().await;
+    datasets.clear();
+    // Borrar mientras se retienen los datos, para que una generación
+    // concurrente pida su turno antes o después del borrado
+    let store = Arc::clone(&state.store);
+    tokio::task::spawn_blocking(move || store.remove(&client))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    drop(datasets);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Método handler que descarta un conjunto de datos, liberando la memoria
+///
+/// # Argumentos
+///
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `id` identificador del conjunto de datos
+pub async fn delete_dataset(
+    ClientDatasets(datasets): ClientDatasets,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, ApiError> {
+    match datasets.write().await.remove(id) {
+        Some(_) => Ok(StatusCode::NO_CONTENT),
+        None => Err(ApiError::NotFound(format!("no existe el conjunto de datos {}", id))),
+    }
+}
+
+/// Método handler que copia un conjunto de datos con un nuevo identificador,
+/// compartiendo los valores con el original, para conservarlo antes de
+/// modificarlo o probar otro análisis sin volver a generarlo. El conjunto
+/// actual no cambia
+///
+/// # Argumentos
+///
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `format` formato de la respuesta, según el header Accept
+/// * `query` conjunto a copiar y etiqueta de la copia
+pub async fn snapshot_dataset(
+    ClientDatasets(datasets): ClientDatasets,
+    format: Format,
+    Query(query): Query<SnapshotQuery>,
+) -> Result<Negotiated<DatasetId>, ApiError> {
+    let mut datasets = datasets.write().await;
+    let Some(id) = datasets.snapshot(query.dataset, query.label) else {
+        let missing = query.dataset.unwrap_or(0);
+        return Err(ApiError::NotFound(format!("no existe el conjunto de datos {}", missing)));
+    };
+    let generated = datasets.get(Some(id)).unwrap_or_default();
+    let label = generated.metadata.label;
+    let reproducibility = generated.reproducibility;
+    Ok(Negotiated(format, DatasetId { id, label, strata: None, reproducibility, duplicate: None }))
+}
+
+/// Método handler que lista los experimentos del historial del cliente,
+/// opcionalmente sólo los que tienen una etiqueta
+///
+/// # Argumentos
+///
+/// * `State(history)` Historial de experimentos
+/// * `client` cliente que realiza la petición
+/// * `format` formato de la respuesta, según el header Accept
+/// * `query` etiqueta por la que filtrar
+pub async fn get_history(
+    State(history): State<Arc<History>>,
+    client: Client,
+    format: Format,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Negotiated<Vec<HistoryEntry>>, ApiError> {
+    Ok(Negotiated(format, history.list(&client, query.tag.as_deref())?))
+}
+
+// Función privada, genera los valores del experimento como
+// `generate_samples`, en una tarea bloqueante para no detener al ejecutor
+// mientras tanto
+async fn generate_blocking(
+    state: &AppState,
+    number: u64,
+    experiment: &Experiment,
+    precision: Precision,
+) -> Result<Samples, ApiError> {
+    let spill = state.config.spill();
+    let experiment = experiment.clone();
+    tokio::task::spawn_blocking(move || generate_samples(number, &experiment, precision, spill))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
+
+/// Método handler que vuelve a generar un experimento del historial, con la
+/// misma semilla y parámetros, y lo deja como últimos datos generados
+///
+/// # Argumentos
+///
+/// * `State(state)` Estado compartido, con los datos, la configuración y el historial
+/// * `client` cliente que realiza la petición
+/// * `id` identificador del experimento en el historial
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn replay_history(
+    State(state): State<AppState>,
+    client: Client,
+    Path(id): Path<i64>,
+    format: Format,
+) -> Result<Negotiated<HistoryEntry>, ApiError> {
+    let entry = state
+        .history
+        .get(&client, id)?
+        .ok_or_else(|| ApiError::NotFound(format!("no existe el experimento {}", id)))?;
+    check_number(&state.config, entry.number.saturating_add(entry.discard))?;
+    let _reservation =
+        make_room(&state, entry.number.saturating_mul(entry.precision.size())).await?;
+    let experiment = entry.experiment();
+    let res = generate_blocking(&state, entry.number, &experiment, entry.precision).await?;
+    store(&state, &client, experiment, res, None, entry.metadata.clone(), false).await?;
+    Ok(Negotiated(format, entry))
+}
+
+/// Método handler que repite una generación a partir de su manifiesto de
+/// reproducibilidad, devuelto al generar o en el historial. Con la misma
+/// versión de simrng los valores son idénticos bit a bit
+///
+/// # Argumentos
+///
+/// * `State(state)` Estado compartido, con los datos, la configuración y el historial
+/// * `client` cliente que realiza la petición
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` manifiesto de la generación a repetir
+pub async fn reproduce(
+    State(state): State<AppState>,
+    client: Client,
+    format: Format,
+    data: extract::Json<Reproducibility>,
+) -> Result<Negotiated<DatasetId>, ApiError> {
+    let manifest = data.0;
+    manifest.experiment().validate().map_err(ApiError::BadRequest)?;
+    check_number(&state.config, manifest.number.saturating_add(manifest.discard))?;
+    if manifest.version != storage::VERSION {
+        tracing::warn!(
+            "reproducing data generated with simrng {} on {}, values may differ",
+            manifest.version,
+            storage::VERSION
+        );
+    }
+    let _reservation =
+        make_room(&state, manifest.number.saturating_mul(manifest.precision.size())).await?;
+    let experiment = manifest.experiment();
+    let res = generate_blocking(&state, manifest.number, &experiment, manifest.precision).await?;
+    let strata = experiment.mode.strata(manifest.number, &experiment.distribution);
+    let (id, manifest) =
+        store(&state, &client, experiment, res, None, Metadata::default(), true).await?;
+    Ok(Negotiated(format, DatasetId {
+        id,
+        label: None,
+        strata,
+        reproducibility: Some(manifest),
+        duplicate: None,
+    }))
+}
+
+/// Método handler de las peticiones de cálculo de estadísticas
+/// Devuelve Json con histogram, de tipo HistogramData, y test, de tipo
+/// TestResult
+///
+/// # Argumentos
+///
+/// * `query` conjunto de datos a utilizar, el actual si no se indica
+/// * `State(state)` Estado compartido, con los datos, el almacenamiento y la
+///   significancia por defecto
+/// * `client` cliente que realiza la petición
+/// * `format` formato de la respuesta, según el header Accept
+/// * `language` idioma de los nombres de los campos, según `lang` o, bajo
+///   `/api/v1`, el header Accept-Language
+/// * `data` Datos en Json recibidos del front end
+pub async fn get_statistics(
+    Query(query): Query<DatasetQuery>,
+    State(state): State<AppState>,
+    client: Client,
+    format: Format,
+    language: Language,
+    data: extract::Json<StatisticsInput>,
+) -> Result<Localized<StatisticsResponse>, ApiError> {
+    let datasets = stored_datasets(&state, &client).await?;
+    let res = dataset_statistics(&datasets, &state.config, query.dataset, data.0).await?;
+    Ok(Localized(format, language, res))
+}
+
+/// Método handler que devuelve la tabla de cálculo de la prueba de chi
+/// cuadrado en CSV o como tabla de LaTeX, para pegarla en un informe. Recibe
+/// los mismos datos que `/statistics`
+///
+/// # Argumentos
+///
+/// * `query` formato de la tabla y conjunto de datos, el actual si no se indica
+/// * `State(state)` Estado compartido, con los datos, el almacenamiento y la
+///   significancia por defecto
+/// * `client` cliente que realiza la petición
+/// * `data` Datos en Json recibidos del front end
+pub async fn get_statistics_table(
+    Query(query): Query<TableQuery>,
+    State(state): State<AppState>,
+    client: Client,
+    data: extract::Json<StatisticsInput>,
+) -> Result<impl IntoResponse, ApiError> {
+    let datasets = stored_datasets(&state, &client).await?;
+    let res = dataset_statistics(&datasets, &state.config, query.dataset, data.0).await?;
+    let (content_type, table) = match query.format {
+        TableFormat::Csv => ("text/csv", res.test.to_csv()),
+        TableFormat::Latex => ("application/x-latex", res.test.to_latex()),
+    };
+    Ok(([(header::CONTENT_TYPE, content_type)], table))
+}
+
+// Función privada, valida los datos y calcula las estadísticas de un
+// conjunto de datos del cliente, sin mantener el bloqueo durante el cálculo
+async fn dataset_statistics(
+    datasets: &RwLock<Datasets>,
+    config: &Config,
+    dataset: Option<u64>,
+    mut data: StatisticsInput,
+) -> Result<StatisticsResponse, ApiError> {
+    data.significance.get_or_insert(config.default_significance());
+    data.check_intervals().map_err(ApiError::BadRequest)?;
+    let generated = find_dataset(datasets, dataset).await?;
+    data.check_range(generated.data.len()).map_err(ApiError::BadRequest)?;
+    data.check_significance().map_err(ApiError::BadRequest)?;
+    data.check_alphas().map_err(ApiError::BadRequest)?;
+    data.check_trim(generated.dist.as_ref().as_ref()).map_err(ApiError::BadRequest)?;
+    let data = check_scale(data, &generated.data, &generated.dist).await?;
+    let distribution = generated.reproducibility.as_ref().map(|r| r.distribution.name());
+    let span = statistics_span(distribution, &data, generated.data.len());
+    Ok(generated
+        .data
+        .statistics(data, generated.weights, generated.dist)
+        .instrument(span)
+        .await)
+}
+
+/// Método handler que devuelve la media y la varianza a lo largo de la
+/// secuencia, sobre una ventana móvil o sobre todos los valores recorridos,
+/// para ver la convergencia de la media muestral a la teórica al crecer n
+///
+/// # Argumentos
+///
+/// * `query` ventana, cantidad de posiciones y conjunto de datos
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_rolling_statistics(
+    Query(query): Query<RollingQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    format: Format,
+) -> Result<Negotiated<RollingResponse>, ApiError> {
+    let points = query.points.unwrap_or(100);
+    if query.window == Some(0) || points == 0 || points > MAX_ROLLING_POINTS {
+        return Err(ApiError::BadRequest(format!(
+            "window debe ser mayor a 0 y points estar entre 1 y {}",
+            MAX_ROLLING_POINTS
+        )));
+    }
+    let generated = find_dataset(&datasets, query.dataset).await?;
+    let (expected_mean, expected_variance) = generated
+        .reproducibility
+        .as_ref()
+        .map(|manifest| manifest.mode.tested_distribution(&manifest.distribution).moments())
+        .unzip();
+    let window = query.window;
+    let points = tokio::task::spawn_blocking(move || generated.data.rolling(window, points))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Negotiated(format, RollingResponse { window, points, expected_mean, expected_variance }))
+}
+
+/// Valida `max_points` de los endpoints de gráficos y devuelve el límite,
+/// sin límite si no se indica
+fn check_max_points(max_points: Option<usize>) -> Result<usize, ApiError> {
+    match max_points {
+        Some(0) => Err(ApiError::BadRequest("max_points debe ser mayor a 0".into())),
+        Some(max_points) => Ok(max_points),
+        None => Ok(usize::MAX),
+    }
+}
+
+/// Método handler que devuelve el promedio acumulado de un conjunto de
+/// datos junto con la media teórica, para graficar la ley de los grandes
+/// números. Con más valores que `points`, las posiciones se espacian
+/// geométricamente
+///
+/// # Argumentos
+///
+/// * `query` cantidad de posiciones y conjunto de datos a utilizar
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_lln(
+    Query(query): Query<LlnQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    format: Format,
+) -> Result<Negotiated<LlnResponse>, ApiError> {
+    let points = query.points.unwrap_or(1000);
+    if points == 0 || points > MAX_ROLLING_POINTS {
+        return Err(ApiError::BadRequest(format!(
+            "points debe estar entre 1 y {}",
+            MAX_ROLLING_POINTS
+        )));
+    }
+    let points = points.min(check_max_points(query.max_points)?);
+    let generated = find_dataset(&datasets, query.dataset).await?;
+    let (expected_mean, expected_sd) = generated
+        .reproducibility
+        .as_ref()
+        .map(|manifest| {
+            let (mean, variance) =
+                manifest.mode.tested_distribution(&manifest.distribution).moments();
+            (mean, variance.sqrt())
+        })
+        .unzip();
+    let running = tokio::task::spawn_blocking(move || generated.data.running_means(points))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let points = running
+        .into_iter()
+        .map(|running| LlnPoint {
+            error: expected_mean.map(|mean| running.mean - mean),
+            bound: expected_sd.map(|sd| 1.96 * sd / (running.n as f64).sqrt()),
+            running,
+        })
+        .collect();
+    Ok(Negotiated(format, LlnResponse { points, expected_mean, expected_sd }))
+}
+
+/// Método handler que devuelve, para datos de una distribución discreta, la
+/// frecuencia observada y la esperada de cada valor entero, sin agrupar en
+/// intervalos, para comparar barra contra barra
+///
+/// # Argumentos
+///
+/// * `query` conjunto de datos a utilizar, el actual si no se indica
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_discrete_frequencies(
+    Query(query): Query<DatasetQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    format: Format,
+) -> Result<Negotiated<DiscreteFrequencies>, ApiError> {
+    let generated = find_dataset(&datasets, query.dataset).await?;
+    let res = tokio::task::spawn_blocking(move || {
+        generated.data.discrete_frequencies(generated.dist.as_ref().as_ref())
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?
+    .map_err(ApiError::BadRequest)?;
+    Ok(Negotiated(format, res))
+}
+
+/// Método handler que devuelve los cuantiles empíricos de un conjunto de
+/// datos en las probabilidades pedidas, junto con los de su distribución,
+/// para armar gráficos cuantil-cuantil o informes de percentiles. Los
+/// cuantiles empíricos interpolan entre los valores ordenados (ver
+/// `descriptive::quantiles`) y no usan los pesos de las muestras importadas
+///
+/// # Argumentos
+///
+/// * `query` probabilidades y conjunto de datos a utilizar
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_quantiles(
+    Query(query): Query<QuantilesQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    format: Format,
+) -> Result<Negotiated<QuantilesResponse>, ApiError> {
+    let probabilities = match &query.probabilities {
+        Some(list) => parse_probabilities(list)?,
+        None => DEFAULT_QUANTILES.to_vec(),
+    };
+    let generated = find_dataset(&datasets, query.dataset).await?;
+    let count = generated.data.len() as u64;
+    if count == 0 {
+        return Err(ApiError::BadRequest("el conjunto de datos no tiene valores".into()));
+    }
+    let quantiles = tokio::task::spawn_blocking(move || {
+        let empirical = generated.data.quantiles(&probabilities);
+        probabilities
+            .into_iter()
+            .zip(empirical)
+            .map(|(probability, empirical)| QuantileRow {
+                probability,
+                empirical,
+                theoretical: generated.dist.quantile(probability),
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Negotiated(format, QuantilesResponse { count, quantiles }))
+}
+
+// Función privada, lee las probabilidades separadas por comas de la tabla
+// de cuantiles, verificando que estén entre 0 y 1
+fn parse_probabilities(list: &str) -> Result<Vec<f64>, ApiError> {
+    let probabilities = list
+        .split(',')
+        .map(|p| match p.trim().parse::<f64>() {
+            Ok(p) if (0f64..=1f64).contains(&p) => Ok(p),
+            _ => Err(ApiError::BadRequest(format!(
+                "las probabilidades deben ser números entre 0 y 1: {}",
+                p.trim()
+            ))),
+        })
+        .collect::<Result<Vec<f64>, ApiError>>()?;
+    if probabilities.len() > MAX_QUANTILES {
+        return Err(ApiError::BadRequest(format!(
+            "se pueden pedir hasta {} probabilidades",
+            MAX_QUANTILES
+        )));
+    }
+    Ok(probabilities)
+}
+
+/// Método handler que sugiere familias de distribuciones para un conjunto
+/// de datos, por ejemplo importado, a partir de su asimetría, curtosis,
+/// coeficiente de variación y soporte, como paso previo a elegir contra qué
+/// distribución probarlo (ver `suggest::suggest`)
+///
+/// # Argumentos
+///
+/// * `query` conjunto de datos a utilizar, el actual si no se indica
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_suggestion(
+    Query(query): Query<DatasetQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    format: Format,
+) -> Result<Negotiated<SuggestResponse>, ApiError> {
+    let generated = find_dataset(&datasets, query.dataset).await?;
+    let shape = tokio::task::spawn_blocking(move || generated.data.shape())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| {
+            ApiError::BadRequest("se necesitan al menos 2 valores distintos".into())
+        })?;
+    let candidates = suggest(&shape);
+    Ok(Negotiated(format, SuggestResponse { shape, candidates }))
+}
+
+/// Método handler que devuelve las correlaciones de Pearson y de Spearman
+/// entre dos conjuntos de datos del cliente, de la misma longitud, con la
+/// prueba de que son 0, para verificar la independencia de dos flujos
+///
+/// # Argumentos
+///
+/// * `query` conjuntos de datos y nivel de significancia
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `State(config)` Configuración del servidor, con el nivel de significancia por defecto
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_correlation(
+    Query(query): Query<CorrelationQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    State(config): State<Arc<Config>>,
+    format: Format,
+) -> Result<Negotiated<Correlation>, ApiError> {
+    let alpha = query.alpha.unwrap_or(config.default_alpha);
+    if !(alpha > 0f64 && alpha < 1f64) {
+        return Err(ApiError::BadRequest("alpha debe estar entre 0 y 1".into()));
+    }
+    let first = find_dataset(&datasets, Some(query.first)).await?;
+    let second = find_dataset(&datasets, Some(query.second)).await?;
+    let res = tokio::task::spawn_blocking(move || first.data.correlation(&second.data, alpha))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map_err(ApiError::BadRequest)?;
+    Ok(Negotiated(format, res))
+}
+
+/// Método handler que devuelve la traza del estadístico de chi cuadrado
+/// sobre prefijos de tamaño creciente de los datos (1000, 10000, ...), con
+/// los intervalos de la muestra completa
+///
+/// # Argumentos
+///
+/// * `query` conjunto de datos a utilizar, el actual si no se indica
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `State(config)` Configuración del servidor, con la significancia por defecto
+/// * `format` formato de la respuesta, según el header Accept
+/// * `language` idioma de los nombres de los campos, según `lang` o, bajo
+///   `/api/v1`, el header Accept-Language
+/// * `data` Datos en Json recibidos del front end
+pub async fn get_chi_squared_trace(
+    Query(query): Query<DatasetQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    State(config): State<Arc<Config>>,
+    format: Format,
+    language: Language,
+    data: extract::Json<StatisticsInput>,
+) -> Result<Localized<ChiSquaredTrace>, ApiError> {
+    let mut data = data.0;
+    data.significance.get_or_insert(config.default_significance());
+    data.check_intervals().map_err(ApiError::BadRequest)?;
+    let generated = find_dataset(&datasets, query.dataset).await?;
+    data.check_range(generated.data.len()).map_err(ApiError::BadRequest)?;
+    data.check_significance().map_err(ApiError::BadRequest)?;
+    data.check_trim(generated.dist.as_ref().as_ref()).map_err(ApiError::BadRequest)?;
+    let data = check_scale(data, &generated.data, &generated.dist).await?;
+    let trace = tokio::task::spawn_blocking(move || {
+        generated.data.chi_squared_trace(data, generated.dist.as_ref().as_ref())
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Localized(format, language, trace))
+}
+
+/// Método handler que calcula la prueba de chi cuadrado con cada cantidad
+/// de intervalos de un rango, para ver cuánto depende de los intervalos la
+/// decisión de rechazar. Recibe los mismos datos que `/statistics`, salvo
+/// `intervals`, que se ignora
+///
+/// # Argumentos
+///
+/// * `query` rango de cantidades de intervalos y conjunto de datos a utilizar
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `State(config)` Configuración del servidor, con la significancia por defecto
+/// * `format` formato de la respuesta, según el header Accept
+/// * `language` idioma de los nombres de los campos, según `lang` o, bajo
+///   `/api/v1`, el header Accept-Language
+/// * `data` Datos en Json recibidos del front end
+pub async fn get_interval_sensitivity(
+    Query(query): Query<SensitivityQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    State(config): State<Arc<Config>>,
+    format: Format,
+    language: Language,
+    data: extract::Json<StatisticsInput>,
+) -> Result<Localized<IntervalSensitivity>, ApiError> {
+    let min_intervals = query.min_intervals.unwrap_or(5);
+    let max_intervals = query.max_intervals.unwrap_or(30);
+    if min_intervals == 0 || min_intervals > max_intervals {
+        return Err(ApiError::BadRequest(
+            "min_intervals debe ser mayor a 0 y no mayor a max_intervals".into(),
+        ));
+    }
+    if max_intervals > MAX_SENSITIVITY_INTERVALS {
+        return Err(ApiError::BadRequest(format!(
+            "max_intervals debe ser a lo sumo {}",
+            MAX_SENSITIVITY_INTERVALS
+        )));
+    }
+    let mut data = data.0;
+    data.significance.get_or_insert(config.default_significance());
+    let generated = find_dataset(&datasets, query.dataset).await?;
+    data.check_range(generated.data.len()).map_err(ApiError::BadRequest)?;
+    data.check_significance().map_err(ApiError::BadRequest)?;
+    data.check_trim(generated.dist.as_ref().as_ref()).map_err(ApiError::BadRequest)?;
+    let data = check_scale(data, &generated.data, &generated.dist).await?;
+    let res = tokio::task::spawn_blocking(move || {
+        let dist = generated.dist.as_ref().as_ref();
+        generated.data.interval_sensitivity(data, dist, min_intervals, max_intervals)
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Localized(format, language, res))
+}
+
+/// Método handler de petición para mostrar números de una página
+///
+/// # Argumentos
+///
+/// * `State(state)` Estado compartido, con los datos y el almacenamiento
+/// * `client` cliente que realiza la petición
+/// * `page` número de página a devolver y conjunto de datos
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_page_numbers(
+    Query(page): Query<Pagination>,
+    State(state): State<AppState>,
+    client: Client,
+    format: Format,
+) -> Result<Negotiated<Page>, ApiError> {
+    let datasets = stored_datasets(&state, &client).await?;
+    let generated = find_dataset(&datasets, page.dataset).await?;
+    Ok(Negotiated(format, generated.data.page(page.page)))
+}
+
+// Función privada, flujo uniforme crudo del conjunto de datos indicado, o
+// del actual si no se indica ninguno
+async fn find_uniforms(datasets: &RwLock<Datasets>, id: Option<u64>) -> Result<Samples, ApiError> {
+    find_dataset(datasets, id).await?.uniforms.ok_or_else(|| {
+        ApiError::NotFound(
+            "el conjunto de datos no guardó los números uniformes, se generan con keep_uniforms"
+                .into(),
+        )
+    })
+}
+
+/// Método handler de petición para mostrar una página de los números
+/// uniformes crudos con los que se generó un conjunto de datos
+///
+/// # Argumentos
+///
+/// * `page` número de página a devolver y conjunto de datos
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_page_uniforms(
+    Query(page): Query<Pagination>,
+    ClientDatasets(datasets): ClientDatasets,
+    format: Format,
+) -> Result<Negotiated<Page>, ApiError> {
+    let uniforms = find_uniforms(&datasets, page.dataset).await?;
+    Ok(Negotiated(format, uniforms.page(page.page)))
+}
+
+/// Método handler que calcula el histograma y las pruebas de los números
+/// uniformes crudos de un conjunto de datos contra la Uniforme en [0, 1),
+/// para probar el generador por separado de la distribución
+///
+/// # Argumentos
+///
+/// * `query` conjunto de datos a utilizar, el actual si no se indica
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `State(config)` Configuración del servidor, con la significancia por defecto
+/// * `format` formato de la respuesta, según el header Accept
+/// * `language` idioma de los nombres de los campos, según `lang` o, bajo
+///   `/api/v1`, el header Accept-Language
+/// * `data` Datos en Json recibidos del front end
+pub async fn get_uniform_statistics(
+    Query(query): Query<DatasetQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    State(config): State<Arc<Config>>,
+    format: Format,
+    language: Language,
+    data: extract::Json<StatisticsInput>,
+) -> Result<Localized<StatisticsResponse>, ApiError> {
+    let mut data = data.0;
+    data.significance.get_or_insert(config.default_significance());
+    data.check_intervals().map_err(ApiError::BadRequest)?;
+    let uniforms = find_uniforms(&datasets, query.dataset).await?;
+    data.check_range(uniforms.len()).map_err(ApiError::BadRequest)?;
+    data.check_significance().map_err(ApiError::BadRequest)?;
+    data.check_alphas().map_err(ApiError::BadRequest)?;
+    let dist: Box<dyn Distribution + Send + Sync> = Box::new(Uniform { lower: 0f64, upper: 1f64 });
+    let dist = Arc::new(dist);
+    data.check_trim(dist.as_ref().as_ref()).map_err(ApiError::BadRequest)?;
+    let data = check_scale(data, &uniforms, &dist).await?;
+    let span = statistics_span(Some("Uniform"), &data, uniforms.len());
+    let res = uniforms.statistics(data, None, dist).instrument(span).await;
+    Ok(Localized(format, language, res))
+}
+
+/// Método handler que aplica la batería de pruebas del generador a los
+/// números uniformes crudos de un conjunto de datos (ver `battery`)
+///
+/// # Argumentos
+///
+/// * `query` conjunto de datos, significancia y parámetros de las pruebas
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `State(config)` Configuración del servidor, con la significancia por defecto
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_battery(
+    Query(query): Query<BatteryQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    State(config): State<Arc<Config>>,
+    format: Format,
+) -> Result<Negotiated<Battery>, ApiError> {
+    let defaults = BatteryOptions::default();
+    let options = BatteryOptions {
+        alpha: query.alpha.unwrap_or(config.default_alpha),
+        digits: query.digits.unwrap_or(defaults.digits),
+        collision_dimension: query.collision_dimension.unwrap_or(defaults.collision_dimension),
+        collision_divisions: query.collision_divisions.unwrap_or(defaults.collision_divisions),
+        permutation_length: query.permutation_length.unwrap_or(defaults.permutation_length),
+        entropy_bins: query.entropy_bins.unwrap_or(defaults.entropy_bins),
+    };
+    let uniforms = find_uniforms(&datasets, query.dataset).await?;
+    let res = tokio::task::spawn_blocking(move || uniforms.battery(options))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map_err(ApiError::BadRequest)?;
+    Ok(Negotiated(format, res))
+}
+
+/// Método handler que devuelve una submuestra al azar de un conjunto de
+/// datos, de hasta `k` valores elegidos con igual probabilidad y sin
+/// reposición, para ver cómo son los datos sin recorrer todas las páginas.
+/// Con la misma semilla se eligen siempre los mismos valores
+///
+/// # Argumentos
+///
+/// * `query` cantidad de valores, semilla y conjunto de datos
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_subsample(
+    Query(query): Query<SubsampleQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    format: Format,
+) -> Result<Negotiated<SubsampleResponse>, ApiError> {
+    let k = query.k.unwrap_or(1000);
+    if k == 0 || k > MAX_SUBSAMPLE {
+        return Err(ApiError::BadRequest(format!("k debe estar entre 1 y {}", MAX_SUBSAMPLE)));
+    }
+    let data = find_dataset(&datasets, query.dataset).await?.data;
+    let total = data.len() as u64;
+    let seed = query.seed;
+    let sample = tokio::task::spawn_blocking(move || {
+        data.reservoir_sample(k, &mut seeded_rng(seed))
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let values = sample
+        .into_iter()
+        .map(|(position, value)| SubsampleValue { position: position as u64, value })
+        .collect();
+    Ok(Negotiated(format, SubsampleResponse { seed, total, values }))
+}
+
+/// Método handler que devuelve pares de números uniformes crudos sucesivos,
+/// (uᵢ, uᵢ₊₁), para graficarlos uno contra otro y mostrar la estructura en
+/// retículo de un generador congruencial
+///
+/// # Argumentos
+///
+/// * `query` distancia entre pares, cantidad máxima y conjunto de datos
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_pairs(
+    Query(query): Query<PairsQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    format: Format,
+) -> Result<Negotiated<PairsResponse>, ApiError> {
+    let step = query.step.unwrap_or(1);
+    let limit = query.limit.unwrap_or(10_000);
+    if step == 0 || limit == 0 || limit > MAX_PAIRS {
+        return Err(ApiError::BadRequest(format!(
+            "step debe ser mayor a 0 y limit estar entre 1 y {}",
+            MAX_PAIRS
+        )));
+    }
+    let max_points = check_max_points(query.max_points)?;
+    let uniforms = find_uniforms(&datasets, query.dataset).await?;
+    let step = list::spread_step(uniforms.len(), 2, step, max_points);
+    let available = uniforms.len().saturating_sub(1).div_ceil(step) as u64;
+    let pairs = tokio::task::spawn_blocking(move || uniforms.pairs(step, limit))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Negotiated(format, PairsResponse { step, available, pairs }))
+}
+
+/// Método handler que devuelve ternas de números uniformes crudos
+/// sucesivos, (uᵢ, uᵢ₊₁, uᵢ₊₂), para graficarlas en tres dimensiones. Si el
+/// conjunto de datos se generó con un congruencial, cuenta además los planos
+/// paralelos que ocupan las ternas, con hasta `MAX_PLANE_VALUES` valores
+/// (ver `LinearCongruentialGenerator::planes`)
+///
+/// # Argumentos
+///
+/// * `query` distancia entre ternas, cantidad máxima y conjunto de datos
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_triples(
+    Query(query): Query<PairsQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    format: Format,
+) -> Result<Negotiated<TriplesResponse>, ApiError> {
+    let step = query.step.unwrap_or(1);
+    let limit = query.limit.unwrap_or(10_000);
+    if step == 0 || limit == 0 || limit > MAX_PAIRS {
+        return Err(ApiError::BadRequest(format!(
+            "step debe ser mayor a 0 y limit estar entre 1 y {}",
+            MAX_PAIRS
+        )));
+    }
+    let max_points = check_max_points(query.max_points)?;
+    let dataset = find_dataset(&datasets, query.dataset).await?;
+    let uniforms = find_uniforms(&datasets, query.dataset).await?;
+    let step = list::spread_step(uniforms.len(), 3, step, max_points);
+    let available = uniforms.len().saturating_sub(2).div_ceil(step) as u64;
+    // Los uniformes del congruencial son los estados desde la semilla
+    let lcg = dataset.reproducibility.and_then(|r| r.generator.lcg(r.seed));
+    let count = (uniforms.len() as u64).min(MAX_PLANE_VALUES);
+    let (triples, planes) = tokio::task::spawn_blocking(move || {
+        (uniforms.triples(step, limit), lcg.and_then(|lcg| lcg.planes(count)))
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Negotiated(format, TriplesResponse { step, available, triples, planes }))
+}
+
+/// Método handler que exporta un conjunto de datos como texto, un valor por
+/// línea. La respuesta se envía por bloques, para poder comprimirla a
+/// medida que se genera
+///
+/// # Argumentos
+///
+/// * `query` conjunto de datos a exportar, el actual si no se indica
+/// * `State(state)` Estado compartido, con los datos y el almacenamiento
+/// * `client` cliente que realiza la petición
+pub async fn export_data(
+    Query(query): Query<DatasetQuery>,
+    State(state): State<AppState>,
+    client: Client,
+) -> Result<impl IntoResponse, ApiError> {
+    let datasets = stored_datasets(&state, &client).await?;
+    let nums = find_dataset(&datasets, query.dataset).await?.data;
+    let chunks = nums.len().div_ceil(list::EXPORT_CHUNK);
+    let stream = futures_util::stream::iter(0..chunks)
+        .map(move |chunk| Ok::<_, std::convert::Infallible>(nums.export_chunk(chunk)));
+    Ok((
+        [(header::CONTENT_TYPE, "text/csv")],
+        StreamBody::new(stream),
+    ))
+}
+
+/// Método handler que devuelve el histograma de un conjunto de datos como
+/// imagen SVG, opcionalmente con la curva teórica superpuesta
+///
+/// # Argumentos
+///
+/// * `query` cantidad de intervalos, si se superpone la curva teórica y
+///   conjunto de datos
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+pub async fn get_histogram_svg(
+    Query(query): Query<PlotQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+) -> Result<impl IntoResponse, ApiError> {
+    let intervals = query.intervals.min(check_max_points(query.max_points)?);
+    let input = StatisticsInput {
+        intervals,
+        significance: None,
+        final_bin: query.final_bin,
+        from: None,
+        to: None,
+        scale: Scale::Linear,
+        trim: None,
+        binning: query.binning,
+        alphas: None,
+        parameters_estimated: false,
+        extended: false,
+    };
+    input.check_intervals().map_err(ApiError::BadRequest)?;
+    let arc = find_dataset(&datasets, query.dataset).await?;
+    let res = arc.data.statistics(input, arc.weights.clone(), arc.dist.clone()).await;
+    let histogram = res.histogram;
+    let expected = query.overlay.then(|| expected_frequencies(&arc, &histogram));
+    let svg = plot::histogram_svg(&histogram, expected.as_deref())
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg))
+}
+
+// Función privada, frecuencias esperadas por intervalo según la
+// distribución del conjunto de datos, sobre los mismos límites del
+// histograma, para superponerlas en el gráfico
+fn expected_frequencies(generated: &Generated, histogram: &HistogramData) -> Vec<f64> {
+    let probs = match &histogram.widths {
+        // Con intervalos por cuantiles, por diferencias de la función acumulada
+        Some(widths) => histogram
+            .x
+            .iter()
+            .zip(widths)
+            .map(|(x, w)| generated.dist.cdf(x + w / 2f64) - generated.dist.cdf(x - w / 2f64))
+            .collect(),
+        None => generated.dist.get_expected(histogram.y.len(), histogram.lower, histogram.upper),
+    };
+    probs.iter().map(|p| p * generated.data.len() as f64).collect()
+}
+
+/// Método handler que devuelve el informe de un conjunto de datos como una
+/// página HTML autocontenida, armada en el servidor: parámetros de la
+/// generación, estadísticos descriptivos, histograma con la curva teórica,
+/// tabla de la prueba de chi cuadrado y conclusiones de las pruebas, para
+/// entregar los resultados sin capturas de pantalla
+///
+/// # Argumentos
+///
+/// * `query` cantidad de intervalos y conjunto de datos
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `State(config)` Configuración del servidor, con la significancia por defecto
+pub async fn get_report(
+    Query(query): Query<ReportQuery>,
+    ClientDatasets(datasets): ClientDatasets,
+    State(config): State<Arc<Config>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let input = StatisticsInput {
+        intervals: query.intervals.unwrap_or(10),
+        significance: None,
+        final_bin: query.final_bin,
+        from: None,
+        to: None,
+        scale: Scale::Linear,
+        trim: None,
+        binning: query.binning,
+        alphas: None,
+        parameters_estimated: false,
+        extended: false,
+    };
+    let generated = find_dataset(&datasets, query.dataset).await?;
+    let statistics = dataset_statistics(&datasets, &config, query.dataset, input).await?;
+    let data = generated.data.clone();
+    let summary = tokio::task::spawn_blocking(move || data.summary())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let expected = expected_frequencies(&generated, &statistics.histogram);
+    let histogram = plot::histogram_svg(&statistics.histogram, Some(&expected))
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let html = report::report_html(&report::Report {
+        metadata: &generated.metadata,
+        manifest: generated.reproducibility.as_ref(),
+        summary: &summary,
+        statistics: &statistics,
+        histogram: &histogram,
+    });
+    Ok(([(header::CONTENT_TYPE, "text/html; charset=utf-8")], html))
+}
+
+/// Genera los valores de un experimento a comparar, sin guardarlos en el
+/// estado, limitados y redondeados como al generarlos (ver
+/// `generate_finished`), y la distribución contra la que se prueban. Hace
+/// lugar en el presupuesto de memoria para los valores y su copia ordenada
+/// de la prueba de Kolmogorov-Smirnov, reservándolo mientras viva la
+/// reserva devuelta, y genera en una tarea bloqueante
+async fn generate_run(
+    state: &AppState,
+    client: &Client,
+    run: CompareRun,
+) -> Result<(Reservation, Vec<f64>, Box<dyn Distribution + Send + Sync>), ApiError> {
+    let (number, experiment) = match run {
+        CompareRun::Stored { id } => {
+            let entry = state
+                .history
+                .get(client, id)?
+                .ok_or_else(|| ApiError::NotFound(format!("no existe el experimento {}", id)))?;
+            (entry.number, entry.experiment())
+        }
+        CompareRun::Generate(params) => (params.number, params.experiment()?),
+    };
+    check_number(&state.config, number.saturating_add(experiment.discard))?;
+    let reservation = make_room(state, number.saturating_mul(16)).await?;
+    let (values, tested) = tokio::task::spawn_blocking(move || {
+        generate_finished(number, &experiment).map(|values| (values, experiment.tested()))
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok((reservation, values, tested))
+}
+
+/// Método handler que compara dos experimentos, del historial o nuevos,
+/// devolviendo sus histogramas, estadísticos descriptivos y la prueba de
+/// Kolmogorov-Smirnov de dos muestras. Los valores se limitan y redondean
+/// como al guardarlos. No modifica los últimos datos generados
+///
+/// # Argumentos
+///
+/// * `State(state)` Estado compartido, con la configuración y el historial
+/// * `client` cliente que realiza la petición
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn compare(
+    State(state): State<AppState>,
+    client: Client,
+    format: Format,
+    data: extract::Json<CompareParameters>,
+) -> Result<Negotiated<CompareResponse>, ApiError> {
+    let data = data.0;
+    let alpha = data.alpha.unwrap_or(state.config.default_alpha);
+    if !(alpha > 0f64 && alpha < 1f64) {
+        return Err(ApiError::BadRequest("alpha debe estar entre 0 y 1".into()));
+    }
+    let significance = state.config.default_significance();
+    let input = || StatisticsInput {
+        intervals: data.intervals,
+        significance: Some(significance),
+        final_bin: FinalBin::default(),
+        from: None,
+        to: None,
+        scale: Scale::Linear,
+        trim: None,
+        binning: Binning::EqualWidth,
+        alphas: None,
+        parameters_estimated: false,
+        extended: false,
+    };
+    input().check_intervals().map_err(ApiError::BadRequest)?;
+    let mut sides = Vec::with_capacity(2);
+    let mut samples = Vec::with_capacity(2);
+    // Las reservas viven hasta terminar la prueba de dos muestras
+    let mut reservations = Vec::with_capacity(2);
+    for run in [data.first, data.second] {
+        let (reservation, nums, distribution) = generate_run(&state, &client, run).await?;
+        reservations.push(reservation);
+        let nums = Arc::new(nums);
+        let statistics =
+            full_statistics(input(), nums.clone(), None, Arc::new(distribution)).await;
+        sides.push(CompareSide { summary: summary(nums.as_ref()), statistics });
+        samples.push(nums);
+    }
+    let test = ks::two_sample(&samples[0], &samples[1], alpha);
+    let second = sides.pop().unwrap();
+    let first = sides.pop().unwrap();
+    Ok(Negotiated(format, CompareResponse { first, second, test }))
+}
+
+/// Método handler que estima la integral de una función por Monte Carlo,
+/// devolviendo la estimación, su error estándar y la traza de convergencia
+///
+/// # Argumentos
+///
+/// * `State(config)` Configuración del servidor, con el máximo de valores
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn integrate(
+    State(config): State<Arc<Config>>,
+    format: Format,
+    data: extract::Json<MonteCarloParameters>,
+) -> Result<Negotiated<Estimate>, ApiError> {
+    let data = data.0;
+    check_number(&config, data.samples)?;
+    if data.lower >= data.upper {
+        return Err(ApiError::BadRequest("lower debe ser menor a upper".into()));
+    }
+    let f = Expr::parse(&data.function).map_err(ApiError::BadRequest)?;
+    let res = tokio::task::spawn_blocking(move || {
+        let mut rng = seeded_rng(data.seed);
+        montecarlo::integrate(|x| f.eval(x), data.lower, data.upper, data.samples, data.trace_points, &mut rng)
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Negotiated(format, res))
+}
+
+/// Método handler que estima una integral por Monte Carlo ajustada con una
+/// variable de control, informando la reducción de varianza lograda
+///
+/// # Argumentos
+///
+/// * `State(config)` Configuración del servidor, con el máximo de valores
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn integrate_control_variates(
+    State(config): State<Arc<Config>>,
+    format: Format,
+    data: extract::Json<ControlVariateParameters>,
+) -> Result<Negotiated<ControlVariateEstimate>, ApiError> {
+    let data = data.0;
+    check_number(&config, data.samples)?;
+    if data.lower >= data.upper {
+        return Err(ApiError::BadRequest("lower debe ser menor a upper".into()));
+    }
+    let f = Expr::parse(&data.function).map_err(ApiError::BadRequest)?;
+    let (g, control_mean) = match (&data.control, data.control_mean) {
+        (None, _) => (Expr::parse("x").map_err(ApiError::BadRequest)?, (data.lower + data.upper) / 2f64),
+        (Some(control), Some(mean)) => (Expr::parse(control).map_err(ApiError::BadRequest)?, mean),
+        (Some(_), None) => {
+            return Err(ApiError::BadRequest("control necesita control_mean".into()));
+        }
+    };
+    let res = tokio::task::spawn_blocking(move || {
+        let mut rng = seeded_rng(data.seed);
+        montecarlo::control_variates(
+            |x| f.eval(x),
+            |x| g.eval(x),
+            control_mean,
+            data.lower,
+            data.upper,
+            data.samples,
+            &mut rng,
+        )
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Negotiated(format, res))
+}
+
+/// Método handler que simula una cola M/M/1 o M/M/c, devolviendo las medidas
+/// de desempeño con intervalos de confianza y sus valores teóricos
+///
+/// # Argumentos
+///
+/// * `State(config)` Configuración del servidor, con el máximo de valores
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn simulate_queue(
+    State(config): State<Arc<Config>>,
+    format: Format,
+    data: extract::Json<QueueRequest>,
+) -> Result<Negotiated<QueueReport>, ApiError> {
+    let QueueRequest { seed, queue: params } = data.0;
+    if params.arrival_rate <= 0f64 || params.service_rate <= 0f64 {
+        return Err(ApiError::BadRequest("las tasas deben ser mayores a 0".into()));
+    }
+    if params.servers == 0 || params.customers == 0 {
+        return Err(ApiError::BadRequest("servers y customers deben ser mayores a 0".into()));
+    }
+    if params.replications < 2 {
+        return Err(ApiError::BadRequest("se necesitan al menos 2 replicaciones".into()));
+    }
+    check_number(&config, params.customers.saturating_mul(params.replications as u64))?;
+    let res = tokio::task::spawn_blocking(move || queue::simulate(&params, &mut seeded_rng(seed)))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Negotiated(format, res))
+}
+
+/// Método handler que simula un inventario con revisión periódica y política
+/// (s, S), devolviendo los costos por período y el nivel de servicio con
+/// intervalos de confianza
+///
+/// # Argumentos
+///
+/// * `State(config)` Configuración del servidor, con el máximo de valores
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn simulate_inventory(
+    State(config): State<Arc<Config>>,
+    format: Format,
+    data: extract::Json<InventoryRequest>,
+) -> Result<Negotiated<InventoryReport>, ApiError> {
+    let InventoryRequest { seed, inventory: params } = data.0;
+    if params.reorder_point > params.order_up_to {
+        return Err(ApiError::BadRequest("reorder_point no puede superar a order_up_to".into()));
+    }
+    if params.demand_rate <= 0f64 || params.periods == 0 {
+        return Err(ApiError::BadRequest("demand_rate y periods deben ser mayores a 0".into()));
+    }
+    if params.replications < 2 {
+        return Err(ApiError::BadRequest("se necesitan al menos 2 replicaciones".into()));
+    }
+    check_number(&config, params.periods.saturating_mul(params.replications as u64))?;
+    let res = tokio::task::spawn_blocking(move || inventory::simulate(&params, &mut seeded_rng(seed)))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Negotiated(format, res))
+}
+
+/// Método handler que simula una cadena de Markov, devolviendo la ocupación de
+/// cada estado comparada con la distribución estacionaria
+///
+/// # Argumentos
+///
+/// * `State(config)` Configuración del servidor, con el máximo de valores y la
+///   significancia por defecto
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn simulate_markov(
+    State(config): State<Arc<Config>>,
+    format: Format,
+    data: extract::Json<MarkovRequest>,
+) -> Result<Negotiated<MarkovReport>, ApiError> {
+    let MarkovRequest { seed, chain: mut params } = data.0;
+    markov::validate(&params.transitions).map_err(ApiError::BadRequest)?;
+    if params.initial >= params.transitions.len() {
+        return Err(ApiError::BadRequest(format!("no existe el estado {}", params.initial)));
+    }
+    check_number(&config, params.steps)?;
+    params.significance.get_or_insert(config.default_significance());
+    let res = tokio::task::spawn_blocking(move || markov::simulate(&params, &mut seeded_rng(seed)))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Negotiated(format, res))
+}
+
+/// Método handler que compara, para cada dimensión, la varianza del
+/// estimador de la media con hipercubo latino y con muestreo aleatorio simple
+///
+/// # Argumentos
+///
+/// * `State(config)` Configuración del servidor, con el máximo de valores
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn compare_latin_hypercube(
+    State(config): State<Arc<Config>>,
+    format: Format,
+    data: extract::Json<LatinHypercubeParameters>,
+) -> Result<Negotiated<Vec<VarianceComparison>>, ApiError> {
+    let data = data.0;
+    if data.samples == 0 || data.dimensions.is_empty() {
+        return Err(ApiError::BadRequest("samples y dimensions no pueden estar vacíos".into()));
+    }
+    if data.replications < 2 {
+        return Err(ApiError::BadRequest("se necesitan al menos 2 replicaciones".into()));
+    }
+    // Se generan dos muestras (hipercubo y simple) por replicación
+    let values = (data.samples as u64)
+        .saturating_mul(data.replications as u64)
+        .saturating_mul(2 * data.dimensions.len() as u64);
+    check_number(&config, values)?;
+    let dists = data
+        .dimensions
+        .iter()
+        .map(|d| Ok(StoredDistribution::from_parameters(&d.distribution, &d.data)?.boxed()))
+        .collect::<Result<Vec<_>, ApiError>>()?;
+    let res = tokio::task::spawn_blocking(move || {
+        let dists: Vec<&dyn Distribution> = dists.iter().map(|d| d.as_ref() as &dyn Distribution).collect();
+        sampling::compare(data.samples, &dists, data.replications, &mut seeded_rng(data.seed))
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Negotiated(format, res))
+}
+
+/// Método handler del remuestreo bootstrap: elige con reposición valores de
+/// un conjunto guardado o de una muestra subida, y guarda el resultado como
+/// un nuevo conjunto de datos, sobre el que se pueden calcular todas las
+/// estadísticas. Un conjunto guardado se prueba contra su misma
+/// distribución; una muestra subida, contra la indicada en la petición
+///
+/// # Argumentos
+///
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `State(workspaces)` Conjuntos de datos de todos los clientes, para el
+///   presupuesto de memoria
+/// * `State(config)` Configuración del servidor, con el máximo de valores
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn resample(
+    ClientDatasets(datasets): ClientDatasets,
+    State(workspaces): State<Arc<Workspaces>>,
+    State(config): State<Arc<Config>>,
+    format: Format,
+    data: extract::Json<ResampleParameters>,
+) -> Result<Negotiated<DatasetId>, ApiError> {
+    let data = data.0;
+    let (values, dist, precision) = match data.values {
+        Some(values) => {
+            let (Some(kind), Some(params)) = (&data.distribution, &data.data) else {
+                return Err(ApiError::BadRequest(
+                    "una muestra subida necesita distribution y data".into(),
+                ));
+            };
+            let dist = StoredDistribution::from_parameters(kind, params)?.boxed();
+            (Arc::new(values), Arc::new(dist), Precision::F64)
+        }
+        None => {
+            // El remuestreo se guarda en la misma precisión que el original
+            let generated = find_dataset(&datasets, data.dataset).await?;
+            (generated.data.to_f64(), generated.dist, generated.data.precision())
+        }
+    };
+    if values.is_empty() {
+        return Err(ApiError::BadRequest("no hay valores para remuestrear".into()));
+    }
+    let number = data.number.unwrap_or(values.len() as u64);
+    check_number(&config, number)?;
+    let bytes = number.saturating_mul(precision.size());
+    let _reservation =
+        workspaces.make_room(bytes, config.memory_budget, config.memory_policy).await?;
+    let res = tokio::task::spawn_blocking(move || {
+        sampling::bootstrap(&values, number as usize, &mut seeded_rng(data.seed))
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let generated = Generated {
+        data: Samples::new(precision, res),
+        dist,
+        metadata: Metadata::labeled(data.label.clone()),
+        reproducibility: None,
+        weights: None,
+        uniforms: None,
+    };
+    let id = datasets.write().await.insert(generated);
+    Ok(Negotiated(format, DatasetId {
+        id,
+        label: data.label,
+        strata: None,
+        reproducibility: None,
+        duplicate: None,
+    }))
+}
+
+/// Método handler que importa una muestra, opcionalmente con pesos, como un
+/// nuevo conjunto de datos probado contra la distribución indicada. Las
+/// estadísticas del conjunto usan los pesos
+///
+/// # Argumentos
+///
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `State(workspaces)` Conjuntos de datos de todos los clientes, para el
+///   presupuesto de memoria
+/// * `State(config)` Configuración del servidor, con el máximo de valores
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn import_data(
+    ClientDatasets(datasets): ClientDatasets,
+    State(workspaces): State<Arc<Workspaces>>,
+    State(config): State<Arc<Config>>,
+    format: Format,
+    data: extract::Json<ImportParameters>,
+) -> Result<Negotiated<ImportResponse>, ApiError> {
+    let data = data.0;
+    if data.values.is_empty() {
+        return Err(ApiError::BadRequest("no hay valores para importar".into()));
+    }
+    check_number(&config, data.values.len() as u64)?;
+    if let Some(weights) = &data.weights {
+        check_weights(weights, data.values.len()).map_err(ApiError::BadRequest)?;
+    }
+    let dist = StoredDistribution::from_parameters(&data.distribution, &data.data)?.boxed();
+    let weights = data.weights.as_ref().map_or(0, Vec::len) as u64;
+    let bytes = (data.values.len() as u64).saturating_mul(data.precision.size()) + weights * 8;
+    let _reservation =
+        workspaces.make_room(bytes, config.memory_budget, config.memory_policy).await?;
+    let samples = Samples::new(data.precision, data.values);
+    let summary = match &data.weights {
+        Some(weights) => samples.weighted_summary(weights),
+        None => samples.summary(),
+    };
+    let effective_size = data.weights.as_deref().map(effective_size);
+    let generated = Generated::new(samples, dist)
+        .with_label(data.label.clone())
+        .with_weights(data.weights);
+    let id = datasets.write().await.insert(generated);
+    let dataset = DatasetId {
+        id,
+        label: data.label,
+        strata: None,
+        reproducibility: None,
+        duplicate: None,
+    };
+    Ok(Negotiated(format, ImportResponse { dataset, summary, effective_size }))
+}
+
+/// Método handler que lee un CSV de valores en una sola pasada, a medida
+/// que llega el cuerpo, y devuelve sus estadísticas sin guardar los
+/// valores, para archivos más grandes que la memoria. El histograma usa
+/// los intervalos indicados en la consulta (ver `StreamingBins`), y si se
+/// indica una distribución se aplica la prueba de chi cuadrado
+///
+/// # Argumentos
+///
+/// * `State(config)` Configuración del servidor, con el nivel de significancia
+/// * `format` formato de la respuesta, según el header Accept
+/// * `query` intervalos, distribución y nivel de significancia
+/// * `body` archivo CSV, con un valor por línea en la primera columna
+pub async fn import_stream(
+    State(config): State<Arc<Config>>,
+    format: Format,
+    Query(query): Query<StreamImportQuery>,
+    mut body: BodyStream,
+) -> Result<Negotiated<StreamingStatistics>, ApiError> {
+    let bins = StreamingBins { lower: query.lower, upper: query.upper, intervals: query.intervals };
+    bins.validate().map_err(ApiError::BadRequest)?;
+    let significance = match query.alpha {
+        Some(alpha) => significance_for_alpha(alpha).ok_or_else(|| {
+            ApiError::BadRequest(format!("el nivel de significancia {} no está tabulado", alpha))
+        })?,
+        None => config.default_significance(),
+    };
+    let dist = match (&query.distribution, &query.data) {
+        (Some(kind), Some(data)) => {
+            let data: serde_json::Value = serde_json::from_str(data)?;
+            Some(StoredDistribution::from_parameters(kind, &data)?.boxed())
+        }
+        (None, None) => None,
+        _ => return Err(ApiError::BadRequest("distribution y data se indican juntos".into())),
+    };
+
+    // Las partes del cuerpo se juntan hasta PARSE_BATCH bytes, que se
+    // interpretan en paralelo en un hilo bloqueante, sin ocupar el runtime
+    let mut acc = StreamingAccumulator::new(bins);
+    let mut csv = CsvValues::default();
+    let mut batch = Vec::new();
+    loop {
+        let chunk = body.next().await.transpose();
+        let chunk = chunk.map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        let last = chunk.is_none();
+        batch.extend_from_slice(chunk.as_deref().unwrap_or_default());
+        if batch.len() < PARSE_BATCH && !last {
+            continue;
+        }
+        let bytes = std::mem::take(&mut batch);
+        let parsed = tokio::task::spawn_blocking(move || {
+            csv.feed_into(&bytes, &mut acc)?;
+            if last {
+                std::mem::take(&mut csv).finish_into(&mut acc)?;
+            }
+            Ok::<_, String>((csv, acc))
+        });
+        (csv, acc) = parsed
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .map_err(ApiError::BadRequest)?;
+        if last {
+            break;
+        }
+    }
+    let dist = dist.as_deref().map(|d| d as &dyn Distribution);
+    Ok(Negotiated(format, acc.finish(dist, significance, query.parameters_estimated)))
+}
+
+/// Método handler que genera una muestra multivariada con dependencia, a
+/// partir de una cópula y las distribuciones marginales, y guarda cada
+/// dimensión como un conjunto de datos, probado contra su marginal
+///
+/// # Argumentos
+///
+/// * `ClientDatasets(datasets)` Conjuntos de datos del cliente
+/// * `State(workspaces)` Conjuntos de datos de todos los clientes, para el
+///   presupuesto de memoria
+/// * `State(config)` Configuración del servidor, con el máximo de valores
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn generate_copula(
+    ClientDatasets(datasets): ClientDatasets,
+    State(workspaces): State<Arc<Workspaces>>,
+    State(config): State<Arc<Config>>,
+    format: Format,
+    data: extract::Json<CopulaParameters>,
+) -> Result<Negotiated<CopulaResponse>, ApiError> {
+    let data = data.0;
+    data.copula.validate().map_err(ApiError::BadRequest)?;
+    let dims = data.copula.dimensions();
+    if data.marginals.len() != dims {
+        return Err(ApiError::BadRequest(format!("se necesitan {} distribuciones marginales", dims)));
+    }
+    check_number(&config, data.number.saturating_mul(dims as u64))?;
+    let bytes = data.number.saturating_mul(dims as u64 * 8);
+    let _reservation =
+        workspaces.make_room(bytes, config.memory_budget, config.memory_policy).await?;
+    let marginals = data
+        .marginals
+        .iter()
+        .map(|d| Ok(StoredDistribution::from_parameters(&d.distribution, &d.data)?.boxed()))
+        .collect::<Result<Vec<_>, ApiError>>()?;
+    let copula = data.copula;
+    let (sample, marginals) = tokio::task::spawn_blocking(move || {
+        let dists: Vec<&dyn Distribution> =
+            marginals.iter().map(|d| d.as_ref() as &dyn Distribution).collect();
+        let sample = copula.sample(data.number as usize, &dists, &mut seeded_rng(data.seed));
+        (sample, marginals)
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let mut ids = Vec::with_capacity(dims);
+    let mut datasets = datasets.write().await;
+    for (i, (column, dist)) in sample.columns.into_iter().zip(marginals).enumerate() {
+        let label = data.label.as_ref().map(|l| format!("{} {}", l, i));
+        let id = datasets.insert(Generated::new(column, dist).with_label(label.clone()));
+        ids.push(DatasetId { id, label, strata: None, reproducibility: None, duplicate: None });
+    }
+    Ok(Negotiated(format, CopulaResponse { datasets: ids, kendall: sample.kendall }))
+}
+
+/// Método handler que verifica los parámetros de un generador congruencial
+/// lineal antes de usarlos: devuelve su período y advierte si la semilla
+/// 0 con c = 0, un c no coprimo con m, o más valores que el período
+/// producirían una secuencia degenerada
+///
+/// # Argumentos
+///
+/// * `State(config)` Configuración del servidor, con el máximo de valores
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn check_lcg(
+    State(config): State<Arc<Config>>,
+    format: Format,
+    data: extract::Json<LcgParameters>,
+) -> Result<Negotiated<LcgReport>, ApiError> {
+    let data = data.0;
+    let m = data.m.unwrap_or(LCG_M);
+    if m == 0 {
+        return Err(ApiError::BadRequest("m debe ser mayor a 0".into()));
+    }
+    check_number(&config, data.number)?;
+    let a = data.a.unwrap_or(LCG_A);
+    let c = data.c.unwrap_or(LCG_C);
+    let lcg = LinearCongruentialGenerator::new(data.seed, m, a, c);
+    // El período se busca recorriendo la secuencia, en una cantidad de
+    // pasos proporcional a los valores pedidos
+    let report = tokio::task::spawn_blocking(move || {
+        let warnings = lcg.diagnose(data.number);
+        let limit = data.number.saturating_add(1).saturating_mul(4);
+        for warning in &warnings {
+            tracing::warn!("generador congruencial degenerado: {}", warning);
+        }
+        LcgReport {
+            period: lcg.period(limit).map(|(length, _)| length),
+            full_period: lcg.full_period(),
+            messages: warnings.iter().map(|w| w.to_string()).collect(),
+            warnings,
+        }
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Negotiated(format, report))
+}
+
+/// Método handler que mide el rendimiento de la generación en el hardware
+/// del servidor: genera `BENCHMARK_SAMPLES` valores con cada combinación de
+/// generador y distribución, una a la vez, y devuelve los valores generados
+/// por segundo. Los valores no se guardan
+///
+/// # Argumentos
+///
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn benchmark(format: Format) -> Result<Negotiated<BenchmarkResponse>, ApiError> {
+    let generators = [
+        Generator::Xoshiro256PlusPlus,
+        Generator::Lcg { m: LCG_M, a: LCG_A, c: LCG_C },
+    ];
+    let distributions = [
+        StoredDistribution::Uniform(Uniform { lower: 0f64, upper: 1f64 }),
+        StoredDistribution::Normal(Normal::default()),
+        StoredDistribution::Normal(Normal {
+            algorithm: Algorithm::Convolution,
+            ..Normal::default()
+        }),
+        StoredDistribution::Exponential(Exponential::default()),
+        StoredDistribution::Poisson(Poisson::default()),
+        StoredDistribution::Gamma(Gamma::default()),
+    ];
+    let results = tokio::task::spawn_blocking(move || {
+        let mut results = Vec::with_capacity(generators.len() * distributions.len());
+        for generator in generators {
+            for distribution in &distributions {
+                let experiment = Experiment {
+                    seed: 1,
+                    distribution: distribution.clone(),
+                    mode: GenerationMode::Independent,
+                    discard: 0,
+                    generator,
+                    decimals: None,
+                    bounds: None,
+                };
+                let start = Instant::now();
+                let values = generate(BENCHMARK_SAMPLES, &experiment);
+                let seconds = start.elapsed().as_secs_f64();
+                std::hint::black_box(values);
+                results.push(BenchmarkResult {
+                    generator,
+                    distribution: distribution.clone(),
+                    seconds,
+                    samples_per_second: BENCHMARK_SAMPLES as f64 / seconds,
+                });
+            }
+        }
+        results
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Negotiated(format, BenchmarkResponse { samples: BENCHMARK_SAMPLES, results }))
+}
+
+/// Memoria ocupada por los conjuntos de datos de todos los clientes
+#[derive(Serialize)]
+pub struct MemoryResponse {
+    /// Bytes en memoria de los conjuntos guardados, sin los bloques en disco
+    pub bytes: u64,
+    /// Presupuesto de memoria configurado, None si no hay límite
+    pub budget: Option<u64>,
+    /// Qué se hace cuando una generación superaría el presupuesto
+    pub policy: MemoryPolicy,
+    /// Bytes de los bloques libres guardados para reusar, fuera del presupuesto
+    pub pooled: u64,
+}
+
+/// Método handler que devuelve la memoria ocupada por los conjuntos de
+/// datos de todos los clientes y el presupuesto configurado
+///
+/// # Argumentos
+///
+/// * `State(state)` Estado compartido, con los datos y la configuración
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_memory(
+    State(state): State<AppState>,
+    format: Format,
+) -> Negotiated<MemoryResponse> {
+    let config = &state.config;
+    Negotiated(
+        format,
+        MemoryResponse {
+            bytes: state.datasets.memory_bytes(),
+            budget: config.memory_budget,
+            policy: config.memory_policy,
+            pooled: chunked::pooled_bytes() as u64,
+        },
+    )
+}
+
+/// Método handler de depuración que devuelve los últimos spans de
+/// generación y estadísticas, el más reciente primero, con sus campos, su
+/// duración y la diferencia de memoria, para diagnosticar peticiones lentas
+///
+/// # Argumentos
+///
+/// * `State(spans)` Últimos spans registrados
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_last_run(
+    State(spans): State<Arc<SpanLog>>,
+    format: Format,
+) -> Negotiated<Vec<SpanRecord>> {
+    Negotiated(format, spans.recent())
+}
+
+/// Método handler que devuelve las generaciones y los cálculos de
+/// estadísticas en curso, con el último avance informado de cada generación
+/// (ver `trace::Progress`), para distinguir una generación lenta de una
+/// detenida
+///
+/// # Argumentos
+///
+/// * `State(spans)` Spans registrados
+/// * `format` formato de la respuesta, según el header Accept
+pub async fn get_active_runs(
+    State(spans): State<Arc<SpanLog>>,
+    format: Format,
+) -> Negotiated<Vec<ActiveSpan>> {
+    Negotiated(format, spans.active())
+}
+
+/// Método handler que genera replicaciones independientes de un experimento,
+/// cada una con `number` valores de un subflujo distinto de la semilla, y
+/// devuelve los estadísticos de cada replicación con intervalos de confianza
+/// entre replicaciones. Los valores se limitan y redondean como al
+/// guardarlos, pero no se guardan
+///
+/// # Argumentos
+///
+/// * `State(config)` Configuración del servidor, con el máximo de valores y
+///   la significancia por defecto
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn replicate(
+    State(config): State<Arc<Config>>,
+    format: Format,
+    data: extract::Json<ReplicationParameters>,
+) -> Result<Negotiated<ReplicationsResponse>, ApiError> {
+    let ReplicationParameters { generation, replications, intervals } = data.0;
+    if replications < 2 {
+        return Err(ApiError::BadRequest("se necesitan al menos 2 replicaciones".into()));
+    }
+    if generation.number == 0 {
+        return Err(ApiError::BadRequest("number debe ser mayor a 0".into()));
+    }
+    if let Generator::Lcg { .. } = generation.generator {
+        // Cada replicación usa un subflujo de xoshiro256++
+        return Err(ApiError::BadRequest(
+            "las replicaciones no admiten el generador congruencial".into(),
+        ));
+    }
+    check_number(&config, generation.total().saturating_mul(replications as u64))?;
+    let experiment = generation.experiment()?;
+    let number = generation.number;
+    let significance = config.default_significance();
+    let input = move |intervals| StatisticsInput {
+        intervals,
+        significance: Some(significance),
+        final_bin: FinalBin::default(),
+        from: None,
+        to: None,
+        scale: Scale::Linear,
+        trim: None,
+        binning: Binning::EqualWidth,
+        alphas: None,
+        parameters_estimated: false,
+        extended: false,
+    };
+    if let Some(intervals) = intervals {
+        input(intervals).check_intervals().map_err(ApiError::BadRequest)?;
+    }
+    let res = tokio::task::spawn_blocking(move || {
+        let dist = experiment.tested();
+        // Cada replicación usa su propio subflujo; la primera es el
+        // generador de la semilla
+        let streams = UniformGenerator::substreams(experiment.seed, replications);
+        let replications = streams
+            .into_par_iter()
+            .map(|rng| {
+                let values = generate_finished_with(number, &experiment, &rng)?;
+                let test = intervals.map(|intervals| {
+                    let res = statistics(input(intervals), &values, dist.as_ref()).test;
+                    ReplicationTest {
+                        calculated: res.calculated,
+                        critical: res.critical,
+                        reject: res.reject,
+                    }
+                });
+                Ok(Replication { summary: summary(&values), test })
+            })
+            .collect::<io::Result<Vec<Replication>>>()?;
+        let means: Vec<f64> = replications.iter().map(|r| r.summary.mean).collect();
+        let variances: Vec<f64> = replications.iter().map(|r| r.summary.variance).collect();
+        let (expected_mean, expected_variance) =
+            experiment.mode.tested_distribution(&experiment.distribution).moments();
+        let rejection_rate = intervals.map(|_| {
+            let rejected = replications
+                .iter()
+                .filter(|r| r.test.as_ref().is_some_and(|t| t.reject))
+                .count();
+            rejected as f64 / replications.len() as f64
+        });
+        Ok(ReplicationsResponse {
+            mean: ConfidenceInterval::from_replications(&means),
+            variance: ConfidenceInterval::from_replications(&variances),
+            replications,
+            expected_mean,
+            expected_variance,
+            rejection_rate,
+        })
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?
+    .map_err(|e: io::Error| ApiError::Internal(e.to_string()))?;
+    Ok(Negotiated(format, res))
+}
+
+/// Método handler que demuestra el teorema central del límite: genera
+/// `samples` muestras independientes de `number` valores de la distribución,
+/// cada una de un subflujo distinto de la semilla, y guarda sus medias como
+/// un conjunto de datos, probado contra la Normal con la media de la
+/// distribución y desviación σ/√n. Devuelve las pruebas de normalidad de las
+/// medias. Las medias se guardan siempre en f64
+///
+/// # Argumentos
+///
+/// * `State(state)` Estado compartido, con los datos y la configuración
+/// * `client` cliente que realiza la petición
+/// * `format` formato de la respuesta, según el header Accept
+/// * `data` Datos en Json recibidos del front end
+pub async fn sample_means(
+    State(state): State<AppState>,
+    client: Client,
+    format: Format,
+    data: extract::Json<SampleMeansParameters>,
+) -> Result<Negotiated<SampleMeansResponse>, ApiError> {
+    let SampleMeansParameters { generation, samples, intervals } = data.0;
+    if samples < 2 {
+        return Err(ApiError::BadRequest("se necesitan al menos 2 muestras".into()));
+    }
+    if generation.number == 0 {
+        return Err(ApiError::BadRequest("number debe ser mayor a 0".into()));
+    }
+    if let Generator::Lcg { .. } = generation.generator {
+        // Cada muestra usa un subflujo de xoshiro256++
+        return Err(ApiError::BadRequest(
+            "las medias muestrales no admiten el generador congruencial".into(),
+        ));
+    }
+    check_number(&state.config, generation.total().saturating_mul(samples as u64))?;
+    let experiment = generation.experiment()?;
+    let size = generation.number;
+    let tested = experiment.mode.tested_distribution(&experiment.distribution);
+    let (expected_mean, expected_variance) = tested.moments();
+    let expected_sd = (expected_variance / size as f64).sqrt();
+    if !expected_mean.is_finite() || !expected_sd.is_finite() || expected_sd <= 0f64 {
+        return Err(ApiError::BadRequest(
+            "la distribución debe tener media finita y varianza finita y positiva".into(),
+        ));
+    }
+    let _reservation = make_room(&state, samples as u64 * 8).await?;
+    let normal = StoredDistribution::Normal(Normal {
+        algorithm: Algorithm::BoxMuller,
+        mean: expected_mean,
+        sd: expected_sd,
+    });
+    let (alpha, significance) = (state.config.default_alpha, state.config.default_significance());
+    let input = StatisticsInput {
+        intervals,
+        significance: Some(significance),
+        final_bin: FinalBin::default(),
+        from: None,
+        to: None,
+        scale: Scale::Linear,
+        trim: None,
+        binning: Binning::EqualWidth,
+        alphas: None,
+        parameters_estimated: false,
+        extended: false,
+    };
+    input.check_intervals().map_err(ApiError::BadRequest)?;
+    let tested_normal = normal.clone();
+    let (means, summary, statistics, kolmogorov_smirnov, jarque_bera) =
+        tokio::task::spawn_blocking(move || {
+            let streams = UniformGenerator::substreams(experiment.seed, samples);
+            let means: Vec<f64> = streams
+                .into_par_iter()
+                .map(|mut rng| {
+                    let values = generate_with(size, &experiment, &mut rng);
+                    values.iter().sum::<f64>() / values.len() as f64
+                })
+                .collect();
+            let dist = tested_normal.boxed();
+            let statistics = statistics(input, &means, dist.as_ref());
+            let ks = ks::one_sample(&means, dist.as_ref(), alpha);
+            let (summary, jb) = (summary(&means), jarque_bera(&means, alpha));
+            (means, summary, statistics, ks, jb)
+        })
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let label = generation.metadata.label.clone();
+    let generated = Generated::new(means, normal.boxed()).with_metadata(generation.metadata);
+    let id = state.datasets.get(&client).write().await.insert(generated);
+    Ok(Negotiated(
+        format,
+        SampleMeansResponse {
+            dataset: DatasetId { id, label, strata: None, reproducibility: None, duplicate: None },
+            samples,
+            size,
+            expected_mean,
+            expected_sd,
+            summary,
+            statistics,
+            kolmogorov_smirnov,
+            jarque_bera,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::SqliteStore;
+
+    // Función privada, estado del servidor con la configuración por defecto
+    // y el almacenamiento indicado
+    fn test_state(store: Box<dyn crate::store::DatasetStore>) -> AppState {
+        AppState {
+            datasets: Arc::new(Workspaces::default()),
+            config: Arc::new(Config::default()),
+            history: Arc::new(History::open(None).unwrap()),
+            store: Arc::new(Persistence::new(store)),
+            spans: Arc::new(SpanLog::default()),
+        }
+    }
+
+    // Función privada, experimento Uniforme(0, 1) con la semilla indicada
+    fn uniform_experiment(seed: u64) -> Experiment {
+        Experiment {
+            seed,
+            distribution: StoredDistribution::Uniform(Uniform { lower: 0f64, upper: 1f64 }),
+            mode: GenerationMode::Independent,
+            discard: 0,
+            generator: Generator::default(),
+            decimals: None,
+            bounds: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stored_datasets() {
+        let name = format!("simrng-controllers-{}-stored.db", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_file(&path);
+        let state = test_state(Box::new(SqliteStore::open(&path).unwrap()));
+        let (client, other) = (Client("a".into()), Client("b".into()));
+        let manifest = Reproducibility::new(&uniform_experiment(5), 3, Precision::F64);
+        let metadata = Metadata { label: Some("x".into()), ..Metadata::default() };
+        let data = Samples::from(vec![0.1, 0.2, 0.3]);
+        let ticket = state.store.ticket(&client);
+        state.store.save(ticket, &client, &manifest, &metadata, &data).unwrap();
+        // Sin conjuntos en memoria, el experimento se recupera del almacenamiento
+        let datasets = stored_datasets(&state, &client).await.unwrap();
+        let restored = find_dataset(&datasets, None).await.unwrap();
+        assert_eq!(restored.data.range_f64(0, 3), vec![0.1, 0.2, 0.3]);
+        assert_eq!(restored.metadata, metadata);
+        assert_eq!(restored.reproducibility.unwrap().seed, 5);
+        assert_eq!(datasets.read().await.list().len(), 1);
+        // Los demás clientes no lo ven
+        let datasets = stored_datasets(&state, &other).await.unwrap();
+        assert!(datasets.read().await.is_empty());
+        // Con conjuntos en memoria no se vuelve a leer el almacenamiento
+        let datasets = stored_datasets(&state, &client).await.unwrap();
+        assert_eq!(datasets.read().await.list().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    // Función privada, parámetros de replicaciones de una Exponencial con la
+    // semilla indicada, como los recibe `/replications`
+    fn replication_parameters(seed: u64) -> ReplicationParameters {
+        serde_json::from_value(serde_json::json!({
+            "seed": seed, "number": 1000, "distribution": "Exponential", "data": {"lambda": 2},
+            "replications": 5, "intervals": 10
+        }))
+        .unwrap()
+    }
+
+    // Función privada, resultados de cada replicación, en Json para compararlos
+    async fn replications(seed: u64) -> serde_json::Value {
+        let config = State(Arc::new(Config::default()));
+        let data = extract::Json(replication_parameters(seed));
+        let res = replicate(config, Format::Json, data).await.unwrap().1;
+        serde_json::to_value(res.replications).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_replicate() {
+        // Con la misma semilla se repiten las mismas replicaciones
+        let first = replications(3).await;
+        assert_eq!(first, replications(3).await);
+        assert_ne!(first, replications(4).await);
+        let means: Vec<f64> = first
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["summary"]["mean"].as_f64().unwrap())
+            .collect();
+        assert_eq!(means.len(), 5);
+        for (i, a) in means.iter().enumerate() {
+            assert!(means[i + 1..].iter().all(|b| a != b));
+        }
+        // La primera replicación es la generación con la semilla
+        let experiment = replication_parameters(3).generation.experiment().unwrap();
+        let values = generate_finished(1000, &experiment).unwrap();
+        assert_eq!(summary(&values).mean, means[0]);
+        // Y los subflujos de las demás no están correlacionados con ella
+        let streams = UniformGenerator::substreams(3, 5);
+        for rng in &streams[1..] {
+            let other = generate_finished_with(1000, &experiment, rng).unwrap();
+            let correlation = simrng::stats::correlation::correlation(&values, &other, 0.01);
+            assert!(!correlation.unwrap().pearson.reject);
+        }
+    }
+
+    // Función privada, pide la vista previa de `number` valores Uniformes
+    // con las estadísticas indicadas
+    async fn preview(
+        state: &AppState,
+        number: u64,
+        statistics: serde_json::Value,
+    ) -> Result<PreviewResponse, ApiError> {
+        let data = serde_json::from_value(serde_json::json!({
+            "seed": 11, "number": number, "distribution": "Uniform",
+            "data": {"lower": 0, "upper": 1}, "statistics": statistics
+        }))
+        .unwrap();
+        let client = Client::anonymous();
+        let res = generate_preview(
+            State(state.clone()),
+            client,
+            Format::Json,
+            Language::English,
+            extract::Json(data),
+        )
+        .await?;
+        Ok(res.2)
+    }
+
+    #[tokio::test]
+    async fn test_generate_preview() {
+        let state = test_state(Box::new(crate::store::MemoryStore));
+        let client = Client::anonymous();
+        let res = preview(&state, 500, serde_json::json!({"intervals": 10})).await.unwrap();
+        // La respuesta trae lo mismo que `/page` y `/statistics` del conjunto guardado
+        let datasets = state.datasets.get(&client);
+        let stored = find_dataset(&datasets, None).await.unwrap();
+        let page = serde_json::to_value(stored.data.page(1)).unwrap();
+        assert_eq!(serde_json::to_value(&res.page).unwrap(), page);
+        let input = serde_json::from_value(serde_json::json!({"intervals": 10})).unwrap();
+        let id = Some(res.dataset.id);
+        let statistics = dataset_statistics(&datasets, &state.config, id, input).await.unwrap();
+        assert_eq!(
+            serde_json::to_value(&res.statistics).unwrap(),
+            serde_json::to_value(&statistics).unwrap()
+        );
+        assert_eq!(res.dataset.reproducibility.unwrap().seed, 11);
+        assert_eq!(state.history.list(&client, None).unwrap().len(), 1);
+        // Más de MAX_PREVIEW_NUMBERS valores se rechazan, sin generar nada
+        let res = preview(&state, MAX_PREVIEW_NUMBERS + 1, serde_json::json!({"intervals": 10}));
+        assert!(matches!(res.await, Err(ApiError::TooLarge(_))));
+        // Las estadísticas inválidas se rechazan antes de guardar el conjunto
+        for statistics in [
+            serde_json::json!({"intervals": 0}),
+            serde_json::json!({"intervals": 10, "significance": 99}),
+            serde_json::json!({"intervals": 10, "from": 0, "to": 600}),
+        ] {
+            let res = preview(&state, 500, statistics).await;
+            assert!(matches!(res, Err(ApiError::BadRequest(_))));
+        }
+        assert_eq!(datasets.read().await.list().len(), 1);
+        assert_eq!(state.history.list(&client, None).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_seed() {
+        let state = test_state(Box::new(crate::store::MemoryStore));
+        let (client, other) = (Client::anonymous(), Client("b".into()));
+        assert!(duplicate_seed(&state, &client, &uniform_experiment(1)).unwrap().is_none());
+        let data = Samples::from(vec![0.5]);
+        let metadata = Metadata::labeled(Some("primero".into()));
+        store(&state, &client, uniform_experiment(1), data, None, metadata, true).await.unwrap();
+        // La misma semilla, generador y distribución del mismo cliente
+        let duplicate = duplicate_seed(&state, &client, &uniform_experiment(1)).unwrap().unwrap();
+        assert_eq!(duplicate.label.as_deref(), Some("primero"));
+        assert!(duplicate.message.contains("semilla 1"));
+        // Otra semilla, otra distribución u otro cliente no son duplicados
+        assert!(duplicate_seed(&state, &client, &uniform_experiment(2)).unwrap().is_none());
+        let mut exponential = uniform_experiment(1);
+        let distribution = Exponential { lambda: 1f64, shift: 0f64 };
+        exponential.distribution = StoredDistribution::Exponential(distribution);
+        assert!(duplicate_seed(&state, &client, &exponential).unwrap().is_none());
+        assert!(duplicate_seed(&state, &other, &uniform_experiment(1)).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_seed_batch() {
+        let state = test_state(Box::new(crate::store::MemoryStore));
+        let item = |seed: u64, label: &str| {
+            serde_json::json!({
+                "seed": seed, "number": 10, "distribution": "Uniform",
+                "data": {"lower": 0, "upper": 1}, "label": label
+            })
+        };
+        let batch = serde_json::json!([item(4, "a"), item(4, "b"), item(5, "c")]);
+        let data = extract::Json(serde_json::from_value(batch).unwrap());
+        let client = Client::anonymous();
+        let response = get_unified(State(state), client, ApiVersion::V1, Format::Json, data);
+        let mut body = response.await.unwrap().into_body();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = axum::body::HttpBody::data(&mut body).await {
+            bytes.extend_from_slice(&chunk.unwrap());
+        }
+        let ids: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        // Los conjuntos del lote se registran en orden, así que el segundo
+        // advierte que repite la semilla del primero, y el tercero no
+        assert!(ids[0].get("duplicate").is_none());
+        assert_eq!(ids[1]["duplicate"]["label"], "a");
+        assert!(ids[2].get("duplicate").is_none());
+    }
+}
@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+
+use crate::dist::gamma::standard_gamma;
+use crate::dist::normal::{standard_cdf, Normal};
+use crate::dist::Distribution;
+use crate::rng::Random;
+
+/// Cópula con la que se generan muestras multivariadas con dependencia
+#[derive(Deserialize, Serialize, Clone)]
+pub enum Copula {
+    /// Cópula Gaussiana, con la matriz de correlación de las normales
+    /// subyacentes
+    Gaussian { correlation: Vec<Vec<f64>> },
+    /// Cópula de Clayton, con dependencia en la cola inferior, θ > 0
+    Clayton { theta: f64, dimensions: usize },
+}
+
+/// Tau de Kendall entre dos dimensiones de la muestra
+#[derive(Serialize, Clone, Debug)]
+pub struct KendallTau {
+    pub first: usize,
+    pub second: usize,
+    /// Tau calculado sobre la muestra
+    pub empirical: f64,
+    /// Tau de la cópula
+    pub theoretical: f64,
+}
+
+/// Muestra multivariada generada con una cópula
+#[derive(Serialize, Clone, Debug)]
+pub struct CopulaSample {
+    /// Valores de cada dimensión, con su distribución marginal
+    pub columns: Vec<Vec<f64>>,
+    /// Tau de Kendall de cada par de dimensiones
+    pub kendall: Vec<KendallTau>,
+}
+
+impl Copula {
+    /// Cantidad de dimensiones de la cópula
+    pub fn dimensions(&self) -> usize {
+        match self {
+            Copula::Gaussian { correlation } => correlation.len(),
+            Copula::Clayton { dimensions, .. } => *dimensions,
+        }
+    }
+
+    /// Verifica que los parámetros de la cópula sean válidos: la matriz de
+    /// correlación debe ser simétrica, con unos en la diagonal y definida
+    /// positiva
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Copula::Gaussian { correlation } => {
+                let n = correlation.len();
+                if n < 2 {
+                    return Err("la cópula necesita al menos dos dimensiones".into());
+                }
+                if correlation.iter().any(|row| row.len() != n) {
+                    return Err("la matriz de correlación debe ser cuadrada".into());
+                }
+                for (i, row) in correlation.iter().enumerate() {
+                    if row[i] != 1f64 {
+                        return Err("la diagonal de la matriz de correlación debe ser 1".into());
+                    }
+                    if (0..i).any(|j| row[j] != correlation[j][i]) {
+                        return Err("la matriz de correlación debe ser simétrica".into());
+                    }
+                }
+                cholesky(correlation)
+                    .map(|_| ())
+                    .ok_or_else(|| "la matriz de correlación debe ser definida positiva".into())
+            }
+            Copula::Clayton { theta, dimensions } => {
+                if *dimensions < 2 {
+                    return Err("la cópula necesita al menos dos dimensiones".into());
+                }
+                if !(*theta > 0f64 && theta.is_finite()) {
+                    return Err("theta debe ser mayor a 0".into());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Tau de Kendall de la cópula entre dos dimensiones: 2/π·arcsen(ρ) en
+    /// la Gaussiana, θ/(θ+2) en la de Clayton
+    ///
+    /// # Argumentos
+    ///
+    /// * `i` primera dimensión
+    /// * `j` segunda dimensión
+    pub fn kendall_tau(&self, i: usize, j: usize) -> f64 {
+        match self {
+            Copula::Gaussian { correlation } => {
+                2f64 / std::f64::consts::PI * correlation[i][j].asin()
+            }
+            Copula::Clayton { theta, .. } => theta / (theta + 2f64),
+        }
+    }
+
+    /// Genera n puntos de la cópula, uniformes en [0, 1) en cada dimensión
+    /// y dependientes entre sí. Devuelve los valores de cada dimensión. La
+    /// cópula debe ser válida (ver `validate`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `n` cantidad de puntos
+    /// * `rand` el generador de números aleatorios a utilizar, implementa Random
+    pub fn uniforms(&self, n: usize, rand: &mut dyn Random) -> Vec<Vec<f64>> {
+        let dims = self.dimensions();
+        let mut columns = vec![Vec::with_capacity(n); dims];
+        let mut normal = Normal::default().sampler();
+        match self {
+            Copula::Gaussian { correlation } => {
+                // Normales independientes, correlacionadas con el factor de Cholesky
+                let l = cholesky(correlation).expect("matriz de correlación válida");
+                let mut z = vec![0f64; dims];
+                for _ in 0..n {
+                    z.iter_mut().for_each(|z| *z = normal.next(rand));
+                    for (row, column) in l.iter().zip(columns.iter_mut()) {
+                        let x: f64 = row.iter().zip(&z).map(|(a, b)| a * b).sum();
+                        column.push(standard_cdf(x));
+                    }
+                }
+            }
+            Copula::Clayton { theta, .. } => {
+                // Algoritmo de Marshall-Olkin: V ~ Gamma(1/θ), Eᵢ ~ Exp(1),
+                // Uᵢ = (1 + Eᵢ/V)^(-1/θ)
+                for _ in 0..n {
+                    let v = standard_gamma(1f64 / theta, &mut normal, rand);
+                    for column in columns.iter_mut() {
+                        let e = -(1f64 - rand.next()).ln();
+                        column.push((1f64 + e / v).powf(-1f64 / theta));
+                    }
+                }
+            }
+        }
+        columns
+    }
+
+    /// Genera n puntos de la cópula y los transforma con el cuantil de la
+    /// distribución marginal de cada dimensión. El tau de Kendall se calcula
+    /// sobre los uniformes, ya que no cambia con transformaciones crecientes
+    /// y así no le afectan los empates de las marginales discretas
+    ///
+    /// # Argumentos
+    ///
+    /// * `n` cantidad de puntos
+    /// * `marginals` distribución marginal de cada dimensión
+    /// * `rand` el generador de números aleatorios a utilizar, implementa Random
+    pub fn sample(
+        &self,
+        n: usize,
+        marginals: &[&dyn Distribution],
+        rand: &mut dyn Random,
+    ) -> CopulaSample {
+        let uniforms = self.uniforms(n, rand);
+        let mut kendall = Vec::new();
+        for i in 0..uniforms.len() {
+            for j in i + 1..uniforms.len() {
+                kendall.push(KendallTau {
+                    first: i,
+                    second: j,
+                    empirical: kendall_tau(&uniforms[i], &uniforms[j]),
+                    theoretical: self.kendall_tau(i, j),
+                });
+            }
+        }
+        let columns = uniforms
+            .into_iter()
+            .zip(marginals)
+            .map(|(column, dist)| column.into_iter().map(|u| dist.quantile(u)).collect())
+            .collect();
+        CopulaSample { columns, kendall }
+    }
+}
+
+/// Tau de Kendall de la muestra, (concordantes - discordantes)/(n(n-1)/2),
+/// contando los pares discordantes como inversiones con mergesort, en
+/// O(n log n). Supone valores sin empates
+///
+/// # Argumentos
+///
+/// * `x` valores de la primera variable
+/// * `y` valores de la segunda variable, en el mismo orden
+pub fn kendall_tau(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len().min(y.len());
+    if n < 2 {
+        return 0f64;
+    }
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| x[a].total_cmp(&x[b]));
+    let mut values: Vec<f64> = order.iter().map(|&i| y[i]).collect();
+    let discordant = inversions(&mut values) as f64;
+    let pairs = (n * (n - 1)) as f64 / 2f64;
+    1f64 - 2f64 * discordant / pairs
+}
+
+// Función privada, ordena los valores y devuelve la cantidad de inversiones
+fn inversions(values: &mut [f64]) -> u64 {
+    let n = values.len();
+    if n < 2 {
+        return 0;
+    }
+    let (left, right) = values.split_at_mut(n / 2);
+    let mut count = inversions(left) + inversions(right);
+    let mut merged = Vec::with_capacity(n);
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        if left[i] <= right[j] {
+            merged.push(left[i]);
+            i += 1;
+        } else {
+            // Todos los restantes de la izquierda son mayores
+            count += (left.len() - i) as u64;
+            merged.push(right[j]);
+            j += 1;
+        }
+    }
+    merged.extend_from_slice(&left[i..]);
+    merged.extend_from_slice(&right[j..]);
+    values.copy_from_slice(&merged);
+    count
+}
+
+// Función privada, factor de Cholesky L (triangular inferior, A = L·Lᵀ).
+// Devuelve None si la matriz no es definida positiva
+fn cholesky(a: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    let mut l = vec![vec![0f64; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| l[i][k] * l[j][k]).sum();
+            if i == j {
+                let d = a[i][i] - sum;
+                if d <= 0f64 {
+                    return None;
+                }
+                l[i][j] = d.sqrt();
+            } else {
+                l[i][j] = (a[i][j] - sum) / l[j][j];
+            }
+        }
+    }
+    Some(l)
+}
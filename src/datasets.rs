@@ -0,0 +1,888 @@
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Serialize, Serializer};
+use simrng::dist::Distribution;
+use simrng::chunked::{self, ChunkedVec, SampleChunks};
+use simrng::float::Float;
+use simrng::list::{export_chunk, page_range, pairs, reservoir_sample, triples};
+use simrng::rng::Random;
+use simrng::stats::battery::{battery, Battery, BatteryOptions};
+use simrng::stats::correlation::{correlation, Correlation};
+use simrng::stats::descriptive::{
+    quantiles, rolling, running_means, summary, weighted_summary, RollingPoint, RunningMean,
+    Summary,
+};
+use simrng::stats::discrete::{discrete_frequencies, DiscreteFrequencies};
+use simrng::stats::suggest::{shape, Shape};
+use simrng::stats::{
+    chi_squared_trace, full_statistics, interval_sensitivity, ChiSquaredTrace,
+    IntervalSensitivity, StatisticsInput, StatisticsResponse,
+};
+use simrng::transform::{transform, Operation, Transformation};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::auth::Client;
+use crate::config::MemoryPolicy;
+use crate::controllers::{AppState, Generated};
+use crate::error::ApiError;
+use crate::history::Metadata;
+use crate::storage::Reproducibility;
+
+/// Precisión con la que se guardan los valores generados. Se generan
+/// siempre en f64; en f32 ocupan la mitad de la memoria
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Precision {
+    #[default]
+    F64,
+    F32,
+}
+
+impl Precision {
+    /// Bytes que ocupa cada valor en esta precisión
+    pub fn size(self) -> u64 {
+        match self {
+            Precision::F64 => 8,
+            Precision::F32 => 4,
+        }
+    }
+}
+
+/// Valores de un conjunto de datos, en la precisión con la que se guardan.
+/// Se guardan por bloques, que pueden estar en disco (ver `ChunkedVec`)
+#[derive(Clone)]
+pub enum Samples {
+    F64(Arc<ChunkedVec<f64>>),
+    F32(Arc<ChunkedVec<f32>>),
+}
+
+/// Página de valores de un conjunto de datos, en su precisión. Guarda una
+/// referencia a los valores y se serializa directamente desde los bloques,
+/// sin copiarlos
+pub struct Page {
+    data: Samples,
+    start: usize,
+    end: usize,
+}
+
+impl Serialize for Page {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.data {
+            Samples::F64(nums) => serialize_range(nums.as_ref(), self.start, self.end, serializer),
+            Samples::F32(nums) => serialize_range(nums.as_ref(), self.start, self.end, serializer),
+        }
+    }
+}
+
+// Función privada, serializa los valores del rango como una lista de
+// longitud conocida, que necesitan MessagePack y CBOR
+fn serialize_range<T: Float + Serialize, S: Serializer>(
+    nums: &ChunkedVec<T>,
+    start: usize,
+    end: usize,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let len = end.min(nums.len()).saturating_sub(start);
+    let mut seq = serializer.serialize_seq(Some(len))?;
+    for num in chunked::slices(nums, start, end).flatten() {
+        seq.serialize_element(num)?;
+    }
+    seq.end()
+}
+
+impl From<Vec<f64>> for Samples {
+    fn from(values: Vec<f64>) -> Self {
+        Samples::F64(Arc::new(values.into()))
+    }
+}
+
+impl Samples {
+    /// Guarda los valores generados en la precisión indicada, en memoria
+    ///
+    /// # Argumentos
+    ///
+    /// * `precision` precisión con la que se guardan
+    /// * `values` valores generados
+    pub fn new(precision: Precision, values: Vec<f64>) -> Self {
+        match precision {
+            Precision::F64 => Samples::F64(Arc::new(values.into())),
+            Precision::F32 => {
+                let values: Vec<f32> = values.into_iter().map(f32::from_f64).collect();
+                Samples::F32(Arc::new(values.into()))
+            }
+        }
+    }
+
+    /// Precisión con la que están guardados los valores
+    pub fn precision(&self) -> Precision {
+        match self {
+            Samples::F64(_) => Precision::F64,
+            Samples::F32(_) => Precision::F32,
+        }
+    }
+
+    /// Cantidad de valores
+    pub fn len(&self) -> usize {
+        match self {
+            Samples::F64(nums) => nums.len(),
+            Samples::F32(nums) => nums.len(),
+        }
+    }
+
+    /// Cantidad de bloques guardados en disco
+    pub fn spilled_chunks(&self) -> usize {
+        match self {
+            Samples::F64(nums) => nums.spilled_chunks(),
+            Samples::F32(nums) => nums.spilled_chunks(),
+        }
+    }
+
+    /// Bytes que ocupan los valores en memoria, sin los bloques en disco
+    pub fn memory_bytes(&self) -> u64 {
+        match self {
+            Samples::F64(nums) => nums.memory_bytes() as u64,
+            Samples::F32(nums) => nums.memory_bytes() as u64,
+        }
+    }
+
+    /// Si comparte los valores con `other`, como una copia de un conjunto
+    /// con el original (ver `Datasets::snapshot`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `other` valores con los que comparar
+    pub fn shares(&self, other: &Samples) -> bool {
+        match (self, other) {
+            (Samples::F64(a), Samples::F64(b)) => Arc::ptr_eq(a, b),
+            (Samples::F32(a), Samples::F32(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// Copia todos los valores a un vector en memoria, en f64
+    pub fn to_f64(&self) -> Arc<Vec<f64>> {
+        Arc::new(self.range_f64(0, self.len()))
+    }
+
+    /// Copia los valores entre las posiciones indicadas, en f64
+    ///
+    /// # Argumentos
+    ///
+    /// * `start` posición del primer valor
+    /// * `end` posición siguiente al último valor
+    pub fn range_f64(&self, start: usize, end: usize) -> Vec<f64> {
+        match self {
+            Samples::F64(nums) => nums.range(start, end),
+            Samples::F32(nums) => nums.range(start, end).iter().map(|x| x.to_f64()).collect(),
+        }
+    }
+
+    /// Recorre los valores en orden, en f64, bloque por bloque
+    pub fn values_f64(&self) -> Box<dyn Iterator<Item = f64> + '_> {
+        match self {
+            Samples::F64(nums) => Box::new(chunked::values(nums.as_ref())),
+            Samples::F32(nums) => Box::new(chunked::values(nums.as_ref()).map(|x| x.to_f64())),
+        }
+    }
+
+    /// Estadísticos descriptivos de los valores
+    pub fn summary(&self) -> Summary {
+        match self {
+            Samples::F64(nums) => summary(nums.as_ref()),
+            Samples::F32(nums) => summary(nums.as_ref()),
+        }
+    }
+
+    /// Cuantiles empíricos de los valores en las probabilidades indicadas
+    /// (ver `descriptive::quantiles`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `probabilities` probabilidades de los cuantiles, entre 0 y 1
+    pub fn quantiles(&self, probabilities: &[f64]) -> Vec<f64> {
+        match self {
+            Samples::F64(nums) => quantiles(nums.as_ref(), probabilities),
+            Samples::F32(nums) => quantiles(nums.as_ref(), probabilities),
+        }
+    }
+
+    /// Forma de los valores, para sugerir familias de distribuciones (ver
+    /// `suggest::shape`)
+    pub fn shape(&self) -> Option<Shape> {
+        match self {
+            Samples::F64(nums) => shape(nums.as_ref()),
+            Samples::F32(nums) => shape(nums.as_ref()),
+        }
+    }
+
+    /// Estadísticos descriptivos de los valores con pesos
+    ///
+    /// # Argumentos
+    ///
+    /// * `weights` peso de cada valor
+    pub fn weighted_summary(&self, weights: &[f64]) -> Summary {
+        match self {
+            Samples::F64(nums) => weighted_summary(nums.as_ref(), weights),
+            Samples::F32(nums) => weighted_summary(nums.as_ref(), weights),
+        }
+    }
+
+    /// Correlaciones de Pearson y de Spearman con otro conjunto de valores,
+    /// emparejados por posición (ver `correlation`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `other` valores con los que se correlacionan
+    /// * `alpha` nivel de significancia
+    pub fn correlation(&self, other: &Samples, alpha: f64) -> Result<Correlation, String> {
+        match (self, other) {
+            (Samples::F64(a), Samples::F64(b)) => correlation(a.as_ref(), b.as_ref(), alpha),
+            (Samples::F64(a), Samples::F32(b)) => correlation(a.as_ref(), b.as_ref(), alpha),
+            (Samples::F32(a), Samples::F64(b)) => correlation(a.as_ref(), b.as_ref(), alpha),
+            (Samples::F32(a), Samples::F32(b)) => correlation(a.as_ref(), b.as_ref(), alpha),
+        }
+    }
+
+    /// Batería de pruebas del flujo uniforme (ver `battery`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `options` significancia y parámetros de las pruebas
+    pub fn battery(&self, options: BatteryOptions) -> Result<Battery, String> {
+        match self {
+            Samples::F64(nums) => battery(nums.as_ref(), options),
+            Samples::F32(nums) => battery(nums.as_ref(), options),
+        }
+    }
+
+    /// Media y varianza a lo largo de la secuencia (ver `rolling`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `window` cantidad de valores de la ventana, None para usar todos
+    /// * `points` cantidad de posiciones a devolver
+    pub fn rolling(&self, window: Option<usize>, points: usize) -> Vec<RollingPoint> {
+        match self {
+            Samples::F64(nums) => rolling(nums.as_ref(), window, points),
+            Samples::F32(nums) => rolling(nums.as_ref(), window, points),
+        }
+    }
+
+    /// Promedio acumulado, en cada posición o en posiciones geométricas
+    /// (ver `running_means`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `points` cantidad máxima de posiciones a devolver
+    pub fn running_means(&self, points: usize) -> Vec<RunningMean> {
+        match self {
+            Samples::F64(nums) => running_means(nums.as_ref(), points),
+            Samples::F32(nums) => running_means(nums.as_ref(), points),
+        }
+    }
+
+    /// Frecuencias observadas y esperadas de cada valor entero, para datos
+    /// de una distribución discreta (ver `discrete_frequencies`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `dist` distribución discreta contra la que se comparan
+    pub fn discrete_frequencies(
+        &self,
+        dist: &dyn Distribution,
+    ) -> Result<DiscreteFrequencies, String> {
+        match self {
+            Samples::F64(nums) => discrete_frequencies(nums.as_ref(), dist),
+            Samples::F32(nums) => discrete_frequencies(nums.as_ref(), dist),
+        }
+    }
+
+    /// Calcula el histograma y las pruebas sobre los valores
+    ///
+    /// # Argumentos
+    ///
+    /// * `input` cantidad de intervalos y significancia
+    /// * `weights` peso de cada valor, None si no tienen pesos
+    /// * `dist` distribución contra la que se prueba
+    pub async fn statistics(
+        &self,
+        input: StatisticsInput,
+        weights: Option<Arc<Vec<f64>>>,
+        dist: Arc<Box<dyn Distribution + Send + Sync>>,
+    ) -> StatisticsResponse {
+        match self {
+            Samples::F64(nums) => full_statistics(input, Arc::clone(nums), weights, dist).await,
+            Samples::F32(nums) => full_statistics(input, Arc::clone(nums), weights, dist).await,
+        }
+    }
+
+    /// Verifica que los valores admitan la escala pedida (ver
+    /// `StatisticsInput::check_scale`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `input` escala y parte de los valores a usar
+    /// * `dist` distribución contra la que se prueba
+    pub fn check_scale(
+        &self,
+        input: &StatisticsInput,
+        dist: &dyn Distribution,
+    ) -> Result<(), String> {
+        match self {
+            Samples::F64(nums) => input.check_scale(nums.as_ref(), dist),
+            Samples::F32(nums) => input.check_scale(nums.as_ref(), dist),
+        }
+    }
+
+    /// Traza del estadístico de chi cuadrado sobre prefijos de los valores
+    /// (ver `chi_squared_trace`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `input` cantidad de intervalos, significancia, intervalo final y parte de los valores
+    /// * `dist` distribución contra la que se prueba
+    pub fn chi_squared_trace(
+        &self,
+        input: StatisticsInput,
+        dist: &dyn Distribution,
+    ) -> ChiSquaredTrace {
+        match self {
+            Samples::F64(nums) => chi_squared_trace(input, nums.as_ref(), dist),
+            Samples::F32(nums) => chi_squared_trace(input, nums.as_ref(), dist),
+        }
+    }
+
+    /// Prueba de chi cuadrado con cada cantidad de intervalos de un rango
+    /// (ver `interval_sensitivity`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `input` significancia, intervalo final, agrupamiento y parte de los valores
+    /// * `dist` distribución contra la que se prueba
+    /// * `min_intervals` menor cantidad de intervalos
+    /// * `max_intervals` mayor cantidad de intervalos
+    pub fn interval_sensitivity(
+        &self,
+        input: StatisticsInput,
+        dist: &dyn Distribution,
+        min_intervals: usize,
+        max_intervals: usize,
+    ) -> IntervalSensitivity {
+        match self {
+            Samples::F64(nums) => {
+                interval_sensitivity(input, nums.as_ref(), dist, min_intervals, max_intervals)
+            }
+            Samples::F32(nums) => {
+                interval_sensitivity(input, nums.as_ref(), dist, min_intervals, max_intervals)
+            }
+        }
+    }
+
+    /// Aplica una operación a cada valor, en f64 (ver `transform::transform`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `operation` operación a aplicar
+    pub fn transform(&self, operation: Operation) -> Result<Transformation, String> {
+        match self {
+            Samples::F64(nums) => transform(nums.as_ref(), operation),
+            Samples::F32(nums) => transform(nums.as_ref(), operation),
+        }
+    }
+
+    /// Pares de valores sucesivos, para graficarlos (ver `pairs`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `step` distancia entre el primer valor de un par y el del siguiente
+    /// * `limit` cantidad máxima de pares a devolver
+    pub fn pairs(&self, step: usize, limit: usize) -> Vec<(f64, f64)> {
+        match self {
+            Samples::F64(nums) => pairs(nums.as_ref(), step, limit),
+            Samples::F32(nums) => pairs(nums.as_ref(), step, limit),
+        }
+    }
+
+    /// Ternas de valores sucesivos, para graficarlas (ver `triples`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `step` distancia entre el primer valor de una terna y el de la siguiente
+    /// * `limit` cantidad máxima de ternas a devolver
+    pub fn triples(&self, step: usize, limit: usize) -> Vec<(f64, f64, f64)> {
+        match self {
+            Samples::F64(nums) => triples(nums.as_ref(), step, limit),
+            Samples::F32(nums) => triples(nums.as_ref(), step, limit),
+        }
+    }
+
+    /// Valores elegidos al azar sin reposición, con su posición, en f64 (ver
+    /// `reservoir_sample`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `k` cantidad de valores a elegir
+    /// * `rand` el generador de números aleatorios a utilizar
+    pub fn reservoir_sample(&self, k: usize, rand: &mut dyn Random) -> Vec<(usize, f64)> {
+        match self {
+            Samples::F64(nums) => reservoir_sample(nums.as_ref(), k, rand),
+            Samples::F32(nums) => reservoir_sample(nums.as_ref(), k, rand)
+                .into_iter()
+                .map(|(i, x)| (i, x.to_f64()))
+                .collect(),
+        }
+    }
+
+    /// Devuelve la página indicada, empezando en 1
+    ///
+    /// # Argumentos
+    ///
+    /// * `page` número de página
+    pub fn page(&self, page: usize) -> Page {
+        let (start, end) = page_range(page);
+        Page { data: self.clone(), start, end }
+    }
+
+    /// Devuelve el bloque `chunk` de los valores como texto, un valor por línea
+    ///
+    /// # Argumentos
+    ///
+    /// * `chunk` número de bloque, empezando en 0
+    pub fn export_chunk(&self, chunk: usize) -> String {
+        match self {
+            Samples::F64(nums) => export_chunk(nums.as_ref(), chunk),
+            Samples::F32(nums) => export_chunk(nums.as_ref(), chunk),
+        }
+    }
+}
+
+/// Memoria ocupada por los conjuntos de datos de todos los clientes, y
+/// reloj para saber cuáles se usaron hace más tiempo
+#[derive(Default)]
+pub struct MemoryUsage {
+    /// Bytes en memoria de los conjuntos guardados (ver `Generated::memory_bytes`)
+    bytes: AtomicU64,
+    /// Bytes de los conjuntos guardados más los reservados para los que se
+    /// están generando (ver `Reservation`), contra los que se compara el
+    /// presupuesto
+    reserved: AtomicU64,
+    /// Se incrementa en cada uso de un conjunto de datos
+    clock: AtomicU64,
+}
+
+impl MemoryUsage {
+    /// Bytes en memoria de los conjuntos guardados
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    // Función privada, momento del reloj para marcar un uso
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    // Función privada, cuenta los bytes de un conjunto guardado
+    fn add(&self, bytes: u64) {
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.reserved.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    // Función privada, descuenta los bytes de un conjunto descartado
+    fn sub(&self, bytes: u64) {
+        self.bytes.fetch_sub(bytes, Ordering::Relaxed);
+        self.reserved.fetch_sub(bytes, Ordering::Relaxed);
+    }
+
+    // Función privada, reserva `bytes` si entran en el presupuesto, en una
+    // sola operación para que dos peticiones no tomen el mismo lugar
+    fn try_reserve(&self, bytes: u64, budget: u64) -> bool {
+        self.reserved
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |reserved| {
+                reserved.checked_add(bytes).filter(|&total| total <= budget)
+            })
+            .is_ok()
+    }
+}
+
+/// Memoria reservada en el presupuesto para un conjunto de datos mientras
+/// se genera (ver `Workspaces::make_room`). Se libera al descartarla, ya
+/// sea porque la petición falló o porque el conjunto ya se guardó y cuenta
+/// en `MemoryUsage` por sí mismo
+#[must_use]
+pub struct Reservation {
+    /// Memoria ocupada, None si no hay presupuesto
+    usage: Option<Arc<MemoryUsage>>,
+    /// Bytes reservados
+    bytes: u64,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        if let Some(usage) = &self.usage {
+            usage.reserved.fetch_sub(self.bytes, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Conjuntos de datos generados, identificados por un número. El último
+/// generado es el conjunto actual, usado cuando una petición no indica otro
+#[derive(Default)]
+pub struct Datasets {
+    /// Identificador a asignar al próximo conjunto
+    next_id: u64,
+    /// Identificador del conjunto actual
+    current: Option<u64>,
+    /// Conjuntos guardados, por identificador
+    map: BTreeMap<u64, Generated>,
+    /// Último uso de cada conjunto, según el reloj de `usage`
+    last_used: BTreeMap<u64, AtomicU64>,
+    /// Memoria ocupada, compartida con los conjuntos de los demás clientes
+    usage: Arc<MemoryUsage>,
+}
+
+/// Descripción de un conjunto de datos, para listarlos
+#[derive(Serialize)]
+pub struct DatasetInfo {
+    pub id: u64,
+    /// Etiqueta, descripción y etiquetas del conjunto
+    #[serde(flatten)]
+    pub metadata: Metadata,
+    /// Cantidad de valores del conjunto
+    pub len: usize,
+    /// Precisión con la que se guardan los valores
+    pub precision: Precision,
+    /// Bytes que ocupa en memoria, sin los bloques en disco
+    pub bytes: u64,
+    /// Cantidad de bloques de valores guardados en disco
+    pub spilled_chunks: usize,
+    /// Si los valores tienen pesos
+    pub weighted: bool,
+    /// Cantidad de números uniformes crudos guardados, si se guardaron
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uniforms: Option<usize>,
+    /// Si es el conjunto actual
+    pub current: bool,
+    /// Manifiesto para repetir la generación, si los datos se generaron
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reproducibility: Option<Reproducibility>,
+}
+
+impl Datasets {
+    /// Crea los conjuntos de datos de un cliente, que suman su memoria a la
+    /// de los demás
+    ///
+    /// # Argumentos
+    ///
+    /// * `usage` memoria ocupada por los conjuntos de todos los clientes
+    pub fn new(usage: Arc<MemoryUsage>) -> Self {
+        Datasets { usage, ..Default::default() }
+    }
+
+    /// Guarda un conjunto de datos, lo deja como actual y devuelve su identificador
+    ///
+    /// # Argumentos
+    ///
+    /// * `generated` datos generados con su distribución
+    pub fn insert(&mut self, generated: Generated) -> u64 {
+        let id = self.store(generated);
+        self.current = Some(id);
+        id
+    }
+
+    /// Copia un conjunto de datos con un nuevo identificador, sin copiar los
+    /// valores: la copia los comparte con el original y no ocupa más
+    /// memoria. El conjunto actual no cambia. Devuelve el identificador de
+    /// la copia, o None si el conjunto no existe
+    ///
+    /// # Argumentos
+    ///
+    /// * `id` identificador del conjunto, el actual si no se indica
+    /// * `label` etiqueta de la copia, la del original si no se indica
+    pub fn snapshot(&mut self, id: Option<u64>, label: Option<String>) -> Option<u64> {
+        let mut generated = self.map.get(&id.or(self.current)?)?.clone();
+        if label.is_some() {
+            generated.metadata.label = label;
+        }
+        Some(self.store(generated))
+    }
+
+    // Función privada, guarda un conjunto de datos con un nuevo identificador
+    fn store(&mut self, generated: Generated) -> u64 {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.usage.add(self.unshared_bytes(&generated));
+        self.map.insert(id, generated);
+        self.last_used.insert(id, AtomicU64::new(self.usage.tick()));
+        id
+    }
+
+    // Función privada, bytes en memoria de `generated` que no comparte con
+    // los conjuntos guardados, para contar una sola vez los valores de un
+    // conjunto y sus copias
+    fn unshared_bytes(&self, generated: &Generated) -> u64 {
+        let others = || self.map.values();
+        let data = match others().any(|g| g.data.shares(&generated.data)) {
+            true => 0,
+            false => generated.data.memory_bytes(),
+        };
+        let uniforms = generated.uniforms.as_ref().map_or(0, |uniforms| {
+            match others().any(|g| g.uniforms.as_ref().is_some_and(|u| u.shares(uniforms))) {
+                true => 0,
+                false => uniforms.memory_bytes(),
+            }
+        });
+        let weights = generated.weights.as_ref().map_or(0, |weights| {
+            match others().any(|g| g.weights.as_ref().is_some_and(|w| Arc::ptr_eq(w, weights))) {
+                true => 0,
+                false => weights.len() as u64 * 8,
+            }
+        });
+        data + uniforms + weights
+    }
+
+    /// Devuelve el conjunto indicado, o el actual si no se indica ninguno.
+    /// Si no hay conjunto actual devuelve uno vacío, como antes de generar
+    ///
+    /// # Argumentos
+    ///
+    /// * `id` identificador del conjunto
+    pub fn get(&self, id: Option<u64>) -> Option<Generated> {
+        match id.or(self.current) {
+            Some(id) => {
+                if let Some(used) = self.last_used.get(&id) {
+                    used.store(self.usage.tick(), Ordering::Relaxed);
+                }
+                self.map.get(&id).cloned()
+            }
+            None => Some(Generated::default()),
+        }
+    }
+
+    /// Elimina el conjunto indicado, devolviéndolo si existía
+    ///
+    /// # Argumentos
+    ///
+    /// * `id` identificador del conjunto
+    pub fn remove(&mut self, id: u64) -> Option<Generated> {
+        if self.current == Some(id) {
+            self.current = None;
+        }
+        self.last_used.remove(&id);
+        let generated = self.map.remove(&id)?;
+        self.usage.sub(self.unshared_bytes(&generated));
+        Some(generated)
+    }
+
+    /// Elimina todos los conjuntos guardados
+    pub fn clear(&mut self) {
+        let ids: Vec<u64> = self.map.keys().copied().collect();
+        for id in ids {
+            self.remove(id);
+        }
+        self.current = None;
+    }
+
+    /// Si el cliente no tiene ningún conjunto guardado
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Conjunto usado hace más tiempo, con el momento de su último uso
+    pub fn least_recent(&self) -> Option<(u64, u64)> {
+        self.last_used
+            .iter()
+            .map(|(&id, used)| (used.load(Ordering::Relaxed), id))
+            .min()
+    }
+
+    /// Devuelve la descripción de todos los conjuntos guardados
+    pub fn list(&self) -> Vec<DatasetInfo> {
+        self.map
+            .iter()
+            .map(|(&id, generated)| DatasetInfo {
+                id,
+                metadata: generated.metadata.clone(),
+                len: generated.data.len(),
+                precision: generated.data.precision(),
+                bytes: generated.memory_bytes(),
+                spilled_chunks: generated.data.spilled_chunks(),
+                weighted: generated.weights.is_some(),
+                uniforms: generated.uniforms.as_ref().map(Samples::len),
+                current: self.current == Some(id),
+                reproducibility: generated.reproducibility.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Conjuntos de datos de cada cliente, para que no puedan ver ni modificar
+/// los datos de otros
+#[derive(Default)]
+pub struct Workspaces {
+    map: std::sync::RwLock<HashMap<Client, Arc<RwLock<Datasets>>>>,
+    /// Memoria ocupada por los conjuntos de todos los clientes
+    usage: Arc<MemoryUsage>,
+}
+
+impl Workspaces {
+    /// Devuelve los conjuntos de datos del cliente, creándolos si no existen
+    ///
+    /// # Argumentos
+    ///
+    /// * `client` cliente autenticado
+    pub fn get(&self, client: &Client) -> Arc<RwLock<Datasets>> {
+        if let Some(datasets) = self.map.read().unwrap().get(client) {
+            return datasets.clone();
+        }
+        self.map
+            .write()
+            .unwrap()
+            .entry(client.clone())
+            .or_insert_with(|| Arc::new(RwLock::new(Datasets::new(Arc::clone(&self.usage)))))
+            .clone()
+    }
+
+    /// Bytes en memoria de los conjuntos de datos de todos los clientes
+    pub fn memory_bytes(&self) -> u64 {
+        self.usage.bytes()
+    }
+
+    /// Hace lugar para guardar `bytes` más sin superar el presupuesto de
+    /// memoria, y los reserva hasta que se descarte la reserva devuelta, que
+    /// debe vivir hasta guardar el conjunto. Con `MemoryPolicy::Evict`
+    /// descarta los conjuntos usados hace más tiempo, de cualquier cliente;
+    /// con `MemoryPolicy::Reject`, o si no alcanza, devuelve un error 507.
+    /// Un conjunto descartado que alguna petición sigue usando se libera
+    /// cuando ésta termina
+    ///
+    /// # Argumentos
+    ///
+    /// * `bytes` memoria que ocupará el conjunto de datos nuevo
+    /// * `budget` presupuesto de memoria, None si no hay límite
+    /// * `policy` qué hacer si no hay lugar
+    pub async fn make_room(
+        &self,
+        bytes: u64,
+        budget: Option<u64>,
+        policy: MemoryPolicy,
+    ) -> Result<Reservation, ApiError> {
+        let Some(budget) = budget else { return Ok(Reservation { usage: None, bytes }) };
+        let insufficient = || {
+            ApiError::InsufficientStorage(format!(
+                "no hay memoria para guardar {} bytes: hay {} de {} bytes ocupados o reservados",
+                bytes,
+                self.usage.reserved.load(Ordering::Relaxed),
+                budget
+            ))
+        };
+        if bytes > budget {
+            return Err(insufficient());
+        }
+        while !self.usage.try_reserve(bytes, budget) {
+            if policy == MemoryPolicy::Reject {
+                return Err(insufficient());
+            }
+            let workspaces: Vec<_> = self.map.read().unwrap().values().cloned().collect();
+            let mut oldest = None;
+            for datasets in workspaces {
+                let Some((used, id)) = datasets.read().await.least_recent() else { continue };
+                if oldest.as_ref().is_none_or(|&(min, _, _)| used < min) {
+                    oldest = Some((used, id, datasets));
+                }
+            }
+            let Some((_, id, datasets)) = oldest else { return Err(insufficient()) };
+            let evicted = datasets.write().await.remove(id);
+            if let Some(generated) = evicted {
+                tracing::info!(
+                    "evicted dataset {} ({} bytes) to stay within the memory budget",
+                    id,
+                    generated.memory_bytes()
+                );
+            }
+        }
+        Ok(Reservation { usage: Some(Arc::clone(&self.usage)), bytes })
+    }
+}
+
+/// Extractor de los conjuntos de datos del cliente que realiza la petición
+pub struct ClientDatasets(pub Arc<RwLock<Datasets>>);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ClientDatasets {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let client = Client::from_request_parts(parts, state).await?;
+        Ok(ClientDatasets(state.datasets.get(&client)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use simrng::dist::uniform::Uniform;
+
+    // Función privada, conjunto de `len` valores en f64, `len * 8` bytes
+    fn dataset(len: usize) -> Generated {
+        Generated::new(vec![0.5f64; len], Box::new(Uniform { lower: 0f64, upper: 1f64 }))
+    }
+
+    #[tokio::test]
+    async fn test_make_room_reject() {
+        let workspaces = Workspaces::default();
+        let datasets = workspaces.get(&Client::anonymous());
+        let id = datasets.write().await.insert(dataset(10));
+        assert_eq!(workspaces.memory_bytes(), 80);
+        let err = workspaces.make_room(40, Some(100), MemoryPolicy::Reject).await.err().unwrap();
+        assert_eq!(err.into_response().status(), StatusCode::INSUFFICIENT_STORAGE);
+        // Lo reservado no lo puede tomar otra petición hasta descartarlo
+        let reservation = workspaces.make_room(20, Some(100), MemoryPolicy::Reject).await.unwrap();
+        assert!(workspaces.make_room(1, Some(100), MemoryPolicy::Reject).await.is_err());
+        drop(reservation);
+        assert!(workspaces.make_room(20, Some(100), MemoryPolicy::Reject).await.is_ok());
+        // Más que todo el presupuesto no entra aunque se descarte todo
+        assert!(workspaces.make_room(101, Some(100), MemoryPolicy::Evict).await.is_err());
+        assert!(datasets.read().await.get(Some(id)).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_make_room_evict() {
+        let workspaces = Workspaces::default();
+        let (a, b) = (workspaces.get(&Client("a".into())), workspaces.get(&Client("b".into())));
+        let first = a.write().await.insert(dataset(10));
+        let second = b.write().await.insert(dataset(10));
+        let third = a.write().await.insert(dataset(10));
+        // El primero se usó hace poco, el menos usado es el del otro cliente
+        a.read().await.get(Some(first));
+        let reservation = workspaces.make_room(80, Some(240), MemoryPolicy::Evict).await.unwrap();
+        assert!(b.read().await.get(Some(second)).is_none());
+        assert!(a.read().await.get(Some(first)).is_some());
+        assert!(a.read().await.get(Some(third)).is_some());
+        assert_eq!(workspaces.memory_bytes(), 160);
+        drop(reservation);
+    }
+
+    #[tokio::test]
+    async fn test_make_room_shared() {
+        let workspaces = Workspaces::default();
+        let datasets = workspaces.get(&Client::anonymous());
+        let id = datasets.write().await.insert(dataset(10));
+        // La copia comparte los valores, no ocupa más memoria
+        let copy = datasets.write().await.snapshot(Some(id), None).unwrap();
+        assert_eq!(workspaces.memory_bytes(), 80);
+        assert!(workspaces.make_room(20, Some(100), MemoryPolicy::Reject).await.is_ok());
+        datasets.write().await.remove(id);
+        assert_eq!(workspaces.memory_bytes(), 80);
+        datasets.write().await.remove(copy);
+        assert_eq!(workspaces.memory_bytes(), 0);
+    }
+}
@@ -1,28 +1,62 @@
 use crate::rng::Random;
-use serde::Deserialize;
-use crate::dist::Distribution;
+use serde::{Deserialize, Serialize};
+use crate::dist::{Distribution, DistributionLimits};
+use crate::math;
+use alloc::string::String;
+use alloc::vec::Vec;
 
-/// Distribución Exponencial, permite su generación y cálculo de estadísticas
-#[derive(Deserialize)]
+/// Distribución Exponencial, permite su generación y cálculo de estadísticas.
+/// Con `shift` es la exponencial de dos parámetros, desplazada para empezar
+/// en ese valor en lugar de 0
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(try_from = "ExponentialParameters")]
 pub struct Exponential {
     /// Lambda de la distribución
     pub lambda: f64,
+    /// Límite inferior de la distribución, 0 si no se indica
+    pub shift: f64,
+}
+
+/// Parámetros sin validar, tal como se reciben en Json: lambda o la media,
+/// que son excluyentes, y el límite inferior
+#[derive(Deserialize)]
+struct ExponentialParameters {
+    lambda: Option<f64>,
+    mean: Option<f64>,
+    #[serde(default)]
+    shift: f64,
+}
+
+impl TryFrom<ExponentialParameters> for Exponential {
+    type Error = String;
+
+    fn try_from(parameters: ExponentialParameters) -> Result<Self, Self::Error> {
+        let ExponentialParameters { lambda, mean, shift } = parameters;
+        match (lambda, mean) {
+            (Some(lambda), None) => Exponential::builder().lambda(lambda).shift(shift).build(),
+            (None, Some(mean)) => Exponential::from_mean(mean, shift),
+            (Some(_), Some(_)) => Err("lambda y mean son excluyentes, debe indicarse uno".into()),
+            (None, None) => Err("debe indicarse lambda o mean".into()),
+        }
+    }
 }
 
 impl Distribution for Exponential {
     fn get_expected(&self, intervals: usize, lower: f64, upper: f64) -> Vec<f64> {
         let size = (upper - lower) / intervals as f64;
-        let lambda = self.lambda;
         let mut interval_list: Vec<f64> = Vec::with_capacity(intervals);
         let mut interval = lower + size;
+        // La probabilidad por debajo de lower, si la hubiera, se suma al
+        // primer intervalo, y la de por encima de upper al último
         let mut acc_prev = 0f64;
         for i in 0..intervals {
+            #[allow(clippy::needless_late_init)]
             let acc;
             if i == intervals - 1 {
                 acc = 1.0;
             }
             else {
-                acc = 1.0 - (-lambda * interval).exp();
+                acc = self.cdf(interval);
             }
             let prob = acc - acc_prev;
             interval_list.push(prob);
@@ -37,19 +71,111 @@ impl Distribution for Exponential {
         else { 1 }
     }
 
-    fn get_intervals(&self, limits: crate::stats::DistributionLimits) -> crate::stats::DistributionLimits {
-        limits
+    fn get_intervals(&self, limits: DistributionLimits) -> DistributionLimits {
+        // Los intervalos empiezan en el límite inferior de la distribución,
+        // no antes, aunque el mínimo redondeado de la muestra sea menor
+        DistributionLimits {
+            lower: limits.lower.max(self.shift),
+            ..limits
+        }
+    }
+
+    fn intervals_reason(&self) -> Option<&'static str> {
+        Some("los intervalos empiezan en shift, el límite inferior de la Exponencial")
+    }
+
+    fn quantile(&self, p: f64) -> f64 {
+        self.shift - 1f64 / self.lambda * math::ln(1f64 - p)
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        if x <= self.shift { 0f64 }
+        else { 1f64 - math::exp(-self.lambda * (x - self.shift)) }
+    }
+}
+
+impl Default for Exponential {
+    /// Distribución con lambda 1, sin desplazar
+    fn default() -> Self {
+        Exponential { lambda: 1f64, shift: 0f64 }
     }
 }
 
 impl Exponential {
+    /// Devuelve un constructor de la distribución, con lambda 1 y sin
+    /// desplazar si no se indica
+    pub fn builder() -> ExponentialBuilder {
+        ExponentialBuilder::default()
+    }
+
+    /// Crea la distribución a partir de su media, con lambda
+    /// 1/(media - shift). Sin desplazar, la media es 1/λ
+    ///
+    /// # Argumentos
+    ///
+    /// * `mean` media de la distribución, mayor a shift
+    /// * `shift` límite inferior de la distribución
+    pub fn from_mean(mean: f64, shift: f64) -> Result<Exponential, String> {
+        if !shift.is_finite() {
+            return Err("shift debe ser un número finito".into());
+        }
+        if !(mean > shift && mean.is_finite()) {
+            return Err("la media debe ser mayor a shift".into());
+        }
+        Exponential::builder().lambda(1f64 / (mean - shift)).shift(shift).build()
+    }
+
+    /// Media de la distribución, shift + 1/λ
+    pub fn mean(&self) -> f64 {
+        self.shift + 1f64 / self.lambda
+    }
+
     /// Devuelve el siguiente número a ser generado por la distribución
     ///
     /// # Argumentos
     ///
     /// * `rand` el generador de números aleatorios a utilizar, implementa Random
     pub fn next(&self, rand: &mut dyn Random) -> f64 {
-        // (-1/λ) * ln(1-RND)
-        -1f64 / self.lambda * f64::ln(1f64 - rand.next())
+        // shift + (-1/λ) * ln(1-RND)
+        self.shift - 1f64 / self.lambda * math::ln(1f64 - rand.next())
+    }
+}
+
+/// Constructor de la distribución Exponential, valida los parámetros al construirla
+#[derive(Clone, Copy)]
+pub struct ExponentialBuilder {
+    lambda: f64,
+    shift: f64,
+}
+
+impl Default for ExponentialBuilder {
+    fn default() -> Self {
+        ExponentialBuilder { lambda: 1f64, shift: 0f64 }
+    }
+}
+
+impl ExponentialBuilder {
+    /// Lambda de la distribución
+    pub fn lambda(mut self, lambda: f64) -> Self {
+        self.lambda = lambda;
+        self
+    }
+
+    /// Límite inferior de la distribución
+    pub fn shift(mut self, shift: f64) -> Self {
+        self.shift = shift;
+        self
+    }
+
+    /// Construye la distribución, verificando que lambda sea positivo y el
+    /// desplazamiento finito
+    pub fn build(self) -> Result<Exponential, String> {
+        if !(self.lambda > 0f64 && self.lambda.is_finite()) {
+            return Err("lambda debe ser mayor a 0".into());
+        }
+        if !self.shift.is_finite() {
+            return Err("shift debe ser un número finito".into());
+        }
+        Ok(Exponential { lambda: self.lambda, shift: self.shift })
     }
 }
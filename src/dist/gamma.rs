@@ -0,0 +1,278 @@
+use crate::rng::Random;
+use serde::{Deserialize, Serialize};
+use crate::dist::normal::{Normal, NormalSampler};
+use crate::dist::{Distribution, DistributionLimits};
+use crate::math;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Distribución Gamma, de forma k y escala θ, permite el cálculo de
+/// estadísticas. Con forma entera es la distribución de Erlang, la suma de k
+/// exponenciales de lambda 1/θ. La generación se hace con un GammaSampler
+/// (ver `sampler`)
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(try_from = "GammaParameters")]
+pub struct Gamma {
+    /// Parámetro de forma, k
+    pub shape: f64,
+    /// Parámetro de escala, θ
+    pub scale: f64,
+}
+
+/// Parámetros sin validar, tal como se reciben en Json: la forma y la
+/// escala, la media y la varianza, o k y lambda de una Erlang
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GammaParameters {
+    Shape { shape: f64, scale: f64 },
+    Moments { mean: f64, variance: f64 },
+    Erlang { k: u64, lambda: f64 },
+}
+
+impl TryFrom<GammaParameters> for Gamma {
+    type Error = String;
+
+    fn try_from(parameters: GammaParameters) -> Result<Self, Self::Error> {
+        match parameters {
+            GammaParameters::Shape { shape, scale } => {
+                Gamma::builder().shape(shape).scale(scale).build()
+            }
+            GammaParameters::Moments { mean, variance } => Gamma::from_moments(mean, variance),
+            GammaParameters::Erlang { k, lambda } => Gamma::erlang(k, lambda),
+        }
+    }
+}
+
+/// Generador de valores de una distribución Gamma, con el estado de la
+/// Normal estándar que usa el método de Marsaglia y Tsang
+#[derive(Clone)]
+pub struct GammaSampler {
+    /// Parámetros de la distribución
+    gamma: Gamma,
+    /// Generador de la Normal estándar
+    normal: NormalSampler,
+}
+
+impl Distribution for Gamma {
+    fn get_expected(&self, intervals: usize, lower: f64, upper: f64) -> Vec<f64> {
+        let size = (upper - lower) / intervals as f64;
+        let mut interval_list: Vec<f64> = Vec::with_capacity(intervals);
+        let mut interval = lower + size;
+        // La probabilidad por debajo de lower, si la hubiera, se suma al
+        // primer intervalo, y la de por encima de upper al último
+        let mut acc_prev = 0f64;
+        for i in 0..intervals {
+            let acc = if i == intervals - 1 {
+                1.0
+            } else {
+                self.cdf(interval)
+            };
+            let prob = acc - acc_prev;
+            interval_list.push(prob);
+            acc_prev = acc;
+            interval += size;
+        }
+        interval_list
+    }
+
+    fn get_degrees(&self, intervals: usize) -> usize {
+        if intervals >= 4 { intervals - 3 }
+        else { 1 }
+    }
+
+    fn get_intervals(&self, limits: DistributionLimits) -> DistributionLimits {
+        // La distribución empieza en 0
+        DistributionLimits {
+            lower: limits.lower.max(0f64),
+            ..limits
+        }
+    }
+
+    fn intervals_reason(&self) -> Option<&'static str> {
+        Some("los intervalos empiezan en 0, el límite inferior de la Gamma")
+    }
+
+    fn quantile(&self, p: f64) -> f64 {
+        if p <= 0f64 {
+            return 0f64;
+        }
+        if p >= 1f64 {
+            return f64::INFINITY;
+        }
+        // No tiene forma cerrada: se acota y se busca por bisección
+        let mut lower = 0f64;
+        let mut upper = self.mean() + 10f64 * math::sqrt(self.variance());
+        while self.cdf(upper) < p {
+            lower = upper;
+            upper *= 2f64;
+        }
+        for _ in 0..200 {
+            let middle = (lower + upper) / 2f64;
+            if self.cdf(middle) < p {
+                lower = middle;
+            } else {
+                upper = middle;
+            }
+            if upper - lower <= 1e-14 * upper {
+                break;
+            }
+        }
+        (lower + upper) / 2f64
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        if x <= 0f64 { 0f64 }
+        else { math::gamma_p(self.shape, x / self.scale) }
+    }
+}
+
+impl Default for Gamma {
+    /// Distribución con forma 1 y escala 1, la exponencial de lambda 1
+    fn default() -> Self {
+        Gamma { shape: 1f64, scale: 1f64 }
+    }
+}
+
+impl Gamma {
+    /// Devuelve un constructor de la distribución, con forma 1 y escala 1 si
+    /// no se indican
+    pub fn builder() -> GammaBuilder {
+        GammaBuilder::default()
+    }
+
+    /// Crea la distribución a partir de su media y su varianza, con forma
+    /// media²/varianza y escala varianza/media
+    ///
+    /// # Argumentos
+    ///
+    /// * `mean` media de la distribución, mayor a 0
+    /// * `variance` varianza de la distribución, mayor a 0
+    pub fn from_moments(mean: f64, variance: f64) -> Result<Gamma, String> {
+        if !(mean > 0f64 && mean.is_finite()) {
+            return Err("la media debe ser mayor a 0".into());
+        }
+        if !(variance > 0f64 && variance.is_finite()) {
+            return Err("la varianza debe ser mayor a 0".into());
+        }
+        Gamma::builder().shape(mean * mean / variance).scale(variance / mean).build()
+    }
+
+    /// Crea una distribución de Erlang, la suma de k exponenciales de
+    /// parámetro lambda
+    ///
+    /// # Argumentos
+    ///
+    /// * `k` cantidad de exponenciales, mayor a 0
+    /// * `lambda` lambda de cada exponencial, mayor a 0
+    pub fn erlang(k: u64, lambda: f64) -> Result<Gamma, String> {
+        if k == 0 {
+            return Err("k debe ser mayor a 0".into());
+        }
+        if !(lambda > 0f64 && lambda.is_finite()) {
+            return Err("lambda debe ser mayor a 0".into());
+        }
+        Gamma::builder().shape(k as f64).scale(1f64 / lambda).build()
+    }
+
+    /// Media de la distribución, k·θ
+    pub fn mean(&self) -> f64 {
+        self.shape * self.scale
+    }
+
+    /// Varianza de la distribución, k·θ²
+    pub fn variance(&self) -> f64 {
+        self.shape * self.scale * self.scale
+    }
+
+    /// Devuelve un generador de valores de la distribución
+    pub fn sampler(&self) -> GammaSampler {
+        GammaSampler { gamma: self.clone(), normal: Normal::default().sampler() }
+    }
+}
+
+impl GammaSampler {
+    /// Parámetros de la distribución que genera
+    pub fn gamma(&self) -> &Gamma {
+        &self.gamma
+    }
+
+    /// Devuelve el siguiente número a ser generado por la distribución
+    ///
+    /// # Argumentos
+    ///
+    /// * `rand` el generador de números aleatorios a utilizar, implementa Random
+    pub fn next(&mut self, rand: &mut dyn Random) -> f64 {
+        self.gamma.scale * standard_gamma(self.gamma.shape, &mut self.normal, rand)
+    }
+}
+
+/// Valor de una Gamma(shape, 1) por el método de Marsaglia y Tsang. Para
+/// shape < 1 se usa Gamma(shape + 1)·U^(1/shape)
+///
+/// # Argumentos
+///
+/// * `shape` parámetro de forma, mayor a 0
+/// * `normal` generador de la Normal estándar
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub(crate) fn standard_gamma(
+    shape: f64,
+    normal: &mut NormalSampler,
+    rand: &mut dyn Random,
+) -> f64 {
+    if shape < 1f64 {
+        let u = 1f64 - rand.next();
+        return standard_gamma(shape + 1f64, normal, rand) * math::powf(u, 1f64 / shape);
+    }
+    let d = shape - 1f64 / 3f64;
+    let c = 1f64 / math::sqrt(9f64 * d);
+    loop {
+        let z = normal.next(rand);
+        let v = math::powi(1f64 + c * z, 3);
+        if v <= 0f64 {
+            continue;
+        }
+        let u = 1f64 - rand.next();
+        if math::ln(u) < 0.5 * z * z + d - d * v + d * math::ln(v) {
+            return d * v;
+        }
+    }
+}
+
+/// Constructor de la distribución Gamma, valida los parámetros al construirla
+#[derive(Clone, Copy)]
+pub struct GammaBuilder {
+    shape: f64,
+    scale: f64,
+}
+
+impl Default for GammaBuilder {
+    fn default() -> Self {
+        GammaBuilder { shape: 1f64, scale: 1f64 }
+    }
+}
+
+impl GammaBuilder {
+    /// Parámetro de forma de la distribución
+    pub fn shape(mut self, shape: f64) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Parámetro de escala de la distribución
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Construye la distribución, verificando que la forma y la escala sean
+    /// positivas y finitas
+    pub fn build(self) -> Result<Gamma, String> {
+        if !(self.shape > 0f64 && self.shape.is_finite()) {
+            return Err("shape debe ser mayor a 0".into());
+        }
+        if !(self.scale > 0f64 && self.scale.is_finite()) {
+            return Err("scale debe ser mayor a 0".into());
+        }
+        Ok(Gamma { shape: self.shape, scale: self.scale })
+    }
+}
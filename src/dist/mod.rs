@@ -1,9 +1,20 @@
-use crate::stats::DistributionLimits;
+use alloc::vec::Vec;
+use serde::Serialize;
 
 pub mod uniform;
 pub mod normal;
 pub mod exponential;
 pub mod poisson;
+pub mod gamma;
+pub mod table;
+
+/// Límites de los intervalos de la prueba de chi cuadrado y su cantidad
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct DistributionLimits {
+    pub lower: f64,
+    pub upper: f64,
+    pub intervals: usize,
+}
 
 /// Interfaz requerida para cualquier distribución
 pub trait Distribution {
@@ -16,19 +27,45 @@ pub trait Distribution {
     /// * `upper` límite superior de los intervalos a calcular
     fn get_expected(&self, intervals: usize, lower: f64, upper: f64) -> Vec<f64>;
     /// Devuelve los grados de libertad de la distribución para la prueba
-    /// de chi cuadrado
+    /// de chi cuadrado, con sus parámetros estimados a partir de la muestra:
+    /// intervalos - 1 - cantidad de parámetros. Con los parámetros fijados de
+    /// antemano, la prueba usa intervalos - 1 (ver
+    /// `StatisticsInput::parameters_estimated`)
     ///
     /// # Argumentos
     /// * `intervals` cantidad de intervalos a usarse para la prueba
     fn get_degrees(&self, intervals: usize) -> usize; // overflow error
     /// Devuelve el límite máximo y mínimo de la distribución generada
     /// y la cantidad de intervalos, en caso de que fuera necesario
-    /// sobreescribir los calculados
+    /// sobreescribir los calculados. Las estadísticas siempre usan los
+    /// límites devueltos, para el histograma y la prueba; los recibidos son
+    /// el mínimo y el máximo de la muestra, redondeados hacia afuera
     ///
     /// # Argumentos
-    /// * `intervals` cantidad de intervalos a usarse para la prueba
-    /// * `lower` límite inferior de los intervalos a calcular
-    /// * `upper` límite superior de los intervalos a calcular
+    /// * `limits` límites y cantidad de intervalos calculados con la muestra
     fn get_intervals(&self, limits: DistributionLimits) -> DistributionLimits;
+    /// Explica por qué `get_intervals` cambia los límites o la cantidad de
+    /// intervalos calculados con la muestra, para informarlo en la
+    /// respuesta de estadísticas. None si los usa sin cambios
+    fn intervals_reason(&self) -> Option<&'static str> {
+        None
+    }
+    /// Devuelve el cuantil p de la distribución, la inversa de la función
+    /// de distribución acumulada
+    ///
+    /// # Argumentos
+    /// * `p` probabilidad acumulada, entre 0 y 1
+    fn quantile(&self, p: f64) -> f64;
+    /// Devuelve la función de distribución acumulada evaluada en x
+    ///
+    /// # Argumentos
+    /// * `x` valor en el que se evalúa la función
+    fn cdf(&self, x: f64) -> f64;
+    /// Indica si la distribución es discreta. En la prueba de chi cuadrado,
+    /// el último intervalo de una distribución discreta es la clase abierta
+    /// "≥ k", con la probabilidad de toda la cola superior
+    fn is_discrete(&self) -> bool {
+        false
+    }
 }
 
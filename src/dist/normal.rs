@@ -1,17 +1,23 @@
 use crate::rng::Random;
-use serde::Deserialize;
-use crate::dist::Distribution;
-use std::f64::consts::PI;
+use serde::{Deserialize, Serialize};
+use crate::dist::{Distribution, DistributionLimits};
+use crate::math;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::f64::consts::{PI, SQRT_2};
 
 /// Algoritmo a usarse para la generación de una distribución Normal
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
 pub enum Algorithm {
+    #[default]
     BoxMuller,
     Convolution,
 }
 
-/// Distribución Normal, permite su generación y cálculo de estadísticas
-#[derive(Deserialize)]
+/// Distribución Normal, permite el cálculo de estadísticas. Sólo contiene
+/// los parámetros; la generación se hace con un NormalSampler (ver `sampler`)
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(try_from = "NormalParameters")]
 pub struct Normal {
     /// Algoritmo a utilizar para la generación
     pub algorithm: Algorithm,
@@ -19,9 +25,48 @@ pub struct Normal {
     pub mean: f64,
     /// Desviación estándar de la distribución
     pub sd: f64,
+}
+
+/// Parámetros sin validar, tal como se reciben en Json: el algoritmo, la
+/// media y la desviación estándar o la varianza, que son excluyentes
+#[derive(Deserialize)]
+struct NormalParameters {
+    algorithm: Algorithm,
+    mean: f64,
+    sd: Option<f64>,
+    variance: Option<f64>,
+}
+
+impl TryFrom<NormalParameters> for Normal {
+    type Error = String;
+
+    fn try_from(parameters: NormalParameters) -> Result<Self, Self::Error> {
+        let NormalParameters { algorithm, mean, sd, variance } = parameters;
+        let sd = match (sd, variance) {
+            (Some(sd), None) => sd,
+            (None, Some(variance)) if variance > 0f64 && variance.is_finite() => {
+                math::sqrt(variance)
+            }
+            (None, Some(_)) => return Err("variance debe ser mayor a 0".into()),
+            (Some(_), Some(_)) => {
+                return Err("sd y variance son excluyentes, debe indicarse uno".into())
+            }
+            (None, None) => return Err("debe indicarse sd o variance".into()),
+        };
+        Normal::builder().algorithm(algorithm).mean(mean).sd(sd).build()
+    }
+}
+
+/// Generador de valores de una distribución Normal, con el estado de la
+/// generación. No se serializa, por lo que el estado no puede recibirse
+/// desde afuera
+#[derive(Clone)]
+pub struct NormalSampler {
+    /// Parámetros de la distribución
+    normal: Normal,
     /// Para el caso de Box-Müller, next() devuelve el segundo número del par
     /// generado cuando se llama por segunda vez
-    pub pair: Option<f64>,
+    pair: Option<f64>,
 }
 
 impl Distribution for Normal {
@@ -33,8 +78,8 @@ impl Distribution for Normal {
         let mut interval = lower + (size / 2f64);
 
         for _ in 0..intervals {
-            let pt1 = 1f64 / (sd * f64::sqrt(2f64 * PI));
-            let pt2 = (-0.5 * ((interval - mean) / sd).powi(2)).exp();
+            let pt1 = 1f64 / (sd * math::sqrt(2f64 * PI));
+            let pt2 = math::exp(-0.5 * math::powi((interval - mean) / sd, 2));
             let prob = pt1 * pt2 * size;
             interval_list.push(prob);
             interval += size;
@@ -47,12 +92,69 @@ impl Distribution for Normal {
         else { 1 }
     }
 
-    fn get_intervals(&self, limits: crate::stats::DistributionLimits) -> crate::stats::DistributionLimits {
+    fn get_intervals(&self, limits: DistributionLimits) -> DistributionLimits {
         limits
     }
+
+    fn quantile(&self, p: f64) -> f64 {
+        self.mean + self.sd * standard_quantile(p)
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        standard_cdf((x - self.mean) / self.sd)
+    }
+}
+
+impl Default for Normal {
+    /// Normal estándar, generada por Box-Müller
+    fn default() -> Self {
+        Normal { algorithm: Algorithm::BoxMuller, mean: 0f64, sd: 1f64 }
+    }
 }
 
 impl Normal {
+    /// Devuelve un constructor de la distribución, con media 0, desviación
+    /// estándar 1 y el algoritmo de Box-Müller si no se indican
+    pub fn builder() -> NormalBuilder {
+        NormalBuilder::default()
+    }
+
+    /// Devuelve un generador de valores de la distribución, sin ningún
+    /// valor pendiente del par de Box-Müller
+    pub fn sampler(&self) -> NormalSampler {
+        NormalSampler { normal: self.clone(), pair: None }
+    }
+
+    // Funciones privadas, para uso por el generador
+
+    /// Devuelve un par de números generados por Box-Müller
+    fn get_bm(&self, rand: &mut dyn Random) -> (f64, f64) {
+        let rnd1 = rand.next();
+        let rnd2 = rand.next();
+        let z1 = math::sqrt(-2f64 * math::ln(1f64 - rnd1)) * math::cos(2f64 * PI * rnd2);
+        let z2 = math::sqrt(-2f64 * math::ln(1f64 - rnd1)) * math::sin(2f64 * PI * rnd2);
+        let n1 = z1 * self.sd + self.mean;
+        let n2 = z2 * self.sd + self.mean;
+        (n1, n2)
+    }
+
+    /// Devuelve un número generado por Convolución
+    fn get_conv(&self, rand: &mut dyn Random) -> f64 {
+        let mut sum = 0.0;
+        for _ in 0..12 {
+            sum += rand.next();
+        }
+        sum -= 6.0;
+        self.mean + self.sd * sum
+    }
+}
+
+impl NormalSampler {
+    /// Parámetros de la distribución que genera
+    pub fn normal(&self) -> &Normal {
+        &self.normal
+    }
+
     /// Devuelve el siguiente número a ser generado por la distribución
     ///
     /// # Argumentos
@@ -61,7 +163,7 @@ impl Normal {
     pub fn next(&mut self, rand: &mut dyn Random) -> f64 {
         // Define la variable a devolver, de tipo float de 64 bits
         let ret: f64;
-        match self.algorithm {
+        match self.normal.algorithm {
             Algorithm::BoxMuller => match self.pair {
                 Some(x) => {
                     // Si ya hay un valor generado que todavía no se devolvió
@@ -71,7 +173,7 @@ impl Normal {
                 }
                 None => {
                     // Si no, generar un par de valores nuevos por Box-Müller
-                    let gen = self.get_bm(rand);
+                    let gen = self.normal.get_bm(rand);
                     // Guardar el segundo para devolverlo en la próxima invocación
                     // y devolver el primero
                     self.pair = Some(gen.1);
@@ -79,32 +181,108 @@ impl Normal {
                 }
             },
             Algorithm::Convolution => {
-                ret = self.get_conv(rand);
+                ret = self.normal.get_conv(rand);
             }
         }
         ret
     }
+}
 
-    // Funciones privadas, para uso por el generador
+// Función privada, cuantil de la Normal estándar por la aproximación
+// racional de Acklam (error relativo menor a 1.15e-9)
+fn standard_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2,
+        1.38357751867269e2, -3.066479806614716e1, 2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2,
+        6.680131188771972e1, -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838,
+        -2.549732539343734, 4.374664141464968, 2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996, 3.754408661907416,
+    ];
+    const LOW: f64 = 0.02425;
+    if p <= 0f64 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1f64 {
+        return f64::INFINITY;
+    }
+    if p < LOW {
+        // Cola inferior
+        let q = math::sqrt(-2f64 * math::ln(p));
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1f64)
+    } else if p <= 1f64 - LOW {
+        // Región central
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1f64)
+    } else {
+        // Cola superior, por simetría
+        -standard_quantile(1f64 - p)
+    }
+}
 
-    /// Devuelve un par de números generados por Box-Müller
-    fn get_bm(&self, rand: &mut dyn Random) -> (f64, f64) {
-        let rnd1 = rand.next();
-        let rnd2 = rand.next();
-        let z1 = (-2f64 * (1f64 - rnd1).ln()).sqrt() * (2f64 * PI * rnd2).cos();
-        let z2 = (-2f64 * (1f64 - rnd1).ln()).sqrt() * (2f64 * PI * rnd2).sin();
-        let n1 = z1 * self.sd + self.mean;
-        let n2 = z2 * self.sd + self.mean;
-        (n1, n2)
+/// Función de distribución acumulada de la Normal estándar,
+/// Φ(z) = erfc(-z/√2)/2, con la erfc de `math` para no perder precisión en
+/// las colas
+///
+/// # Argumentos
+///
+/// * `z` valor en el que se evalúa la función
+pub fn standard_cdf(z: f64) -> f64 {
+    0.5 * math::erfc(-z / SQRT_2)
+}
+
+/// Constructor de la distribución Normal, valida los parámetros al construirla
+#[derive(Clone, Copy)]
+pub struct NormalBuilder {
+    algorithm: Algorithm,
+    mean: f64,
+    sd: f64,
+}
+
+impl Default for NormalBuilder {
+    fn default() -> Self {
+        NormalBuilder { algorithm: Algorithm::BoxMuller, mean: 0f64, sd: 1f64 }
     }
+}
 
-    /// Devuelve un número generado por Convolución
-    fn get_conv(&self, rand: &mut dyn Random) -> f64 {
-        let mut sum = 0.0;
-        for _ in 0..12 {
-            sum += rand.next();
+impl NormalBuilder {
+    /// Algoritmo de generación
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Media de la distribución
+    pub fn mean(mut self, mean: f64) -> Self {
+        self.mean = mean;
+        self
+    }
+
+    /// Desviación estándar de la distribución
+    pub fn sd(mut self, sd: f64) -> Self {
+        self.sd = sd;
+        self
+    }
+
+    /// Construye la distribución, verificando que la media sea finita y la
+    /// desviación estándar positiva
+    pub fn build(self) -> Result<Normal, String> {
+        if !self.mean.is_finite() {
+            return Err("mean debe ser finita".into());
         }
-        sum -= 6.0;
-        self.mean + self.sd * sum
+        if !(self.sd > 0f64 && self.sd.is_finite()) {
+            return Err("sd debe ser mayor a 0".into());
+        }
+        Ok(Normal { algorithm: self.algorithm, mean: self.mean, sd: self.sd })
     }
 }
@@ -1,24 +1,55 @@
-use crate::{dist::Distribution, stats::DistributionLimits};
-use serde::Deserialize;
+use crate::dist::{Distribution, DistributionLimits};
+use serde::{Deserialize, Serialize};
+use crate::math;
 use crate::rng::Random;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
 
 /// Distribución Poisson, permite su generación y cálculo de estadísticas
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(try_from = "PoissonParameters")]
 pub struct Poisson {
     /// Lambda de la distribución
     pub lambda: f64,
 }
 
+/// Parámetros sin validar, tal como se reciben en Json
+#[derive(Deserialize)]
+struct PoissonParameters {
+    lambda: f64,
+}
+
+impl TryFrom<PoissonParameters> for Poisson {
+    type Error = String;
+
+    fn try_from(parameters: PoissonParameters) -> Result<Self, Self::Error> {
+        Poisson::builder().lambda(parameters.lambda).build()
+    }
+}
+
 impl Distribution for Poisson {
     fn get_expected(&self, intervals: usize, lower: f64, _: f64) -> Vec<f64> {
-        let lambda = self.lambda;
+        // Un único intervalo incluye las dos colas, toda la probabilidad
+        match intervals {
+            0 => return Vec::new(),
+            1 => return vec![1f64],
+            _ => {}
+        }
         let mut interval_list: Vec<f64> = Vec::with_capacity(intervals);
-        let mut interval = lower as u64;
-        for _ in 0..intervals {
-            let prob = ((-lambda).exp() * lambda.powi(interval as i32)) / factorial(interval);
+        let first = lower as u64;
+        let last = first + intervals as u64 - 1;
+        for interval in first..last {
+            // El primer intervalo incluye la probabilidad de los valores
+            // menores, P(X <= first)
+            let prob = if interval == first { self.cdf(first as f64) } else { self.pmf(interval) };
             interval_list.push(prob);
-            interval += 1;
         }
+        // El último intervalo es la clase abierta "≥ last", con la
+        // probabilidad complementaria de la acumulada, P(X ≥ k) = P(k, λ)
+        let tail = if last == 0 { 1f64 } else { math::gamma_p(last as f64, self.lambda) };
+        interval_list.push(tail);
         interval_list
     }
 
@@ -28,26 +59,87 @@ impl Distribution for Poisson {
     }
 
     fn get_intervals(&self, limits: DistributionLimits) -> DistributionLimits {
-        let lower = limits.lower.trunc() as f64;
-        let upper = limits.upper.trunc() as f64 + 1.0;
+        let lower = math::trunc(limits.lower);
+        let upper = math::trunc(limits.upper) + 1.0;
         DistributionLimits {
             lower,
             upper,
             intervals: (upper - lower) as usize,
         }
     }
+
+    fn intervals_reason(&self) -> Option<&'static str> {
+        Some("un intervalo por cada valor entero entre el mínimo y el máximo de la \
+            muestra, el último como clase abierta")
+    }
+
+    fn quantile(&self, p: f64) -> f64 {
+        // Menor k tal que F(k) >= p, acumulando P(k) = P(k-1)·λ/k. Se parte
+        // de 10 desvíos por debajo de la media, donde F es despreciable
+        let mut k = math::floor(self.lambda - 10f64 * math::sqrt(self.lambda)).max(0f64) as u64;
+        let mut prob = self.pmf(k);
+        let mut acc = self.cdf(k as f64);
+        while acc < p && prob > 0f64 {
+            k += 1;
+            prob *= self.lambda / k as f64;
+            acc += prob;
+        }
+        k as f64
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        if x < 0f64 {
+            return 0f64;
+        }
+        // P(X <= k) = Q(k+1, λ), sin sumar los términos uno por uno
+        math::gamma_q(math::floor(x) + 1f64, self.lambda)
+    }
+
+    fn is_discrete(&self) -> bool {
+        true
+    }
+}
+
+impl Default for Poisson {
+    /// Distribución con lambda 1
+    fn default() -> Self {
+        Poisson { lambda: 1f64 }
+    }
 }
 
 impl Poisson {
+    /// Devuelve un constructor de la distribución, con lambda 1 si no se indica
+    pub fn builder() -> PoissonBuilder {
+        PoissonBuilder::default()
+    }
+
+    /// Probabilidad de k, P(X = k) = e^-λ·λ^k / k!, calculada en escala
+    /// logarítmica con ln Γ(k+1) para no desbordar con k o λ grandes
+    ///
+    /// # Argumentos
+    ///
+    /// * `k` valor cuya probabilidad se calcula
+    pub fn pmf(&self, k: u64) -> f64 {
+        let k = k as f64;
+        math::exp(k * math::ln(self.lambda) - self.lambda - math::ln_gamma(k + 1f64))
+    }
+
     /// Devuelve el siguiente número a ser generado por la distribución
     ///
     /// # Argumentos
     ///
     /// * `rand` el generador de números aleatorios a utilizar, implementa Random
     pub fn next(&self, rand: &mut dyn Random) -> f64 {
+        // Para lambda grande e^-λ no se puede representar; la suma de
+        // Poisson independientes es Poisson con la suma de los lambda
+        if self.lambda > MAX_DIRECT_LAMBDA {
+            let parts = math::ceil(self.lambda / MAX_DIRECT_LAMBDA);
+            let part = Poisson { lambda: self.lambda / parts };
+            return (0..parts as u64).map(|_| part.next(rand)).sum();
+        }
         let mut p: f64 = 1f64;
         let mut x: i64 = -1;
-        let a = f64::exp(-self.lambda);
+        let a = math::exp(-self.lambda);
         loop {
             let u = rand.next();
             p *= u;
@@ -60,8 +152,38 @@ impl Poisson {
     }
 }
 
-// Función privada, requerida por get_expected() de Poisson
-fn factorial(n: u64) -> f64 {
-    let prod: u64 = (1..=n as u64).product();
-    prod as f64
+/// Mayor lambda que se genera directamente, con e^-λ representable en f64
+const MAX_DIRECT_LAMBDA: f64 = 700f64;
+
+/// Mayor lambda admitido por el constructor: cada valor generado usa del
+/// orden de λ números uniformes
+pub const MAX_LAMBDA: f64 = 1e6;
+
+/// Constructor de la distribución Poisson, valida los parámetros al construirla
+#[derive(Clone, Copy)]
+pub struct PoissonBuilder {
+    lambda: f64,
+}
+
+impl Default for PoissonBuilder {
+    fn default() -> Self {
+        PoissonBuilder { lambda: 1f64 }
+    }
+}
+
+impl PoissonBuilder {
+    /// Lambda de la distribución
+    pub fn lambda(mut self, lambda: f64) -> Self {
+        self.lambda = lambda;
+        self
+    }
+
+    /// Construye la distribución, verificando que lambda sea positivo y no
+    /// supere `MAX_LAMBDA`
+    pub fn build(self) -> Result<Poisson, String> {
+        if !(self.lambda > 0f64 && self.lambda <= MAX_LAMBDA) {
+            return Err(format!("lambda debe ser mayor a 0 y a lo sumo {}", MAX_LAMBDA));
+        }
+        Ok(Poisson { lambda: self.lambda })
+    }
 }
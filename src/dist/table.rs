@@ -0,0 +1,147 @@
+use crate::rng::Random;
+use serde::{Deserialize, Serialize};
+use crate::dist::{Distribution, DistributionLimits};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Distribución definida numéricamente por una tabla de su función de
+/// distribución acumulada, interpolada linealmente entre los puntos
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(try_from = "CdfTable")]
+pub struct TabulatedCdf {
+    /// Puntos (x, F(x)), con x creciente, F no decreciente, el primero con
+    /// F = 0 y el último con F = 1
+    points: Vec<(f64, f64)>,
+}
+
+/// Tabla sin validar, tal como se recibe en Json
+#[derive(Deserialize)]
+struct CdfTable {
+    points: Vec<(f64, f64)>,
+}
+
+impl TryFrom<CdfTable> for TabulatedCdf {
+    type Error = String;
+
+    fn try_from(table: CdfTable) -> Result<Self, Self::Error> {
+        TabulatedCdf::new(table.points)
+    }
+}
+
+impl TabulatedCdf {
+    /// Crea la distribución a partir de los puntos (x, F(x)) de la tabla,
+    /// verificando que formen una función de distribución acumulada
+    ///
+    /// # Argumentos
+    ///
+    /// * `points` puntos de la tabla, ordenados por x
+    pub fn new(points: Vec<(f64, f64)>) -> Result<Self, String> {
+        if points.len() < 2 {
+            return Err("la tabla necesita al menos dos puntos".into());
+        }
+        if points[0].1 != 0f64 || points[points.len() - 1].1 != 1f64 {
+            return Err("la tabla debe comenzar en F = 0 y terminar en F = 1".into());
+        }
+        if points.iter().any(|(x, f)| !x.is_finite() || !f.is_finite()) {
+            return Err("la tabla contiene valores no finitos".into());
+        }
+        for pair in points.windows(2) {
+            if pair[1].0 <= pair[0].0 {
+                return Err("los valores de x deben ser crecientes".into());
+            }
+            if pair[1].1 < pair[0].1 {
+                return Err("los valores de F no pueden decrecer".into());
+            }
+        }
+        Ok(TabulatedCdf { points })
+    }
+
+    /// Puntos (x, F(x)) de la tabla
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    /// Devuelve la media y la varianza de la distribución, que es uniforme
+    /// dentro de cada segmento de la tabla
+    pub fn moments(&self) -> (f64, f64) {
+        let (mean, square) = self.points.windows(2).fold((0f64, 0f64), |(m, s), pair| {
+            let ((a, fa), (b, fb)) = (pair[0], pair[1]);
+            let p = fb - fa;
+            (m + p * (a + b) / 2f64, s + p * (a * a + a * b + b * b) / 3f64)
+        });
+        (mean, square - mean * mean)
+    }
+
+    /// Devuelve el siguiente número a ser generado por la distribución,
+    /// por transformada inversa
+    ///
+    /// # Argumentos
+    ///
+    /// * `rand` el generador de números aleatorios a utilizar, implementa Random
+    pub fn next(&self, rand: &mut dyn Random) -> f64 {
+        self.quantile(rand.next())
+    }
+}
+
+impl Distribution for TabulatedCdf {
+    fn get_expected(&self, intervals: usize, lower: f64, upper: f64) -> Vec<f64> {
+        let size = (upper - lower) / intervals as f64;
+        let mut interval_list: Vec<f64> = Vec::with_capacity(intervals);
+        let mut acc_prev = 0f64;
+        for i in 0..intervals {
+            // El último intervalo acumula hasta 1, por redondeo
+            let acc = if i == intervals - 1 {
+                1f64
+            } else {
+                self.cdf(lower + (i + 1) as f64 * size)
+            };
+            interval_list.push(acc - acc_prev);
+            acc_prev = acc;
+        }
+        interval_list
+    }
+
+    fn get_degrees(&self, intervals: usize) -> usize {
+        if intervals >= 2 { intervals - 1 }
+        else { 1 }
+    }
+
+    fn get_intervals(&self, limits: DistributionLimits) -> DistributionLimits {
+        DistributionLimits {
+            lower: self.points[0].0,
+            upper: self.points[self.points.len() - 1].0,
+            intervals: limits.intervals,
+        }
+    }
+
+    fn intervals_reason(&self) -> Option<&'static str> {
+        Some("los intervalos cubren la tabla, del primer al último punto")
+    }
+
+    fn quantile(&self, p: f64) -> f64 {
+        // Búsqueda binaria del primer punto con F mayor a p; el segmento
+        // anterior tiene F creciente, los segmentos planos se saltean
+        let i = self.points.partition_point(|&(_, f)| f <= p);
+        if i == 0 {
+            return self.points[0].0;
+        }
+        if i == self.points.len() {
+            return self.points[i - 1].0;
+        }
+        let ((x0, f0), (x1, f1)) = (self.points[i - 1], self.points[i]);
+        x0 + (x1 - x0) * (p - f0) / (f1 - f0)
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        // Primer punto con abscisa mayor a x; entre puntos se interpola
+        let i = self.points.partition_point(|&(px, _)| px <= x);
+        if i == 0 {
+            return 0f64;
+        }
+        if i == self.points.len() {
+            return 1f64;
+        }
+        let ((x0, f0), (x1, f1)) = (self.points[i - 1], self.points[i]);
+        f0 + (f1 - f0) * (x - x0) / (x1 - x0)
+    }
+}
@@ -1,9 +1,14 @@
-use crate::{rng::Random, stats::DistributionLimits};
-use serde::Deserialize;
-use crate::dist::Distribution;
+use crate::rng::Random;
+use serde::{Deserialize, Serialize};
+use crate::dist::{Distribution, DistributionLimits};
+use alloc::string::String;
+use alloc::vec::Vec;
 
-/// Distribución Uniforme, permite su generación y cálculo de estadísticas
-#[derive(Deserialize)]
+/// Distribución Uniforme, permite su generación y cálculo de estadísticas.
+/// Si se construye directamente con los dos límites iguales es la
+/// distribución degenerada en ese punto
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(try_from = "UniformParameters")]
 pub struct Uniform {
     /// Límite inferior de la distribución
     pub lower: f64,
@@ -11,12 +16,31 @@ pub struct Uniform {
     pub upper: f64,
 }
 
+/// Parámetros sin validar, tal como se reciben en Json
+#[derive(Deserialize)]
+struct UniformParameters {
+    lower: f64,
+    upper: f64,
+}
+
+impl TryFrom<UniformParameters> for Uniform {
+    type Error = String;
+
+    fn try_from(parameters: UniformParameters) -> Result<Self, Self::Error> {
+        Uniform::builder().lower(parameters.lower).upper(parameters.upper).build()
+    }
+}
+
 impl Distribution for Uniform {
     fn get_expected(&self, intervals: usize, lower: f64, upper: f64) -> Vec<f64> {
         let size = (upper - lower) / intervals as f64;
+        if self.upper <= self.lower {
+            return self.point_expected(intervals, lower, upper, size);
+        }
         let mut interval_list: Vec<f64> = Vec::with_capacity(intervals);
         let mut interval = lower;
         for _ in 0..intervals {
+            #[allow(clippy::if_same_then_else)]
             let inside_interval = {
                 if interval >= self.lower && (interval + size <= self.upper) {
                     size
@@ -48,9 +72,37 @@ impl Distribution for Uniform {
             intervals: limits.intervals,
         }
     }
+
+    fn intervals_reason(&self) -> Option<&'static str> {
+        Some("los intervalos cubren el soporte de la Uniforme, de lower a upper")
+    }
+
+    fn quantile(&self, p: f64) -> f64 {
+        self.lower + p * (self.upper - self.lower)
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        if self.upper <= self.lower {
+            return if x >= self.lower { 1f64 } else { 0f64 };
+        }
+        ((x - self.lower) / (self.upper - self.lower)).clamp(0f64, 1f64)
+    }
+}
+
+impl Default for Uniform {
+    /// Uniforme en [0, 1)
+    fn default() -> Self {
+        Uniform { lower: 0f64, upper: 1f64 }
+    }
 }
 
 impl Uniform {
+    /// Devuelve un constructor de la distribución, en [0, 1) si no se
+    /// indican los límites
+    pub fn builder() -> UniformBuilder {
+        UniformBuilder::default()
+    }
+
     /// Devuelve el siguiente número a ser generado por la distribución
     ///
     /// # Argumentos
@@ -60,4 +112,56 @@ impl Uniform {
         // a + RND * (b-a)
         self.lower + rand.next() * (self.upper - self.lower)
     }
+
+    // Función privada, probabilidades de los intervalos de la distribución
+    // degenerada: toda la probabilidad en el intervalo que contiene a lower,
+    // el último si es upper, y ninguna si está fuera de los intervalos
+    fn point_expected(&self, intervals: usize, lower: f64, upper: f64, size: f64) -> Vec<f64> {
+        let mut interval_list = alloc::vec![0f64; intervals];
+        if intervals == 0 || self.lower < lower || self.lower > upper {
+            return interval_list;
+        }
+        let index = if size > 0f64 { ((self.lower - lower) / size) as usize } else { 0 };
+        interval_list[index.min(intervals - 1)] = 1f64;
+        interval_list
+    }
+}
+
+/// Constructor de la distribución Uniforme, valida los parámetros al construirla
+#[derive(Clone, Copy)]
+pub struct UniformBuilder {
+    lower: f64,
+    upper: f64,
+}
+
+impl Default for UniformBuilder {
+    fn default() -> Self {
+        UniformBuilder { lower: 0f64, upper: 1f64 }
+    }
+}
+
+impl UniformBuilder {
+    /// Límite inferior de la distribución
+    pub fn lower(mut self, lower: f64) -> Self {
+        self.lower = lower;
+        self
+    }
+
+    /// Límite superior de la distribución
+    pub fn upper(mut self, upper: f64) -> Self {
+        self.upper = upper;
+        self
+    }
+
+    /// Construye la distribución, verificando que los límites sean finitos
+    /// y el inferior menor al superior
+    pub fn build(self) -> Result<Uniform, String> {
+        if !(self.lower.is_finite() && self.upper.is_finite()) {
+            return Err("los límites deben ser finitos".into());
+        }
+        if self.lower >= self.upper {
+            return Err("lower debe ser menor a upper".into());
+        }
+        Ok(Uniform { lower: self.lower, upper: self.upper })
+    }
 }
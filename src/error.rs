@@ -0,0 +1,82 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+
+/// Errores que pueden devolver los handlers de la API
+#[derive(Debug)]
+pub enum ApiError {
+    /// Los parámetros recibidos no son válidos
+    BadRequest(String),
+    /// No existe el recurso pedido
+    NotFound(String),
+    /// La petición no incluye una clave de API válida
+    Unauthorized(String),
+    /// La petición excede los límites configurados en el servidor
+    TooLarge(String),
+    /// No hay memoria disponible para guardar los datos dentro del
+    /// presupuesto configurado
+    InsufficientStorage(String),
+    /// Error interno del servidor
+    Internal(String),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ApiError::Unauthorized(message) => (StatusCode::UNAUTHORIZED, message),
+            ApiError::TooLarge(message) => (StatusCode::PAYLOAD_TOO_LARGE, message),
+            ApiError::InsufficientStorage(message) => (StatusCode::INSUFFICIENT_STORAGE, message),
+            ApiError::Internal(message) => {
+                tracing::error!("{}", message);
+                (StatusCode::INTERNAL_SERVER_ERROR, message)
+            }
+        };
+        // El mensaje se guarda también en las extensiones, para que la API
+        // versionada pueda devolverlo como Json
+        let mut response = (status, message.clone()).into_response();
+        response.extensions_mut().insert(ErrorMessage(message));
+        response
+    }
+}
+
+/// Mensaje de un ApiError, guardado en las extensiones de la respuesta
+#[derive(Clone)]
+pub struct ErrorMessage(pub String);
+
+/// Cuerpo Json de las respuestas de error de la API versionada
+#[derive(Serialize)]
+pub struct ErrorBody {
+    pub status: u16,
+    pub error: String,
+}
+
+/// Reemplaza el cuerpo de texto de las respuestas de error por un ErrorBody
+///
+/// # Argumentos
+///
+/// * `response` respuesta del handler
+pub async fn json_errors(response: Response) -> Response {
+    match response.extensions().get::<ErrorMessage>().cloned() {
+        Some(ErrorMessage(error)) => {
+            let status = response.status();
+            let body = ErrorBody { status: status.as_u16(), error };
+            (status, Json(body)).into_response()
+        }
+        None => response,
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(err: serde_json::Error) -> Self {
+        ApiError::BadRequest(err.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for ApiError {
+    fn from(err: rusqlite::Error) -> Self {
+        ApiError::Internal(err.to_string())
+    }
+}
@@ -0,0 +1,40 @@
+use core::fmt::Display;
+
+/// Tipo de punto flotante en el que se pueden guardar los valores generados.
+/// Los valores se generan y los cálculos se hacen siempre en f64; guardarlos
+/// en f32 reduce a la mitad la memoria de las muestras grandes. Sólo se
+/// implementa para f32 y f64, ya que los bloques en disco se leen como
+/// valores de estos tipos (ver `chunked`)
+pub trait Float: sealed::Sealed + Copy + PartialOrd + Display + Send + Sync + 'static {
+    /// Convierte un valor generado al tipo de almacenamiento
+    fn from_f64(x: f64) -> Self;
+    /// Convierte el valor guardado a f64, para los cálculos
+    fn to_f64(self) -> f64;
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for f64 {}
+    impl Sealed for f32 {}
+}
+
+impl Float for f64 {
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl Float for f32 {
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
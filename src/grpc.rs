@@ -0,0 +1,254 @@
+use futures_util::Stream;
+use simrng::dist::exponential::Exponential;
+use simrng::dist::normal::{Algorithm, Normal};
+use simrng::dist::poisson::Poisson;
+use simrng::dist::uniform::Uniform;
+use simrng::list::{EXPORT_CHUNK, PAGE_SIZE};
+use simrng::stats::{Binning, FinalBin, Scale, StatisticsInput, StatisticsResponse};
+use std::pin::Pin;
+use tonic::{Request, Response, Status};
+use tracing::Instrument;
+
+use crate::auth::{self, Client};
+use crate::controllers::{
+    check_number, check_scale, find_dataset, generate_samples, make_room, statistics_span, store,
+    stored_datasets, AppState,
+};
+use crate::datasets::Precision;
+use crate::error::ApiError;
+use crate::history::Metadata;
+use crate::storage::{Experiment, GenerationMode, Generator, StoredDistribution};
+
+/// Tipos generados a partir de proto/simrng.proto
+pub mod proto {
+    tonic::include_proto!("simrng");
+}
+
+use proto::generate_request::Distribution as ProtoDistribution;
+use proto::simrng_server::Simrng;
+pub use proto::simrng_server::SimrngServer;
+
+/// Servicio gRPC, comparte el estado con los handlers HTTP
+pub struct GrpcService {
+    state: AppState,
+}
+
+impl GrpcService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    // Función privada, autentica al cliente con la clave de los metadatos
+    // x-api-key o authorization, igual que en HTTP
+    #[allow(clippy::result_large_err)]
+    fn client<T>(&self, request: &Request<T>) -> Result<Client, Status> {
+        let metadata = request.metadata();
+        let key = auth::key_from_headers(
+            metadata.get("x-api-key").and_then(|v| v.to_str().ok()),
+            metadata.get("authorization").and_then(|v| v.to_str().ok()),
+        );
+        auth::authenticate(&self.state.config, key)
+            .ok_or_else(|| Status::unauthenticated("clave de API inválida o ausente"))
+    }
+}
+
+impl From<ApiError> for Status {
+    fn from(err: ApiError) -> Self {
+        match err {
+            ApiError::BadRequest(message) => Status::invalid_argument(message),
+            ApiError::NotFound(message) => Status::not_found(message),
+            ApiError::Unauthorized(message) => Status::unauthenticated(message),
+            ApiError::TooLarge(message) => Status::resource_exhausted(message),
+            ApiError::InsufficientStorage(message) => Status::resource_exhausted(message),
+            ApiError::Internal(message) => Status::internal(message),
+        }
+    }
+}
+
+impl TryFrom<ProtoDistribution> for StoredDistribution {
+    type Error = String;
+
+    // Con los constructores de cada distribución, que validan los parámetros
+    // igual que al deserializar el Json
+    fn try_from(dist: ProtoDistribution) -> Result<Self, Self::Error> {
+        Ok(match dist {
+            ProtoDistribution::Normal(n) => StoredDistribution::Normal(
+                Normal::builder()
+                    .algorithm(match n.algorithm() {
+                        proto::normal::Algorithm::BoxMuller => Algorithm::BoxMuller,
+                        proto::normal::Algorithm::Convolution => Algorithm::Convolution,
+                    })
+                    .mean(n.mean)
+                    .sd(n.sd)
+                    .build()?,
+            ),
+            ProtoDistribution::Uniform(u) => StoredDistribution::Uniform(
+                Uniform::builder().lower(u.lower).upper(u.upper).build()?,
+            ),
+            ProtoDistribution::Exponential(e) => StoredDistribution::Exponential(
+                Exponential::builder().lambda(e.lambda).shift(e.shift).build()?,
+            ),
+            ProtoDistribution::Poisson(p) => {
+                StoredDistribution::Poisson(Poisson::builder().lambda(p.lambda).build()?)
+            }
+        })
+    }
+}
+
+impl From<StatisticsResponse> for proto::StatisticsReply {
+    fn from(res: StatisticsResponse) -> Self {
+        let histogram = res.histogram;
+        let test = res.test;
+        proto::StatisticsReply {
+            histogram: Some(proto::Histogram {
+                x: histogram.x,
+                y: histogram.y,
+                lower: histogram.lower,
+                upper: histogram.upper,
+                size: histogram.size,
+                open_final_bin: histogram.final_bin == FinalBin::Open,
+                log_scale: histogram.scale == Scale::Log,
+                widths: histogram.widths.unwrap_or_default(),
+            }),
+            test: Some(proto::TestResult {
+                intervals: test
+                    .intervals
+                    .into_iter()
+                    .map(|i| proto::ChiInterval {
+                        lower: i.lower,
+                        upper: i.upper,
+                        fo: i.fo,
+                        fe: i.fe,
+                        c: i.c.unwrap_or(0f64),
+                        c_ac: i.c_ac.unwrap_or(0f64),
+                        open_upper: i.open_upper,
+                        upper_closed: i.upper_closed,
+                    })
+                    .collect(),
+                calculated: test.calculated,
+                critical: test.critical,
+                reject: test.reject,
+                alpha: test.alpha,
+                degrees: test.degrees as u32,
+                conclusion: test.conclusion,
+            }),
+            extended: res.extended.map(|d| proto::Distances {
+                total_variation: d.total_variation,
+                wasserstein: d.wasserstein,
+                max_cdf_gap: d.max_cdf_gap,
+            }),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Simrng for GrpcService {
+    async fn generate(
+        &self,
+        request: Request<proto::GenerateRequest>,
+    ) -> Result<Response<proto::GenerateReply>, Status> {
+        let client = self.client(&request)?;
+        let req = request.into_inner();
+        let dist = req
+            .distribution
+            .ok_or_else(|| Status::invalid_argument("falta la distribución"))?;
+        check_number(&self.state.config, req.number)?;
+        let metadata = Metadata { label: req.label, description: req.description, tags: req.tags };
+        metadata.validate().map_err(Status::invalid_argument)?;
+        let experiment = Experiment {
+            seed: req.seed,
+            distribution: StoredDistribution::try_from(dist).map_err(Status::invalid_argument)?,
+            mode: GenerationMode::Independent,
+            discard: 0,
+            generator: Generator::default(),
+            decimals: None,
+            bounds: None,
+        };
+        let number = req.number;
+        let _reservation = make_room(&self.state, number.saturating_mul(8)).await?;
+        let task_experiment = experiment.clone();
+        let spill = self.state.config.spill();
+        let res = tokio::task::spawn_blocking(move || {
+            generate_samples(number, &task_experiment, Precision::F64, spill)
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map_err(|e| Status::internal(e.to_string()))?;
+        let (id, _) = store(&self.state, &client, experiment, res, None, metadata, true).await?;
+        Ok(Response::new(proto::GenerateReply { id }))
+    }
+
+    async fn statistics(
+        &self,
+        request: Request<proto::StatisticsRequest>,
+    ) -> Result<Response<proto::StatisticsReply>, Status> {
+        let client = self.client(&request)?;
+        let req = request.into_inner();
+        let datasets = stored_datasets(&self.state, &client).await?;
+        let generated = find_dataset(&datasets, req.dataset).await?;
+        let input = StatisticsInput {
+            intervals: req.intervals as usize,
+            significance: Some(
+                req.significance
+                    .map_or(self.state.config.default_significance(), |s| s as usize),
+            ),
+            final_bin: if req.open_final_bin { FinalBin::Open } else { FinalBin::Closed },
+            from: req.from.map(|from| from as usize),
+            to: req.to.map(|to| to as usize),
+            scale: if req.log_scale { Scale::Log } else { Scale::Linear },
+            trim: None,
+            binning: if req.quantile_bins { Binning::Quantile } else { Binning::EqualWidth },
+            alphas: None,
+            parameters_estimated: req.parameters_estimated,
+            extended: req.extended,
+        };
+        input.check_intervals().map_err(Status::invalid_argument)?;
+        input.check_range(generated.data.len()).map_err(Status::invalid_argument)?;
+        input.check_significance().map_err(Status::invalid_argument)?;
+        let input = check_scale(input, &generated.data, &generated.dist).await?;
+        let distribution = generated.reproducibility.as_ref().map(|r| r.distribution.name());
+        let span = statistics_span(distribution, &input, generated.data.len());
+        let res = generated
+            .data
+            .statistics(input, generated.weights, generated.dist)
+            .instrument(span)
+            .await;
+        Ok(Response::new(res.into()))
+    }
+
+    async fn page(
+        &self,
+        request: Request<proto::PageRequest>,
+    ) -> Result<Response<proto::PageReply>, Status> {
+        let client = self.client(&request)?;
+        let req = request.into_inner();
+        if req.page == 0 {
+            return Err(Status::invalid_argument("page empieza en 1"));
+        }
+        let datasets = stored_datasets(&self.state, &client).await?;
+        let generated = find_dataset(&datasets, req.dataset).await?;
+        let start = PAGE_SIZE * (req.page as usize - 1);
+        let values = generated.data.range_f64(start, start + PAGE_SIZE);
+        Ok(Response::new(proto::PageReply { values }))
+    }
+
+    type ExportStream = Pin<Box<dyn Stream<Item = Result<proto::Chunk, Status>> + Send>>;
+
+    // Status es grande, pero el tipo del stream lo define tonic
+    #[allow(clippy::result_large_err)]
+    async fn export(
+        &self,
+        request: Request<proto::DatasetRequest>,
+    ) -> Result<Response<Self::ExportStream>, Status> {
+        let client = self.client(&request)?;
+        let req = request.into_inner();
+        let datasets = stored_datasets(&self.state, &client).await?;
+        let nums = find_dataset(&datasets, req.dataset).await?.data;
+        let chunks = nums.len().div_ceil(EXPORT_CHUNK);
+        let stream = futures_util::stream::iter((0..chunks).map(move |chunk| {
+            let start = chunk * EXPORT_CHUNK;
+            Ok(proto::Chunk { values: nums.range_f64(start, start + EXPORT_CHUNK) })
+        }));
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
@@ -0,0 +1,360 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use simrng::stats::descriptive::Summary;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::auth::Client;
+use crate::datasets::Precision;
+use crate::storage::{
+    Bounds, Experiment, GenerationMode, Generator, Reproducibility, StoredDistribution,
+};
+
+/// Historial de experimentos generados, guardado en SQLite
+pub struct History {
+    conn: Mutex<Connection>,
+}
+
+/// Cantidad máxima de etiquetas de un experimento
+pub const MAX_TAGS: usize = 32;
+
+/// Cantidad máxima de caracteres de una etiqueta
+pub const MAX_TAG_LEN: usize = 64;
+
+/// Cantidad de experimentos recientes del cliente entre los que se busca
+/// uno con la misma semilla, generador y distribución
+pub const DUPLICATE_LOOKBACK: i64 = 100;
+
+/// Nombre, descripción y etiquetas de un experimento, para organizar los
+/// conjuntos de datos y el historial en una sesión larga
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct Metadata {
+    /// Etiqueta (nombre) del conjunto de datos
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Descripción libre del experimento
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Etiquetas del experimento, para filtrar el historial con `?tag=`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Metadata {
+    /// Metadatos con sólo la etiqueta indicada
+    ///
+    /// # Argumentos
+    ///
+    /// * `label` etiqueta (nombre) del conjunto de datos
+    pub fn labeled(label: Option<String>) -> Self {
+        Self { label, ..Self::default() }
+    }
+
+    /// Verifica que haya como mucho `MAX_TAGS` etiquetas, no vacías, sin
+    /// repetir y de hasta `MAX_TAG_LEN` caracteres
+    pub fn validate(&self) -> Result<(), String> {
+        if self.tags.len() > MAX_TAGS {
+            return Err(format!("no puede haber más de {} etiquetas", MAX_TAGS));
+        }
+        for (i, tag) in self.tags.iter().enumerate() {
+            if tag.trim().is_empty() {
+                return Err("las etiquetas no pueden estar vacías".into());
+            }
+            if tag.chars().count() > MAX_TAG_LEN {
+                return Err(format!("las etiquetas no pueden superar {} caracteres", MAX_TAG_LEN));
+            }
+            if self.tags[..i].contains(tag) {
+                return Err(format!("la etiqueta {} está repetida", tag));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Experimento registrado en el historial
+#[derive(Serialize)]
+pub struct HistoryEntry {
+    /// Identificador del experimento
+    pub id: i64,
+    /// Momento de la generación, en segundos desde UNIX_EPOCH
+    pub timestamp: u64,
+    /// Semilla utilizada
+    pub seed: u64,
+    /// Cantidad de valores generados
+    pub number: u64,
+    /// Distribución con sus parámetros
+    pub distribution: StoredDistribution,
+    /// Modo de generación
+    pub mode: GenerationMode,
+    /// Cantidad de valores iniciales descartados
+    pub discard: u64,
+    /// Estadísticos descriptivos de los datos generados
+    pub summary: Summary,
+    /// Generador de números aleatorios uniformes
+    pub generator: Generator,
+    /// Precisión con la que se guardaron los valores
+    pub precision: Precision,
+    /// Cantidad de decimales a los que se redondearon los valores
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+    /// Límites de los valores
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<Bounds>,
+    /// Versión de simrng con la que se generaron los valores, vacía en los
+    /// experimentos registrados antes de guardarla
+    pub version: String,
+    /// Nombre, descripción y etiquetas del experimento
+    #[serde(flatten)]
+    pub metadata: Metadata,
+}
+
+impl HistoryEntry {
+    /// Parámetros con los que se generó el experimento, para repetirlo
+    pub fn experiment(&self) -> Experiment {
+        Experiment {
+            seed: self.seed,
+            distribution: self.distribution.clone(),
+            mode: self.mode,
+            discard: self.discard,
+            generator: self.generator,
+            decimals: self.decimals,
+            bounds: self.bounds,
+        }
+    }
+
+    /// Manifiesto de reproducibilidad del experimento
+    pub fn reproducibility(&self) -> Reproducibility {
+        Reproducibility {
+            seed: self.seed,
+            generator: self.generator,
+            distribution: self.distribution.clone(),
+            mode: self.mode,
+            discard: self.discard,
+            number: self.number,
+            precision: self.precision,
+            decimals: self.decimals,
+            bounds: self.bounds,
+            version: self.version.clone(),
+        }
+    }
+}
+
+impl History {
+    /// Abre (o crea) la base de datos del historial. Si no se indica una
+    /// ruta, el historial se mantiene en memoria
+    ///
+    /// # Argumentos
+    ///
+    /// * `path` ruta del archivo SQLite
+    pub fn open(path: Option<&Path>) -> rusqlite::Result<Self> {
+        let conn = match path {
+            Some(path) => Connection::open(path)?,
+            None => Connection::open_in_memory()?,
+        };
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                seed TEXT NOT NULL,
+                number INTEGER NOT NULL,
+                distribution TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                client TEXT NOT NULL DEFAULT '',
+                mode TEXT NOT NULL DEFAULT '\"Independent\"',
+                generator TEXT NOT NULL DEFAULT '\"Xoshiro256PlusPlus\"',
+                precision TEXT NOT NULL DEFAULT '\"F64\"',
+                version TEXT NOT NULL DEFAULT '',
+                discard INTEGER NOT NULL DEFAULT 0,
+                label TEXT,
+                description TEXT,
+                tags TEXT NOT NULL DEFAULT '[]',
+                decimals INTEGER,
+                bounds TEXT
+            );",
+        )?;
+        // Bases de datos creadas con versiones anteriores pueden no tener
+        // las columnas agregadas después
+        for (column, definition) in [
+            ("client", "TEXT NOT NULL DEFAULT ''"),
+            ("mode", "TEXT NOT NULL DEFAULT '\"Independent\"'"),
+            ("generator", "TEXT NOT NULL DEFAULT '\"Xoshiro256PlusPlus\"'"),
+            ("precision", "TEXT NOT NULL DEFAULT '\"F64\"'"),
+            ("version", "TEXT NOT NULL DEFAULT ''"),
+            ("discard", "INTEGER NOT NULL DEFAULT 0"),
+            ("label", "TEXT"),
+            ("description", "TEXT"),
+            ("tags", "TEXT NOT NULL DEFAULT '[]'"),
+            ("decimals", "INTEGER"),
+            ("bounds", "TEXT"),
+        ] {
+            let exists: bool = conn.query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('history') WHERE name = ?1",
+                [column],
+                |row| row.get(0),
+            )?;
+            if !exists {
+                conn.execute_batch(&format!("ALTER TABLE history ADD COLUMN {} {}", column, definition))?;
+            }
+        }
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// Registra una generación en el historial y devuelve su identificador
+    ///
+    /// # Argumentos
+    ///
+    /// * `client` cliente que realizó la generación
+    /// * `manifest` semilla, generador, parámetros y versión de la generación
+    /// * `summary` estadísticos descriptivos de los datos generados
+    /// * `metadata` nombre, descripción y etiquetas del experimento
+    pub fn record(
+        &self,
+        client: &Client,
+        manifest: &Reproducibility,
+        summary: &Summary,
+        metadata: &Metadata,
+    ) -> rusqlite::Result<i64> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO history
+                (timestamp, seed, number, distribution, summary, client, mode, generator, precision,
+                 version, discard, label, description, tags, decimals, bounds)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            params![
+                timestamp as i64,
+                // SQLite sólo admite enteros con signo, se guarda como texto
+                manifest.seed.to_string(),
+                manifest.number as i64,
+                serde_json::to_string(&manifest.distribution).unwrap(),
+                serde_json::to_string(summary).unwrap(),
+                client.0,
+                serde_json::to_string(&manifest.mode).unwrap(),
+                serde_json::to_string(&manifest.generator).unwrap(),
+                serde_json::to_string(&manifest.precision).unwrap(),
+                manifest.version,
+                manifest.discard as i64,
+                metadata.label,
+                metadata.description,
+                serde_json::to_string(&metadata.tags).unwrap(),
+                manifest.decimals,
+                manifest.bounds.map(|bounds| serde_json::to_string(&bounds).unwrap()),
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Devuelve los experimentos registrados por el cliente, del más reciente
+    /// al más antiguo, opcionalmente sólo los que tienen una etiqueta
+    ///
+    /// # Argumentos
+    ///
+    /// * `client` cliente que realizó las generaciones
+    /// * `tag` etiqueta que deben tener los experimentos, None para todos
+    pub fn list(&self, client: &Client, tag: Option<&str>) -> rusqlite::Result<Vec<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, timestamp, seed, number, distribution, summary, mode, generator, precision, version,
+                 discard, label, description, tags, decimals, bounds
+             FROM history
+             WHERE client = ?1
+                 AND (?2 IS NULL OR EXISTS (SELECT 1 FROM json_each(history.tags) WHERE value = ?2))
+             ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map(params![client.0, tag], row_to_entry)?;
+        rows.collect()
+    }
+
+    /// Devuelve el experimento más reciente del cliente, entre los últimos
+    /// `DUPLICATE_LOOKBACK`, con la misma semilla, generador y distribución
+    /// que el indicado, que por lo tanto generó los mismos valores
+    ///
+    /// # Argumentos
+    ///
+    /// * `client` cliente que realizó las generaciones
+    /// * `experiment` experimento a buscar
+    pub fn find_duplicate(
+        &self,
+        client: &Client,
+        experiment: &Experiment,
+    ) -> rusqlite::Result<Option<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT id, timestamp, seed, number, distribution, summary, mode, generator, precision, version,
+                 discard, label, description, tags, decimals, bounds
+             FROM (SELECT * FROM history WHERE client = ?1 ORDER BY id DESC LIMIT ?5)
+             WHERE seed = ?2 AND generator = ?3 AND distribution = ?4
+             ORDER BY id DESC LIMIT 1",
+            params![
+                client.0,
+                experiment.seed.to_string(),
+                serde_json::to_string(&experiment.generator).unwrap(),
+                serde_json::to_string(&experiment.distribution).unwrap(),
+                DUPLICATE_LOOKBACK,
+            ],
+            row_to_entry,
+        )
+        .optional()
+    }
+
+    /// Devuelve el experimento con el identificador indicado, si existe y
+    /// pertenece al cliente
+    ///
+    /// # Argumentos
+    ///
+    /// * `client` cliente que realizó la generación
+    /// * `id` identificador del experimento
+    pub fn get(&self, client: &Client, id: i64) -> rusqlite::Result<Option<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT id, timestamp, seed, number, distribution, summary, mode, generator, precision, version,
+                 discard, label, description, tags, decimals, bounds
+             FROM history WHERE id = ?1 AND client = ?2",
+            params![id, client.0],
+            row_to_entry,
+        )
+        .optional()
+    }
+}
+
+// Función privada, convierte una fila de la tabla en un HistoryEntry
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
+    let seed: String = row.get(2)?;
+    let distribution: String = row.get(4)?;
+    let summary: String = row.get(5)?;
+    let mode: String = row.get(6)?;
+    let generator: String = row.get(7)?;
+    let precision: String = row.get(8)?;
+    let tags: String = row.get(13)?;
+    let bounds: Option<String> = row.get(15)?;
+    let json_err = |i, e: serde_json::Error| {
+        rusqlite::Error::FromSqlConversionFailure(i, rusqlite::types::Type::Text, Box::new(e))
+    };
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        timestamp: row.get::<_, i64>(1)? as u64,
+        seed: seed.parse().map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        number: row.get::<_, i64>(3)? as u64,
+        distribution: serde_json::from_str(&distribution).map_err(|e| json_err(4, e))?,
+        summary: serde_json::from_str(&summary).map_err(|e| json_err(5, e))?,
+        mode: serde_json::from_str(&mode).map_err(|e| json_err(6, e))?,
+        discard: row.get::<_, i64>(10)? as u64,
+        generator: serde_json::from_str(&generator).map_err(|e| json_err(7, e))?,
+        precision: serde_json::from_str(&precision).map_err(|e| json_err(8, e))?,
+        decimals: row.get(14)?,
+        bounds: bounds
+            .map(|bounds| serde_json::from_str(&bounds).map_err(|e| json_err(15, e)))
+            .transpose()?,
+        version: row.get(9)?,
+        metadata: Metadata {
+            label: row.get(11)?,
+            description: row.get(12)?,
+            tags: serde_json::from_str(&tags).map_err(|e| json_err(13, e))?,
+        },
+    })
+}
@@ -1,16 +1,38 @@
+// Sin el feature std sólo se compilan las distribuciones y los generadores,
+// con alloc y libm
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod chunked;
+#[cfg(feature = "std")]
+pub mod copula;
 pub mod dist;
+pub mod float;
+#[cfg(feature = "std")]
 pub mod list;
+pub mod math;
+#[cfg(feature = "std")]
+pub mod montecarlo;
 pub mod rng;
+#[cfg(feature = "std")]
+pub mod sampling;
+#[cfg(feature = "std")]
+pub mod sim;
+#[cfg(feature = "std")]
 pub mod stats;
+#[cfg(feature = "std")]
+pub mod transform;
+#[cfg(all(feature = "std", any(test, feature = "testing")))]
+pub mod testing;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
-    use std::sync::Arc;
-
     use super::*;
     use crate::{
         rng::{LinearCongruentialGenerator, Random},
-        stats::{full_statistics, TestResult}, dist::{uniform::Uniform, normal::{Normal, Algorithm}},
+        stats::{statistics, TestResult}, dist::{uniform::Uniform, normal::{Normal, Algorithm}, table::TabulatedCdf},
     };
     use dist::Distribution;
     use stats::chi_squared_critical_value;
@@ -37,7 +59,6 @@ mod tests {
             mean: 10f64,
             sd: 2f64,
             algorithm: Algorithm::BoxMuller,
-            pair: None,
         };
         let data: Vec<f64> = normal
             .get_expected(8, 6f64, 14f64)
@@ -62,7 +83,6 @@ mod tests {
             mean: 182.41f64,
             sd: 7.01f64,
             algorithm: Algorithm::BoxMuller,
-            pair: None,
         };
         let data: Vec<f64> = normal
             .get_expected(12, 164f64, 201.00000001f64)
@@ -93,11 +113,16 @@ mod tests {
             mean: 10f64,
             sd: 2f64,
             algorithm: Algorithm::BoxMuller,
-            pair: None,
-        };
+        }
+        .sampler();
         let mut random = LinearCongruentialGenerator::new(6, 8, 13, 7);
         assert_eq!(trunc_to_dec(normal.next(&mut random), 4), 12.8011);
         assert_eq!(trunc_to_dec(normal.next(&mut random), 4), 10.0);
+        // El estado de la generación no se recibe con los parámetros
+        let json = r#"{"algorithm":"BoxMuller","mean":10,"sd":2,"pair":99}"#;
+        let parsed: Normal = serde_json::from_str(json).unwrap();
+        let mut random = LinearCongruentialGenerator::new(6, 8, 13, 7);
+        assert_eq!(trunc_to_dec(parsed.sampler().next(&mut random), 4), 12.8011);
     }
 
     #[test]
@@ -106,8 +131,8 @@ mod tests {
             mean: 10f64,
             sd: 2f64,
             algorithm: Algorithm::Convolution,
-            pair: None,
-        };
+        }
+        .sampler();
         let mut random = LinearCongruentialGenerator::new(6, 8, 13, 7);
         assert_eq!(trunc_to_dec(normal.next(&mut random), 4), 8.5);
     }
@@ -120,6 +145,40 @@ mod tests {
         assert_eq!(trunc_to_dec(random.next(), 4), 0.875);
     }
 
+    #[test]
+    fn test_lcg_diagnostics() {
+        use rng::SeedWarning;
+        let lcg = LinearCongruentialGenerator::with_seed(1);
+        assert!(lcg.full_period());
+        assert_eq!(lcg.period(10), Some((1 << 32, 0)));
+        assert!(lcg.diagnose(1_000_000).is_empty());
+        // Período completo con m = 8: se repite a partir del noveno valor
+        let lcg = LinearCongruentialGenerator::new(6, 8, 13, 7);
+        assert!(lcg.diagnose(8).is_empty());
+        assert_eq!(lcg.diagnose(9), vec![SeedWarning::ExceedsPeriod { period: 8, requested: 9 }]);
+        // Semilla 0 con c = 0
+        let lcg = LinearCongruentialGenerator::new(0, 16, 5, 0);
+        assert_eq!(
+            lcg.diagnose(10),
+            vec![SeedWarning::ZeroSeed, SeedWarning::ExceedsPeriod { period: 1, requested: 10 }]
+        );
+        // c y m no coprimos: 1, 9, 1, ...
+        let lcg = LinearCongruentialGenerator::new(1, 16, 5, 4);
+        assert!(!lcg.full_period());
+        assert_eq!(lcg.period(100), Some((2, 0)));
+        assert_eq!(
+            lcg.diagnose(10),
+            vec![SeedWarning::NotCoprime { gcd: 4 }, SeedWarning::ExceedsPeriod { period: 2, requested: 10 }]
+        );
+        // Valores previos al ciclo: 2, 4, 8, 0, 0, ...
+        let mut lcg = LinearCongruentialGenerator::new(1, 16, 2, 0);
+        assert_eq!(lcg.period(100), Some((1, 4)));
+        assert!(lcg.diagnose(4).is_empty());
+        assert_eq!(lcg.diagnose(5).len(), 1);
+        let values: Vec<f64> = (0..5).map(|_| lcg.next() * 16f64).collect();
+        assert_eq!(values, vec![2f64, 4f64, 8f64, 0f64, 0f64]);
+    }
+
     #[test]
     fn test_chisquared() {
         let nums: Vec<f64> = vec![
@@ -157,19 +216,1833 @@ mod tests {
             algorithm: Algorithm::BoxMuller,
             mean: 182.41f64,
             sd: 7.01f64,
-            pair: None,
-        };
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        let res = rt.block_on(full_statistics(
-            stats::StatisticsInput { intervals: 12, significance: 7 },
-            Arc::new(nums),
-            Arc::new(Box::new(normal)),
-        ));
+        };
+        let res = statistics(
+            stats::StatisticsInput {
+                intervals: 12,
+                significance: Some(7),
+                final_bin: stats::FinalBin::Closed,
+                from: None,
+                to: None,
+                scale: stats::Scale::Linear,
+                trim: None,
+                binning: stats::Binning::EqualWidth,
+                alphas: None,
+                parameters_estimated: true,
+                extended: false,
+            },
+            &nums,
+            &normal,
+        );
+        // La Normal usa los límites de la muestra sin cambios
+        assert!(res.histogram.adjustment.is_none());
         let test: TestResult = res.test;
         assert_eq!(trunc_to_dec(test.critical, 1), 14.0);
         assert_eq!(trunc_to_dec(test.calculated, 1), 10.1);
     }
 
+    #[test]
+    fn test_summary() {
+        let summary = stats::descriptive::summary(&[2f64, 4f64, 4f64, 4f64, 5f64, 5f64, 7f64, 9f64]);
+        assert_eq!(summary.count, 8);
+        assert_eq!(summary.mean, 5f64);
+        assert_eq!(trunc_to_dec(summary.variance, 4), 4.5714);
+        assert_eq!(summary.min, 2f64);
+        assert_eq!(summary.max, 9f64);
+        assert_eq!(stats::descriptive::summary::<f64, _>(&[]).count, 0);
+    }
+
+    #[test]
+    fn test_quantiles() {
+        use stats::descriptive::{quantiles, quartiles};
+        let nums = [9f64, 2f64, 5f64, 4f64, 7f64, 4f64, 4f64, 5f64];
+        // Posiciones p·(n-1) de los valores ordenados 2, 4, 4, 4, 5, 5, 7, 9
+        let res = quantiles(&nums[..], &[0f64, 0.1, 0.5, 1f64]);
+        assert_eq!(res[0], 2f64);
+        assert_eq!(trunc_to_dec(res[1], 4), 3.4);
+        assert_eq!(res[2], 4.5);
+        assert_eq!(res[3], 9f64);
+        assert_eq!(quartiles(&nums[..]), (4f64, 5.5));
+        assert_eq!(quantiles::<f64, _>(&[][..], &[0.5]), vec![0f64]);
+    }
+
+    #[test]
+    fn test_transform() {
+        use std::sync::Arc;
+        use transform::{transform, Operation, TransformedDistribution};
+        let nums = [1f64, 2f64, 3f64, 6f64];
+        let res = transform(&nums[..], Operation::Scale { factor: 2f64, shift: 1f64 }).unwrap();
+        assert_eq!(res.values, vec![3f64, 5f64, 7f64, 13f64]);
+        // Estandarizar se resuelve como Scale con la media 3 y el desvío √(14/3)
+        let res = transform(&nums[..], Operation::Standardize).unwrap();
+        let Operation::Scale { factor, shift } = res.operation else { panic!() };
+        assert_eq!(trunc_to_dec(1f64 / factor, 4), 2.1602);
+        assert_eq!(trunc_to_dec(-shift / factor, 4), 3f64);
+        let clip = Operation::Clip { lower: Some(2f64), upper: Some(5f64) };
+        assert_eq!(transform(&nums[..], clip).unwrap().values, vec![2f64, 2f64, 3f64, 5f64]);
+        assert!(transform(&[-1f64, 1f64][..], Operation::Log).is_err());
+        assert!(transform(&nums[..], Operation::Clip { lower: None, upper: None }).is_err());
+
+        let normal: Box<dyn Distribution + Send + Sync> =
+            Box::new(Normal { mean: 0f64, sd: 1f64, algorithm: Algorithm::BoxMuller });
+        let normal = Arc::new(normal);
+        // -2·X + 1 es Normal de media 1 y desvío 2
+        let scaled = Operation::Scale { factor: -2f64, shift: 1f64 };
+        let dist = TransformedDistribution::new(normal.clone(), scaled);
+        assert_eq!(trunc_to_dec(dist.cdf(3f64), 4), 0.8413);
+        assert_eq!(trunc_to_dec(dist.quantile(0.8413), 2), 2.99);
+        // |X| es la semi Normal, con mediana 0.6744
+        let dist = TransformedDistribution::new(normal, Operation::Abs);
+        assert_eq!(trunc_to_dec(dist.cdf(1f64), 4), 0.6826);
+        assert_eq!(trunc_to_dec(dist.quantile(0.5), 4), 0.6744);
+    }
+
+    #[test]
+    fn test_rounded_intervals() {
+        use dist::DistributionLimits;
+        use std::sync::Arc;
+        use transform::{Operation, TransformedDistribution};
+        let round = Operation::Round { decimals: 1 };
+        assert_eq!(round.apply(0.25), 0.3);
+        assert_eq!(round.apply(-0.04).to_bits(), 0f64.to_bits());
+        let normal: Box<dyn Distribution + Send + Sync> =
+            Box::new(Normal { mean: 0f64, sd: 1f64, algorithm: Algorithm::BoxMuller });
+        let dist = TransformedDistribution::new(Arc::new(normal), round);
+        // P(round(X) <= 0) = P(X < 0.05), y no cambia hasta el siguiente valor
+        assert_eq!(trunc_to_dec(dist.cdf(0f64), 4), 0.5199);
+        assert_eq!(dist.cdf(0.09), dist.cdf(0f64));
+        // De -4 a 5 hay 91 valores posibles: 19 intervalos de 5, desde -4.05
+        let limits = DistributionLimits { lower: -4f64, upper: 5f64, intervals: 20 };
+        let limits = dist.get_intervals(limits);
+        assert_eq!(limits.intervals, 19);
+        assert!((limits.lower + 4.05).abs() < 1e-12);
+        assert!((limits.upper - 5.45).abs() < 1e-12);
+        assert!(dist.intervals_reason().is_some());
+    }
+
+    #[test]
+    fn test_truncated_distribution() {
+        use dist::DistributionLimits;
+        use std::sync::Arc;
+        use transform::TruncatedDistribution;
+        let normal: Box<dyn Distribution + Send + Sync> =
+            Box::new(Normal { mean: 0f64, sd: 1f64, algorithm: Algorithm::BoxMuller });
+        let dist = TruncatedDistribution::new(Arc::new(normal), Some(0f64), None);
+        // La mitad positiva de la Normal estándar: P(X <= 0.6745 | X >= 0) = 0.5
+        assert!((dist.acceptance() - 0.5).abs() < 1e-6);
+        assert_eq!(dist.cdf(-0.1), 0f64);
+        assert_eq!(trunc_to_dec(dist.cdf(0.6745), 3), 0.5);
+        assert_eq!(trunc_to_dec(dist.quantile(0.5), 4), 0.6744);
+        let limits = DistributionLimits { lower: -1f64, upper: 4f64, intervals: 10 };
+        assert_eq!(dist.get_intervals(limits).lower, 0f64);
+        // En las discretas se admite el límite inferior: P(X >= 1) = 1 - e^-1
+        let poisson: Box<dyn Distribution + Send + Sync> = Box::new(dist::poisson::Poisson { lambda: 1f64 });
+        let dist = TruncatedDistribution::new(Arc::new(poisson), Some(1f64), None);
+        assert_eq!(trunc_to_dec(dist.acceptance(), 4), 0.6321);
+        assert_eq!(dist.cdf(0.5), 0f64);
+    }
+
+    #[test]
+    fn test_two_sample_ks() {
+        let a = [1f64, 2f64, 3f64, 4f64, 5f64];
+        let b = [1f64, 2f64, 3f64, 4f64, 5f64];
+        let test = stats::ks::two_sample(&a, &b, 0.05);
+        assert_eq!(test.statistic, 0f64);
+        assert!(!test.reject);
+        let b = [6f64, 7f64, 8f64, 9f64, 10f64];
+        let test = stats::ks::two_sample(&a, &b, 0.05);
+        assert_eq!(test.statistic, 1f64);
+        assert_eq!(trunc_to_dec(test.critical, 3), 0.858);
+        assert!(test.reject);
+    }
+
+    #[test]
+    fn test_one_sample_ks() {
+        let uniform = Uniform { lower: 0f64, upper: 1f64 };
+        let test = stats::ks::one_sample(&[0.1, 0.3, 0.5, 0.7, 0.9], &uniform, 0.05);
+        assert_eq!(trunc_to_dec(test.statistic, 4), 0.1);
+        assert!(!test.reject);
+        let test = stats::ks::one_sample(&[0.8, 0.85, 0.9, 0.95, 0.99], &uniform, 0.05);
+        assert!(test.reject);
+        // En distribuciones discretas se compara con el límite por izquierda
+        let poisson = dist::poisson::Poisson { lambda: 2f64 };
+        assert_eq!(trunc_to_dec(poisson.cdf(1.5), 4), 0.406);
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let nums: Vec<f64> = (0..2000).map(|_| poisson.next(&mut rng)).collect();
+        assert!(!stats::ks::one_sample(&nums, &poisson, 0.05).reject);
+    }
+
+    #[test]
+    fn test_export_chunk() {
+        let nums: Vec<f64> = (0..list::EXPORT_CHUNK + 2).map(|n| n as f64 / 2f64).collect();
+        assert!(list::export_chunk(&nums, 0).starts_with("0\n0.5\n1\n"));
+        assert_eq!(list::export_chunk(&nums, 1), "32768\n32768.5\n");
+        assert_eq!(list::export_chunk(&nums, 2), "");
+    }
+
+    #[test]
+    fn test_montecarlo_integrate() {
+        let f = montecarlo::expr::Expr::parse("3 * x^2 + sin(pi * x) - 2 / 2").unwrap();
+        assert_eq!(trunc_to_dec(f.eval(0.5), 4), 0.75);
+        assert!(montecarlo::expr::Expr::parse("3 * y").is_err());
+        assert!(montecarlo::expr::Expr::parse("(x + 1").is_err());
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let res = montecarlo::integrate(|x| f.eval(x), 0f64, 1f64, 10000, 10, &mut rng);
+        // ∫(3x² + sin(πx) - 1) en [0, 1] = 2/π
+        let exact = 2f64 / std::f64::consts::PI;
+        assert!((res.estimate - exact).abs() < 4f64 * res.std_error);
+        assert_eq!(res.trace.len(), 10);
+        assert_eq!(res.trace[9].samples, 10000);
+    }
+
+    #[test]
+    fn test_control_variates() {
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        // ∫eˣ en [0, 1] = e - 1, con el uniforme como control, de media 1/2
+        let res = montecarlo::control_variates(f64::exp, |x| x, 0.5, 0f64, 1f64, 10000, &mut rng);
+        let exact = std::f64::consts::E - 1f64;
+        assert!((res.estimate - exact).abs() < 4f64 * res.std_error);
+        assert!(res.correlation > 0.95);
+        assert!(res.variance_reduction > 0.9);
+        assert!(res.std_error < res.crude_std_error);
+    }
+
+    #[test]
+    fn test_queue() {
+        use sim::queue::{self, QueueParameters};
+        let params = QueueParameters {
+            arrival_rate: 1f64,
+            service_rate: 2f64,
+            servers: 1,
+            customers: 20000,
+            replications: 10,
+        };
+        let theoretical = queue::theoretical(&params).unwrap();
+        assert_eq!(theoretical.queue_length, 0.5);
+        assert_eq!(theoretical.system_time, 1f64);
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let report = queue::simulate(&params, &mut rng);
+        assert!((report.wait.mean - theoretical.wait).abs() < 0.05);
+        assert!((report.utilization.mean - 0.5).abs() < 0.02);
+        assert!(report.wait.lower <= report.wait.mean && report.wait.mean <= report.wait.upper);
+        // M/M/2 con λ=2 y μ=1.5: Lq = 16/15
+        let params = QueueParameters { arrival_rate: 2f64, service_rate: 1.5, servers: 2, ..params };
+        assert_eq!(trunc_to_dec(queue::theoretical(&params).unwrap().queue_length, 4), 1.0666);
+        assert!(queue::theoretical(&QueueParameters { servers: 1, ..params }).is_none());
+    }
+
+    #[test]
+    fn test_inventory() {
+        use sim::inventory::{self, InventoryParameters};
+        let params: InventoryParameters = serde_json::from_str(
+            r#"{"reorder_point": 0, "order_up_to": 1000, "demand_rate": 2, "lead_time": {"Fixed": 1},
+                "ordering_cost": 32, "holding_cost": 1, "shortage_cost": 5, "periods": 100, "replications": 5}"#,
+        )
+        .unwrap();
+        // Con inventario suficiente nunca se pide ni faltan unidades
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let report = inventory::simulate(&params, &mut rng);
+        assert_eq!(report.ordering_cost.mean, 0f64);
+        assert_eq!(report.fill_rate.mean, 1f64);
+        assert!((report.holding_cost.mean - 900f64).abs() < 10f64);
+        // Con s = S se pide en cada período con demanda
+        let params = InventoryParameters { reorder_point: 5, order_up_to: 5, ..params };
+        let metrics = inventory::replicate(&params, &mut rng);
+        assert!(metrics.ordering_cost > 0f64 && metrics.ordering_cost <= 32f64);
+        assert!(metrics.fill_rate > 0f64 && metrics.fill_rate <= 1f64);
+        let sum = metrics.ordering_cost + metrics.holding_cost + metrics.shortage_cost;
+        assert!((metrics.total_cost - sum).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_markov() {
+        use sim::markov::{self, MarkovParameters};
+        let transitions = vec![vec![0.9, 0.1], vec![0.5, 0.5]];
+        assert!(markov::validate(&transitions).is_ok());
+        assert!(markov::validate(&[vec![0.5, 0.6], vec![1f64, 0f64]]).is_err());
+        let pi = markov::stationary(&transitions).unwrap();
+        assert_eq!(trunc_to_dec(pi[0], 4), 0.8333);
+        assert_eq!(trunc_to_dec(pi[1], 4), 0.1666);
+        // Dos clases cerradas: la distribución estacionaria no es única
+        assert!(markov::stationary(&[vec![1f64, 0f64], vec![0f64, 1f64]]).is_none());
+        let params = MarkovParameters { transitions, initial: 1, steps: 20000, significance: Some(7) };
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let report = markov::simulate(&params, &mut rng);
+        assert_eq!(report.states.iter().map(|s| s.visits).sum::<u64>(), 20000);
+        assert!((report.states[0].observed - pi[0]).abs() < 0.02);
+        let test = report.test.unwrap();
+        assert_eq!(test.degrees, 1);
+        assert_eq!(trunc_to_dec(test.critical, 2), 3.84);
+    }
+
+    #[test]
+    fn test_autocorrelation() {
+        use stats::autocorrelation::{autocorrelation, ljung_box};
+        let alternating: Vec<f64> = (0..100).map(|i| if i % 2 == 0 { 1f64 } else { -1f64 }).collect();
+        assert_eq!(trunc_to_dec(autocorrelation(&alternating, 1), 2), -0.99);
+        assert_eq!(trunc_to_dec(autocorrelation(&alternating, 2), 2), 0.98);
+        assert!(ljung_box(&[1f64, 2f64, 3f64], 7).is_none());
+        // Serie AR(1) con φ = 0.8: la prueba rechaza la independencia
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let mut series = vec![0f64; 5000];
+        for t in 1..series.len() {
+            series[t] = 0.8 * series[t - 1] + rng.next() - 0.5;
+        }
+        assert!((autocorrelation(&series, 1) - 0.8).abs() < 0.05);
+        let test = ljung_box(&series, 7).unwrap();
+        assert_eq!(test.lags.len(), 10);
+        assert!(test.calculated > test.critical);
+    }
+
+    #[test]
+    fn test_runs() {
+        use stats::runs::runs;
+        // 1 2 3 | 4 5 6: dos rachas no alcanzan para dos clases
+        let test = runs(&[1f64, 2f64, 3f64, 4f64, 5f64, 6f64], 7);
+        assert!(test.is_none());
+        let alternating: Vec<f64> = (0..100).map(|i| (i % 2) as f64).collect();
+        let test = runs(&alternating, 7).unwrap();
+        assert_eq!((test.above, test.below, test.runs), (50, 50, 100));
+        assert_eq!(test.lengths.len(), 5);
+        assert_eq!(test.lengths[0].observed, 100);
+        assert_eq!(test.lengths[4].expected, 100f64 / 16f64);
+        assert!(test.z > 9f64 && test.calculated > test.critical);
+        // Valores independientes: los largos son geométricos
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let values: Vec<f64> = (0..5000).map(|_| rng.next()).collect();
+        let test = runs(&values, 7).unwrap();
+        assert!(test.z.abs() < 3f64 && test.calculated < test.critical);
+        assert_eq!(test.lengths.iter().map(|l| l.observed).sum::<u64>(), test.runs);
+    }
+
+    #[test]
+    fn test_latin_hypercube() {
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let points = sampling::latin_hypercube(10, 3, &mut rng);
+        assert_eq!(points.len(), 10);
+        // Cada estrato de cada dimensión tiene exactamente un punto
+        for d in 0..3 {
+            let mut strata: Vec<usize> = points.iter().map(|p| (p[d] * 10f64) as usize).collect();
+            strata.sort();
+            assert_eq!(strata, (0..10).collect::<Vec<usize>>());
+        }
+        let normal = Normal { algorithm: Algorithm::BoxMuller, mean: 10f64, sd: 2f64 };
+        assert_eq!(normal.quantile(0.5), 10f64);
+        assert_eq!(trunc_to_dec(normal.quantile(0.975), 3), 13.919);
+        let uniform = Uniform { lower: 0f64, upper: 5f64 };
+        let comparison = sampling::compare(50, &[&normal, &uniform], 30, &mut rng);
+        assert_eq!(comparison.len(), 2);
+        assert!(comparison.iter().all(|c| c.lhs_variance < c.simple_variance));
+        assert!((comparison[0].lhs_mean - 10f64).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_stratified() {
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        assert_eq!(sampling::allocation(10, 4), vec![3, 3, 2, 2]);
+        let uniform = Uniform { lower: 0f64, upper: 8f64 };
+        let values = sampling::stratified(10, 4, &uniform, &mut rng);
+        assert_eq!(values.len(), 10);
+        let strata = sampling::strata(10, 4, &uniform);
+        assert_eq!(strata[1].lower, 2f64);
+        assert_eq!(strata[1].upper, 4f64);
+        // Cada estrato recibe la cantidad de valores asignada
+        for stratum in &strata {
+            let count = values.iter().filter(|&&x| x >= stratum.lower && x < stratum.upper).count();
+            assert_eq!(count, stratum.count);
+        }
+    }
+
+    #[test]
+    fn test_tabulated_cdf() {
+        let table = TabulatedCdf::new(vec![(0f64, 0f64), (1f64, 0.25), (3f64, 1f64)]).unwrap();
+        assert_eq!(table.cdf(2f64), 0.625);
+        assert_eq!(table.quantile(0.25), 1f64);
+        assert_eq!(table.quantile(0.625), 2f64);
+        assert_eq!(table.get_expected(3, 0f64, 3f64), vec![0.25, 0.375, 0.375]);
+        assert_eq!(table.moments().0, 1.625);
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let values: Vec<f64> = (0..10000).map(|_| table.next(&mut rng)).collect();
+        assert!(values.iter().all(|x| (0f64..=3f64).contains(x)));
+        assert!((values.iter().sum::<f64>() / 10000f64 - 1.625).abs() < 0.05);
+        // La tabla debe ser una función de distribución acumulada
+        assert!(TabulatedCdf::new(vec![(0f64, 0f64), (1f64, 0.5)]).is_err());
+        assert!(TabulatedCdf::new(vec![(0f64, 0f64), (2f64, 0.6), (1f64, 1f64)]).is_err());
+        assert!(serde_json::from_str::<TabulatedCdf>(r#"{"points": [[0, 0.5], [1, 1]]}"#).is_err());
+    }
+
+    #[test]
+    fn test_bootstrap() {
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let values = [1f64, 2f64, 3f64];
+        let resample = sampling::bootstrap(&values, 3000, &mut rng);
+        assert_eq!(resample.len(), 3000);
+        // Cada valor se elige con la misma probabilidad
+        for v in values {
+            let count = resample.iter().filter(|&&x| x == v).count();
+            assert!((900..1100).contains(&count));
+        }
+    }
+
+    #[test]
+    fn test_copula() {
+        use copula::Copula;
+        let tau = copula::kendall_tau(&[1f64, 2f64, 3f64, 4f64], &[1f64, 3f64, 2f64, 4f64]);
+        assert_eq!(trunc_to_dec(tau, 4), 0.6666);
+        assert_eq!(trunc_to_dec(dist::normal::standard_cdf(1.96), 4), 0.975);
+        // Con erfc la cola conserva la precisión relativa: Φ(-8) ≈ 6.221e-16
+        let tail = dist::normal::standard_cdf(-8f64);
+        assert!((tail - 6.22096057427178e-16).abs() / 6.22096057427178e-16 < 1e-10);
+        assert_eq!(dist::normal::standard_cdf(0f64), 0.5);
+        let singular = Copula::Gaussian { correlation: vec![vec![1f64, 1f64], vec![1f64, 1f64]] };
+        assert!(singular.validate().is_err());
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let uniform = Uniform { lower: 0f64, upper: 1f64 };
+        let normal = Normal { algorithm: Algorithm::BoxMuller, mean: 10f64, sd: 2f64 };
+        let copulas = [
+            Copula::Gaussian { correlation: vec![vec![1f64, 0.7], vec![0.7, 1f64]] },
+            Copula::Clayton { theta: 2f64, dimensions: 2 },
+        ];
+        for copula in copulas {
+            assert!(copula.validate().is_ok());
+            let sample = copula.sample(2000, &[&uniform, &normal], &mut rng);
+            assert_eq!(sample.columns.len(), 2);
+            assert_eq!(sample.columns[1].len(), 2000);
+            let tau = &sample.kendall[0];
+            assert!((tau.empirical - tau.theoretical).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_f32_statistics() {
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let uniform = Uniform { lower: 0f64, upper: 1f64 };
+        let nums: Vec<f64> = (0..1000).map(|_| uniform.next(&mut rng)).collect();
+        let single: Vec<f32> = nums.iter().map(|&x| float::Float::from_f64(x)).collect();
+        let input = || stats::StatisticsInput {
+            intervals: 10,
+            significance: Some(7),
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        let res = statistics(input(), &nums, &uniform);
+        let res32 = statistics(input(), &single, &uniform);
+        assert_eq!(res.histogram.y, res32.histogram.y);
+        assert_eq!(trunc_to_dec(res.test.calculated, 4), trunc_to_dec(res32.test.calculated, 4));
+        assert_eq!(list::export_chunk(&[0.1f32], 0), "0.1\n");
+    }
+
+    #[test]
+    fn test_uniform_intervals() {
+        // La muestra ocupa solo parte del rango, y tiene un valor fuera de él
+        let uniform = Uniform { lower: 0.2, upper: 0.7 };
+        let nums = [0.35f64, 0.38, 0.45, 0.52, 0.55, 0.9];
+        let input = stats::StatisticsInput {
+            intervals: 5,
+            significance: Some(7),
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        let res = statistics(input, &nums, &uniform);
+        assert_eq!(res.histogram.lower, 0.2);
+        assert_eq!(res.histogram.upper, 0.7);
+        assert_eq!(res.histogram.y, vec![0, 2, 1, 2, 1]);
+        assert_eq!(res.histogram.y.iter().sum::<u64>(), nums.len() as u64);
+        let fe: f64 = res.test.intervals.iter().map(|i| i.fe).sum();
+        assert!((fe - nums.len() as f64).abs() < 1e-12);
+        assert_eq!(res.test.intervals.first().unwrap().lower, 0.2);
+        assert_eq!(res.test.intervals.last().unwrap().upper, 0.7);
+    }
+
+    #[test]
+    fn test_interval_boundaries() {
+        // Enteros en los límites: cada uno cae en el intervalo que empieza
+        // en él, y el máximo en el intervalo final cerrado
+        let uniform = Uniform { lower: 0f64, upper: 10f64 };
+        let nums: Vec<f64> = (0..=10).map(|i| i as f64).collect();
+        let input =
+            |final_bin| stats::StatisticsInput {
+            intervals: 5,
+            significance: Some(7),
+            final_bin,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        let res = statistics(input(stats::FinalBin::Closed), &nums, &uniform);
+        assert_eq!(res.histogram.y, vec![2, 2, 2, 2, 3]);
+        assert_eq!(res.histogram.final_bin, stats::FinalBin::Closed);
+        let last = res.test.intervals.last().unwrap();
+        assert!(last.upper_closed && !last.open_upper);
+        // Con el intervalo final abierto las frecuencias observadas no
+        // cambian, y la esperada del último es la de toda la cola
+        let res = statistics(input(stats::FinalBin::Open), &nums, &uniform);
+        assert_eq!(res.histogram.y, vec![2, 2, 2, 2, 3]);
+        let last = res.test.intervals.last().unwrap();
+        assert!(last.open_upper && !last.upper_closed);
+        let json = serde_json::to_value(&res.test.intervals[0]).unwrap();
+        assert_eq!(json["upper_closed"], false);
+
+        // Límites que no son exactos en punto flotante: cada valor se cuenta
+        // en el intervalo [lower + i·size, lower + (i+1)·size) que lo contiene
+        let uniform = Uniform { lower: 0f64, upper: 1f64 };
+        let nums: Vec<f64> = (0..=100).map(|i| i as f64 / 100f64).collect();
+        let res = statistics(input(stats::FinalBin::Closed), &nums, &uniform);
+        let size = res.histogram.size;
+        let expected: Vec<u64> = (0..5)
+            .map(|i| {
+                let lower = i as f64 * size;
+                let upper = if i == 4 { f64::INFINITY } else { (i + 1) as f64 * size };
+                nums.iter().filter(|&&x| x >= lower && x < upper).count() as u64
+            })
+            .collect();
+        assert_eq!(res.histogram.y, expected);
+    }
+
+    #[test]
+    fn test_weighted_statistics() {
+        use dist::exponential::Exponential;
+        use rng::UniformGenerator;
+        use stats::descriptive::{check_weights, effective_size, summary, weighted_summary};
+        use stats::weighted_statistics;
+
+        let s = weighted_summary(&[1f64, 2f64, 3f64], &[1f64, 0f64, 1f64]);
+        assert_eq!((s.count, s.mean, s.variance, s.min, s.max), (3, 2f64, 2f64, 1f64, 3f64));
+        assert!(check_weights(&[1f64, -1f64], 2).is_err());
+        assert!(check_weights(&[1f64], 2).is_err());
+
+        // Muestreo por importancia: valores de una Exponential(0.5) con
+        // pesos f(x)/g(x) para probarlos contra una Exponential(1)
+        let proposal = Exponential { lambda: 0.5, shift: 0f64 };
+        let target = Exponential { lambda: 1f64, shift: 0f64 };
+        let mut rng = UniformGenerator::with_seed(42);
+        let nums: Vec<f64> = (0..5000).map(|_| proposal.next(&mut rng)).collect();
+        let weights: Vec<f64> = nums.iter().map(|x| 2f64 * (-0.5 * x).exp()).collect();
+        assert!(check_weights(&weights, nums.len()).is_ok());
+        let input = || stats::StatisticsInput {
+            intervals: 10,
+            significance: Some(7),
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        let plain = statistics(input(), &nums, &target);
+        assert!(plain.test.calculated > plain.test.critical);
+        let weighted = weighted_statistics(input(), &nums, Some(&weights), &target);
+        assert!(weighted.test.calculated < weighted.test.critical);
+        assert_eq!(weighted.test.effective_size, Some(effective_size(&weights)));
+        assert_eq!(weighted.histogram.y, plain.histogram.y);
+        let total: f64 = weighted.histogram.weights.as_ref().unwrap().iter().sum();
+        assert!((total - weights.iter().sum::<f64>()).abs() < 1e-9);
+        assert!((weighted_summary(&nums, &weights).mean - 1f64).abs() < 0.05);
+
+        // Con pesos iguales, la prueba es la misma que sin pesos
+        let ones = vec![1f64; nums.len()];
+        let same = weighted_statistics(input(), &nums, Some(&ones), &proposal);
+        let plain = statistics(input(), &nums, &proposal);
+        assert!((same.test.calculated - plain.test.calculated).abs() < 1e-9);
+        assert_eq!(weighted_summary(&nums, &ones).mean, summary(&nums).mean);
+    }
+
+    #[test]
+    fn test_fill_chunked() {
+        use rng::{fill_chunked, UniformGenerator, PARALLEL_CHUNK, PARALLEL_THRESHOLD};
+        let mut small = vec![0f64; 100];
+        fill_chunked(7, &mut small, |rng, chunk| chunk.iter_mut().for_each(|x| *x = rng.next()));
+        let mut rng = UniformGenerator::with_seed(7);
+        assert!(small.iter().all(|&x| x == rng.next()));
+        // Por encima del umbral, el bloque i usa el subflujo i
+        let mut large = vec![0f64; PARALLEL_THRESHOLD + 1000];
+        fill_chunked(7, &mut large, |rng, chunk| chunk.iter_mut().for_each(|x| *x = rng.next()));
+        let streams = UniformGenerator::substreams(7, large.len().div_ceil(PARALLEL_CHUNK));
+        for (chunk, mut rng) in large.chunks(PARALLEL_CHUNK).zip(streams) {
+            assert!(chunk.iter().all(|&x| x == rng.next()));
+        }
+        assert_eq!(large[..100], small[..]);
+    }
+
+    #[test]
+    fn test_shifted_exponential() {
+        use dist::exponential::Exponential;
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let exponential = Exponential::builder().lambda(2f64).shift(5.5f64).build().unwrap();
+        let nums: Vec<f64> = (0..10000).map(|_| exponential.next(&mut rng)).collect();
+        assert!(nums.iter().all(|&x| x >= 5.5f64));
+        let input = stats::StatisticsInput {
+            intervals: 10,
+            significance: None,
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        let res = statistics(input, &nums, &exponential);
+        // Los intervalos empiezan en el desplazamiento, no en el mínimo redondeado
+        assert_eq!(res.histogram.lower, 5.5f64);
+        let adjustment = res.histogram.adjustment.as_ref().unwrap();
+        assert_eq!((adjustment.requested.lower, adjustment.applied.lower), (5f64, 5.5f64));
+        assert!(res.test.calculated < res.test.critical);
+        assert_eq!(exponential.cdf(5.5f64), 0f64);
+        assert_eq!(trunc_to_dec(exponential.quantile(0.5f64), 4), trunc_to_dec(5.5 + 2f64.ln() / 2f64, 4));
+    }
+
+    #[test]
+    fn test_exponential_mean() {
+        use dist::exponential::Exponential;
+        let parsed: Exponential = serde_json::from_str(r#"{"mean": 0.5}"#).unwrap();
+        assert_eq!(parsed.lambda, 2f64);
+        // Con desplazamiento, la media es la de la distribución, shift + 1/λ
+        let shifted: Exponential = serde_json::from_str(r#"{"mean": 6, "shift": 5.5}"#).unwrap();
+        assert_eq!((shifted.lambda, shifted.mean()), (2f64, 6f64));
+        let parsed: Exponential = serde_json::from_str(r#"{"lambda": 2}"#).unwrap();
+        assert_eq!(parsed.mean(), 0.5f64);
+        // Los dos parámetros son excluyentes y alguno es obligatorio
+        assert!(serde_json::from_str::<Exponential>(r#"{"lambda": 2, "mean": 0.5}"#).is_err());
+        assert!(serde_json::from_str::<Exponential>(r#"{"shift": 1}"#).is_err());
+        assert!(serde_json::from_str::<Exponential>(r#"{"mean": 1, "shift": 2}"#).is_err());
+        assert!(serde_json::from_str::<Exponential>(r#"{"lambda": -1}"#).is_err());
+    }
+
+    #[test]
+    fn test_normal_variance() {
+        use dist::normal::Normal;
+        let variance = r#"{"algorithm": "BoxMuller", "mean": 10, "variance": 4}"#;
+        let parsed: Normal = serde_json::from_str(variance).unwrap();
+        assert_eq!((parsed.mean, parsed.sd), (10f64, 2f64));
+        // Se serializa siempre con la desviación estándar
+        let json = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(json["sd"], 2f64);
+        assert!(json.get("variance").is_none());
+        let both = r#"{"algorithm": "BoxMuller", "mean": 0, "sd": 1, "variance": 1}"#;
+        assert!(serde_json::from_str::<Normal>(both).is_err());
+        let neither = r#"{"algorithm": "BoxMuller", "mean": 0}"#;
+        assert!(serde_json::from_str::<Normal>(neither).is_err());
+        let negative = r#"{"algorithm": "BoxMuller", "mean": 0, "variance": -1}"#;
+        assert!(serde_json::from_str::<Normal>(negative).is_err());
+    }
+
+    #[test]
+    fn test_poisson_open_tail() {
+        let poisson = dist::poisson::Poisson { lambda: 3f64 };
+        // La última clase es "≥ 5", y las probabilidades suman 1
+        let expected = poisson.get_expected(6, 0f64, 6f64);
+        assert_eq!(trunc_to_dec(expected[5], 4), trunc_to_dec(1f64 - poisson.cdf(4f64), 4));
+        assert_eq!(trunc_to_dec(expected.iter().sum::<f64>(), 8), 1f64);
+        // Sin intervalos no hay probabilidades, y uno solo tiene toda
+        assert!(poisson.get_expected(0, 0f64, 0f64).is_empty());
+        assert_eq!(poisson.get_expected(1, 2f64, 3f64), vec![1f64]);
+        let mut rng = rng::UniformGenerator::with_seed(42);
+        let nums: Vec<f64> = (0..5000).map(|_| poisson.next(&mut rng)).collect();
+        let input = stats::StatisticsInput {
+            intervals: 10,
+            significance: None,
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        let res = statistics(input, &nums, &poisson);
+        let fe: f64 = res.test.intervals.iter().map(|i| i.fe).sum();
+        assert_eq!(trunc_to_dec(fe, 4), 5000f64);
+        // La cola superior, con fe < 5, se une a la clase abierta
+        let last = res.test.intervals.last().unwrap();
+        assert!(last.open_upper && last.fe >= 5f64);
+        assert!(res.test.intervals.iter().rev().skip(1).all(|i| !i.open_upper));
+        assert!(res.test.calculated < res.test.critical);
+        // La respuesta informa que se usó un intervalo por entero en lugar de 10
+        let adjustment = res.histogram.adjustment.unwrap();
+        assert_eq!(adjustment.requested.intervals, 10);
+        assert_eq!(adjustment.applied.intervals, res.histogram.y.len());
+        assert_eq!(adjustment.applied.upper, adjustment.requested.upper + 1f64);
+        assert!(adjustment.reason.contains("entero"));
+    }
+
+    #[test]
+    fn test_poisson_large_lambda() {
+        let poisson = dist::poisson::Poisson { lambda: 1000f64 };
+        assert_eq!(trunc_to_dec(poisson.pmf(1000), 10), trunc_to_dec(0.012614611348719664, 10));
+        assert_eq!(trunc_to_dec(poisson.cdf(1000f64), 10), trunc_to_dec(0.5084093671683851, 10));
+        assert_eq!(poisson.quantile(0.5), 1000f64);
+        let expected = poisson.get_expected(200, 900f64, 1100f64);
+        assert!(expected.iter().all(|p| p.is_finite() && *p >= 0f64));
+        assert!((expected.iter().sum::<f64>() - 1f64).abs() < 1e-12);
+        // Con lambda mayor a 745, e^-λ es 0 y la generación no terminaba
+        let mut rng = rng::UniformGenerator::with_seed(42);
+        let mean = (0..200).map(|_| poisson.next(&mut rng)).sum::<f64>() / 200f64;
+        assert!((mean - 1000f64).abs() < 10f64);
+        // Con k >= 21 el factorial en u64 desbordaba. Con lambda 200 los
+        // grados de libertad quedan dentro de la tabla de chi cuadrado
+        let poisson = dist::poisson::Poisson { lambda: 200f64 };
+        let mut rng = rng::UniformGenerator::with_seed(42);
+        let nums: Vec<f64> = (0..5000).map(|_| poisson.next(&mut rng)).collect();
+        let input = stats::StatisticsInput {
+            intervals: 10,
+            significance: None,
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        let res = statistics(input, &nums, &poisson);
+        let fe: f64 = res.test.intervals.iter().map(|i| i.fe).sum();
+        assert!((fe - 5000f64).abs() < 1e-6);
+        assert!(res.test.calculated < res.test.critical);
+    }
+
+    #[test]
+    fn test_special_functions() {
+        let close = |a: f64, b: f64| (a - b).abs() <= 1e-12 * b.abs().max(1e-300);
+        assert!(close(math::ln_gamma(0.5), 0.5723649429247004));
+        assert!(close(math::ln_gamma(10.5), 13.940625219403763));
+        assert!(close(math::ln_gamma(171.3), 708.114947038997));
+        assert!(close(math::ln_gamma(-2.5), -0.05624371649767457));
+        assert!(close(math::ln_gamma(1e-5), 11.512919692895824));
+        assert!(math::ln_gamma(1f64).abs() < 1e-14 && math::ln_gamma(2f64).abs() < 1e-14);
+        // P(1, x) = 1 - e^-x, Q(3, 2) = 5e^-2
+        assert!(close(math::gamma_p(1f64, 3.2), 0.9592377960216338));
+        assert!(close(math::gamma_q(3f64, 2f64), 0.6766764161830635));
+        assert!(close(math::gamma_p(40f64, 35f64) + math::gamma_q(40f64, 35f64), 1f64));
+        assert!(math::gamma_p(-1f64, 1f64).is_nan());
+        // Iₓ(2, 3) = 6x²(1-x)² + 4x³(1-x) + x⁴, I₀.₅(a, a) = 1/2
+        assert!(close(math::beta_inc(2f64, 3f64, 0.3), 0.34829999999999994));
+        assert!(close(math::beta_inc(7.5, 7.5, 0.5), 0.5));
+        assert!(close(math::beta_inc(1f64, 1f64, 0.42), 0.42));
+        assert!(close(math::erf(0.5), 0.5204998778130465));
+        assert!(close(math::erf(-1.7), -0.9837904585907745));
+        assert!(close(math::erfc(5f64), 1.5374597944280351e-12));
+        assert!(close(math::erfc(-0.3), 1.3286267594591274));
+        // La acumulada de chi cuadrado en el valor crítico tabulado es 1 - α
+        let critical = chi_squared_critical_value(10, 7);
+        assert!((math::gamma_p(5f64, critical / 2f64) - 0.95).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_builders() {
+        let normal = Normal::builder().mean(10f64).sd(2f64).algorithm(Algorithm::Convolution).build();
+        let normal = normal.unwrap();
+        assert_eq!((normal.mean, normal.sd), (10f64, 2f64));
+        assert!(Normal::builder().sd(0f64).build().is_err());
+        assert!(Normal::builder().mean(f64::NAN).build().is_err());
+        let uniform = Uniform::builder().build().unwrap();
+        assert_eq!((uniform.lower, uniform.upper), (0f64, 1f64));
+        assert!(Uniform::builder().lower(2f64).upper(1f64).build().is_err());
+        assert!(dist::exponential::Exponential::builder().lambda(-1f64).build().is_err());
+        assert_eq!(dist::poisson::Poisson::builder().lambda(3f64).build().unwrap().lambda, 3f64);
+        assert!(dist::poisson::Poisson::builder().lambda(1e300).build().is_err());
+        // El Json se valida con el constructor
+        let parsed: dist::poisson::Poisson = serde_json::from_str(r#"{"lambda": 4}"#).unwrap();
+        assert_eq!(parsed.lambda, 4f64);
+        assert!(serde_json::from_str::<dist::poisson::Poisson>(r#"{"lambda": 0}"#).is_err());
+    }
+
+    #[test]
+    fn test_degenerate_uniform() {
+        let parsed: Uniform = serde_json::from_str(r#"{"lower": 1, "upper": 3}"#).unwrap();
+        assert_eq!((parsed.lower, parsed.upper), (1f64, 3f64));
+        assert!(serde_json::from_str::<Uniform>(r#"{"lower": 3, "upper": 3}"#).is_err());
+        assert!(serde_json::from_str::<Uniform>(r#"{"lower": 3, "upper": 1}"#).is_err());
+        // Construida directamente, con límites iguales es un único punto
+        let point = Uniform { lower: 2f64, upper: 2f64 };
+        assert_eq!(point.get_expected(4, 0f64, 4f64), vec![0f64, 0f64, 1f64, 0f64]);
+        assert_eq!(point.get_expected(4, 0f64, 2f64), vec![0f64, 0f64, 0f64, 1f64]);
+        assert_eq!(point.get_expected(2, 2f64, 2f64), vec![1f64, 0f64]);
+        assert_eq!(point.get_expected(2, 3f64, 4f64), vec![0f64, 0f64]);
+        assert_eq!((point.cdf(1.9), point.cdf(2f64)), (0f64, 1f64));
+    }
+
+    #[test]
+    fn test_suggest() {
+        use dist::exponential::Exponential;
+        use dist::poisson::Poisson;
+        use stats::suggest::{shape, suggest, Family};
+        let mut rng = LinearCongruentialGenerator::with_seed(1021218219);
+        let families = |nums: &[f64]| -> Vec<Family> {
+            suggest(&shape(nums).unwrap()).into_iter().map(|c| c.family).collect()
+        };
+        let exponential = Exponential::builder().lambda(2f64).build().unwrap();
+        let nums: Vec<f64> = (0..20000).map(|_| exponential.next(&mut rng)).collect();
+        let candidates = suggest(&shape(&nums[..]).unwrap());
+        // La Gamma con forma cercana a 1 es casi la misma Exponencial
+        assert!(candidates[..2].iter().any(|c| c.family == Family::Exponential));
+        let lambda = candidates.iter().find(|c| c.family == Family::Exponential).unwrap();
+        assert!((lambda.parameters["lambda"] - 2f64).abs() < 0.1);
+        let uniform = Uniform::builder().lower(3f64).upper(5f64).build().unwrap();
+        let nums: Vec<f64> = (0..20000).map(|_| uniform.next(&mut rng)).collect();
+        assert!(families(&nums)[..2].contains(&Family::Uniform));
+        assert!(!families(&nums).contains(&Family::Poisson));
+        let mut normal = Normal::builder().mean(-5f64).sd(2f64).build().unwrap().sampler();
+        let nums: Vec<f64> = (0..20000).map(|_| normal.next(&mut rng)).collect();
+        let normal_families = families(&nums);
+        assert_eq!(normal_families[0], Family::Normal);
+        // Con valores negativos no se sugieren familias de soporte positivo
+        assert!(!normal_families.contains(&Family::Exponential));
+        let poisson = Poisson { lambda: 4f64 };
+        let nums: Vec<f64> = (0..20000).map(|_| poisson.next(&mut rng)).collect();
+        assert!(families(&nums)[..2].contains(&Family::Poisson));
+        assert!(shape(&[1f64, 1f64][..]).is_none());
+    }
+
+    #[test]
+    fn test_chunked() {
+        use chunked::{ChunkedVec, SampleChunks, SpillConfig, CHUNK_LEN};
+        use rng::fill_chunked;
+        let fill = |rng: &mut rng::UniformGenerator, chunk: &mut [f64]| {
+            chunk.iter_mut().for_each(|x| *x = rng.next())
+        };
+        let number = CHUNK_LEN + 1000;
+        let mut expected = vec![0f64; number];
+        fill_chunked(3, &mut expected, fill);
+        // Con un solo bloque en memoria, el segundo se escribe en disco
+        let spill = SpillConfig { dir: std::env::temp_dir(), memory_chunks: 1 };
+        let nums: ChunkedVec<f64> = ChunkedVec::generate(3, 0, number, Some(spill), fill, |_| {}).unwrap();
+        assert_eq!(nums.len(), number);
+        assert_eq!(nums.spilled_chunks(), 1);
+        assert_eq!(nums.memory_bytes(), CHUNK_LEN * 8);
+        assert_eq!(nums.range(CHUNK_LEN - 5, CHUNK_LEN + 5), expected[CHUNK_LEN - 5..CHUNK_LEN + 5]);
+        // Un rango entre dos bloques se recorre en dos partes, sin copiarlo
+        let parts: Vec<&[f64]> = chunked::slices(&nums, CHUNK_LEN - 5, CHUNK_LEN + 5).collect();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts.concat(), expected[CHUNK_LEN - 5..CHUNK_LEN + 5]);
+        assert_eq!(chunked::slices(&nums, number + 5, number + 10).count(), 0);
+        assert_eq!(stats::descriptive::summary(&nums), stats::descriptive::summary(&expected));
+        assert_eq!(nums.into_vec(), expected);
+        // Los bloques se generan en orden y se deja de generar al pedirlo
+        let mut starts = Vec::new();
+        chunked::generate_blocks(3, number, fill, |start, block| {
+            assert_eq!(block, &expected[start..start + block.len()]);
+            starts.push(start);
+            Ok(false)
+        })
+        .unwrap();
+        assert_eq!(starts, vec![0]);
+        // Los tramos escritos se juntan en bloques enteros
+        let mut writer = chunked::ChunkedWriter::new(number, None);
+        for part in expected.chunks(1000) {
+            writer.write(part).unwrap();
+        }
+        assert_eq!(writer.written(), number);
+        let nums: ChunkedVec<f64> = writer.finish().unwrap();
+        assert_eq!(nums.into_vec(), expected);
+    }
+
+    #[test]
+    fn test_chunk_pool() {
+        use chunked::{ChunkedVec, CHUNK_LEN};
+        use rng::fill_chunked;
+        let fill = |rng: &mut rng::UniformGenerator, chunk: &mut [f64]| {
+            chunk.iter_mut().for_each(|x| *x = rng.next())
+        };
+        // Los bloques del primer vector vuelven a los libres al descartarlo,
+        // y el segundo los reusa sin que queden valores del primero
+        let number = 2 * CHUNK_LEN + 10;
+        let first: ChunkedVec<f64> = ChunkedVec::generate(3, 0, number, None, fill, |_| {}).unwrap();
+        drop(first);
+        let mut expected = vec![0f64; number + 7];
+        fill_chunked(4, &mut expected, fill);
+        let second: ChunkedVec<f64> = ChunkedVec::generate(4, 7, number, None, fill, |_| {}).unwrap();
+        assert_eq!(second.into_vec(), expected[7..]);
+        let second: ChunkedVec<f32> = ChunkedVec::generate(4, 7, number, None, fill, |_| {}).unwrap();
+        let expected: Vec<f32> = expected[7..].iter().map(|&x| x as f32).collect();
+        assert_eq!(second.into_vec(), expected);
+    }
+
+    #[test]
+    fn test_custom_lcg() {
+        // Secuencia calculada a mano: x0 = 7, m = 16, a = 5, c = 3
+        let mut lcg = LinearCongruentialGenerator::new(7, 16, 5, 3);
+        let values: Vec<f64> = (0..5).map(|_| lcg.next() * 16f64).collect();
+        assert_eq!(values, vec![6f64, 1f64, 8f64, 11f64, 10f64]);
+        // Con m cerca de 2^64 los productos no desbordan
+        let m = u64::MAX - 58;
+        let mut lcg = LinearCongruentialGenerator::new(m - 1, m, m - 1, 0);
+        assert_eq!(lcg.next(), 1f64 / m as f64);
+        assert_eq!(lcg.next(), (m - 1) as f64 / m as f64);
+    }
+
+    #[test]
+    fn test_chi_squared_trace() {
+        use chunked::{ChunkedVec, CHUNK_LEN};
+        use stats::{chi_squared_trace, StatisticsInput};
+        let uniform = Uniform::builder().build().unwrap();
+        let mut rng = rng::UniformGenerator::with_seed(4);
+        let number = CHUNK_LEN + 500;
+        let values: Vec<f64> = (0..number).map(|_| rng.next()).collect();
+        let input = |to| StatisticsInput {
+            intervals: 10,
+            significance: Some(7),
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        // Los prefijos cruzan el límite de un bloque
+        let nums: ChunkedVec<f64> = values.clone().into();
+        let trace = chi_squared_trace(input(None), &nums, &uniform);
+        let sizes: Vec<u64> = trace.points.iter().map(|p| p.n).collect();
+        assert_eq!(sizes, vec![1000, 10000, 100000, 1000000, number as u64]);
+        // La Uniforme fija los intervalos, por lo que cada prefijo coincide
+        // con la prueba sobre esa parte de la muestra
+        for point in &trace.points {
+            let res = statistics(input(Some(point.n as usize)), &values, &uniform);
+            assert!((point.calculated - res.test.calculated).abs() < 1e-9);
+            assert_eq!(point.critical, res.test.critical);
+        }
+        assert_eq!(chi_squared_trace(input(Some(500)), &values, &uniform).points.len(), 1);
+    }
+
+    #[test]
+    fn test_interval_sensitivity() {
+        use chunked::{ChunkedVec, CHUNK_LEN};
+        use dist::exponential::Exponential;
+        use stats::{interval_sensitivity, StatisticsInput};
+        let exponential = Exponential::builder().lambda(1.5).build().unwrap();
+        let mut rng = rng::UniformGenerator::with_seed(11);
+        let values: Vec<f64> = (0..CHUNK_LEN + 500).map(|_| exponential.next(&mut rng)).collect();
+        let input = |intervals, binning| StatisticsInput {
+            intervals,
+            significance: Some(7),
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: Some(200000),
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        let nums: ChunkedVec<f64> = values.clone().into();
+        let (width, quantile) = (stats::Binning::EqualWidth, stats::Binning::Quantile);
+        let res = interval_sensitivity(input(0, width), &nums, &exponential, 5, 30);
+        assert_eq!(res.points.len(), 26);
+        assert_eq!(res.alpha, 0.05);
+        assert_eq!(res.rejections, res.points.iter().filter(|p| p.reject).count());
+        let by_quantile = interval_sensitivity(input(0, quantile), &nums, &exponential, 8, 9);
+        // Cada cantidad de intervalos coincide con la prueba completa
+        let checks = [
+            (&res.points[0], width),
+            (&res.points[7], width),
+            (&res.points[25], width),
+            (&by_quantile.points[0], quantile),
+            (&by_quantile.points[1], quantile),
+        ];
+        for (point, binning) in checks {
+            let full = statistics(input(point.requested, binning), &values, &exponential);
+            assert!((point.calculated - full.test.calculated).abs() < 1e-9);
+            assert_eq!(point.intervals, full.test.intervals.len());
+            assert_eq!((point.critical, point.degrees), (full.test.critical, full.test.degrees));
+        }
+    }
+
+    #[test]
+    fn test_rolling() {
+        use stats::descriptive::{rolling, summary};
+        let nums: Vec<f64> = (0..1000).map(|i| ((i * 37) % 101) as f64).collect();
+        // Sin ventana, cada punto es el resumen de todos los valores recorridos
+        let points = rolling(&nums, None, 7);
+        assert_eq!(points.len(), 7);
+        assert_eq!(points.last().unwrap().n, 1000);
+        for point in &points {
+            let expected = summary(&nums[..point.n as usize]);
+            assert!((point.mean - expected.mean).abs() < 1e-9);
+            assert!((point.variance - expected.variance).abs() < 1e-6);
+        }
+        // Con ventana, sólo los últimos valores
+        for point in rolling(&nums, Some(50), 20).iter().filter(|p| p.n >= 50) {
+            let expected = summary(&nums[point.n as usize - 50..point.n as usize]);
+            assert!((point.mean - expected.mean).abs() < 1e-9);
+            assert!((point.variance - expected.variance).abs() < 1e-6);
+        }
+        assert_eq!(rolling(&nums[..3], None, 10).len(), 3);
+    }
+
+    #[test]
+    fn test_running_means() {
+        use stats::descriptive::{running_means, summary};
+        let nums: Vec<f64> = (0..100_000).map(|i| ((i * 37) % 101) as f64).collect();
+        // Con pocos valores, la media en cada posición
+        let res = running_means(&nums[..5], 10);
+        assert_eq!(res.iter().map(|p| p.n).collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+        assert_eq!(res[1].mean, 18.5);
+        // Con muchos, posiciones geométricas de 1 a n, sin repetir
+        let res = running_means(&nums, 50);
+        assert!(res.len() <= 50 && res.len() > 40);
+        assert_eq!((res[0].n, res.last().unwrap().n), (1, 100_000));
+        assert!(res.windows(2).all(|w| w[0].n < w[1].n));
+        let point = &res[res.len() / 2];
+        assert!((point.mean - summary(&nums[..point.n as usize]).mean).abs() < 1e-9);
+        assert_eq!(running_means(&nums, 1)[0].n, 100_000);
+    }
+
+    #[test]
+    fn test_statistics_range() {
+        let uniform = Uniform::builder().build().unwrap();
+        let nums: Vec<f64> = (0..100).map(|i| (i as f64 + 0.5) / 100f64).collect();
+        let input = |from, to| stats::StatisticsInput {
+            intervals: 5,
+            significance: Some(7),
+            final_bin: stats::FinalBin::Closed,
+            from,
+            to,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        // Los primeros 50 valores están en [0, 0.5)
+        let prefix = statistics(input(None, Some(50)), &nums, &uniform);
+        let expected = statistics(input(None, None), &nums[..50], &uniform);
+        assert_eq!(prefix.histogram.y, expected.histogram.y);
+        assert_eq!(prefix.histogram.y.iter().sum::<u64>(), 50);
+        let window = statistics(input(Some(90), None), &nums, &uniform);
+        assert_eq!(window.histogram.y.iter().sum::<u64>(), 10);
+        assert!(input(Some(10), Some(101)).check_range(100).is_err());
+        assert!(input(Some(60), Some(50)).check_range(100).is_err());
+        assert!(input(Some(0), Some(100)).check_range(100).is_ok());
+    }
+
+    #[test]
+    fn test_discrete_frequencies() {
+        use stats::discrete::discrete_frequencies;
+        let poisson = dist::poisson::Poisson::builder().lambda(2f64).build().unwrap();
+        let nums = vec![3f64, 1f64, 0f64, 1f64];
+        let res = discrete_frequencies(&nums, &poisson).unwrap();
+        let observed: Vec<(i64, u64)> = res.values.iter().map(|f| (f.value, f.observed)).collect();
+        assert_eq!(observed, vec![(0, 1), (1, 2), (2, 0), (3, 1)]);
+        // P(X = 2) = e^-2·2²/2!
+        assert!((res.values[2].expected - 4f64 * 2f64 * (-2f64).exp()).abs() < 1e-12);
+        assert_eq!(res.expected_below, 0f64);
+        let total: f64 = res.values.iter().map(|f| f.expected).sum::<f64>() + res.expected_above;
+        assert!((total - 4f64).abs() < 1e-12);
+        assert!(discrete_frequencies(&vec![1.5f64], &poisson).is_err());
+        let uniform = Uniform::builder().build().unwrap();
+        assert!(discrete_frequencies(&nums, &uniform).is_err());
+    }
+
+    #[test]
+    fn test_recorded_uniforms() {
+        use rng::{fill_chunked, fill_chunked_recorded, Random, PARALLEL_THRESHOLD};
+        // Dos uniformes por valor, para que el flujo crudo no coincida con los valores
+        let fill = |rng: &mut dyn Random, chunk: &mut [f64]| {
+            chunk.iter_mut().for_each(|x| *x = rng.next() + rng.next())
+        };
+        for number in [100, PARALLEL_THRESHOLD + 100] {
+            let mut expected = vec![0f64; number];
+            fill_chunked(9, &mut expected, |rng: &mut rng::UniformGenerator, chunk: &mut [f64]| {
+                fill(rng, chunk)
+            });
+            let mut values = vec![0f64; number];
+            let uniforms = fill_chunked_recorded(9, &mut values, fill);
+            assert_eq!(values, expected);
+            assert_eq!(uniforms.len(), 2 * number);
+            assert_eq!(uniforms[number] + uniforms[number + 1], values[number / 2]);
+        }
+    }
+
+    #[test]
+    fn test_sequence_generator() {
+        use rng::{Random, SequenceGenerator};
+        let mut rng = SequenceGenerator::new(vec![0.1, 0.5, 0.9]).unwrap();
+        // Transformada inversa de la Exponencial de lambda 2, como a mano
+        let exponential = dist::exponential::Exponential::builder().lambda(2f64).build().unwrap();
+        assert!((exponential.next(&mut rng) - -(0.9f64).ln() / 2f64).abs() < 1e-12);
+        assert_eq!((rng.used(), rng.exhausted()), (1, false));
+        assert_eq!((rng.next(), rng.next()), (0.5, 0.9));
+        // Al terminarse vuelve a empezar, y lo indica
+        assert_eq!(rng.next(), 0.1);
+        assert!(rng.exhausted());
+        assert!(SequenceGenerator::new(vec![]).is_err());
+        assert!(SequenceGenerator::new(vec![0.2, 1f64]).is_err());
+        assert!(SequenceGenerator::new(vec![f64::NAN]).is_err());
+    }
+
+    #[test]
+    fn test_record_replay() {
+        use dist::gamma::Gamma;
+        use rng::{Random, Recorder, Replayer, UniformGenerator};
+        // La Gamma usa un número variable de uniformes por valor
+        let gamma = Gamma::builder().shape(2.5).scale(1f64).build().unwrap();
+        let mut recorder = Recorder::new(UniformGenerator::with_seed(8));
+        let mut sampler = gamma.sampler();
+        let expected: Vec<f64> = (0..1000).map(|_| sampler.next(&mut recorder)).collect();
+        let draws = recorder.values().len();
+        assert!(draws > 1000);
+        let mut replayer = recorder.into_replayer();
+        let mut sampler = gamma.sampler();
+        let replayed: Vec<f64> = (0..1000).map(|_| sampler.next(&mut replayer)).collect();
+        assert_eq!(replayed, expected);
+        assert_eq!(replayer.remaining(), 0);
+        // También registra un generador prestado
+        let mut rng = UniformGenerator::with_seed(8);
+        let mut recorder = Recorder::new(&mut rng as &mut dyn Random);
+        let first = recorder.next();
+        assert_eq!(recorder.into_values(), [first]);
+        assert_eq!(Replayer::new(vec![0.25]).next(), 0.25);
+    }
+
+    #[test]
+    fn test_fixtures() {
+        use testing::{assert_bit_identical, fixtures, fingerprint};
+        if let Err(e) = testing::check_fixtures() {
+            panic!("{}", e);
+        }
+        // Las secuencias se generan igual cada vez
+        let (first, second) = (fixtures(), fixtures());
+        for (a, b) in first.iter().zip(&second) {
+            assert_bit_identical(&a.values, &b.values);
+        }
+        assert_eq!(first.len(), 19);
+        assert_ne!(fingerprint(&[0f64]), fingerprint(&[-0f64]));
+        // Un valor que difiere en el último bit
+        let next = f64::from_bits(1f64.to_bits() + 1);
+        assert!(std::panic::catch_unwind(|| assert_bit_identical(&[1f64], &[next])).is_err());
+    }
+
+    #[test]
+    fn test_discard() {
+        use chunked::{ChunkedVec, SampleChunks, CHUNK_LEN};
+        use rng::fill_chunked;
+        // El salto del congruencial equivale a generar y descartar
+        let mut jumped = LinearCongruentialGenerator::with_seed(7);
+        let mut stepped = jumped.clone();
+        jumped.advance(1000);
+        (0..1000).for_each(|_| {
+            stepped.next();
+        });
+        assert_eq!(jumped.next(), stepped.next());
+        let mut small = LinearCongruentialGenerator::new(3, 16, 5, 7);
+        small.advance(0);
+        assert_eq!(small.next() * 16f64, 6f64);
+        // Los bloques guardados con descarte son los generados sin el prefijo,
+        // aunque el descarte no coincida con el límite de un bloque
+        let fill = |rng: &mut rng::UniformGenerator, chunk: &mut [f64]| {
+            chunk.iter_mut().for_each(|x| *x = rng.next())
+        };
+        for (skip, number) in [(10, 100), (1000, CHUNK_LEN + 10), (CHUNK_LEN + 3, 5)] {
+            let mut expected = vec![0f64; skip + number];
+            fill_chunked(5, &mut expected, fill);
+            let nums: ChunkedVec<f64> =
+                ChunkedVec::generate(5, skip, number, None, fill, |_| {}).unwrap();
+            assert_eq!(nums.len(), number);
+            assert_eq!(nums.into_vec(), expected[skip..]);
+        }
+    }
+
+    #[test]
+    fn test_gamma() {
+        use dist::gamma::Gamma;
+        // Por media y varianza: forma 4 y escala 0.5
+        let gamma = Gamma::from_moments(2f64, 1f64).unwrap();
+        assert!((gamma.shape - 4f64).abs() < 1e-12 && (gamma.scale - 0.5).abs() < 1e-12);
+        assert!(Gamma::from_moments(-1f64, 1f64).is_err());
+        assert!(Gamma::builder().shape(0f64).build().is_err());
+        // La Erlang de k = 1 es la exponencial
+        let erlang = Gamma::erlang(1, 2f64).unwrap();
+        assert!((erlang.cdf(1f64) - (1f64 - (-2f64).exp())).abs() < 1e-12);
+        let parsed: Gamma = serde_json::from_str(r#"{"mean": 2.0, "variance": 1.0}"#).unwrap();
+        assert_eq!(parsed.shape, gamma.shape);
+        assert!(serde_json::from_str::<Gamma>(r#"{"k": 0, "lambda": 1.0}"#).is_err());
+        for p in [0.01, 0.5, 0.99] {
+            assert!((gamma.cdf(gamma.quantile(p)) - p).abs() < 1e-10);
+        }
+        // Media y varianza de la muestra, también con forma menor a 1
+        for gamma in [gamma, Gamma::builder().shape(0.5).scale(3f64).build().unwrap()] {
+            let mut rand = rng::UniformGenerator::with_seed(11);
+            let mut sampler = gamma.sampler();
+            let nums: Vec<f64> = (0..100_000).map(|_| sampler.next(&mut rand)).collect();
+            let summary = stats::descriptive::summary(&nums);
+            assert!((summary.mean - gamma.mean()).abs() < 0.02 * gamma.mean());
+            assert!((summary.variance - gamma.variance()).abs() < 0.05 * gamma.variance());
+        }
+    }
+
+    #[test]
+    fn test_log_scale() {
+        use dist::exponential::Exponential;
+        use stats::{Scale, StatisticsInput};
+        let exponential = Exponential::builder().lambda(0.01).build().unwrap();
+        let mut rand = rng::UniformGenerator::with_seed(3);
+        let nums: Vec<f64> = (0..50_000).map(|_| exponential.next(&mut rand)).collect();
+        let input = |scale| StatisticsInput {
+            intervals: 20,
+            significance: Some(7),
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        assert!(input(Scale::Log).check_scale(&nums, &exponential).is_ok());
+        let res = statistics(input(Scale::Log), &nums, &exponential);
+        assert_eq!(res.histogram.scale, Scale::Log);
+        // Los límites son logaritmos, y las frecuencias esperadas suman n
+        assert!(res.histogram.upper < 15f64);
+        let expected: f64 = res.test.intervals.iter().map(|i| i.fe).sum();
+        assert!((expected - nums.len() as f64).abs() < 1e-6);
+        assert!(res.test.calculated < res.test.critical);
+        // Los valores no positivos y las distribuciones discretas no se admiten
+        assert!(input(Scale::Log).check_scale(&[1f64, 0f64], &exponential).is_err());
+        let poisson = dist::poisson::Poisson::builder().lambda(2f64).build().unwrap();
+        assert!(input(Scale::Log).check_scale(&[1f64], &poisson).is_err());
+        assert!(input(Scale::Linear).check_scale(&[0f64], &poisson).is_ok());
+    }
+
+    #[test]
+    fn test_trim() {
+        use stats::{StatisticsInput, Trim};
+        let normal = Normal::builder().build().unwrap();
+        let mut sampler = normal.sampler();
+        let mut rand = rng::UniformGenerator::with_seed(8);
+        let mut nums: Vec<f64> = (0..20_000).map(|_| sampler.next(&mut rand)).collect();
+        // Un valor extremo estira todos los intervalos
+        nums.push(50f64);
+        let input = |trim| StatisticsInput {
+            intervals: 10,
+            significance: Some(7),
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        assert_eq!(statistics(input(None), &nums, &normal).histogram.upper, 50f64);
+        let bounds = Trim::Bounds { lower: None, upper: Some(10f64) };
+        let res = statistics(input(Some(bounds)), &nums, &normal);
+        let trimmed = res.trimmed.unwrap();
+        assert_eq!((trimmed.excluded, trimmed.upper), (1, Some(10f64)));
+        assert!(res.histogram.upper < 6f64);
+        assert_eq!(res.histogram.y.iter().sum::<u64>(), 20_000);
+        // Con el criterio de Tukey se excluye cerca del 0.7% de una Normal, y
+        // la prueba usa la Normal truncada
+        let res = statistics(input(Some(Trim::Iqr { k: 1.5 })), &nums, &normal);
+        let excluded = res.trimmed.unwrap().excluded;
+        assert!((100..200).contains(&excluded));
+        let expected: f64 = res.test.intervals.iter().map(|i| i.fe).sum();
+        assert!((expected - (nums.len() as u64 - excluded) as f64).abs() < 1e-6);
+        assert!(res.test.calculated < res.test.critical);
+        // Parámetros no válidos y distribuciones discretas
+        assert!(input(Some(Trim::Iqr { k: -1f64 })).check_trim(&normal).is_err());
+        let inverted = Trim::Bounds { lower: Some(1f64), upper: Some(0f64) };
+        assert!(input(Some(inverted)).check_trim(&normal).is_err());
+        let poisson = dist::poisson::Poisson::builder().lambda(2f64).build().unwrap();
+        assert!(input(Some(bounds)).check_trim(&poisson).is_err());
+    }
+
+    #[test]
+    fn test_quantile_bins() {
+        use stats::{Binning, StatisticsInput};
+        let exponential = dist::exponential::Exponential::builder().lambda(1f64).build().unwrap();
+        let mut rand = rng::UniformGenerator::with_seed(11);
+        let nums: Vec<f64> = (0..20_000).map(|_| exponential.next(&mut rand)).collect();
+        let input = |binning| StatisticsInput {
+            intervals: 10,
+            significance: Some(7),
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        // Con igual ancho, la cola deja casi todos los valores en los primeros
+        let res = statistics(input(Binning::EqualWidth), &nums, &exponential);
+        assert!(res.histogram.y[0] > 10_000 && res.histogram.widths.is_none());
+        // Por cuantiles, cada intervalo tiene n/k valores y los anchos crecen
+        let res = statistics(input(Binning::Quantile), &nums, &exponential);
+        assert_eq!(res.histogram.binning, Binning::Quantile);
+        assert!(res.histogram.y.iter().all(|&y| y == 2_000));
+        let widths = res.histogram.widths.unwrap();
+        assert!(widths[9] > widths[5] && widths[5] > widths[0]);
+        let total: f64 = widths.iter().sum();
+        assert!((total - (res.histogram.upper - res.histogram.lower)).abs() < 1e-9);
+        let expected: f64 = res.test.intervals.iter().map(|i| i.fe).sum();
+        assert!((expected - nums.len() as f64).abs() < 1e-6);
+        assert!(res.test.calculated < res.test.critical);
+        // Las distribuciones discretas usan siempre intervalos de igual ancho
+        let poisson = dist::poisson::Poisson::builder().lambda(2f64).build().unwrap();
+        let counts: Vec<f64> = (0..1_000).map(|_| poisson.next(&mut rand)).collect();
+        let res = statistics(input(Binning::Quantile), &counts, &poisson);
+        assert_eq!(res.histogram.binning, Binning::EqualWidth);
+    }
+
+    #[test]
+    fn test_chi_squared_table() {
+        let uniform = Uniform::builder().lower(0f64).upper(1f64).build().unwrap();
+        let mut rand = rng::UniformGenerator::with_seed(5);
+        let nums: Vec<f64> = (0..1_000).map(|_| rand.next()).collect();
+        let input = stats::StatisticsInput {
+            intervals: 4,
+            significance: Some(7),
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        let test = statistics(input, &nums, &uniform).test;
+        let csv = test.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "lower,upper,fo,fe,c,c_ac");
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1].starts_with("0,0.25,") && lines[4].contains(",250,"));
+        let fo: u64 =
+            lines[1..].iter().map(|l| l.split(',').nth(2).unwrap().parse::<u64>().unwrap()).sum();
+        assert_eq!(fo, 1_000);
+        let latex = test.to_latex();
+        assert!(latex.starts_with("\\begin{tabular}") && latex.ends_with("\\end{tabular}\n"));
+        assert!(latex.contains("[0.0000 & 0.2500) &") && latex.contains("1.0000] &"));
+        assert!(latex.contains(&format!("& {:.4} \\\\", test.calculated)));
+        let html = test.to_html();
+        assert!(html.starts_with("<table>") && html.ends_with("</table>\n"));
+        assert_eq!(html.matches("<tr><td>").count(), 4);
+        assert!(html.contains("<td>[0.0000</td><td>0.2500)</td>") && html.contains("1.0000]</td>"));
+        assert!(html.contains(&format!("<td>{:.4}</td></tr>", test.calculated)));
+    }
+
+    #[test]
+    fn test_significance_levels() {
+        let uniform = Uniform::builder().lower(0f64).upper(1f64).build().unwrap();
+        let mut rand = rng::UniformGenerator::with_seed(6);
+        let nums: Vec<f64> = (0..1_000).map(|_| rand.next()).collect();
+        let input = |alphas| stats::StatisticsInput {
+            intervals: 10,
+            significance: Some(7),
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas,
+            parameters_estimated: false,
+            extended: false,
+        };
+        let test = statistics(input(Some(vec![0.10, 0.05, 0.01])), &nums, &uniform).test;
+        let levels = test.levels.unwrap();
+        assert_eq!(levels.iter().map(|l| l.alpha).collect::<Vec<_>>(), vec![0.10, 0.05, 0.01]);
+        // Con 10 intervalos quedan 9 grados de libertad
+        assert!(levels[0].critical < levels[1].critical && levels[1].critical < levels[2].critical);
+        assert_eq!(levels[1].critical, test.critical);
+        assert!((levels[2].critical - 21.666).abs() < 1e-3);
+        assert!(levels.iter().all(|l| l.reject == (test.calculated > l.critical)));
+        // Sin niveles adicionales no se devuelven
+        assert!(statistics(input(None), &nums, &uniform).test.levels.is_none());
+        assert!(input(Some(vec![0.05, 0.07])).check_alphas().is_err());
+        assert!(input(Some(vec![0.001])).check_alphas().is_ok());
+        // El índice de significancia debe ser una fila de la tabla
+        let mut out_of_range = input(None);
+        assert!(out_of_range.check_significance().is_ok());
+        out_of_range.significance = Some(0);
+        assert!(out_of_range.check_significance().is_err());
+        out_of_range.significance = Some(stats::ALPHAS.len() + 1);
+        assert!(out_of_range.check_significance().is_err());
+        assert!(out_of_range.check_intervals().is_ok());
+        out_of_range.intervals = 0;
+        assert!(out_of_range.check_intervals().is_err());
+    }
+
+    #[test]
+    fn test_verdict() {
+        let uniform = Uniform::builder().lower(0f64).upper(1f64).build().unwrap();
+        let mut rand = rng::UniformGenerator::with_seed(7);
+        let input = || stats::StatisticsInput {
+            intervals: 10,
+            significance: Some(9),
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: false,
+        };
+        let nums: Vec<f64> = (0..1_000).map(|_| rand.next()).collect();
+        let test = statistics(input(), &nums, &uniform).test;
+        assert_eq!((test.alpha, test.degrees), (0.01, 9));
+        assert_eq!(test.reject, test.calculated > test.critical);
+        assert!(!test.reject && test.conclusion.starts_with("No se rechaza"));
+        // Los cuadrados de uniformes se concentran cerca de 0
+        let squares: Vec<f64> = nums.iter().map(|x| x * x).collect();
+        let test = statistics(input(), &squares, &uniform).test;
+        assert!(test.reject && test.conclusion.starts_with("Se rechaza"));
+        assert!(test.conclusion.contains("α = 0.01 y 9 grados de libertad"));
+    }
+
+    #[test]
+    fn test_estimated_parameters() {
+        let normal = Normal { algorithm: Algorithm::BoxMuller, mean: 10f64, sd: 2f64 };
+        let mut rand = rng::UniformGenerator::with_seed(11);
+        let mut sampler = normal.sampler();
+        let nums: Vec<f64> = (0..10_000).map(|_| sampler.next(&mut rand)).collect();
+        let input = |parameters_estimated| stats::StatisticsInput {
+            intervals: 8,
+            significance: Some(7),
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated,
+            extended: false,
+        };
+        // Con los parámetros fijados de antemano no se restan grados
+        let known = statistics(input(false), &nums, &normal).test;
+        let k = known.intervals.len();
+        assert_eq!(known.degrees, k - 1);
+        // La media y el desvío estimados restan dos grados más
+        let estimated = statistics(input(true), &nums, &normal).test;
+        assert_eq!(estimated.degrees, k - 3);
+        assert_eq!(estimated.calculated, known.calculated);
+        assert!(estimated.critical < known.critical);
+    }
+
+    #[test]
+    fn test_distances() {
+        use stats::distance::cdf_distances;
+        let uniform = Uniform::builder().lower(0f64).upper(1f64).build().unwrap();
+        // Un solo valor en 0.5: ∫F en [0, 0.5] más ∫(1 - F) en [0.5, 1]
+        let (wasserstein, gap) = cdf_distances(&[0.5], None, &uniform);
+        assert!((wasserstein - 0.25).abs() < 1e-9 && (gap - 0.5).abs() < 1e-12);
+        // Todos los valores en 0 de una Poisson: la distancia es la media
+        let poisson = dist::poisson::Poisson { lambda: 2f64 };
+        let (wasserstein, _) = cdf_distances(&[0f64; 10], None, &poisson);
+        assert!((wasserstein - 2f64).abs() < 1e-6);
+        let mut rand = rng::UniformGenerator::with_seed(5);
+        let nums: Vec<f64> = (0..10_000).map(|_| rand.next()).collect();
+        let input = || stats::StatisticsInput {
+            intervals: 10,
+            significance: None,
+            final_bin: stats::FinalBin::Closed,
+            from: None,
+            to: None,
+            scale: stats::Scale::Linear,
+            trim: None,
+            binning: stats::Binning::EqualWidth,
+            alphas: None,
+            parameters_estimated: false,
+            extended: true,
+        };
+        let close = statistics(input(), &nums, &uniform).extended.unwrap();
+        assert!(close.total_variation < 0.02 && close.wasserstein < 0.01);
+        assert!(close.max_cdf_gap < 0.02);
+        // Llevar la muestra a [0.1, 1]: la máxima diferencia es 0.1, en x = 0.1
+        let shifted: Vec<f64> = nums.iter().map(|x| x * 0.9 + 0.1).collect();
+        let far = statistics(input(), &shifted, &uniform).extended.unwrap();
+        assert!((far.wasserstein - 0.05).abs() < 0.01 && (far.max_cdf_gap - 0.1).abs() < 0.02);
+        assert!(far.total_variation > 0.05);
+    }
+
+    #[test]
+    fn test_streaming() {
+        use stats::streaming::{CsvValues, StreamingAccumulator, StreamingBins};
+        let uniform = Uniform::builder().lower(0f64).upper(1f64).build().unwrap();
+        let mut rand = rng::UniformGenerator::with_seed(3);
+        let nums: Vec<f64> = (0..20_000).map(|_| rand.next()).collect();
+        // El CSV llega en partes que cortan las líneas
+        let text: String = nums.iter().map(|x| format!("{},1\n", x)).collect();
+        let text = format!("value,peso\n{}", text.trim_end());
+        let bins = StreamingBins { lower: 0f64, upper: 0.5, intervals: 5 };
+        let mut acc = StreamingAccumulator::new(bins);
+        let mut csv = CsvValues::default();
+        for part in text.as_bytes().chunks(1000) {
+            csv.feed(part, |x| acc.push(x)).unwrap();
+        }
+        csv.finish(|x| acc.push(x)).unwrap();
+        let res = acc.finish(Some(&uniform), 7, false);
+        let summary = stats::descriptive::summary(&nums);
+        assert_eq!(res.summary.count, summary.count);
+        assert!((res.summary.mean - summary.mean).abs() < 1e-12);
+        assert!((res.summary.variance - summary.variance).abs() < 1e-12);
+        // La Uniforme no tiene asimetría y su exceso de curtosis es -1.2
+        assert!(res.skewness.abs() < 0.05 && (res.kurtosis + 1.2).abs() < 0.05);
+        // Los valores fuera de [0, 0.5] no entran en el histograma
+        let inside: u64 = res.histogram.y.iter().sum();
+        assert_eq!(inside + res.outside, 20_000);
+        let test = res.test.unwrap();
+        assert!(!test.reject && (test.intervals[0].fe - inside as f64 / 5f64).abs() < 1e-6);
+        let mut csv = CsvValues::default();
+        assert!(csv.feed(b"1\n2\nx\n", |_| {}).unwrap_err().contains("línea 3"));
+    }
+
+    #[test]
+    fn test_parallel_csv() {
+        use stats::streaming::{CsvValues, StreamingAccumulator, StreamingBins, PARSE_BLOCK};
+        let mut rand = rng::UniformGenerator::with_seed(8);
+        let nums: Vec<f64> = (0..150_000).map(|_| rand.next() * 10f64).collect();
+        let lines: String = nums.iter().map(|x| format!("{}\n", x)).collect();
+        let text = format!("value\n{}", lines);
+        // Más de una parte, que se interpretan por separado y se combinan
+        assert!(text.len() > 2 * PARSE_BLOCK);
+        let bins = StreamingBins { lower: 0f64, upper: 10f64, intervals: 10 };
+        let (mut sequential, mut parallel) =
+            (StreamingAccumulator::new(bins), StreamingAccumulator::new(bins));
+        let mut csv = CsvValues::default();
+        csv.feed(text.as_bytes(), |x| sequential.push(x)).unwrap();
+        csv.finish(|x| sequential.push(x)).unwrap();
+        let mut csv = CsvValues::default();
+        csv.feed_into(text.as_bytes(), &mut parallel).unwrap();
+        csv.finish_into(&mut parallel).unwrap();
+        let (a, b) = (sequential.finish(None, 7, false), parallel.finish(None, 7, false));
+        assert_eq!((a.summary.count, &a.histogram.y), (b.summary.count, &b.histogram.y));
+        assert!((a.summary.mean - b.summary.mean).abs() < 1e-12);
+        assert!((a.summary.variance - b.summary.variance).abs() < 1e-9);
+        assert!((a.skewness - b.skewness).abs() < 1e-9 && (a.kurtosis - b.kurtosis).abs() < 1e-9);
+        // El número de línea del error cuenta las partes anteriores
+        let bad = text.replacen(&format!("\n{}\n", nums[120_000]), "\nx\n", 1);
+        let mut csv = CsvValues::default();
+        csv.feed_into(bad.as_bytes(), &mut StreamingAccumulator::new(bins)).unwrap();
+        let err = csv.finish_into(&mut StreamingAccumulator::new(bins)).unwrap_err();
+        assert!(err.contains("línea 120002"), "{}", err);
+    }
+
+    #[test]
+    fn test_correlation() {
+        use stats::correlation::correlation;
+        // Dos flujos con semillas distintas no están correlacionados
+        let mut first = rng::UniformGenerator::with_seed(1);
+        let mut second = rng::UniformGenerator::with_seed(2);
+        let a: Vec<f64> = (0..10_000).map(|_| first.next()).collect();
+        let b: Vec<f32> = (0..10_000).map(|_| second.next() as f32).collect();
+        let res = correlation(&a, &b, 0.05).unwrap();
+        assert!(res.pearson.coefficient.abs() < 0.05 && !res.pearson.reject);
+        assert!(!res.spearman.reject && res.spearman.p_value > 0.05);
+        // Una transformación monótona no lineal: Spearman 1, Pearson menor
+        let cubes: Vec<f64> = a.iter().map(|x| (x - 0.5).powi(3)).collect();
+        let res = correlation(&a, &cubes, 0.05).unwrap();
+        assert!((res.spearman.coefficient - 1f64).abs() < 1e-12);
+        assert!(res.pearson.coefficient < 0.95 && res.pearson.reject);
+        // Empates con el rango promedio: rangos 1, 2.5, 2.5, 4
+        let res = correlation(&[1f64, 2f64, 2f64, 3f64], &[1f64, 2f64, 3f64, 4f64], 0.05).unwrap();
+        assert!((res.spearman.coefficient - 0.9486832980505138).abs() < 1e-12);
+        // r = 0.8 con n = 5: t = 2.309 con 3 grados de libertad, p bilateral 0.104
+        let x = [1f64, 2f64, 3f64, 4f64, 5f64];
+        let res = correlation(&x, &[2f64, 1f64, 4f64, 3f64, 5f64], 0.05).unwrap();
+        assert!((res.pearson.coefficient - 0.8).abs() < 1e-12);
+        assert!((res.pearson.statistic - 2.309401).abs() < 1e-6);
+        assert!((res.pearson.p_value - 0.104088).abs() < 1e-5 && !res.pearson.reject);
+        assert!(correlation(&x, &x[..4], 0.05).is_err());
+        assert!(correlation(&x[..2], &x[..2], 0.05).is_err());
+    }
+
+    #[test]
+    fn test_pairs() {
+        use list::pairs;
+        let nums: Vec<f64> = (0..10).map(f64::from).collect();
+        let all = pairs(&nums, 1, 100);
+        assert_eq!(all.len(), 9);
+        assert_eq!((all[0], all[8]), ((0f64, 1f64), (8f64, 9f64)));
+        assert_eq!(pairs(&nums, 3, 100), vec![(0f64, 1f64), (3f64, 4f64), (6f64, 7f64)]);
+        assert_eq!(pairs(&nums, 1, 2).len(), 2);
+        assert!(pairs(&nums[..1], 1, 100).is_empty());
+        // Con max_points, los pares se reparten hasta el final
+        assert_eq!(list::spread_step(10, 2, 1, 100), 1);
+        let step = list::spread_step(10, 2, 1, 3);
+        assert_eq!(pairs(&nums, step, 100), vec![(0f64, 1f64), (3f64, 4f64), (6f64, 7f64)]);
+        assert_eq!(list::spread_step(10, 3, 2, 2), 4);
+        // Con un congruencial de m = 16, los 16 pares de un período son
+        // todos distintos: cada valor tiene un único sucesor
+        let mut lcg = LinearCongruentialGenerator::new(0, 16, 5, 3);
+        let stream: Vec<f64> = (0..17).map(|_| lcg.next()).collect();
+        let mut lattice = pairs(&stream, 1, 100);
+        lattice.sort_by(|a, b| a.0.total_cmp(&b.0));
+        lattice.dedup();
+        assert_eq!(lattice.len(), 16);
+    }
+
+    #[test]
+    fn test_reservoir_sample() {
+        use list::reservoir_sample;
+        use rng::UniformGenerator;
+        let nums: Vec<f64> = (0..1000).map(f64::from).collect();
+        let sample = reservoir_sample(&nums, 10, &mut UniformGenerator::with_seed(7));
+        assert_eq!(sample.len(), 10);
+        // En el orden de los datos, sin repetir, con el valor de cada posición
+        assert!(sample.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(sample.iter().all(|&(i, x)| x == i as f64));
+        assert_eq!(sample, reservoir_sample(&nums, 10, &mut UniformGenerator::with_seed(7)));
+        assert_eq!(reservoir_sample(&nums[..5], 10, &mut UniformGenerator::with_seed(7)).len(), 5);
+        // Cada posición se elige con probabilidad k / n = 1/2
+        let mut rng = UniformGenerator::with_seed(1);
+        let mut counts = [0u32; 4];
+        for _ in 0..4000 {
+            for (i, _) in reservoir_sample(&nums[..4], 2, &mut rng) {
+                counts[i] += 1;
+            }
+        }
+        assert!(counts.iter().all(|&c| (1800..2200).contains(&c)));
+    }
+
+    #[test]
+    fn test_triples() {
+        use list::triples;
+        let nums: Vec<f64> = (0..10).map(f64::from).collect();
+        let all = triples(&nums, 1, 100);
+        assert_eq!(all.len(), 8);
+        assert_eq!((all[0], all[7]), ((0f64, 1f64, 2f64), (7f64, 8f64, 9f64)));
+        assert_eq!(triples(&nums, 4, 100), vec![(0f64, 1f64, 2f64), (4f64, 5f64, 6f64)]);
+        assert!(triples(&nums[..2], 1, 100).is_empty());
+        // RANDU: todas sus ternas caen en 15 planos, 9x - 6y + z = k
+        let randu = LinearCongruentialGenerator::new(1, 1 << 31, 65539, 0);
+        let planes = randu.planes(10_000).unwrap();
+        assert_eq!(planes.normal, [9, -6, 1]);
+        assert_eq!(planes.planes, 15);
+        assert_eq!(planes.triples, 9_998);
+        assert!((planes.spacing - 1f64 / 118f64.sqrt()).abs() < 1e-12);
+        assert!(randu.planes(2).is_none());
+    }
+
+    #[test]
+    fn test_digit_frequency() {
+        use stats::battery::{battery, BatteryOptions};
+        // 1000 valores equiespaciados: cada par de dígitos aparece 10 veces
+        let even: Vec<f64> = (0..1000).map(|i| (i as f64 + 0.5) / 1000f64).collect();
+        let options = BatteryOptions { digits: 2, ..BatteryOptions::default() };
+        let res = battery(&even, options).unwrap();
+        let frequency = res.frequency.unwrap();
+        assert_eq!(frequency.expected, 10f64);
+        assert_eq!(frequency.observed.as_ref().unwrap().len(), 100);
+        assert_eq!((frequency.test.statistic, frequency.test.degrees), (0f64, 99));
+        assert!(!frequency.test.reject);
+        // Sólo valores menores a 0.5: el primer dígito nunca es 5 o más
+        let low: Vec<f64> = even.iter().map(|u| u / 2f64).collect();
+        let options = BatteryOptions { digits: 1, ..options };
+        let frequency = battery(&low, options).unwrap().frequency.unwrap();
+        assert!((frequency.test.statistic - 1000f64).abs() < 1e-9);
+        assert!(frequency.test.reject);
+        // Con 3 dígitos la frecuencia esperada es 1, no se aplica
+        let options = BatteryOptions { digits: 3, ..options };
+        assert!(battery(&even, options).unwrap().frequency.is_none());
+        assert!(battery(&even, BatteryOptions { digits: 7, ..options }).is_err());
+        assert!(battery(&even, BatteryOptions { alpha: 1f64, ..options }).is_err());
+    }
+
+    #[test]
+    fn test_ks_uniformity() {
+        use stats::ks::{kolmogorov_cdf, uniformity};
+        // Valores críticos exactos tabulados para α = 0.05
+        for (n, expected) in [(5, 0.56328), (10, 0.40925), (20, 0.29408), (40, 0.21012)] {
+            let test = uniformity(&vec![0.5; n], 0.05);
+            assert!(test.exact);
+            assert!((test.critical - expected).abs() < 5e-5, "{} {}", n, test.critical);
+        }
+        assert!((kolmogorov_cdf(1, 0.75) - 0.5).abs() < 1e-12);
+        // Valores equiespaciados: D = 1/(2n), no se rechaza
+        let even: Vec<f64> = (0..20).map(|i| (i as f64 + 0.5) / 20f64).collect();
+        let test = uniformity(&even, 0.05);
+        assert!((test.statistic - 0.025).abs() < 1e-12);
+        assert!((test.p_value - 1f64).abs() < 1e-9 && !test.reject);
+        let low: Vec<f64> = even.iter().map(|u| u / 2f64).collect();
+        let test = uniformity(&low, 0.05);
+        assert!(test.reject && test.p_value < 0.05);
+        // Con más de 40 valores, la distribución asintótica
+        let even: Vec<f64> = (0..1000).map(|i| (i as f64 + 0.5) / 1000f64).collect();
+        let test = uniformity(&even, 0.05);
+        assert!(!test.exact && !test.reject);
+        assert!((test.critical - 1.358102 / 1000f64.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_collision() {
+        use rng::UniformGenerator;
+        use stats::battery::{battery, BatteryOptions};
+        // 2¹⁴ pares en 2²⁰ celdas: se esperan 127.33 colisiones
+        let mut rng = UniformGenerator::with_seed(5);
+        let nums: Vec<f64> = (0..1 << 15).map(|_| rng.next()).collect();
+        let options = BatteryOptions::default();
+        let test = battery(&nums, options).unwrap().collision.unwrap();
+        assert_eq!((test.cells, test.points), (1 << 20, 1 << 14));
+        assert!((test.expected - 127.328).abs() < 1e-3);
+        assert!(!test.reject);
+        // Un congruencial de período 4096 repite sus pares
+        let mut lcg = LinearCongruentialGenerator::new(1, 4096, 1 + 4 * 37, 1);
+        let short: Vec<f64> = (0..1 << 15).map(|_| lcg.next()).collect();
+        let test = battery(&short, options).unwrap().collision.unwrap();
+        assert!(test.observed > 8000 && test.reject && test.p_value < 1e-12);
+        let options = BatteryOptions { collision_divisions: 8, ..options };
+        assert!(battery(&nums, options).is_err());
+    }
+
+    #[test]
+    fn test_permutation() {
+        use stats::battery::permutation;
+        // Las 6 permutaciones de una terna, 5 veces cada una: cada orden
+        // tiene su propio índice
+        let orders = [[1, 2, 3], [1, 3, 2], [2, 1, 3], [2, 3, 1], [3, 1, 2], [3, 2, 1]];
+        let nums: Vec<f64> = (0..5)
+            .flat_map(|_| orders.iter().flatten().map(|&i| i as f64 / 4f64))
+            .collect();
+        let test = permutation(&nums, 3, 0.05).unwrap();
+        assert_eq!(test.groups, 30);
+        assert_eq!(test.observed.as_deref(), Some(&[5u64; 6][..]));
+        assert_eq!((test.test.statistic, test.test.degrees), (0f64, 5));
+        // Una secuencia creciente tiene siempre el mismo orden
+        let increasing: Vec<f64> = (0..300).map(|i| i as f64 / 300f64).collect();
+        assert!(permutation(&increasing, 3, 0.05).unwrap().test.reject);
+        assert!(permutation(&increasing, 5, 0.05).is_none());
+    }
+
+    #[test]
+    fn test_entropy() {
+        use rng::UniformGenerator;
+        use stats::battery::entropy;
+        // Un ciclo por los 4 intervalos: todos igual de frecuentes, pero
+        // cada uno determina el siguiente
+        let cycle: Vec<f64> = (0..1000).map(|i| (i % 4) as f64 / 4f64 + 0.1).collect();
+        let res = entropy(&cycle, 4).unwrap();
+        assert!((res.shannon - 2f64).abs() < 1e-12 && res.max == 2f64);
+        assert!(res.approximate.abs() < 1e-12 && res.score == 0f64);
+        let mut rng = UniformGenerator::with_seed(11);
+        let nums: Vec<f64> = (0..100_000).map(|_| rng.next()).collect();
+        let res = entropy(&nums, 4).unwrap();
+        assert!(res.score > 0.99 && res.shannon > 1.99);
+        assert!(entropy(&nums[..63], 4).is_none());
+    }
+
+    #[test]
+    fn test_jarque_bera() {
+        use rng::UniformGenerator;
+        use stats::normality::jarque_bera;
+        let res = jarque_bera(&[1f64, 2f64, 3f64, 4f64, 10f64], 0.05).unwrap();
+        assert!((res.skewness - 1.138420).abs() < 1e-6 && (res.kurtosis + 0.212).abs() < 1e-12);
+        assert!((res.statistic - 1.089363).abs() < 1e-6 && (res.p_value - 0.580026).abs() < 1e-6);
+        // Las medias de 12 uniformes son aproximadamente normales; los
+        // uniformes no, su exceso de curtosis es -1.2
+        let mut rng = UniformGenerator::with_seed(5);
+        let nums: Vec<f64> = (0..24_000).map(|_| rng.next()).collect();
+        let means: Vec<f64> = nums.chunks(12).map(|c| c.iter().sum::<f64>() / 12f64).collect();
+        assert!(!jarque_bera(&means, 0.01).unwrap().reject);
+        let res = jarque_bera(&nums, 0.05).unwrap();
+        assert!(res.reject && (res.kurtosis + 1.2).abs() < 0.05);
+        assert!(jarque_bera(&[1f32, 1f32, 1f32], 0.05).is_none());
+    }
+
+    #[test]
+    fn test_reproducibility() {
+        // Valores fijos de cada combinación de generador y distribución con
+        // semilla 42: si cambian, los manifiestos de reproducibilidad de
+        // versiones anteriores dejan de dar los mismos valores
+        let expected: [[f64; 5]; 14] = [
+            // UniformGenerator: uniformes, Normal Box-Muller y convolución,
+            // Uniform, Exponential, Poisson y tabulada
+            [0.15935863614561074, 0.30424576388446023, 0.295564972432393, 0.7285577917075581, 0.5392678696949764],
+            [0.6060765701284642, 2.1106509497083064, 0.7751274123624226, -0.6590074543505062, 2.450359359894896],
+            [-3.920845399683108, 1.4615751275854638, 3.137401460646524, 1.19346310917558, 0.14315389825995517],
+            [-0.362565455417557, 0.21698305553784092, 0.18225988972957197, 1.9142311668302323, 1.1570714787799057],
+            [0.3471803001724295, 0.7255175800695748, 0.7007183535653417, 2.6080120461350833, 1.5498769342252936],
+            [2.0, 4.0, 0.0, 2.0, 5.0],
+            [0.3187172722912215, 0.6084915277689205, 0.591129944864786, 1.9142311668302323, 1.1570714787799057],
+            // LinearCongruentialGenerator, en el mismo orden
+            [0.263929987559095, 0.8205513339489698, 0.9173067116644233, 0.4362489595077932, 0.2077465367037803],
+            [1.6715494853634913, -0.4143767539573373, -3.1120493119284385, 2.741259645300439, 1.9723350537821838],
+            [1.4127411292865872, 0.5811499329283834, 1.2237251298502088, -0.36439778190106153, 2.5787135707214475],
+            [0.0557199502363801, 2.2822053357958794, 2.6692268466576934, 0.7449958380311728, -0.16901385318487883],
+            [0.6128600783567161, 3.435732189914716, 4.985233674020741, 1.146285085009002, 0.46574781790356473],
+            [4.0, 4.0, 5.0, 2.0, 5.0],
+            [0.52785997511819, 2.2822053357958794, 2.6692268466576934, 0.8724979190155864, 0.4154930734075606],
+        ];
+        for (values, expected) in golden_draws().iter().zip(expected) {
+            assert_eq!(values[..], expected);
+        }
+        // Los pedidos grandes usan subflujos obtenidos por salto
+        let mut streams = rng::UniformGenerator::substreams(42, 2);
+        assert_eq!((streams[1].next(), streams[1].next()), (0.0747702938286352, 0.9833283943462894));
+    }
+
+    // Primeros valores de cada combinación de generador y distribución
+    fn golden_draws() -> Vec<Vec<f64>> {
+        let generators: [fn() -> Box<dyn Random>; 2] = [
+            || Box::new(rng::UniformGenerator::with_seed(42)),
+            || Box::new(LinearCongruentialGenerator::with_seed(42)),
+        ];
+        let table = TabulatedCdf::new(vec![(0f64, 0f64), (1f64, 0.5f64), (3f64, 1f64)]).unwrap();
+        let mut out = Vec::new();
+        for generator in generators {
+            let draw = |f: &mut dyn FnMut(&mut dyn Random) -> f64| {
+                let mut rand = generator();
+                (0..5).map(|_| f(rand.as_mut())).collect::<Vec<f64>>()
+            };
+            out.push(draw(&mut |r| r.next()));
+            for algorithm in [Algorithm::BoxMuller, Algorithm::Convolution] {
+                let mut sampler = Normal { algorithm, mean: 1f64, sd: 2f64 }.sampler();
+                out.push(draw(&mut |r| sampler.next(r)));
+            }
+            let uniform = Uniform { lower: -1f64, upper: 3f64 };
+            out.push(draw(&mut |r| uniform.next(r)));
+            let exponential = dist::exponential::Exponential { lambda: 0.5f64, shift: 0f64 };
+            out.push(draw(&mut |r| exponential.next(r)));
+            let poisson = dist::poisson::Poisson { lambda: 4f64 };
+            out.push(draw(&mut |r| poisson.next(r)));
+            out.push(draw(&mut |r| table.next(r)));
+        }
+        out
+    }
+
     fn trunc_to_dec(num: f64, dec: i32) -> f64 {
         (num * 10f64.powi(dec)).trunc() / 10f64.powi(dec)
     }
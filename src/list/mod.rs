@@ -1,13 +1,172 @@
-use std::sync::Arc;
+use std::fmt::Write;
 
-pub fn get_page(nums: Arc<Vec<f64>>, pagenum: usize) -> Vec<f64> {
-    let page_size = 30;
-    let start: usize = page_size * (pagenum-1);
-    let end = (start + page_size).min(nums.len());
-    match nums.get(start..end) {
-        Some(nums) => nums.to_vec(),
-        None => {
-            vec![]
+use crate::chunked::{self, SampleChunks};
+use crate::float::Float;
+use crate::rng::Random;
+
+/// Cantidad de valores por página
+pub const PAGE_SIZE: usize = 30;
+
+/// Devuelve los valores de la página indicada, de PAGE_SIZE valores,
+/// empezando en 1
+///
+/// # Argumentos
+///
+/// * `nums` valores generados, leídos por bloques
+/// * `pagenum` número de página
+pub fn get_page<T: Float, S: SampleChunks<T> + ?Sized>(nums: &S, pagenum: usize) -> Vec<T> {
+    let (start, end) = page_range(pagenum);
+    nums.range(start, end)
+}
+
+/// Posiciones del primer valor de la página indicada y del siguiente al
+/// último, sin limitar a la cantidad de valores. Con `chunked::slices`
+/// sirve para recorrer la página sin copiarla
+///
+/// # Argumentos
+///
+/// * `pagenum` número de página, empezando en 1
+pub fn page_range(pagenum: usize) -> (usize, usize) {
+    let start: usize = PAGE_SIZE * (pagenum-1);
+    (start, start + PAGE_SIZE)
+}
+
+/// Cantidad de valores por bloque al exportar
+pub const EXPORT_CHUNK: usize = 65536;
+
+/// Devuelve el bloque `chunk` de los números como texto, un valor por línea,
+/// para exportarlos por partes sin armar todo el archivo en memoria. Los
+/// valores se escriben directamente desde los bloques, sin copiarlos
+///
+/// # Argumentos
+///
+/// * `nums` valores generados, leídos por bloques
+/// * `chunk` número de bloque, empezando en 0
+pub fn export_chunk<T: Float, S: SampleChunks<T> + ?Sized>(nums: &S, chunk: usize) -> String {
+    let (start, end) = (chunk * EXPORT_CHUNK, (chunk + 1) * EXPORT_CHUNK);
+    let len = end.min(nums.len()).saturating_sub(start);
+    let mut out = String::with_capacity(len * 20);
+    for num in chunked::slices(nums, start, end).flatten() {
+        // Escribir en un String no falla
+        let _ = writeln!(out, "{}", num);
+    }
+    out
+}
+
+/// Distancia entre tuplas de valores sucesivos para que no pasen de
+/// `max_points` y se repartan a lo largo de todos los valores, en lugar de
+/// quedarse con las primeras. Es un múltiplo de `step`, así que las tuplas
+/// siguen empezando en las mismas posiciones que con `step`
+///
+/// # Argumentos
+///
+/// * `len` cantidad de valores
+/// * `size` valores de cada tupla, 2 en los pares y 3 en las ternas
+/// * `step` distancia pedida entre el primer valor de una tupla y el de la siguiente, mayor a 0
+/// * `max_points` cantidad máxima de tuplas, mayor a 0
+pub fn spread_step(len: usize, size: usize, step: usize, max_points: usize) -> usize {
+    let available = len.saturating_sub(size - 1).div_ceil(step);
+    step * available.div_ceil(max_points).max(1)
+}
+
+/// Devuelve pares de valores sucesivos (uᵢ, uᵢ₊₁), para graficar uno contra
+/// otro y ver la estructura en retículo de un generador congruencial. Los
+/// pares se solapan: con `step` 1 son (u₀, u₁), (u₁, u₂), ...; con `step`
+/// k se toma un par cada k posiciones
+///
+/// # Argumentos
+///
+/// * `nums` valores generados, leídos por bloques
+/// * `step` distancia entre el primer valor de un par y el del siguiente, mayor a 0
+/// * `limit` cantidad máxima de pares a devolver
+pub fn pairs<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    step: usize,
+    limit: usize,
+) -> Vec<(f64, f64)> {
+    let mut res = Vec::with_capacity(limit.min(nums.len().saturating_sub(1)));
+    let mut previous: Option<f64> = None;
+    for (i, x) in chunked::values(nums).enumerate() {
+        let x = x.to_f64();
+        if res.len() == limit {
+            break;
+        }
+        // El par que empieza en i - 1, si le corresponde
+        if let Some(previous) = previous {
+            if (i - 1) % step == 0 {
+                res.push((previous, x));
+            }
+        }
+        previous = Some(x);
+    }
+    res
+}
+
+/// Devuelve ternas de valores sucesivos (uᵢ, uᵢ₊₁, uᵢ₊₂), para graficarlas en
+/// tres dimensiones: las de un congruencial caen sobre pocos planos
+/// paralelos. Las ternas se solapan igual que los pares de `pairs`
+///
+/// # Argumentos
+///
+/// * `nums` valores generados, leídos por bloques
+/// * `step` distancia entre el primer valor de una terna y el de la siguiente, mayor a 0
+/// * `limit` cantidad máxima de ternas a devolver
+pub fn triples<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    step: usize,
+    limit: usize,
+) -> Vec<(f64, f64, f64)> {
+    let mut res = Vec::with_capacity(limit.min(nums.len().saturating_sub(2)));
+    let mut previous: Option<(f64, f64)> = None;
+    let mut first: Option<f64> = None;
+    for (i, x) in chunked::values(nums).enumerate() {
+        let x = x.to_f64();
+        if res.len() == limit {
+            break;
+        }
+        // La terna que empieza en i - 2, si le corresponde
+        if let Some((u, v)) = previous {
+            if (i - 2) % step == 0 {
+                res.push((u, v, x));
+            }
+        }
+        previous = first.map(|u| (u, x));
+        first = Some(x);
+    }
+    res
+}
+
+/// Elige k valores al azar, todos con la misma probabilidad y sin
+/// reposición, en una sola pasada y sin copiar los datos (algoritmo R de
+/// muestreo de reservorio). Devuelve la posición de cada valor, desde 0,
+/// junto con el valor, en el orden de los datos. Con la misma semilla y los
+/// mismos datos se eligen los mismos valores
+///
+/// # Argumentos
+///
+/// * `nums` valores generados, leídos por bloques
+/// * `k` cantidad de valores a elegir; con menos valores se devuelven todos
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn reservoir_sample<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    k: usize,
+    rand: &mut dyn Random,
+) -> Vec<(usize, T)> {
+    let mut reservoir: Vec<(usize, T)> = Vec::with_capacity(k.min(nums.len()));
+    if k == 0 {
+        return reservoir;
+    }
+    for (i, x) in chunked::values(nums).enumerate() {
+        if i < k {
+            reservoir.push((i, x));
+            continue;
+        }
+        // El valor i reemplaza a uno del reservorio con probabilidad k / (i + 1)
+        let j = ((rand.next() * (i + 1) as f64) as usize).min(i);
+        if j < k {
+            reservoir[j] = (i, x);
         }
     }
+    reservoir.sort_unstable_by_key(|&(i, _)| i);
+    reservoir
 }
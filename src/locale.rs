@@ -0,0 +1,214 @@
+use axum::async_trait;
+use axum::extract::{FromRequestParts, Query};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::convert::Infallible;
+
+use crate::error::ApiError;
+use crate::negotiate::{Format, Negotiated};
+use crate::version::ApiVersion;
+
+/// Idioma de los nombres de los campos de las respuestas de estadísticas
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Language {
+    /// Los nombres de los campos de la librería
+    #[default]
+    English,
+    /// Los nombres de `SPANISH_FIELDS`, para el material del curso en español
+    Spanish,
+}
+
+/// Nombre en español de los campos de las respuestas de estadísticas
+/// (`StatisticsResponse`, `ChiSquaredTrace` e `IntervalSensitivity`). Los
+/// que no están, como x, y, fo y fe, se mantienen
+pub const SPANISH_FIELDS: &[(&str, &str)] = &[
+    ("histogram", "histograma"),
+    ("test", "prueba"),
+    ("autocorrelation", "autocorrelacion"),
+    ("runs", "rachas"),
+    ("above", "sobre"),
+    ("below", "bajo"),
+    ("expected_runs", "rachas_esperadas"),
+    ("lengths", "largos"),
+    ("length", "largo"),
+    ("open", "abierta"),
+    ("median", "mediana"),
+    ("trimmed", "recorte"),
+    ("lower", "inferior"),
+    ("upper", "superior"),
+    ("size", "ancho"),
+    ("widths", "anchos"),
+    ("binning", "agrupamiento"),
+    ("weights", "pesos"),
+    ("final_bin", "intervalo_final"),
+    ("scale", "escala"),
+    ("intervals", "intervalos"),
+    ("calculated", "calculado"),
+    ("critical", "critico"),
+    ("effective_size", "tamano_efectivo"),
+    ("fo_weighted", "fo_ponderada"),
+    ("upper_closed", "superior_cerrado"),
+    ("open_upper", "clase_abierta"),
+    ("lags", "retardos"),
+    ("excluded", "excluidos"),
+    ("points", "puntos"),
+    ("reject", "rechaza"),
+    ("levels", "niveles"),
+    ("degrees", "grados"),
+    ("conclusion", "conclusion"),
+    ("extended", "extendidas"),
+    ("total_variation", "variacion_total"),
+    ("max_cdf_gap", "maxima_diferencia"),
+    ("requested", "pedidos"),
+    ("rejections", "rechazos"),
+    ("adjustment", "ajuste"),
+    ("applied", "aplicados"),
+    ("reason", "motivo"),
+];
+
+impl Language {
+    /// Elige el idioma a partir del valor del header Accept-Language,
+    /// tomando el primero soportado. Si no hay ninguno se usa inglés
+    ///
+    /// # Argumentos
+    ///
+    /// * `accept` valor del header Accept-Language, por ejemplo "es-AR,es;q=0.9"
+    pub fn from_accept(accept: &str) -> Self {
+        accept
+            .split(',')
+            .filter_map(|t| t.split(';').next())
+            .find_map(|t| Self::from_tag(t.trim()))
+            .unwrap_or_default()
+    }
+
+    // Función privada, idioma de una etiqueta como "es" o "en-US", según su
+    // subetiqueta principal
+    fn from_tag(tag: &str) -> Option<Self> {
+        match tag.split('-').next()?.to_ascii_lowercase().as_str() {
+            "es" => Some(Language::Spanish),
+            "en" => Some(Language::English),
+            _ => None,
+        }
+    }
+}
+
+/// Idioma pedido en la consulta, que tiene prioridad sobre Accept-Language.
+/// El header sólo se tiene en cuenta bajo `/api/v1`: en las rutas sin
+/// versión los campos se traducen únicamente con `?lang=es`, para no romper
+/// el front end actual en navegadores configurados en español
+#[derive(Deserialize)]
+struct LanguageQuery {
+    lang: Option<String>,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Language {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let query = Query::<LanguageQuery>::try_from_uri(&parts.uri).ok();
+        let from_query = query.and_then(|q| q.0.lang).and_then(|lang| Self::from_tag(&lang));
+        let version = parts.extensions.get::<ApiVersion>().copied().unwrap_or_default();
+        Ok(from_query.unwrap_or_else(|| match version {
+            ApiVersion::Legacy => Language::English,
+            ApiVersion::V1 => parts
+                .headers
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|v| v.to_str().ok())
+                .map_or(Language::English, Language::from_accept),
+        }))
+    }
+}
+
+/// Respuesta de estadísticas serializada en el formato pedido, con los
+/// nombres de los campos en el idioma pedido. Además de Accept, indica
+/// Accept-Language en Vary
+pub struct Localized<T>(pub Format, pub Language, pub T);
+
+impl<T: Serialize> IntoResponse for Localized<T> {
+    fn into_response(self) -> Response {
+        let Localized(format, language, value) = self;
+        let mut response = if language == Language::English {
+            Negotiated(format, value).into_response()
+        } else {
+            // Los valores no finitos, que JSON no admite, quedan como null
+            // igual que en la respuesta en JSON
+            match serde_json::to_value(&value) {
+                Ok(value) => Negotiated(format, translate(value)).into_response(),
+                Err(e) => ApiError::Internal(e.to_string()).into_response(),
+            }
+        };
+        response.headers_mut().append(header::VARY, HeaderValue::from_static("Accept-Language"));
+        response
+    }
+}
+
+// Función privada, renombra al español los campos de todos los objetos
+fn translate(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let key = SPANISH_FIELDS
+                        .iter()
+                        .find(|(en, _)| *en == key)
+                        .map_or(key, |(_, es)| es.to_string());
+                    (key, translate(value))
+                })
+                .collect::<Map<String, Value>>(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(translate).collect()),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+    use serde_json::json;
+
+    // Función privada, idioma elegido para una petición
+    async fn language(uri: &str, accept: Option<&str>, version: ApiVersion) -> Language {
+        let mut request = Request::builder().uri(uri);
+        if let Some(accept) = accept {
+            request = request.header(header::ACCEPT_LANGUAGE, accept);
+        }
+        let (mut parts, _) = request.extension(version).body(()).unwrap().into_parts();
+        Language::from_request_parts(&mut parts, &()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_language() {
+        assert_eq!(language("/api/v1/statistics", None, ApiVersion::V1).await, Language::English);
+        let es_ar = Some("es-AR,es;q=0.9");
+        assert_eq!(language("/api/v1/statistics", es_ar, ApiVersion::V1).await, Language::Spanish);
+        // En las rutas sin versión el header no cambia los campos
+        assert_eq!(language("/api/statistics", es_ar, ApiVersion::Legacy).await, Language::English);
+        let query = language("/api/statistics?lang=es", None, ApiVersion::Legacy).await;
+        assert_eq!(query, Language::Spanish);
+        let query = language("/api/v1/statistics?lang=en", es_ar, ApiVersion::V1).await;
+        assert_eq!(query, Language::English);
+        assert_eq!(Language::from_accept("fr,de;q=0.5"), Language::English);
+    }
+
+    #[test]
+    fn test_translate() {
+        let value = json!({"histogram": [{"x": 0.5, "lower": 0}], "test": {"reject": false}});
+        let expected =
+            json!({"histograma": [{"x": 0.5, "inferior": 0}], "prueba": {"rechaza": false}});
+        assert_eq!(translate(value), expected);
+    }
+
+    #[test]
+    fn test_vary() {
+        for language in [Language::English, Language::Spanish] {
+            let response = Localized(Format::Json, language, json!({"x": 1})).into_response();
+            let vary: Vec<_> = response.headers().get_all(header::VARY).iter().collect();
+            assert_eq!(vary, ["Accept", "Accept-Language"]);
+        }
+    }
+}
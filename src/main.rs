@@ -1,52 +1,312 @@
+use std::net::SocketAddr;
 use std::sync::Arc;
 
-use crate::controllers::Generated;
-use axum::{http::Method, routing::post, routing::get, Router};
-use tokio::sync::RwLock;
-use tower_http::cors::{Any, CorsLayer};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use crate::config::{Cli, Config};
+use crate::controllers::{AppState, Generated};
+use crate::datasets::Workspaces;
+use crate::grpc::{GrpcService, SimrngServer};
+use crate::history::History;
+use crate::trace::{RecentSpans, SpanLog};
+use crate::version::ApiVersion;
+use axum::http::{header, HeaderValue, Method};
+use axum::{middleware, routing::delete, routing::post, routing::get, Extension, Router};
+use axum_server::tls_rustls::RustlsConfig;
+use clap::Parser;
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
+mod auth;
+mod config;
 mod controllers;
+mod datasets;
+mod error;
+mod grpc;
+mod history;
+mod locale;
+mod negotiate;
+mod plot;
+mod report;
+mod storage;
+mod store;
+mod trace;
+mod version;
+
+/// Rutas de la API, con sus métodos y autenticación. Se montan bajo cada
+/// versión de la API
+///
+/// # Argumentos
+///
+/// * `state` estado compartido, con las claves de API
+fn api_routes(state: &AppState) -> Router<AppState> {
+    Router::new()
+        .route("/generate", post(controllers::get_unified))
+        .route("/generate/preview", post(controllers::generate_preview))
+        .route("/transform", post(controllers::transform_uniforms))
+        .route("/statistics", post(controllers::get_statistics))
+        .route("/statistics/table", post(controllers::get_statistics_table))
+        .route("/statistics/rolling", get(controllers::get_rolling_statistics))
+        .route("/lln", get(controllers::get_lln))
+        .route("/statistics/trace", post(controllers::get_chi_squared_trace))
+        .route("/statistics/sensitivity", post(controllers::get_interval_sensitivity))
+        .route("/page", get(controllers::get_page_numbers))
+        .route("/sample", get(controllers::get_subsample))
+        .route("/discrete", get(controllers::get_discrete_frequencies))
+        .route("/suggest", get(controllers::get_suggestion))
+        .route("/quantiles", get(controllers::get_quantiles))
+        .route("/correlation", get(controllers::get_correlation))
+        .route("/uniforms/page", get(controllers::get_page_uniforms))
+        .route("/uniforms/statistics", post(controllers::get_uniform_statistics))
+        .route("/uniforms/battery", get(controllers::get_battery))
+        .route("/data", get(controllers::list_datasets).delete(controllers::delete_data))
+        .route("/data/snapshot", post(controllers::snapshot_dataset))
+        .route("/data/transform", post(controllers::transform_dataset))
+        .route("/data/:id", delete(controllers::delete_dataset))
+        .route("/export", get(controllers::export_data))
+        .route("/plot/histogram.svg", get(controllers::get_histogram_svg))
+        .route("/plot/pairs", get(controllers::get_pairs))
+        .route("/plot/triples", get(controllers::get_triples))
+        .route("/report", get(controllers::get_report))
+        .route("/compare", post(controllers::compare))
+        .route("/montecarlo", post(controllers::integrate))
+        .route("/montecarlo/control", post(controllers::integrate_control_variates))
+        .route("/queue", post(controllers::simulate_queue))
+        .route("/inventory", post(controllers::simulate_inventory))
+        .route("/markov", post(controllers::simulate_markov))
+        .route("/sampling/lhs", post(controllers::compare_latin_hypercube))
+        .route("/resample", post(controllers::resample))
+        .route("/replications", post(controllers::replicate))
+        .route("/sample-means", post(controllers::sample_means))
+        .route("/import", post(controllers::import_data))
+        .route("/import/stream", post(controllers::import_stream))
+        .route("/rng/lcg", post(controllers::check_lcg))
+        .route("/benchmark", get(controllers::benchmark))
+        .route("/last-run", get(controllers::get_last_run))
+        .route("/last-run/active", get(controllers::get_active_runs))
+        .route("/memory", get(controllers::get_memory))
+        .route("/copula", post(controllers::generate_copula))
+        .route("/history", get(controllers::get_history))
+        .route("/history/:id/replay", post(controllers::replay_history))
+        .route("/reproduce", post(controllers::reproduce))
+        .layer(middleware::from_fn_with_state(state.clone(), auth::require_api_key))
+}
 
 #[tokio::main]
 async fn main() {
-    // Guarda el último Vec generado y su distribución
-    // Necesario para calcular estadísticas
-    let last: Arc<RwLock<Generated>> = Arc::new(RwLock::new(Generated::default()));
+    // Leer configuración del archivo TOML y de la línea de comandos
+    let config = Config::load(Cli::parse()).expect("failed to load configuration");
+    simrng::chunked::set_pool_limit(config.pool_chunks);
 
+    // El filtro de RUST_LOG se aplica sólo a los logs: los spans de
+    // generación y estadísticas se guardan siempre para `/last-run`
+    let spans = Arc::new(SpanLog::default());
     tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
+        .with(RecentSpans::new(Arc::clone(&spans)))
+        .with(tracing_subscriber::fmt::layer().with_filter(tracing_subscriber::EnvFilter::new(
             std::env::var("RUST_LOG").unwrap_or_else(|_| "simrng=info".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
+        )))
         .init();
 
-    // Permitir peticiones desde el puerto del web server, aceptando
-    // cualquier header
+    // Guarda los Vec generados y sus distribuciones
+    // Necesario para calcular estadísticas
+    // Los experimentos persistidos se recuperan como conjunto actual de
+    // cada cliente
+    let datasets = Workspaces::default();
+    let store = store::Persistence::new(store::open(&config).expect("failed to open dataset store"));
+    match store.load() {
+        Ok(snapshots) => {
+            for (client, snapshot) in snapshots {
+                tracing::info!(
+                    "Loaded {} values (seed {}) from the {:?} store",
+                    snapshot.data.len(),
+                    snapshot.seed,
+                    config.store()
+                );
+                let generated = Generated::from(snapshot);
+                // Los experimentos recuperados también cuentan en el
+                // presupuesto de memoria
+                let (budget, policy) = (config.memory_budget, config.memory_policy);
+                let bytes = generated.memory_bytes();
+                let _reservation = match datasets.make_room(bytes, budget, policy).await {
+                    Ok(reservation) => reservation,
+                    Err(_) => {
+                        tracing::warn!(
+                            "not loading data of client {}: it exceeds the memory budget",
+                            client.0
+                        );
+                        continue;
+                    }
+                };
+                datasets.get(&client).write().await.insert(generated);
+            }
+        }
+        Err(e) => tracing::warn!("failed to load data from the {:?} store: {}", config.store(), e),
+    }
+
+    // Historial de experimentos, en SQLite
+    let history = History::open(config.history_db.as_deref()).expect("failed to open history");
+
+    // Permitir peticiones desde los orígenes configurados (o cualquiera si
+    // se incluye "*"), aceptando cualquier header
+    let origins = if config.allowed_origins.iter().any(|o| o == "*") {
+        AllowOrigin::from(Any)
+    } else {
+        let origins: Vec<HeaderValue> = config
+            .allowed_origins
+            .iter()
+            .map(|o| o.parse().expect("invalid allowed origin"))
+            .collect();
+        AllowOrigin::list(origins)
+    };
+    // La capa de CORS reemplaza el header Vary de la respuesta por el suyo,
+    // así que incluye también Accept y Accept-Language, de los que dependen
+    // las respuestas negociadas (ver `Negotiated` y `Localized`)
     let cors = CorsLayer::new()
-        .allow_origin(Any)
+        .allow_origin(origins)
         .allow_headers(Any)
-        .allow_methods([Method::GET, Method::POST]);
+        .allow_methods([Method::GET, Method::POST, Method::DELETE])
+        .vary([
+            header::ORIGIN,
+            header::ACCESS_CONTROL_REQUEST_METHOD,
+            header::ACCESS_CONTROL_REQUEST_HEADERS,
+            header::ACCEPT,
+            header::ACCEPT_LANGUAGE,
+        ]);
+
+    // Crear servidor e iniciar en la dirección configurada
+    let addr = config.addr();
+    let grpc_addr = config.grpc_port.map(|port| SocketAddr::new(config.bind, port));
+    let tls = config.tls().map(|(cert, key)| (cert.to_owned(), key.to_owned()));
+    let state = AppState {
+        datasets: Arc::new(datasets),
+        config: Arc::new(config),
+        history: Arc::new(history),
+        store: Arc::new(store),
+        spans,
+    };
 
-    // Configurar rutas con sus métodos, CORS y estado
+    // Configurar rutas con sus métodos, CORS y estado. La API versionada
+    // devuelve los errores en Json; las rutas sin versión se mantienen como
+    // alias obsoletos para el front end actual
+    let v1 = api_routes(&state)
+        .layer(middleware::map_response(error::json_errors))
+        .layer(Extension(ApiVersion::V1));
+    let legacy = api_routes(&state).layer(middleware::map_response(version::deprecated));
     let app = Router::new()
-        .route("/api/generate", post(controllers::get_unified))
-        .route("/api/statistics", post(controllers::get_statistics))
-        .route("/api/page", get(controllers::get_page_numbers))
+        .nest("/api/v1", v1)
+        .nest("/api", legacy)
+        .layer(CompressionLayer::new())
         .layer(cors)
-        .with_state(last);
+        .with_state(state.clone());
 
-    let port;
-    if let Ok(n) = std::env::var("SIMRNG_PORT") {
-        port = n.parse().unwrap_or(3000);
-    } else {
-        port = 3000;
+    // Iniciar el servicio gRPC, si está configurado, con el mismo estado. Si
+    // hay certificado configurado gRPC usa TLS con el mismo certificado que
+    // HTTPS, nunca texto plano
+    if let Some(grpc_addr) = grpc_addr {
+        let service = SimrngServer::new(GrpcService::new(state.clone()));
+        let mut builder = tonic::transport::Server::builder();
+        if let Some((cert, key)) = &tls {
+            let cert = tokio::fs::read(cert).await.expect("failed to read TLS certificate");
+            let key = tokio::fs::read(key).await.expect("failed to read TLS key");
+            let identity = tonic::transport::Identity::from_pem(cert, key);
+            builder = builder
+                .tls_config(tonic::transport::ServerTlsConfig::new().identity(identity))
+                .expect("failed to load TLS certificate for gRPC");
+            tracing::info!("gRPC listening on {} (TLS)", grpc_addr);
+        } else {
+            tracing::info!("gRPC listening on {}", grpc_addr);
+        }
+        tokio::spawn(async move {
+            builder
+                .add_service(service)
+                .serve(grpc_addr)
+                .await
+                .expect("failed to start gRPC server");
+        });
+    }
+
+    // Servir HTTPS si hay certificado configurado, HTTP si no
+    match tls {
+        Some((cert, key)) => {
+            let tls = RustlsConfig::from_pem_file(cert, key)
+                .await
+                .expect("failed to load TLS certificate");
+            tracing::info!("Listening on https://{}", addr);
+            axum_server::bind_rustls(addr, tls)
+                .serve(app.into_make_service())
+                .await
+                .expect("failed to start server");
+        }
+        None => {
+            tracing::info!("Listening on {}", addr);
+            axum::Server::bind(&addr)
+                .serve(app.into_make_service())
+                .await
+                .expect("failed to start server");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Client;
+    use axum::body::{Body, HttpBody};
+    use axum::http::{Request, StatusCode};
+    use simrng::dist::uniform::Uniform;
+    use tower::ServiceExt;
+
+    // Función privada, hace la petición a la API y devuelve el estado y el
+    // cuerpo de la respuesta
+    async fn call(app: &Router, method: Method, key: Option<&str>) -> (StatusCode, String) {
+        let mut req = Request::builder().method(method).uri("/data");
+        if let Some(key) = key {
+            req = req.header("x-api-key", key);
+        }
+        let res = app.clone().oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        let status = res.status();
+        let mut body = res.into_body();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = body.data().await {
+            bytes.extend_from_slice(&chunk.unwrap());
+        }
+        (status, String::from_utf8(bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_client_isolation() {
+        let mut config = Config::default();
+        config.api_keys.insert("alice".into(), "clave-de-alice".into());
+        config.api_keys.insert("bob".into(), "clave-de-bob".into());
+        let state = AppState {
+            datasets: Arc::new(Workspaces::default()),
+            config: Arc::new(config),
+            history: Arc::new(History::open(None).unwrap()),
+            store: Arc::new(store::Persistence::new(Box::new(store::MemoryStore))),
+            spans: Arc::new(SpanLog::default()),
+        };
+        let uniform = Uniform { lower: 0f64, upper: 1f64 };
+        let generated = Generated::new(vec![0.5], Box::new(uniform)).with_label(Some("a".into()));
+        state.datasets.get(&Client("alice".into())).write().await.insert(generated);
+        let app = api_routes(&state).with_state(state.clone());
+        // Cada cliente ve sólo sus conjuntos de datos
+        let (status, body) = call(&app, Method::GET, Some("clave-de-alice")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("\"label\":\"a\""));
+        assert_eq!(call(&app, Method::GET, Some("clave-de-bob")).await.1, "[]");
+        // Y no puede borrar los de otro
+        let (status, _) = call(&app, Method::DELETE, Some("clave-de-bob")).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(call(&app, Method::GET, Some("clave-de-alice")).await.1.contains("\"id\""));
+        // Sin clave, o con una que sólo coincide en parte, no hay acceso
+        assert_eq!(call(&app, Method::GET, None).await.0, StatusCode::UNAUTHORIZED);
+        let (status, _) = call(&app, Method::GET, Some("clave-de-alicf")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        // Una ruta sin el middleware no cae en el cliente anónimo
+        let bare = Router::new()
+            .route("/data", get(controllers::list_datasets))
+            .with_state(state.clone());
+        assert_eq!(call(&bare, Method::GET, None).await.0, StatusCode::UNAUTHORIZED);
+        assert!(state.datasets.get(&Client::anonymous()).read().await.is_empty());
     }
-    // Crear servidor e iniciar en puerto 3000
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
-    tracing::info!("Listening on {}", addr);
-    axum::Server::bind(&addr)
-        .serve(app.into_make_service())
-        .await
-        .expect("failed to start server");
 }
@@ -0,0 +1,111 @@
+//! Funciones matemáticas elementales usadas por las distribuciones y los
+//! generadores. Con el feature `std` usan los métodos de f64; sin él, las
+//! implementaciones de libm, para compilar en entornos sin la biblioteca
+//! estándar. Las funciones especiales (gamma, beta y error) se construyen
+//! sobre ellas, en `special`
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("se necesita el feature std o el feature libm");
+
+mod special;
+
+pub use special::{beta_inc, erf, erfc, gamma_p, gamma_q, ln_gamma};
+
+/// Logaritmo natural
+#[inline]
+pub fn ln(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.ln();
+    #[cfg(not(feature = "std"))]
+    return libm::log(x);
+}
+
+/// Función exponencial, e^x
+#[inline]
+pub fn exp(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.exp();
+    #[cfg(not(feature = "std"))]
+    return libm::exp(x);
+}
+
+/// Raíz cuadrada
+#[inline]
+pub fn sqrt(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.sqrt();
+    #[cfg(not(feature = "std"))]
+    return libm::sqrt(x);
+}
+
+/// Potencia con exponente real, x^y
+#[inline]
+pub fn powf(x: f64, y: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.powf(y);
+    #[cfg(not(feature = "std"))]
+    return libm::pow(x, y);
+}
+
+/// Potencia con exponente entero, x^n
+#[inline]
+pub fn powi(x: f64, n: i32) -> f64 {
+    #[cfg(feature = "std")]
+    return x.powi(n);
+    #[cfg(not(feature = "std"))]
+    return libm::pow(x, n as f64);
+}
+
+/// Seno, en radianes
+#[inline]
+pub fn sin(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.sin();
+    #[cfg(not(feature = "std"))]
+    return libm::sin(x);
+}
+
+/// Coseno, en radianes
+#[inline]
+pub fn cos(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.cos();
+    #[cfg(not(feature = "std"))]
+    return libm::cos(x);
+}
+
+/// Parte entera, truncando hacia 0
+#[inline]
+pub fn trunc(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.trunc();
+    #[cfg(not(feature = "std"))]
+    return libm::trunc(x);
+}
+
+/// Mayor entero menor o igual a x
+#[inline]
+pub fn floor(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.floor();
+    #[cfg(not(feature = "std"))]
+    return libm::floor(x);
+}
+
+/// Menor entero mayor o igual a x
+#[inline]
+pub fn ceil(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.ceil();
+    #[cfg(not(feature = "std"))]
+    return libm::ceil(x);
+}
+
+/// Valor absoluto
+#[inline]
+pub fn abs(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.abs();
+    #[cfg(not(feature = "std"))]
+    return libm::fabs(x);
+}
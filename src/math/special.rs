@@ -0,0 +1,246 @@
+//! Funciones especiales: logaritmo de la función gamma, funciones gamma y
+//! beta incompletas regularizadas y función de error. Se implementan sin
+//! dependencias externas, con error relativo del orden de 1e-13 o menor,
+//! para la función acumulada de chi cuadrado, las distribuciones gamma y
+//! beta y el cálculo de valores p
+
+use core::f64::consts::PI;
+
+use super::{abs, exp, ln, sin};
+
+/// Error relativo con el que se cortan las series y fracciones continuas
+const EPSILON: f64 = 1e-15;
+
+/// Valor mínimo para evitar divisiones por 0 en las fracciones continuas
+const FPMIN: f64 = 1e-300;
+
+/// Cantidad máxima de iteraciones de las series y fracciones continuas
+const MAX_ITERATIONS: usize = 10_000;
+
+/// Coeficientes de la aproximación de Lanczos, con g = 7 y n = 9
+const LANCZOS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+/// Logaritmo natural del valor absoluto de la función gamma, ln|Γ(x)|, por
+/// la aproximación de Lanczos. Para x < 0.5 usa la fórmula de reflexión.
+/// Devuelve infinito en 0 y en los enteros negativos
+///
+/// # Argumentos
+///
+/// * `x` valor en el que se evalúa la función
+pub fn ln_gamma(x: f64) -> f64 {
+    if x < 0.5 {
+        // Γ(x)·Γ(1-x) = π / sin(πx)
+        return ln(PI / abs(sin(PI * x))) - ln_gamma(1f64 - x);
+    }
+    let x = x - 1f64;
+    let mut sum = LANCZOS[0];
+    for (i, c) in LANCZOS.iter().enumerate().skip(1) {
+        sum += c / (x + i as f64);
+    }
+    let t = x + 7.5;
+    0.5 * ln(2f64 * PI) + (x + 0.5) * ln(t) - t + ln(sum)
+}
+
+/// Función gamma incompleta inferior regularizada, P(a, x) = γ(a, x) / Γ(a).
+/// Es la función acumulada de una Gamma de forma a y escala 1; la de chi
+/// cuadrado con k grados de libertad es P(k/2, x/2). Devuelve NaN si a <= 0
+/// o x < 0
+///
+/// # Argumentos
+///
+/// * `a` parámetro de forma, mayor a 0
+/// * `x` límite superior de la integral, mayor o igual a 0
+pub fn gamma_p(a: f64, x: f64) -> f64 {
+    if a <= 0f64 || x < 0f64 || x.is_nan() {
+        return f64::NAN;
+    }
+    if x == 0f64 {
+        return 0f64;
+    }
+    if x < a + 1f64 {
+        gamma_series(a, x)
+    } else {
+        1f64 - gamma_continued_fraction(a, x)
+    }
+}
+
+/// Función gamma incompleta superior regularizada, Q(a, x) = 1 - P(a, x),
+/// calculada directamente para no perder precisión en la cola. Devuelve NaN
+/// si a <= 0 o x < 0
+///
+/// # Argumentos
+///
+/// * `a` parámetro de forma, mayor a 0
+/// * `x` límite inferior de la integral, mayor o igual a 0
+pub fn gamma_q(a: f64, x: f64) -> f64 {
+    if a <= 0f64 || x < 0f64 || x.is_nan() {
+        return f64::NAN;
+    }
+    if x == 0f64 {
+        return 1f64;
+    }
+    if x < a + 1f64 {
+        1f64 - gamma_series(a, x)
+    } else {
+        gamma_continued_fraction(a, x)
+    }
+}
+
+// Función privada, P(a, x) por su serie, que converge rápido para x < a + 1
+fn gamma_series(a: f64, x: f64) -> f64 {
+    let mut term = 1f64 / a;
+    let mut sum = term;
+    let mut n = a;
+    for _ in 0..MAX_ITERATIONS {
+        n += 1f64;
+        term *= x / n;
+        sum += term;
+        if abs(term) < abs(sum) * EPSILON {
+            break;
+        }
+    }
+    sum * exp(-x + a * ln(x) - ln_gamma(a))
+}
+
+// Función privada, Q(a, x) por su fracción continua, evaluada con el método
+// de Lentz modificado, que converge rápido para x >= a + 1
+fn gamma_continued_fraction(a: f64, x: f64) -> f64 {
+    let mut b = x + 1f64 - a;
+    let mut c = 1f64 / FPMIN;
+    let mut d = 1f64 / b;
+    let mut h = d;
+    for i in 1..MAX_ITERATIONS {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2f64;
+        d = an * d + b;
+        if abs(d) < FPMIN {
+            d = FPMIN;
+        }
+        c = b + an / c;
+        if abs(c) < FPMIN {
+            c = FPMIN;
+        }
+        d = 1f64 / d;
+        let delta = d * c;
+        h *= delta;
+        if abs(delta - 1f64) < EPSILON {
+            break;
+        }
+    }
+    exp(-x + a * ln(x) - ln_gamma(a)) * h
+}
+
+/// Función beta incompleta regularizada, Iₓ(a, b) = B(x; a, b) / B(a, b).
+/// Es la función acumulada de una Beta(a, b), y de ella se obtienen las de
+/// la t de Student y la F. Devuelve NaN si a <= 0, b <= 0 o x no está entre
+/// 0 y 1
+///
+/// # Argumentos
+///
+/// * `a` primer parámetro de forma, mayor a 0
+/// * `b` segundo parámetro de forma, mayor a 0
+/// * `x` límite superior de la integral, entre 0 y 1
+pub fn beta_inc(a: f64, b: f64, x: f64) -> f64 {
+    if a <= 0f64 || b <= 0f64 || !(0f64..=1f64).contains(&x) {
+        return f64::NAN;
+    }
+    if x == 0f64 || x == 1f64 {
+        return x;
+    }
+    let front = exp(
+        ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * ln(x) + b * ln(1f64 - x),
+    );
+    // La fracción continua converge rápido a un lado del punto
+    // (a+1)/(a+b+2); del otro se usa la simetría Iₓ(a, b) = 1 - I₁₋ₓ(b, a)
+    if x < (a + 1f64) / (a + b + 2f64) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1f64 - front * beta_continued_fraction(b, a, 1f64 - x) / b
+    }
+}
+
+// Función privada, fracción continua de la beta incompleta, evaluada con el
+// método de Lentz modificado
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    let qab = a + b;
+    let qap = a + 1f64;
+    let qam = a - 1f64;
+    let mut c = 1f64;
+    let mut d = 1f64 - qab * x / qap;
+    if abs(d) < FPMIN {
+        d = FPMIN;
+    }
+    d = 1f64 / d;
+    let mut h = d;
+    for m in 1..MAX_ITERATIONS {
+        let m = m as f64;
+        let m2 = 2f64 * m;
+        // Paso par de la fracción
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1f64 + aa * d;
+        if abs(d) < FPMIN {
+            d = FPMIN;
+        }
+        c = 1f64 + aa / c;
+        if abs(c) < FPMIN {
+            c = FPMIN;
+        }
+        d = 1f64 / d;
+        h *= d * c;
+        // Paso impar de la fracción
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1f64 + aa * d;
+        if abs(d) < FPMIN {
+            d = FPMIN;
+        }
+        c = 1f64 + aa / c;
+        if abs(c) < FPMIN {
+            c = FPMIN;
+        }
+        d = 1f64 / d;
+        let delta = d * c;
+        h *= delta;
+        if abs(delta - 1f64) < EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+/// Función de error, erf(x) = 2/√π ∫₀ˣ e^(-t²) dt, calculada como
+/// P(1/2, x²) con el signo de x
+///
+/// # Argumentos
+///
+/// * `x` valor en el que se evalúa la función
+pub fn erf(x: f64) -> f64 {
+    if x < 0f64 {
+        -gamma_p(0.5, x * x)
+    } else {
+        gamma_p(0.5, x * x)
+    }
+}
+
+/// Función de error complementaria, erfc(x) = 1 - erf(x), calculada
+/// directamente para no perder precisión en la cola
+///
+/// # Argumentos
+///
+/// * `x` valor en el que se evalúa la función
+pub fn erfc(x: f64) -> f64 {
+    if x < 0f64 {
+        2f64 - gamma_q(0.5, x * x)
+    } else {
+        gamma_q(0.5, x * x)
+    }
+}
@@ -0,0 +1,215 @@
+/// Expresión en una variable `x`, para definir la función a integrar
+///
+/// Admite números, `x`, las constantes `pi` y `e`, los operadores
+/// `+ - * / ^`, paréntesis y las funciones `sin cos tan exp ln sqrt abs`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Var,
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
+    Call(Function, Box<Expr>),
+}
+
+/// Funciones admitidas en las expresiones
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Exp,
+    Ln,
+    Sqrt,
+    Abs,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "tan" => Function::Tan,
+            "exp" => Function::Exp,
+            "ln" => Function::Ln,
+            "sqrt" => Function::Sqrt,
+            "abs" => Function::Abs,
+            _ => return None,
+        })
+    }
+
+    fn apply(self, x: f64) -> f64 {
+        match self {
+            Function::Sin => x.sin(),
+            Function::Cos => x.cos(),
+            Function::Tan => x.tan(),
+            Function::Exp => x.exp(),
+            Function::Ln => x.ln(),
+            Function::Sqrt => x.sqrt(),
+            Function::Abs => x.abs(),
+        }
+    }
+}
+
+impl Expr {
+    /// Interpreta una expresión, devolviendo un mensaje si no es válida
+    ///
+    /// # Argumentos
+    ///
+    /// * `text` expresión a interpretar, por ejemplo `x^2 + sin(x)`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser { chars: text.chars().collect(), pos: 0 };
+        let expr = parser.sum()?;
+        parser.skip_spaces();
+        match parser.peek() {
+            None => Ok(expr),
+            Some(c) => Err(format!("carácter inesperado '{}' en la posición {}", c, parser.pos)),
+        }
+    }
+
+    /// Evalúa la expresión en el punto indicado
+    ///
+    /// # Argumentos
+    ///
+    /// * `x` valor de la variable
+    pub fn eval(&self, x: f64) -> f64 {
+        match self {
+            Expr::Number(n) => *n,
+            Expr::Var => x,
+            Expr::Neg(a) => -a.eval(x),
+            Expr::Add(a, b) => a.eval(x) + b.eval(x),
+            Expr::Sub(a, b) => a.eval(x) - b.eval(x),
+            Expr::Mul(a, b) => a.eval(x) * b.eval(x),
+            Expr::Div(a, b) => a.eval(x) / b.eval(x),
+            Expr::Pow(a, b) => a.eval(x).powf(b.eval(x)),
+            Expr::Call(f, a) => f.apply(a.eval(x)),
+        }
+    }
+}
+
+// Analizador descendente recursivo, con la precedencia usual:
+// suma < producto < unario < potencia (asociativa a derecha)
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    // Avanza si el próximo carácter (sin contar espacios) es el indicado
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_spaces();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+        loop {
+            if self.eat('+') {
+                expr = Expr::Add(Box::new(expr), Box::new(self.product()?));
+            } else if self.eat('-') {
+                expr = Expr::Sub(Box::new(expr), Box::new(self.product()?));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        loop {
+            if self.eat('*') {
+                expr = Expr::Mul(Box::new(expr), Box::new(self.unary()?));
+            } else if self.eat('/') {
+                expr = Expr::Div(Box::new(expr), Box::new(self.unary()?));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            Ok(Expr::Pow(Box::new(base), Box::new(self.unary()?)))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        self.skip_spaces();
+        let start = self.pos;
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let expr = self.sum()?;
+                if !self.eat(')') {
+                    return Err(format!("falta ')' en la posición {}", self.pos));
+                }
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                    self.pos += 1;
+                }
+                let text: String = self.chars[start..self.pos].iter().collect();
+                text.parse()
+                    .map(Expr::Number)
+                    .map_err(|_| format!("número inválido '{}'", text))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                while self.peek().is_some_and(|c| c.is_ascii_alphanumeric()) {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[start..self.pos].iter().collect();
+                match name.as_str() {
+                    "x" => Ok(Expr::Var),
+                    "pi" => Ok(Expr::Number(std::f64::consts::PI)),
+                    "e" => Ok(Expr::Number(std::f64::consts::E)),
+                    _ => {
+                        let function = Function::from_name(&name)
+                            .ok_or_else(|| format!("identificador desconocido '{}'", name))?;
+                        if !self.eat('(') {
+                            return Err(format!("falta '(' después de '{}'", name));
+                        }
+                        let arg = self.sum()?;
+                        if !self.eat(')') {
+                            return Err(format!("falta ')' en la posición {}", self.pos));
+                        }
+                        Ok(Expr::Call(function, Box::new(arg)))
+                    }
+                }
+            }
+            Some(c) => Err(format!("carácter inesperado '{}' en la posición {}", c, self.pos)),
+            None => Err("expresión incompleta".into()),
+        }
+    }
+}
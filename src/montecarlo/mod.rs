@@ -0,0 +1,163 @@
+use crate::dist::uniform::Uniform;
+use crate::rng::Random;
+use serde::Serialize;
+
+pub mod expr;
+
+/// Estimación parcial de la integral, para seguir su convergencia
+#[derive(Serialize, Clone, Debug)]
+pub struct TracePoint {
+    /// Cantidad de muestras usadas hasta este punto
+    pub samples: u64,
+    /// Estimación de la integral con esas muestras
+    pub estimate: f64,
+    /// Error estándar de la estimación
+    pub std_error: f64,
+}
+
+/// Resultado de la integración por Monte Carlo
+#[derive(Serialize, Clone, Debug)]
+pub struct Estimate {
+    /// Estimación de la integral
+    pub estimate: f64,
+    /// Error estándar de la estimación
+    pub std_error: f64,
+    /// Cantidad de muestras utilizadas
+    pub samples: u64,
+    /// Estimaciones parciales, a intervalos regulares de muestras
+    pub trace: Vec<TracePoint>,
+}
+
+/// Estima ∫f(x)dx en [lower, upper] promediando f en puntos con distribución
+/// Uniforme: (b-a)·media(f(X)), con error estándar (b-a)·s/√n
+///
+/// # Argumentos
+///
+/// * `f` función a integrar
+/// * `lower` límite inferior de integración
+/// * `upper` límite superior de integración
+/// * `samples` cantidad de puntos a muestrear
+/// * `trace_points` cantidad de estimaciones parciales a registrar
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn integrate<F: Fn(f64) -> f64>(
+    f: F,
+    lower: f64,
+    upper: f64,
+    samples: u64,
+    trace_points: usize,
+    rand: &mut dyn Random,
+) -> Estimate {
+    let uniform = Uniform { lower, upper };
+    let width = upper - lower;
+    // Muestra en la que se registra la k-ésima estimación parcial, de forma
+    // que la última coincida con el total de muestras
+    let trace_points = trace_points.min(samples as usize);
+    let checkpoint = |k: usize| (samples as u128 * k as u128 / trace_points as u128) as u64;
+    let mut trace = Vec::with_capacity(trace_points);
+    // Media y varianza de f(X) con el algoritmo de Welford
+    let mut mean = 0f64;
+    let mut m2 = 0f64;
+    let point = |n: u64, mean: f64, m2: f64| TracePoint {
+        samples: n,
+        estimate: width * mean,
+        std_error: if n > 1 {
+            width * (m2 / (n - 1) as f64 / n as f64).sqrt()
+        } else {
+            0f64
+        },
+    };
+    for n in 1..=samples {
+        let y = f(uniform.next(rand));
+        let delta = y - mean;
+        mean += delta / n as f64;
+        m2 += delta * (y - mean);
+        while trace.len() < trace_points && checkpoint(trace.len() + 1) <= n {
+            trace.push(point(n, mean, m2));
+        }
+    }
+    let last = point(samples, mean, m2);
+    Estimate { estimate: last.estimate, std_error: last.std_error, samples, trace }
+}
+
+/// Resultado de la estimación con variable de control
+#[derive(Serialize, Clone, Debug)]
+pub struct ControlVariateEstimate {
+    /// Estimación ajustada con la variable de control
+    pub estimate: f64,
+    /// Error estándar de la estimación ajustada
+    pub std_error: f64,
+    /// Estimación sin ajustar, la de `integrate`
+    pub crude_estimate: f64,
+    /// Error estándar de la estimación sin ajustar
+    pub crude_std_error: f64,
+    /// Coeficiente óptimo c* = -Cov(Y, C)/Var(C) aplicado al control
+    pub coefficient: f64,
+    /// Correlación entre el estimador y el control
+    pub correlation: f64,
+    /// Reducción relativa de la varianza, 1 - Var(ajustada)/Var(sin ajustar)
+    pub variance_reduction: f64,
+    /// Cantidad de muestras utilizadas
+    pub samples: u64,
+}
+
+/// Estima ∫f(x)dx en [lower, upper] como `integrate`, ajustando cada
+/// observación Y = (b-a)·f(X) con una variable de control C = g(X) de media
+/// conocida: Y + c*·(C - μ), con el coeficiente c* que minimiza la varianza,
+/// estimado sobre la misma muestra. Con g(x) = x el control es el propio
+/// uniforme, de media (a+b)/2
+///
+/// # Argumentos
+///
+/// * `f` función a integrar
+/// * `g` función de control, correlacionada con f
+/// * `control_mean` media conocida de g(X), con X Uniforme en [lower, upper]
+/// * `lower` límite inferior de integración
+/// * `upper` límite superior de integración
+/// * `samples` cantidad de puntos a muestrear
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn control_variates<F: Fn(f64) -> f64, G: Fn(f64) -> f64>(
+    f: F,
+    g: G,
+    control_mean: f64,
+    lower: f64,
+    upper: f64,
+    samples: u64,
+    rand: &mut dyn Random,
+) -> ControlVariateEstimate {
+    let uniform = Uniform { lower, upper };
+    let width = upper - lower;
+    // Medias, sumas de cuadrados y co-momento, con el algoritmo de Welford
+    let (mut mean_y, mut mean_c) = (0f64, 0f64);
+    let (mut m2_y, mut m2_c, mut co) = (0f64, 0f64, 0f64);
+    for n in 1..=samples {
+        let x = uniform.next(rand);
+        let (y, c) = (width * f(x), g(x));
+        let delta_y = y - mean_y;
+        let delta_c = c - mean_c;
+        mean_y += delta_y / n as f64;
+        mean_c += delta_c / n as f64;
+        m2_y += delta_y * (y - mean_y);
+        m2_c += delta_c * (c - mean_c);
+        co += delta_y * (c - mean_c);
+    }
+    let coefficient = if m2_c > 0f64 { -co / m2_c } else { 0f64 };
+    let correlation = if m2_y > 0f64 && m2_c > 0f64 { co / (m2_y * m2_c).sqrt() } else { 0f64 };
+    // Varianza de una observación, sin ajustar y ajustada
+    let (variance, adjusted) = if samples > 1 {
+        let variance = m2_y / (samples - 1) as f64;
+        (variance, variance * (1f64 - correlation * correlation))
+    } else {
+        (0f64, 0f64)
+    };
+    let n = samples.max(1) as f64;
+    ControlVariateEstimate {
+        estimate: mean_y + coefficient * (mean_c - control_mean),
+        std_error: (adjusted / n).sqrt(),
+        crude_estimate: mean_y,
+        crude_std_error: (variance / n).sqrt(),
+        coefficient,
+        correlation,
+        variance_reduction: if variance > 0f64 { 1f64 - adjusted / variance } else { 0f64 },
+        samples,
+    }
+}
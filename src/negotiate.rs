@@ -0,0 +1,117 @@
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use std::convert::Infallible;
+
+/// Formato de serialización de las respuestas, según el header Accept
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    MessagePack,
+    Cbor,
+}
+
+impl Format {
+    /// Content-Type correspondiente al formato
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::MessagePack => "application/msgpack",
+            Format::Cbor => "application/cbor",
+        }
+    }
+
+    /// Elige el formato a partir del valor del header Accept, tomando el
+    /// primer tipo soportado. Si no hay ninguno se usa Json
+    ///
+    /// # Argumentos
+    ///
+    /// * `accept` valor del header Accept
+    pub fn from_accept(accept: &str) -> Self {
+        accept
+            .split(',')
+            .map(|t| t.split(';').next().unwrap_or("").trim())
+            .find_map(|t| match t {
+                "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                    Some(Format::MessagePack)
+                }
+                "application/cbor" => Some(Format::Cbor),
+                "application/json" => Some(Format::Json),
+                _ => None,
+            })
+            .unwrap_or(Format::Json)
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Format {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .map_or(Format::Json, Format::from_accept))
+    }
+}
+
+/// Respuesta serializada en el formato pedido por el cliente. Como depende
+/// del header Accept, se indica en Vary para que los caches no devuelvan un
+/// formato por otro
+pub struct Negotiated<T>(pub Format, pub T);
+
+impl<T: Serialize> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        let Negotiated(format, value) = self;
+        let body = match format {
+            Format::Json => serde_json::to_vec(&value).map_err(|e| e.to_string()),
+            Format::MessagePack => rmp_serde::to_vec_named(&value).map_err(|e| e.to_string()),
+            Format::Cbor => {
+                let mut buf = Vec::new();
+                ciborium::into_writer(&value, &mut buf)
+                    .map(|_| buf)
+                    .map_err(|e| e.to_string())
+            }
+        };
+        match body {
+            Ok(body) => (
+                [
+                    (header::CONTENT_TYPE, HeaderValue::from_static(format.content_type())),
+                    (header::VARY, HeaderValue::from_static("Accept")),
+                ],
+                body,
+            )
+                .into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_accept() {
+        assert!(Format::from_accept("application/json") == Format::Json);
+        assert!(Format::from_accept("application/x-msgpack") == Format::MessagePack);
+        assert!(Format::from_accept("application/vnd.msgpack") == Format::MessagePack);
+        // Se ignoran los parámetros y los espacios, y gana el primer tipo soportado
+        let accept = "text/html, application/cbor;q=0.9, application/json";
+        assert!(Format::from_accept(accept) == Format::Cbor);
+        // Sin tipos soportados, o con cualquiera, se responde en Json
+        assert!(Format::from_accept("text/html, */*") == Format::Json);
+        assert!(Format::from_accept("") == Format::Json);
+    }
+
+    #[test]
+    fn test_vary() {
+        let response = Negotiated(Format::Cbor, vec![1u8]).into_response();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/cbor");
+        assert_eq!(response.headers()[header::VARY], "Accept");
+    }
+}
@@ -0,0 +1,98 @@
+use plotters::prelude::*;
+use simrng::stats::HistogramData;
+
+/// Ancho del gráfico, en píxeles
+const WIDTH: u32 = 800;
+/// Alto del gráfico, en píxeles
+const HEIGHT: u32 = 500;
+
+/// Dibuja el histograma como SVG, con la curva de frecuencias esperadas
+/// superpuesta si se indica
+///
+/// # Argumentos
+///
+/// * `histogram` datos del histograma, como los devuelve full_statistics()
+/// * `expected` frecuencias esperadas por intervalo, según la distribución
+pub fn histogram_svg(
+    histogram: &HistogramData,
+    expected: Option<&[f64]>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&WHITE)?;
+
+        // Con intervalos por cuantiles, de anchos variables, las barras y la
+        // curva muestran la frecuencia dividida por el ancho del intervalo,
+        // para que el área sea proporcional a la frecuencia. Los intervalos
+        // sin ancho, entre valores repetidos, no se dibujan
+        let density = |i: usize, f: f64| match &histogram.widths {
+            Some(widths) if widths[i] > 0f64 => f / widths[i],
+            Some(_) => 0f64,
+            None => f,
+        };
+        let observed: Vec<f64> =
+            histogram.y.iter().enumerate().map(|(i, &fo)| density(i, fo as f64)).collect();
+        let expected: Option<Vec<f64>> =
+            expected.map(|e| e.iter().enumerate().map(|(i, &fe)| density(i, fe)).collect());
+
+        // Altura máxima entre frecuencias observadas y esperadas, con margen
+        let max_observed = observed.iter().copied().fold(0f64, f64::max);
+        let max_expected = expected
+            .as_ref()
+            .map(|e| e.iter().copied().fold(0f64, f64::max))
+            .unwrap_or(0f64);
+        let top = (max_observed.max(max_expected) * 1.1).max(1f64);
+        // Evitar un rango vacío si todos los valores son iguales
+        let upper = if histogram.upper > histogram.lower {
+            histogram.upper
+        } else {
+            histogram.lower + 1f64
+        };
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Histograma de frecuencias", ("sans-serif", 24))
+            .margin(15)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(histogram.lower..upper, 0f64..top)?;
+        chart
+            .configure_mesh()
+            .x_desc("x")
+            .y_desc(if histogram.widths.is_some() { "Frecuencia / ancho" } else { "Frecuencia" })
+            .draw()?;
+
+        // Una barra por intervalo, con la frecuencia observada
+        chart.draw_series(observed.iter().enumerate().map(|(i, &fo)| {
+            let (x0, x1) = match &histogram.widths {
+                Some(widths) => {
+                    let half = widths[i] / 2f64;
+                    (histogram.x[i] - half, histogram.x[i] + half)
+                }
+                None => {
+                    let x0 = histogram.lower + i as f64 * histogram.size;
+                    (x0, x0 + histogram.size)
+                }
+            };
+            Rectangle::new([(x0, 0f64), (x1, fo)], BLUE.mix(0.5).filled())
+        }))?;
+
+        // Curva de frecuencias esperadas, sobre las marcas de clase
+        if let Some(expected) = &expected {
+            chart
+                .draw_series(LineSeries::new(
+                    histogram.x.iter().copied().zip(expected.iter().copied()),
+                    RED.stroke_width(2),
+                ))?
+                .label("Frecuencia esperada")
+                .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], RED));
+            chart
+                .configure_series_labels()
+                .background_style(WHITE.mix(0.8))
+                .border_style(BLACK)
+                .draw()?;
+        }
+        root.present()?;
+    }
+    Ok(svg)
+}
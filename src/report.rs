@@ -0,0 +1,203 @@
+use serde::Serialize;
+use simrng::stats::descriptive::Summary;
+use simrng::stats::StatisticsResponse;
+use std::fmt::Write;
+
+use crate::history::Metadata;
+use crate::storage::Reproducibility;
+
+/// Estilos del informe, incluidos en el mismo archivo para que se vea igual
+/// sin conexión
+const STYLE: &str = "body{font-family:sans-serif;max-width:860px;margin:2em auto;color:#222}\
+table{border-collapse:collapse;margin:1em 0}\
+th,td{border:1px solid #ccc;padding:4px 8px;text-align:right}\
+th{background:#f4f4f4}\
+td.text,th.text{text-align:left}\
+.reject{color:#b00020}.accept{color:#1b5e20}";
+
+/// Datos de un conjunto de datos con los que se arma su informe
+pub struct Report<'a> {
+    /// Etiqueta, descripción y etiquetas del conjunto de datos
+    pub metadata: &'a Metadata,
+    /// Manifiesto de la generación, None en las muestras importadas
+    pub manifest: Option<&'a Reproducibility>,
+    /// Estadísticos descriptivos de los valores
+    pub summary: &'a Summary,
+    /// Histograma y pruebas de los valores
+    pub statistics: &'a StatisticsResponse,
+    /// Histograma como imagen SVG (ver `plot::histogram_svg`)
+    pub histogram: &'a str,
+}
+
+/// Arma el informe de un conjunto de datos como una página HTML
+/// autocontenida, sin scripts ni recursos externos: parámetros de la
+/// generación, estadísticos descriptivos, histograma, tabla de la prueba de
+/// chi cuadrado y conclusiones de las pruebas
+///
+/// # Argumentos
+///
+/// * `report` datos del conjunto de datos
+pub fn report_html(report: &Report) -> String {
+    let title = report.metadata.label.as_deref().unwrap_or("Conjunto de datos");
+    let mut res = String::new();
+    // Escribir en un String no falla
+    let _ = write!(
+        res,
+        "<!DOCTYPE html>\n<html lang=\"es\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        escape(title),
+        STYLE,
+        escape(title)
+    );
+    if let Some(description) = &report.metadata.description {
+        let _ = writeln!(res, "<p>{}</p>", escape(description));
+    }
+    if !report.metadata.tags.is_empty() {
+        let tags: Vec<String> = report.metadata.tags.iter().map(|tag| escape(tag)).collect();
+        let _ = writeln!(res, "<p>Etiquetas: {}</p>", tags.join(", "));
+    }
+    parameters(&mut res, report.manifest);
+    summary(&mut res, report.summary);
+    let _ = writeln!(res, "<h2>Histograma</h2>\n{}", report.histogram);
+    tests(&mut res, report.statistics);
+    res.push_str("</body>\n</html>\n");
+    res
+}
+
+// Función privada, sección con los parámetros de la generación
+fn parameters(res: &mut String, manifest: Option<&Reproducibility>) {
+    res.push_str("<h2>Parámetros</h2>\n");
+    let Some(manifest) = manifest else {
+        res.push_str("<p>Muestra importada o derivada, sin parámetros de generación.</p>\n");
+        return;
+    };
+    let name = manifest.distribution.name();
+    // Los parámetros de la distribución, sin el nombre del tipo
+    let data = serde_json::to_value(&manifest.distribution)
+        .ok()
+        .and_then(|value| value.get(name).cloned())
+        .map_or_else(|| json(&manifest.distribution), |value| value.to_string());
+    let mut rows = vec![
+        ("Distribución", name.to_string()),
+        ("Parámetros", data),
+        ("Cantidad de valores", manifest.number.to_string()),
+        ("Semilla", manifest.seed.to_string()),
+        ("Generador", json(&manifest.generator)),
+        ("Modo", json(&manifest.mode)),
+        ("Descarte inicial", manifest.discard.to_string()),
+        ("Precisión", json(&manifest.precision)),
+    ];
+    if let Some(decimals) = manifest.decimals {
+        rows.push(("Decimales", decimals.to_string()));
+    }
+    if let Some(bounds) = &manifest.bounds {
+        rows.push(("Límites", json(bounds)));
+    }
+    rows.push(("Versión de simrng", manifest.version.clone()));
+    res.push_str("<table>\n");
+    for (name, value) in rows {
+        let _ = writeln!(
+            res,
+            "<tr><th class=\"text\">{}</th><td class=\"text\">{}</td></tr>",
+            name,
+            escape(&value)
+        );
+    }
+    res.push_str("</table>\n");
+}
+
+// Función privada, sección con los estadísticos descriptivos
+fn summary(res: &mut String, summary: &Summary) {
+    res.push_str("<h2>Estadísticos descriptivos</h2>\n<table>\n");
+    for (name, value) in [
+        ("Media", summary.mean),
+        ("Varianza", summary.variance),
+        ("Desviación estándar", summary.variance.sqrt()),
+        ("Mínimo", summary.min),
+        ("Máximo", summary.max),
+    ] {
+        let _ = writeln!(res, "<tr><th class=\"text\">{}</th><td>{:.6}</td></tr>", name, value);
+    }
+    let _ = writeln!(res, "<tr><th class=\"text\">n</th><td>{}</td></tr>", summary.count);
+    res.push_str("</table>\n");
+}
+
+// Función privada, secciones con la prueba de chi cuadrado y las de
+// independencia, con sus conclusiones
+fn tests(res: &mut String, statistics: &StatisticsResponse) {
+    let test = &statistics.test;
+    let _ = write!(res, "<h2>Prueba de chi cuadrado</h2>\n{}", test.to_html());
+    verdict(res, test.reject, &test.conclusion);
+    res.push_str("<h2>Pruebas de independencia</h2>\n");
+    match &statistics.autocorrelation {
+        Some(ljung_box) => {
+            let reject = ljung_box.calculated > ljung_box.critical;
+            let _ = writeln!(
+                res,
+                "<p>Ljung-Box, {} retardos: Q = {:.4}, crítico = {:.4}.</p>",
+                ljung_box.lags.len(),
+                ljung_box.calculated,
+                ljung_box.critical
+            );
+            verdict(res, reject, independence(reject));
+        }
+        None => res.push_str("<p>Ljung-Box: la muestra es muy chica.</p>\n"),
+    }
+    match &statistics.runs {
+        Some(runs) => {
+            let reject = runs.calculated > runs.critical;
+            let _ = writeln!(
+                res,
+                "<p>Rachas por encima y por debajo de la mediana: {} rachas, {:.2} esperadas, \
+                 z = {:.4}; largos de las rachas: χ² = {:.4}, crítico = {:.4}.</p>",
+                runs.runs,
+                runs.expected_runs,
+                runs.z,
+                runs.calculated,
+                runs.critical
+            );
+            verdict(res, reject, independence(reject));
+        }
+        None => res.push_str("<p>Rachas: no alcanzan para la prueba.</p>\n"),
+    }
+}
+
+// Función privada, conclusión de una prueba, resaltada si se rechaza
+fn verdict(res: &mut String, reject: bool, text: &str) {
+    let class = if reject { "reject" } else { "accept" };
+    let _ = writeln!(res, "<p class=\"{}\"><strong>{}</strong></p>", class, escape(text));
+}
+
+// Función privada, conclusión de una prueba de independencia
+fn independence(reject: bool) -> &'static str {
+    if reject {
+        "Se rechaza la hipótesis de que los valores son independientes."
+    } else {
+        "No se rechaza la hipótesis de que los valores son independientes."
+    }
+}
+
+// Función privada, valor en JSON, sin comillas si es un texto
+fn json(value: &impl Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(text)) => text,
+        Ok(value) => value.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+// Función privada, escapa los caracteres especiales de HTML de un texto
+fn escape(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            '\'' => res.push_str("&#39;"),
+            c => res.push(c),
+        }
+    }
+    res
+}
@@ -1,6 +1,21 @@
-use rand::rngs::SmallRng;
-use rand::Rng;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use crate::math;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
 use rand::prelude::Distribution;
+use rand_xoshiro::Xoshiro256PlusPlus;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Cantidad de valores a partir de la cual la generación se divide en bloques,
+/// cada uno con su propio subflujo del generador
+pub const PARALLEL_THRESHOLD: usize = 1 << 20;
+
+/// Cantidad de valores de cada bloque de la generación por bloques
+pub const PARALLEL_CHUNK: usize = 1 << 16;
 
 /// Interfaz de generador de números aleatorios
 pub trait Random {
@@ -8,7 +23,17 @@ pub trait Random {
     fn next(&mut self) -> f64;
 }
 
+/// Módulo del generador congruencial de `with_seed`
+pub const LCG_M: u64 = 4294967296;
+
+/// Multiplicador del generador congruencial de `with_seed`
+pub const LCG_A: u64 = 1 + (4 * 712300);
+
+/// Incremento del generador congruencial de `with_seed`
+pub const LCG_C: u64 = 1013904223;
+
 /// Generador congruencial lineal, implementa interfaz Random
+#[derive(Clone)]
 pub struct LinearCongruentialGenerator {
     /// Semilla del generador, x0
     x0: u64,
@@ -25,21 +50,272 @@ impl LinearCongruentialGenerator {
     pub fn with_seed(x0: u64) -> Self {
         Self {
             x0,
-            m: 4294967296,
-            a: 1 + (4 * 712300),
-            c: 1013904223,
+            m: LCG_M,
+            a: LCG_A,
+            c: LCG_C,
         }
     }
 
-    /// Constructor completo
+    /// Constructor completo. Los parámetros no se validan; `diagnose` indica
+    /// si producen una secuencia degenerada
     pub fn new(x0: u64, m: u64, a: u64, c: u64) -> Self {
         Self { x0, m, a, c }
     }
+
+    /// Si los parámetros cumplen el teorema de Hull-Dobell, por lo que el
+    /// período es m para cualquier semilla: c y m coprimos, a - 1 divisible
+    /// por todos los factores primos de m, y por 4 si m lo es
+    pub fn full_period(&self) -> bool {
+        if self.m == 0 || self.c == 0 || gcd(self.c, self.m) != 1 {
+            return false;
+        }
+        let a1 = self.a.wrapping_sub(1) % self.m;
+        // Se quitan de m los factores que comparte con a - 1; queda 1 sólo
+        // si todos sus factores primos dividen a a - 1
+        let mut rest = self.m;
+        loop {
+            let d = gcd(rest, a1);
+            if d <= 1 {
+                break;
+            }
+            while rest.is_multiple_of(d) {
+                rest /= d;
+            }
+        }
+        rest == 1 && (!self.m.is_multiple_of(4) || a1.is_multiple_of(4))
+    }
+
+    /// Longitud del ciclo de la secuencia a partir de la semilla actual y
+    /// cantidad de valores previos al ciclo, que no se repiten, como
+    /// (ciclo, previos). None si no se encuentra en `limit` pasos; si la
+    /// suma de ambos es n, se encuentra en a lo sumo 4n pasos
+    ///
+    /// # Argumentos
+    ///
+    /// * `limit` cantidad máxima de pasos a recorrer
+    pub fn period(&self, limit: u64) -> Option<(u64, u64)> {
+        if self.m == 0 {
+            return None;
+        }
+        if self.full_period() {
+            return Some((self.m, 0));
+        }
+        // Algoritmo de Brent: la longitud del ciclo, y luego los previos
+        let mut power = 1u64;
+        let mut length = 1u64;
+        let mut tortoise = self.x0 % self.m;
+        let mut hare = self.step(tortoise);
+        let mut steps = 1u64;
+        while tortoise != hare {
+            if steps >= limit {
+                return None;
+            }
+            if power == length {
+                tortoise = hare;
+                power *= 2;
+                length = 0;
+            }
+            hare = self.step(hare);
+            length += 1;
+            steps += 1;
+        }
+        let mut tortoise = self.x0 % self.m;
+        let mut hare = tortoise;
+        for _ in 0..length {
+            hare = self.step(hare);
+        }
+        let mut tail = 0u64;
+        while tortoise != hare {
+            tortoise = self.step(tortoise);
+            hare = self.step(hare);
+            tail += 1;
+        }
+        Some((length, tail))
+    }
+
+    /// Problemas de los parámetros que producen una secuencia degenerada al
+    /// generar `requested` valores. Vacío si no hay ninguno
+    ///
+    /// # Argumentos
+    ///
+    /// * `requested` cantidad de valores a generar
+    pub fn diagnose(&self, requested: u64) -> Vec<SeedWarning> {
+        let mut warnings = Vec::new();
+        if self.m == 0 {
+            return warnings;
+        }
+        if self.c == 0 && self.x0.is_multiple_of(self.m) {
+            warnings.push(SeedWarning::ZeroSeed);
+        }
+        let divisor = gcd(self.c, self.m);
+        if self.c != 0 && divisor != 1 {
+            warnings.push(SeedWarning::NotCoprime { gcd: divisor });
+        }
+        // Se repite si se piden más valores que los distintos de la
+        // secuencia; la semilla no es un valor generado, por lo que si está
+        // en el ciclo hay tantos como su longitud
+        if let Some((length, tail)) = self.period(requested.saturating_add(1).saturating_mul(4)) {
+            let distinct = if tail == 0 { length } else { tail + length - 1 };
+            if requested > distinct {
+                warnings.push(SeedWarning::ExceedsPeriod { period: length, requested });
+            }
+        }
+        warnings
+    }
+
+    /// Avanza la secuencia `n` valores sin generarlos, en O(log n) pasos:
+    /// aplicar n veces x -> ax + c es x -> Ax + C, con A = aⁿ mod m y
+    /// C = c(aⁿ⁻¹ + ... + a + 1) mod m, que se componen por duplicación
+    ///
+    /// # Argumentos
+    ///
+    /// * `n` cantidad de valores a descartar
+    pub fn advance(&mut self, mut n: u64) {
+        if self.m == 0 {
+            return;
+        }
+        let m = self.m as u128;
+        // Transformación acumulada (mul, add) y la de 2^k pasos (a, c)
+        let (mut mul, mut add) = (1u128, 0u128);
+        let (mut a, mut c) = (self.a as u128 % m, self.c as u128 % m);
+        while n > 0 {
+            if n & 1 == 1 {
+                mul = mul * a % m;
+                add = (add * a + c) % m;
+            }
+            c = (c * a + c) % m;
+            a = a * a % m;
+            n >>= 1;
+        }
+        self.x0 = ((mul * self.x0 as u128 + add) % m) as u64;
+    }
+
+    /// Cantidad de planos paralelos que ocupan las ternas sucesivas de los
+    /// primeros `count` valores generados, vistas como puntos del cubo
+    /// unitario. Si h₁ + h₂a + h₃a² ≡ 0 (mod m), todas las ternas de estados
+    /// cumplen h·(xᵢ, xᵢ₊₁, xᵢ₊₂) ≡ c(h₂ + h₃(a + 1)) (mod m), por lo que
+    /// caen sobre planos de normal h, a lo sumo |h₁| + |h₂| + |h₃|. Se busca
+    /// el h de menor suma, hasta `PLANE_SEARCH`, y se cuentan los planos con
+    /// al menos una terna. Por ejemplo, RANDU (a = 65539, m = 2³¹) tiene sus
+    /// ternas en 15 planos de normal (9, -6, 1). None si no hay un h con esa
+    /// suma o se piden menos de 3 valores. Como la secuencia se repite, se
+    /// usan a lo sumo m + 2 valores
+    ///
+    /// # Argumentos
+    ///
+    /// * `count` cantidad de valores a generar desde el estado actual
+    pub fn planes(&self, count: u64) -> Option<LatticePlanes> {
+        if self.m == 0 || count < 3 {
+            return None;
+        }
+        let m = self.m as i128;
+        let a = self.a as i128 % m;
+        let a2 = a * a % m;
+        let bound = PLANE_SEARCH as i128;
+        // Normal de menor suma de valores absolutos, con el primer
+        // componente no nulo desde h₃ positivo, para no repetir -h
+        let mut best: Option<([i128; 3], i128)> = None;
+        for h3 in 0..=bound {
+            for h2 in (h3 - bound)..=(bound - h3) {
+                if h3 == 0 && h2 <= 0 {
+                    continue;
+                }
+                // h₁ ≡ -(h₂a + h₃a²) (mod m), el representante más chico
+                let rest = (h2 * a + h3 * a2).rem_euclid(m);
+                let h1 = if 2 * rest > m { m - rest } else { -rest };
+                let sum = h1.abs() + h2.abs() + h3;
+                if sum <= bound && best.is_none_or(|(_, best)| sum < best) {
+                    best = Some(([h1, h2, h3], sum));
+                }
+            }
+        }
+        let (normal, _) = best?;
+        let count = count.min(self.m.saturating_add(2)) as usize;
+        let mut states = Vec::with_capacity(count);
+        let mut x = self.x0;
+        for _ in 0..count {
+            x = self.step(x);
+            states.push(x as i128);
+        }
+        // Cada plano es un valor de ⌊h·X / m⌋
+        let mut levels: Vec<i128> = states
+            .windows(3)
+            .map(|w| (normal[0] * w[0] + normal[1] * w[1] + normal[2] * w[2]).div_euclid(m))
+            .collect();
+        levels.sort_unstable();
+        levels.dedup();
+        let squares: i128 = normal.iter().map(|h| h * h).sum();
+        Some(LatticePlanes {
+            normal: normal.map(|h| h as i64),
+            planes: levels.len() as u64,
+            spacing: 1f64 / math::sqrt(squares as f64),
+            triples: (count - 2) as u64,
+        })
+    }
+
+    // Función privada, siguiente estado de la secuencia, sin desbordar
+    fn step(&self, x: u64) -> u64 {
+        ((self.a as u128 * x as u128 + self.c as u128) % self.m as u128) as u64
+    }
+}
+
+/// Problema de los parámetros de un generador congruencial, que hace que la
+/// secuencia generada sea degenerada
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeedWarning {
+    /// Semilla múltiplo de m con c = 0: todos los valores son 0
+    ZeroSeed,
+    /// c y m no son coprimos: el período es menor a m
+    NotCoprime { gcd: u64 },
+    /// Se piden más valores que los distintos de la secuencia, que se repite
+    ExceedsPeriod { period: u64, requested: u64 },
+}
+
+impl fmt::Display for SeedWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedWarning::ZeroSeed => write!(f, "con semilla 0 y c = 0 todos los valores son 0"),
+            SeedWarning::NotCoprime { gcd } => {
+                write!(f, "c y m tienen el divisor común {}, el período es menor a m", gcd)
+            }
+            SeedWarning::ExceedsPeriod { period, requested } => write!(
+                f,
+                "se piden {} valores, pero la secuencia se repite con período {}",
+                requested, period
+            ),
+        }
+    }
+}
+
+/// Suma máxima de los valores absolutos de la normal de los planos que
+/// busca `LinearCongruentialGenerator::planes`
+pub const PLANE_SEARCH: i64 = 1000;
+
+/// Familia de planos paralelos sobre los que caen las ternas sucesivas de un
+/// generador congruencial (ver `LinearCongruentialGenerator::planes`)
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct LatticePlanes {
+    /// Normal h de los planos, con componentes enteros
+    pub normal: [i64; 3],
+    /// Cantidad de planos con al menos una terna
+    pub planes: u64,
+    /// Distancia entre planos consecutivos en el cubo unitario, 1/‖h‖
+    pub spacing: f64,
+    /// Cantidad de ternas recorridas
+    pub triples: u64,
+}
+
+// Función privada, máximo común divisor por el algoritmo de Euclides
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
 }
 
 impl Random for LinearCongruentialGenerator {
     fn next(&mut self) -> f64 {
-        self.x0 = (self.a * self.x0 + self.c) % self.m;
+        self.x0 = self.step(self.x0);
         self.x0 as f64 / self.m as f64
     }
 }
@@ -50,9 +326,284 @@ impl<T: Rng> Random for T {
     }
 }
 
+/// Generador uniforme en [0, 1) sobre xoshiro256++, el mismo algoritmo que
+/// SmallRng en plataformas de 64 bits, con saltos para obtener subflujos
+/// independientes
+#[derive(Clone)]
 pub struct UniformGenerator {
     pub dist: rand::distributions::Uniform<f64>,
-    pub rng: SmallRng,
+    pub rng: Xoshiro256PlusPlus,
+}
+
+/// Semilla expandida con el algoritmo por defecto de SeedableRng, el que usa
+/// SmallRng, para que una misma semilla genere los mismos valores
+struct SeedBytes([u8; 32]);
+
+impl SeedableRng for SeedBytes {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        SeedBytes(seed)
+    }
+}
+
+impl UniformGenerator {
+    /// Constructor con la semilla del generador, uniforme en [0, 1)
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            dist: rand::distributions::Uniform::new(0.0, 1.0),
+            rng: Xoshiro256PlusPlus::from_seed(SeedBytes::seed_from_u64(seed).0),
+        }
+    }
+
+    /// Avanza el generador 2^128 valores, el comienzo del siguiente subflujo
+    pub fn jump(&mut self) {
+        self.rng.jump();
+    }
+
+    /// Devuelve los primeros `count` subflujos de la semilla: el primero es
+    /// el generador de la semilla y cada uno de los siguientes comienza 2^128
+    /// valores después del anterior, por lo que no se superponen
+    ///
+    /// # Argumentos
+    ///
+    /// * `seed` semilla del generador
+    /// * `count` cantidad de subflujos
+    pub fn substreams(seed: u64, count: usize) -> Vec<UniformGenerator> {
+        let mut rng = UniformGenerator::with_seed(seed);
+        let mut streams = Vec::with_capacity(count);
+        for _ in 0..count {
+            streams.push(rng.clone());
+            rng.jump();
+        }
+        streams
+    }
+}
+
+/// Completa `out` con valores generados por `fill`. Hasta PARALLEL_THRESHOLD
+/// valores se usa un único generador con la semilla; por encima, `out` se
+/// divide en bloques de PARALLEL_CHUNK valores y el bloque i se genera con el
+/// subflujo i. Con el feature `parallel` los bloques se generan en paralelo;
+/// el resultado es el mismo que en secuencia
+///
+/// # Argumentos
+///
+/// * `seed` semilla del generador
+/// * `out` valores a completar
+/// * `fill` completa un bloque con el generador indicado
+pub fn fill_chunked<F>(seed: u64, out: &mut [f64], fill: F)
+where
+    F: Fn(&mut UniformGenerator, &mut [f64]) + Sync,
+{
+    if out.len() <= PARALLEL_THRESHOLD {
+        fill(&mut UniformGenerator::with_seed(seed), out);
+        return;
+    }
+    let streams = UniformGenerator::substreams(seed, out.len().div_ceil(PARALLEL_CHUNK));
+    fill_blocks(&streams, out, &fill);
+}
+
+/// Completa `out` por bloques de PARALLEL_CHUNK valores, el bloque i con una
+/// copia del subflujo i de `streams`. Con el feature `parallel` los bloques se
+/// generan en paralelo
+///
+/// # Argumentos
+///
+/// * `streams` subflujos del generador, al menos uno por bloque
+/// * `out` valores a completar
+/// * `fill` completa un bloque con el generador indicado
+pub fn fill_blocks<F>(streams: &[UniformGenerator], out: &mut [f64], fill: &F)
+where
+    F: Fn(&mut UniformGenerator, &mut [f64]) + Sync,
+{
+    #[cfg(feature = "parallel")]
+    out.par_chunks_mut(PARALLEL_CHUNK)
+        .zip(streams)
+        .for_each(|(chunk, rng)| fill(&mut rng.clone(), chunk));
+    #[cfg(not(feature = "parallel"))]
+    out.chunks_mut(PARALLEL_CHUNK)
+        .zip(streams)
+        .for_each(|(chunk, rng)| fill(&mut rng.clone(), chunk));
+}
+
+/// Generador que devuelve los números de otro y los guarda, para obtener el
+/// flujo uniforme crudo que usa una distribución, o para repetirlo después
+/// con un `Replayer`. El generador puede ser propio o prestado, como
+/// `&mut dyn Random`
+pub struct Recorder<R> {
+    /// Generador del que se toman los números
+    rng: R,
+    /// Números devueltos, en orden
+    values: Vec<f64>,
+}
+
+impl<R: Random> Recorder<R> {
+    /// Constructor a partir del generador a registrar
+    pub fn new(rng: R) -> Self {
+        Self { rng, values: Vec::new() }
+    }
+
+    /// Números generados hasta el momento, en orden
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Devuelve los números generados hasta el momento, en orden
+    pub fn into_values(self) -> Vec<f64> {
+        self.values
+    }
+
+    /// Devuelve un generador que repite los números generados hasta el momento
+    pub fn into_replayer(self) -> Replayer {
+        Replayer::new(self.values)
+    }
+}
+
+impl<R: Random> Random for Recorder<R> {
+    fn next(&mut self) -> f64 {
+        let x = self.rng.next();
+        self.values.push(x);
+        x
+    }
+}
+
+impl Random for &mut (dyn Random + '_) {
+    fn next(&mut self) -> f64 {
+        (**self).next()
+    }
+}
+
+/// Generador que repite, en orden, números uniformes registrados con un
+/// `Recorder`, para probar el código de estadísticas y las distribuciones
+/// contra flujos fijos. A diferencia de `SequenceGenerator` no vuelve a
+/// empezar: pedir más números de los registrados es un error del que llama
+pub struct Replayer {
+    /// Números a repetir
+    values: Vec<f64>,
+    /// Posición del próximo número a devolver
+    position: usize,
+}
+
+impl Replayer {
+    /// Constructor a partir de los números a repetir, en orden
+    ///
+    /// # Argumentos
+    ///
+    /// * `values` números registrados
+    pub fn new(values: Vec<f64>) -> Self {
+        Self { values, position: 0 }
+    }
+
+    /// Cantidad de números que quedan por repetir
+    pub fn remaining(&self) -> usize {
+        self.values.len() - self.position
+    }
+}
+
+impl Random for Replayer {
+    /// # Panics
+    ///
+    /// Si ya se devolvieron todos los números registrados
+    fn next(&mut self) -> f64 {
+        let Some(&x) = self.values.get(self.position) else {
+            panic!("se repitieron los {} números registrados", self.values.len());
+        };
+        self.position += 1;
+        x
+    }
+}
+
+/// Generador que devuelve una secuencia de números uniformes dada, en orden,
+/// por ejemplo de una tabla, para verificar ejercicios resueltos a mano. Si
+/// se piden más números de los que tiene vuelve a empezar, y `exhausted`
+/// indica que los valores generados ya no corresponden a la secuencia
+pub struct SequenceGenerator {
+    /// Números a devolver, en [0, 1)
+    values: Vec<f64>,
+    /// Cantidad de números pedidos
+    used: usize,
+}
+
+impl SequenceGenerator {
+    /// Constructor a partir de la secuencia, que no puede estar vacía y
+    /// debe tener sus valores en [0, 1)
+    ///
+    /// # Argumentos
+    ///
+    /// * `values` números a devolver, en orden
+    pub fn new(values: Vec<f64>) -> Result<Self, String> {
+        if values.is_empty() {
+            return Err("la secuencia de números uniformes está vacía".into());
+        }
+        if let Some(i) = values.iter().position(|x| !(0f64..1f64).contains(x)) {
+            return Err(format!(
+                "el número {} de la secuencia, {}, no está en [0, 1)",
+                i + 1,
+                values[i]
+            ));
+        }
+        Ok(Self { values, used: 0 })
+    }
+
+    /// Cantidad de números pedidos hasta el momento
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Si se pidieron más números de los que tiene la secuencia
+    pub fn exhausted(&self) -> bool {
+        self.used > self.values.len()
+    }
+
+    /// Devuelve los números de la secuencia
+    pub fn into_values(self) -> Vec<f64> {
+        self.values
+    }
+}
+
+impl Random for SequenceGenerator {
+    fn next(&mut self) -> f64 {
+        let x = self.values[self.used % self.values.len()];
+        self.used += 1;
+        x
+    }
+}
+
+/// Como `fill_chunked`, pero devuelve además los números uniformes que usó
+/// cada bloque, en el orden de los bloques. Los valores de `out` son los
+/// mismos que los de `fill_chunked`
+///
+/// # Argumentos
+///
+/// * `seed` semilla del generador
+/// * `out` valores a completar
+/// * `fill` completa un bloque con el generador indicado
+pub fn fill_chunked_recorded<F>(seed: u64, out: &mut [f64], fill: F) -> Vec<f64>
+where
+    F: Fn(&mut dyn Random, &mut [f64]) + Sync,
+{
+    let record = |rng: &UniformGenerator, chunk: &mut [f64]| {
+        let mut recorder = Recorder::new(rng.clone());
+        fill(&mut recorder, chunk);
+        recorder.into_values()
+    };
+    if out.len() <= PARALLEL_THRESHOLD {
+        return record(&UniformGenerator::with_seed(seed), out);
+    }
+    let streams = UniformGenerator::substreams(seed, out.len().div_ceil(PARALLEL_CHUNK));
+    #[cfg(feature = "parallel")]
+    let blocks: Vec<Vec<f64>> = out
+        .par_chunks_mut(PARALLEL_CHUNK)
+        .zip(&streams)
+        .map(|(chunk, rng)| record(rng, chunk))
+        .collect();
+    #[cfg(not(feature = "parallel"))]
+    let blocks: Vec<Vec<f64>> = out
+        .chunks_mut(PARALLEL_CHUNK)
+        .zip(&streams)
+        .map(|(chunk, rng)| record(rng, chunk))
+        .collect();
+    blocks.concat()
 }
 
 impl Random for UniformGenerator {
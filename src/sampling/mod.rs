@@ -0,0 +1,213 @@
+use serde::Serialize;
+
+use crate::dist::Distribution;
+use crate::rng::Random;
+use crate::stats::descriptive::summary;
+
+/// Muestra de hipercubo latino en [0, 1)^dims: cada dimensión se divide en n
+/// estratos de igual probabilidad y cada estrato recibe exactamente un punto.
+/// Devuelve n puntos de dims coordenadas
+///
+/// # Argumentos
+///
+/// * `n` cantidad de puntos
+/// * `dims` cantidad de dimensiones
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn latin_hypercube(n: usize, dims: usize, rand: &mut dyn Random) -> Vec<Vec<f64>> {
+    let mut points = vec![Vec::with_capacity(dims); n];
+    let mut strata: Vec<usize> = (0..n).collect();
+    for _ in 0..dims {
+        shuffle(&mut strata, rand);
+        for (point, &stratum) in points.iter_mut().zip(&strata) {
+            point.push((stratum as f64 + rand.next()) / n as f64);
+        }
+    }
+    points
+}
+
+/// Permutación aleatoria, en el lugar, de los valores, por Fisher-Yates
+///
+/// # Argumentos
+///
+/// * `values` valores a permutar
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn shuffle<T>(values: &mut [T], rand: &mut dyn Random) {
+    for i in (1..values.len()).rev() {
+        let j = ((rand.next() * (i + 1) as f64) as usize).min(i);
+        values.swap(i, j);
+    }
+}
+
+/// Muestra aleatoria simple en [0, 1)^dims, para comparar con el hipercubo
+/// latino
+///
+/// # Argumentos
+///
+/// * `n` cantidad de puntos
+/// * `dims` cantidad de dimensiones
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn simple(n: usize, dims: usize, rand: &mut dyn Random) -> Vec<Vec<f64>> {
+    (0..n).map(|_| (0..dims).map(|_| rand.next()).collect()).collect()
+}
+
+/// Transforma, en el lugar, los puntos en [0, 1)^dims en valores de las
+/// distribuciones, aplicando el cuantil de cada una a su coordenada
+///
+/// # Argumentos
+///
+/// * `points` puntos a transformar
+/// * `dists` distribución de cada dimensión
+pub fn map_quantiles(points: &mut [Vec<f64>], dists: &[&dyn Distribution]) {
+    for point in points.iter_mut() {
+        for (x, dist) in point.iter_mut().zip(dists) {
+            *x = dist.quantile(*x);
+        }
+    }
+}
+
+/// Comparación, para una dimensión, de la varianza del estimador de la media
+/// con hipercubo latino y con muestreo aleatorio simple
+#[derive(Serialize, Clone, Debug)]
+pub struct VarianceComparison {
+    /// Media de las estimaciones con hipercubo latino
+    pub lhs_mean: f64,
+    /// Varianza de las estimaciones con hipercubo latino
+    pub lhs_variance: f64,
+    /// Media de las estimaciones con muestreo aleatorio simple
+    pub simple_mean: f64,
+    /// Varianza de las estimaciones con muestreo aleatorio simple
+    pub simple_variance: f64,
+    /// Reducción relativa de la varianza, 1 - lhs/simple
+    pub reduction: f64,
+}
+
+/// Estima la media de cada distribución con n puntos, repitiendo la
+/// estimación con hipercubo latino y con muestreo aleatorio simple, y
+/// compara la varianza de las estimaciones
+///
+/// # Argumentos
+///
+/// * `n` cantidad de puntos de cada muestra
+/// * `dists` distribución de cada dimensión
+/// * `replications` cantidad de estimaciones con cada método
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn compare(
+    n: usize,
+    dists: &[&dyn Distribution],
+    replications: usize,
+    rand: &mut dyn Random,
+) -> Vec<VarianceComparison> {
+    let dims = dists.len();
+    let mut lhs = vec![Vec::with_capacity(replications); dims];
+    let mut plain = vec![Vec::with_capacity(replications); dims];
+    for _ in 0..replications {
+        for (sampler, estimates) in [
+            (latin_hypercube as fn(usize, usize, &mut dyn Random) -> Vec<Vec<f64>>, &mut lhs),
+            (simple, &mut plain),
+        ] {
+            let mut points = sampler(n, dims, rand);
+            map_quantiles(&mut points, dists);
+            for (d, estimate) in estimates.iter_mut().enumerate() {
+                estimate.push(points.iter().map(|p| p[d]).sum::<f64>() / n as f64);
+            }
+        }
+    }
+    lhs.iter()
+        .zip(&plain)
+        .map(|(lhs, plain)| {
+            let (lhs, plain) = (summary(lhs), summary(plain));
+            VarianceComparison {
+                lhs_mean: lhs.mean,
+                lhs_variance: lhs.variance,
+                simple_mean: plain.mean,
+                simple_variance: plain.variance,
+                reduction: if plain.variance > 0f64 {
+                    1f64 - lhs.variance / plain.variance
+                } else {
+                    0f64
+                },
+            }
+        })
+        .collect()
+}
+
+/// Estrato del muestreo estratificado, con los valores de la distribución
+/// que le corresponden y la cantidad de valores generados en él
+#[derive(Serialize, Clone, Debug)]
+pub struct Stratum {
+    /// Cuantil del límite inferior del estrato
+    pub lower: f64,
+    /// Cuantil del límite superior del estrato
+    pub upper: f64,
+    /// Cantidad de valores generados en el estrato
+    pub count: usize,
+}
+
+/// Asignación proporcional de n valores a k estratos de igual probabilidad:
+/// cada estrato recibe n/k valores, y el resto se reparte de a uno entre los
+/// primeros
+///
+/// # Argumentos
+///
+/// * `n` cantidad de valores
+/// * `k` cantidad de estratos, mayor a 0
+pub fn allocation(n: usize, k: usize) -> Vec<usize> {
+    (0..k).map(|j| n / k + usize::from(j < n % k)).collect()
+}
+
+/// Estratos de la distribución, con la asignación proporcional de n valores
+///
+/// # Argumentos
+///
+/// * `n` cantidad de valores
+/// * `k` cantidad de estratos, mayor a 0
+/// * `dist` distribución de los valores
+pub fn strata(n: usize, k: usize, dist: &dyn Distribution) -> Vec<Stratum> {
+    allocation(n, k)
+        .into_iter()
+        .enumerate()
+        .map(|(j, count)| Stratum {
+            lower: dist.quantile(j as f64 / k as f64),
+            upper: dist.quantile((j + 1) as f64 / k as f64),
+            count,
+        })
+        .collect()
+}
+
+/// Genera n valores de la distribución por muestreo estratificado: el
+/// intervalo [0, 1) se divide en k estratos de igual probabilidad, cada
+/// estrato recibe su parte proporcional de uniformes y cada uniforme se
+/// transforma con el cuantil de la distribución. Los valores se devuelven
+/// en orden aleatorio, para que no queden agrupados por estrato
+///
+/// # Argumentos
+///
+/// * `n` cantidad de valores
+/// * `k` cantidad de estratos, mayor a 0
+/// * `dist` distribución de los valores
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn stratified(n: usize, k: usize, dist: &dyn Distribution, rand: &mut dyn Random) -> Vec<f64> {
+    let mut res = Vec::with_capacity(n);
+    for (j, count) in allocation(n, k).into_iter().enumerate() {
+        for _ in 0..count {
+            res.push(dist.quantile((j as f64 + rand.next()) / k as f64));
+        }
+    }
+    shuffle(&mut res, rand);
+    res
+}
+
+/// Remuestreo bootstrap: elige n valores de la muestra, con reposición y
+/// con igual probabilidad
+///
+/// # Argumentos
+///
+/// * `values` muestra original, no vacía
+/// * `n` cantidad de valores a elegir
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn bootstrap(values: &[f64], n: usize, rand: &mut dyn Random) -> Vec<f64> {
+    let len = values.len();
+    (0..n)
+        .map(|_| values[((rand.next() * len as f64) as usize).min(len - 1)])
+        .collect()
+}
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+
+use crate::dist::exponential::Exponential;
+use crate::dist::poisson::Poisson;
+use crate::dist::uniform::Uniform;
+use crate::rng::Random;
+use crate::sim::ConfidenceInterval;
+
+/// Distribución del tiempo de entrega de los pedidos, en períodos. Los
+/// valores generados se redondean al período más cercano
+#[derive(Deserialize, Clone)]
+pub enum LeadTime {
+    /// Tiempo de entrega constante
+    Fixed(u32),
+    Uniform(Uniform),
+    Exponential(Exponential),
+    Poisson(Poisson),
+}
+
+impl LeadTime {
+    /// Devuelve el tiempo de entrega de un pedido, en períodos
+    ///
+    /// # Argumentos
+    ///
+    /// * `rand` el generador de números aleatorios a utilizar, implementa Random
+    pub fn next(&self, rand: &mut dyn Random) -> usize {
+        let periods = match self {
+            LeadTime::Fixed(periods) => return *periods as usize,
+            LeadTime::Uniform(dist) => dist.next(rand),
+            LeadTime::Exponential(dist) => dist.next(rand),
+            LeadTime::Poisson(dist) => dist.next(rand),
+        };
+        periods.round().max(0f64) as usize
+    }
+}
+
+/// Parámetros de un sistema de inventario con revisión periódica y política
+/// (s, S): al final de cada período, si la posición del inventario es menor
+/// a s se pide lo necesario para llegar a S. La demanda no satisfecha queda
+/// pendiente hasta que se reciba un pedido
+#[derive(Deserialize, Clone)]
+pub struct InventoryParameters {
+    /// Punto de reorden, s
+    pub reorder_point: i64,
+    /// Nivel máximo, S
+    pub order_up_to: i64,
+    /// Inventario al comenzar, S si no se indica
+    pub initial_inventory: Option<i64>,
+    /// Demanda media por período, con distribución Poisson
+    pub demand_rate: f64,
+    /// Distribución del tiempo de entrega
+    pub lead_time: LeadTime,
+    /// Costo fijo de cada pedido
+    pub ordering_cost: f64,
+    /// Costo de cada unidad pedida
+    #[serde(default)]
+    pub unit_cost: f64,
+    /// Costo de mantener una unidad en inventario durante un período
+    pub holding_cost: f64,
+    /// Costo de cada unidad faltante durante un período
+    pub shortage_cost: f64,
+    /// Cantidad de períodos a simular en cada replicación
+    pub periods: u64,
+    /// Cantidad de replicaciones independientes
+    pub replications: usize,
+}
+
+/// Medidas de una replicación, promedio por período
+#[derive(Serialize, Clone, Debug)]
+pub struct InventoryMetrics {
+    /// Costo de los pedidos, fijo más unidades
+    pub ordering_cost: f64,
+    /// Costo de mantenimiento del inventario
+    pub holding_cost: f64,
+    /// Costo por faltantes
+    pub shortage_cost: f64,
+    /// Costo total
+    pub total_cost: f64,
+    /// Nivel de servicio: proporción de la demanda satisfecha con el
+    /// inventario disponible
+    pub fill_rate: f64,
+    /// Proporción de los períodos con demanda no satisfecha
+    pub stockout_rate: f64,
+}
+
+/// Resultado de la simulación, con intervalos de confianza del 95% sobre las
+/// replicaciones
+#[derive(Serialize, Clone, Debug)]
+pub struct InventoryReport {
+    pub ordering_cost: ConfidenceInterval,
+    pub holding_cost: ConfidenceInterval,
+    pub shortage_cost: ConfidenceInterval,
+    pub total_cost: ConfidenceInterval,
+    pub fill_rate: ConfidenceInterval,
+    pub stockout_rate: ConfidenceInterval,
+}
+
+/// Simula una replicación del sistema de inventario
+///
+/// # Argumentos
+///
+/// * `params` parámetros del sistema
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn replicate(params: &InventoryParameters, rand: &mut dyn Random) -> InventoryMetrics {
+    let demand = Poisson { lambda: params.demand_rate };
+    // Inventario neto, negativo si hay demanda pendiente
+    let mut net = params.initial_inventory.unwrap_or(params.order_up_to);
+    // Pedidos en camino: (período de llegada, cantidad)
+    let mut pending: Vec<(u64, i64)> = Vec::new();
+    let (mut ordering, mut holding, mut shortage) = (0f64, 0f64, 0f64);
+    let (mut demanded, mut served, mut stockouts) = (0f64, 0f64, 0u64);
+    for period in 0..params.periods {
+        // Recibir los pedidos que llegan en este período
+        pending.retain(|&(arrival, quantity)| {
+            if arrival <= period {
+                net += quantity;
+                false
+            } else {
+                true
+            }
+        });
+        // Atender la demanda con el inventario disponible
+        let d = demand.next(rand) as i64;
+        let available = net.max(0);
+        demanded += d as f64;
+        served += d.min(available) as f64;
+        if d > available {
+            stockouts += 1;
+        }
+        net -= d;
+        // Revisar la posición del inventario y pedir hasta S si está por debajo de s
+        let position = net + pending.iter().map(|&(_, q)| q).sum::<i64>();
+        if position < params.reorder_point {
+            let quantity = params.order_up_to - position;
+            ordering += params.ordering_cost + params.unit_cost * quantity as f64;
+            let lead = params.lead_time.next(rand) as u64;
+            if lead == 0 {
+                net += quantity;
+            } else {
+                pending.push((period + lead, quantity));
+            }
+        }
+        holding += params.holding_cost * net.max(0) as f64;
+        shortage += params.shortage_cost * (-net).max(0) as f64;
+    }
+    let periods = params.periods.max(1) as f64;
+    InventoryMetrics {
+        ordering_cost: ordering / periods,
+        holding_cost: holding / periods,
+        shortage_cost: shortage / periods,
+        total_cost: (ordering + holding + shortage) / periods,
+        fill_rate: if demanded > 0f64 { served / demanded } else { 1f64 },
+        stockout_rate: stockouts as f64 / periods,
+    }
+}
+
+/// Simula las replicaciones del sistema de inventario y resume sus medidas
+///
+/// # Argumentos
+///
+/// * `params` parámetros del sistema
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn simulate(params: &InventoryParameters, rand: &mut dyn Random) -> InventoryReport {
+    let runs: Vec<InventoryMetrics> =
+        (0..params.replications).map(|_| replicate(params, rand)).collect();
+    let interval = |f: fn(&InventoryMetrics) -> f64| {
+        ConfidenceInterval::from_replications(&runs.iter().map(f).collect::<Vec<f64>>())
+    };
+    InventoryReport {
+        ordering_cost: interval(|m| m.ordering_cost),
+        holding_cost: interval(|m| m.holding_cost),
+        shortage_cost: interval(|m| m.shortage_cost),
+        total_cost: interval(|m| m.total_cost),
+        fill_rate: interval(|m| m.fill_rate),
+        stockout_rate: interval(|m| m.stockout_rate),
+    }
+}
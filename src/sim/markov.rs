@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rng::Random;
+use crate::stats::{chi_squared_critical_value, DEFAULT_SIGNIFICANCE};
+
+/// Parámetros de la simulación de una cadena de Markov de tiempo discreto
+#[derive(Deserialize, Clone)]
+pub struct MarkovParameters {
+    /// Matriz de transición, cada fila suma 1
+    pub transitions: Vec<Vec<f64>>,
+    /// Estado inicial, índice de una fila de la matriz
+    pub initial: usize,
+    /// Cantidad de pasos a simular
+    pub steps: u64,
+    /// Índice del nivel de significancia en la tabla de chi cuadrado, si no
+    /// se indica se usa `DEFAULT_SIGNIFICANCE`
+    pub significance: Option<usize>,
+}
+
+/// Ocupación de un estado de la cadena
+#[derive(Serialize, Clone, Debug)]
+pub struct StateOccupancy {
+    pub state: usize,
+    /// Cantidad de pasos en los que la cadena estuvo en el estado
+    pub visits: u64,
+    /// Proporción de los pasos en el estado
+    pub observed: f64,
+    /// Probabilidad del estado en la distribución estacionaria
+    pub stationary: Option<f64>,
+}
+
+/// Prueba de chi cuadrado de la ocupación observada contra la distribución
+/// estacionaria. Los pasos de la cadena no son independientes, por lo que
+/// el resultado es orientativo
+#[derive(Serialize, Clone, Debug)]
+pub struct OccupancyTest {
+    /// chi cuadrado calculado
+    pub calculated: f64,
+    /// valor crítico, chi cuadrado tabulado
+    pub critical: f64,
+    /// grados de libertad
+    pub degrees: usize,
+}
+
+/// Resultado de la simulación de la cadena
+#[derive(Serialize, Clone, Debug)]
+pub struct MarkovReport {
+    pub states: Vec<StateOccupancy>,
+    /// Estado de la cadena al terminar la simulación
+    pub final_state: usize,
+    /// Prueba de chi cuadrado, si la distribución estacionaria es única y
+    /// los grados de libertad están tabulados
+    pub test: Option<OccupancyTest>,
+}
+
+/// Verifica que la matriz sea cuadrada, sin valores negativos y con filas
+/// que sumen 1
+///
+/// # Argumentos
+///
+/// * `transitions` matriz de transición
+pub fn validate(transitions: &[Vec<f64>]) -> Result<(), String> {
+    let n = transitions.len();
+    if n == 0 {
+        return Err("la matriz de transición está vacía".into());
+    }
+    for (i, row) in transitions.iter().enumerate() {
+        if row.len() != n {
+            return Err(format!("la fila {} no tiene {} columnas", i, n));
+        }
+        if row.iter().any(|&p| !(0f64..=1f64).contains(&p)) {
+            return Err(format!("la fila {} tiene probabilidades fuera de [0, 1]", i));
+        }
+        if (row.iter().sum::<f64>() - 1f64).abs() > 1e-9 {
+            return Err(format!("la fila {} no suma 1", i));
+        }
+    }
+    Ok(())
+}
+
+/// Devuelve el siguiente estado de la cadena, por transformada inversa
+/// sobre la fila del estado actual
+///
+/// # Argumentos
+///
+/// * `transitions` matriz de transición
+/// * `state` estado actual
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn step(transitions: &[Vec<f64>], state: usize, rand: &mut dyn Random) -> usize {
+    let row = &transitions[state];
+    let u = rand.next();
+    let mut acc = 0f64;
+    for (next, p) in row.iter().enumerate() {
+        acc += p;
+        if u < acc {
+            return next;
+        }
+    }
+    // Por redondeo la fila puede sumar apenas menos de 1
+    row.iter().rposition(|&p| p > 0f64).unwrap_or(state)
+}
+
+/// Calcula la distribución estacionaria π, con πP = π y Σπ = 1, por
+/// eliminación de Gauss. Devuelve None si no es única
+///
+/// # Argumentos
+///
+/// * `transitions` matriz de transición
+pub fn stationary(transitions: &[Vec<f64>]) -> Option<Vec<f64>> {
+    let n = transitions.len();
+    // Sistema (Pᵀ - I)π = 0, reemplazando la última ecuación por Σπ = 1
+    let mut a: Vec<Vec<f64>> = (0..n)
+        .map(|i| {
+            let mut row: Vec<f64> = (0..n)
+                .map(|j| transitions[j][i] - if i == j { 1f64 } else { 0f64 })
+                .collect();
+            row.push(0f64);
+            row
+        })
+        .collect();
+    a[n - 1] = vec![1f64; n + 1];
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&x, &y| a[x][col].abs().total_cmp(&a[y][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        let pivot_row = a[col].clone();
+        for (i, row) in a.iter_mut().enumerate() {
+            if i != col {
+                let factor = row[col] / pivot_row[col];
+                for (x, p) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                    *x -= factor * p;
+                }
+            }
+        }
+    }
+    Some((0..n).map(|i| (a[i][n] / a[i][i]).max(0f64)).collect())
+}
+
+/// Simula la cadena durante los pasos indicados y compara la ocupación de
+/// cada estado con la distribución estacionaria. La matriz debe ser válida
+/// (ver `validate`)
+///
+/// # Argumentos
+///
+/// * `params` parámetros de la simulación
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn simulate(params: &MarkovParameters, rand: &mut dyn Random) -> MarkovReport {
+    let n = params.transitions.len();
+    let mut visits = vec![0u64; n];
+    let mut state = params.initial;
+    for _ in 0..params.steps {
+        state = step(&params.transitions, state, rand);
+        visits[state] += 1;
+    }
+    let pi = stationary(&params.transitions);
+    let steps = params.steps.max(1) as f64;
+    let states = visits
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| StateOccupancy {
+            state: i,
+            visits: v,
+            observed: v as f64 / steps,
+            stationary: pi.as_ref().map(|pi| pi[i]),
+        })
+        .collect();
+    let significance = params.significance.unwrap_or(DEFAULT_SIGNIFICANCE);
+    let test = pi.and_then(|pi| {
+        // Los estados con probabilidad estacionaria nula no aportan al estadístico
+        let (calculated, positive) = visits.iter().zip(&pi).filter(|(_, &p)| p > 1e-12).fold(
+            (0f64, 0usize),
+            |(c, k), (&fo, &p)| {
+                let fe = p * steps;
+                (c + (fo as f64 - fe).powi(2) / fe, k + 1)
+            },
+        );
+        let degrees = positive.checked_sub(1)?;
+        ((1..=100).contains(&degrees) && (1..=10).contains(&significance)).then(|| {
+            OccupancyTest {
+                calculated,
+                critical: chi_squared_critical_value(degrees, significance),
+                degrees,
+            }
+        })
+    });
+    MarkovReport { states, final_state: state, test }
+}
@@ -0,0 +1,50 @@
+use serde::Serialize;
+
+use crate::stats::descriptive::summary;
+
+pub mod inventory;
+pub mod markov;
+pub mod queue;
+
+/// Valores críticos de la distribución t de Student para un intervalo de
+/// confianza del 95%, por grados de libertad (1 a 30)
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+
+/// Intervalo de confianza del 95% para la media de una medida, calculado
+/// sobre replicaciones independientes de una simulación
+#[derive(Serialize, Clone, Debug)]
+pub struct ConfidenceInterval {
+    /// Media de las replicaciones
+    pub mean: f64,
+    /// Límite inferior del intervalo
+    pub lower: f64,
+    /// Límite superior del intervalo
+    pub upper: f64,
+}
+
+impl ConfidenceInterval {
+    /// Calcula el intervalo con la distribución t de Student, o la Normal
+    /// si hay más de 30 grados de libertad
+    ///
+    /// # Argumentos
+    ///
+    /// * `values` valor de la medida en cada replicación
+    pub fn from_replications(values: &[f64]) -> Self {
+        let summary = summary(values);
+        let half_width = if values.len() < 2 {
+            0f64
+        } else {
+            let t = T_95.get(values.len() - 2).copied().unwrap_or(1.96);
+            t * (summary.variance / values.len() as f64).sqrt()
+        };
+        ConfidenceInterval {
+            mean: summary.mean,
+            lower: summary.mean - half_width,
+            upper: summary.mean + half_width,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+use crate::dist::exponential::Exponential;
+use crate::rng::Random;
+use crate::sim::ConfidenceInterval;
+
+/// Parámetros de una cola M/M/c: llegadas y servicios con distribución
+/// Exponencial, c servidores y disciplina FIFO
+#[derive(Deserialize, Clone)]
+pub struct QueueParameters {
+    /// Tasa de llegadas, λ
+    pub arrival_rate: f64,
+    /// Tasa de servicio de cada servidor, μ
+    pub service_rate: f64,
+    /// Cantidad de servidores, c (1 para M/M/1)
+    pub servers: usize,
+    /// Cantidad de clientes a simular en cada replicación
+    pub customers: u64,
+    /// Cantidad de replicaciones independientes
+    pub replications: usize,
+}
+
+/// Medidas de desempeño de la cola
+#[derive(Serialize, Clone, Debug)]
+pub struct QueueMetrics {
+    /// Tiempo medio de espera en la cola, Wq
+    pub wait: f64,
+    /// Tiempo medio en el sistema, W
+    pub system_time: f64,
+    /// Longitud media de la cola, Lq
+    pub queue_length: f64,
+    /// Utilización media de los servidores, ρ
+    pub utilization: f64,
+}
+
+/// Resultado de la simulación, con intervalos de confianza del 95% sobre las
+/// replicaciones y los valores teóricos si la cola es estable
+#[derive(Serialize, Clone, Debug)]
+pub struct QueueReport {
+    pub wait: ConfidenceInterval,
+    pub system_time: ConfidenceInterval,
+    pub queue_length: ConfidenceInterval,
+    pub utilization: ConfidenceInterval,
+    /// Valores teóricos de la cola M/M/c, si ρ < 1
+    pub theoretical: Option<QueueMetrics>,
+}
+
+/// Simula una replicación de la cola, asignando cada cliente al servidor
+/// que se libera primero
+///
+/// # Argumentos
+///
+/// * `params` parámetros de la cola
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn replicate(params: &QueueParameters, rand: &mut dyn Random) -> QueueMetrics {
+    let arrivals = Exponential { lambda: params.arrival_rate, shift: 0f64 };
+    let services = Exponential { lambda: params.service_rate, shift: 0f64 };
+    // Momento en que se libera cada servidor
+    let mut free_at = vec![0f64; params.servers.max(1)];
+    let mut clock = 0f64;
+    let mut total_wait = 0f64;
+    let mut total_service = 0f64;
+    let mut end = 0f64;
+    for _ in 0..params.customers {
+        clock += arrivals.next(rand);
+        let (server, &free) = free_at
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap();
+        let start = clock.max(free);
+        let service = services.next(rand);
+        total_wait += start - clock;
+        total_service += service;
+        free_at[server] = start + service;
+        end = end.max(start + service);
+    }
+    let customers = params.customers.max(1) as f64;
+    let wait = total_wait / customers;
+    QueueMetrics {
+        wait,
+        system_time: wait + total_service / customers,
+        // La integral de la longitud de la cola en el tiempo es la suma de
+        // las esperas de los clientes
+        queue_length: if end > 0f64 { total_wait / end } else { 0f64 },
+        utilization: if end > 0f64 {
+            total_service / (end * free_at.len() as f64)
+        } else {
+            0f64
+        },
+    }
+}
+
+/// Simula las replicaciones de la cola y resume sus medidas
+///
+/// # Argumentos
+///
+/// * `params` parámetros de la cola
+/// * `rand` el generador de números aleatorios a utilizar, implementa Random
+pub fn simulate(params: &QueueParameters, rand: &mut dyn Random) -> QueueReport {
+    let runs: Vec<QueueMetrics> = (0..params.replications).map(|_| replicate(params, rand)).collect();
+    let interval = |f: fn(&QueueMetrics) -> f64| {
+        ConfidenceInterval::from_replications(&runs.iter().map(f).collect::<Vec<f64>>())
+    };
+    QueueReport {
+        wait: interval(|m| m.wait),
+        system_time: interval(|m| m.system_time),
+        queue_length: interval(|m| m.queue_length),
+        utilization: interval(|m| m.utilization),
+        theoretical: theoretical(params),
+    }
+}
+
+/// Medidas teóricas de la cola M/M/c en estado estacionario, con la fórmula
+/// C de Erlang. Devuelve None si la cola no es estable (ρ >= 1)
+///
+/// # Argumentos
+///
+/// * `params` parámetros de la cola
+pub fn theoretical(params: &QueueParameters) -> Option<QueueMetrics> {
+    let c = params.servers;
+    let a = params.arrival_rate / params.service_rate;
+    let rho = a / c as f64;
+    if c == 0 || !(0f64..1f64).contains(&rho) {
+        return None;
+    }
+    // Términos a^k/k!, calculados en forma incremental
+    let mut term = 1f64;
+    let mut sum = 0f64;
+    for k in 0..c {
+        sum += term;
+        term *= a / (k + 1) as f64;
+    }
+    let last = term / (1f64 - rho);
+    let erlang_c = last / (sum + last);
+    let queue_length = erlang_c * rho / (1f64 - rho);
+    let wait = queue_length / params.arrival_rate;
+    Some(QueueMetrics {
+        wait,
+        system_time: wait + 1f64 / params.service_rate,
+        queue_length,
+        utilization: rho,
+    })
+}
@@ -0,0 +1,97 @@
+use serde::Serialize;
+
+use crate::chunked::{self, SampleChunks};
+use crate::float::Float;
+use crate::stats::chi_squared_critical_value;
+
+/// Cantidad máxima de retardos a considerar en la prueba
+pub const MAX_LAGS: usize = 10;
+
+/// Resultado de la prueba de Ljung-Box, que verifica si los valores de la
+/// muestra son independientes entre sí
+#[derive(Serialize, Clone, Debug)]
+pub struct AutocorrelationTest {
+    /// Autocorrelación de la muestra para los retardos 1, 2, ...
+    pub lags: Vec<f64>,
+    /// Estadístico Q calculado
+    pub calculated: f64,
+    /// valor crítico, chi cuadrado tabulado con tantos grados de libertad
+    /// como retardos
+    pub critical: f64,
+}
+
+/// Autocorrelación muestral de los valores para el retardo indicado
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra, en el orden en que fueron generados
+/// * `lag` retardo, mayor a 0
+pub fn autocorrelation<T: Float, S: SampleChunks<T> + ?Sized>(nums: &S, lag: usize) -> f64 {
+    if lag == 0 {
+        return 1f64;
+    }
+    let (mean, variance) = moments(nums);
+    lagged(nums, lag, mean, variance)[lag - 1]
+}
+
+// Función privada, media y suma de cuadrados de las diferencias a la media
+fn moments<T: Float, S: SampleChunks<T> + ?Sized>(nums: &S) -> (f64, f64) {
+    let mean = chunked::values(nums).map(|x| x.to_f64()).sum::<f64>() / nums.len() as f64;
+    let variance: f64 = chunked::values(nums).map(|x| (x.to_f64() - mean).powi(2)).sum();
+    (mean, variance)
+}
+
+// Función privada, autocorrelación para los retardos 1 a max_lag con la
+// media y la suma de cuadrados ya calculadas. Recorre la muestra una sola
+// vez, guardando los últimos max_lag valores, por lo que puede leerse por
+// bloques
+fn lagged<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    max_lag: usize,
+    mean: f64,
+    variance: f64,
+) -> Vec<f64> {
+    if variance == 0f64 {
+        return vec![0f64; max_lag];
+    }
+    let mut covariance = vec![0f64; max_lag];
+    // Últimos valores centrados, el más reciente en la posición t % max_lag
+    let mut previous = vec![0f64; max_lag];
+    for (t, x) in chunked::values(nums).enumerate() {
+        let x = x.to_f64() - mean;
+        for (k, cov) in covariance.iter_mut().enumerate().take(t.min(max_lag)) {
+            *cov += previous[(t + max_lag - k - 1) % max_lag] * x;
+        }
+        previous[t % max_lag] = x;
+    }
+    covariance.iter().map(|c| c / variance).collect()
+}
+
+/// Prueba de Ljung-Box, Q = n(n+2)·Σ rₖ²/(n-k), con hasta MAX_LAGS retardos
+/// (menos si la muestra es chica). Devuelve None si la muestra no alcanza
+/// para un retardo
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra, en el orden en que fueron generados
+/// * `significance` índice del nivel de significancia en la tabla de chi cuadrado
+pub fn ljung_box<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    significance: usize,
+) -> Option<AutocorrelationTest> {
+    let n = nums.len();
+    let max_lags = MAX_LAGS.min(n / 4);
+    if max_lags == 0 {
+        return None;
+    }
+    let (mean, variance) = moments(nums);
+    let lags = lagged(nums, max_lags, mean, variance);
+    let sum: f64 = lags
+        .iter()
+        .enumerate()
+        .map(|(i, r)| r * r / (n - i - 1) as f64)
+        .sum();
+    let calculated = n as f64 * (n as f64 + 2f64) * sum;
+    let critical = chi_squared_critical_value(max_lags, significance);
+    Some(AutocorrelationTest { lags, calculated, critical })
+}
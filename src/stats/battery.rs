@@ -0,0 +1,441 @@
+use serde::Serialize;
+
+use crate::chunked::{self, SampleChunks};
+use crate::float::Float;
+use crate::math;
+use crate::stats::ks::{self, UniformityTest};
+
+/// Cantidad máxima de dígitos de la prueba de frecuencia
+pub const MAX_DIGITS: u32 = 6;
+
+/// Frecuencia esperada mínima por categoría para aplicar una prueba de chi
+/// cuadrado
+pub const MIN_EXPECTED: f64 = 5f64;
+
+/// Cantidad máxima de celdas de la prueba de colisiones
+pub const MAX_CELLS: u64 = 1 << 24;
+
+/// Cantidad mínima de celdas de la prueba de colisiones
+pub const MIN_CELLS: u64 = 1 << 7;
+
+/// Cantidad de celdas por cada valor ubicado en la prueba de colisiones: con
+/// pocos valores por celda la prueba detecta estados demasiado chicos
+pub const CELLS_PER_BALL: u64 = 64;
+
+/// Largo máximo de los grupos de la prueba de permutaciones
+pub const MAX_PERMUTATION: usize = 6;
+
+/// Cantidad máxima de intervalos de la estimación de la entropía
+pub const MAX_ENTROPY_BINS: u64 = 64;
+
+/// Opciones de la batería de pruebas del flujo uniforme
+#[derive(Clone, Copy, Debug)]
+pub struct BatteryOptions {
+    /// Nivel de significancia de todas las pruebas, por ejemplo 0.05
+    pub alpha: f64,
+    /// Cantidad de dígitos decimales de la prueba de frecuencia, entre 1 y
+    /// `MAX_DIGITS`
+    pub digits: u32,
+    /// Cantidad de valores consecutivos que forman cada punto de la prueba
+    /// de colisiones, mayor a 0
+    pub collision_dimension: u32,
+    /// Cantidad de divisiones de [0, 1) en cada coordenada de la prueba de
+    /// colisiones, mayor a 1
+    pub collision_divisions: u64,
+    /// Cantidad de valores consecutivos de cada grupo de la prueba de
+    /// permutaciones, entre 2 y `MAX_PERMUTATION`
+    pub permutation_length: usize,
+    /// Cantidad de intervalos de [0, 1) de la estimación de la entropía,
+    /// entre 2 y `MAX_ENTROPY_BINS`
+    pub entropy_bins: u64,
+}
+
+impl Default for BatteryOptions {
+    /// Significancia 0.05, un dígito, colisiones de pares en 2²⁰ celdas,
+    /// permutaciones de ternas y entropía con 16 intervalos
+    fn default() -> Self {
+        BatteryOptions {
+            alpha: 0.05,
+            digits: 1,
+            collision_dimension: 2,
+            collision_divisions: 1 << 10,
+            permutation_length: 3,
+            entropy_bins: 16,
+        }
+    }
+}
+
+/// Resultado de una prueba de chi cuadrado de la batería
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ChiSquaredTest {
+    /// Estadístico Σ(fo - fe)²/fe
+    pub statistic: f64,
+    /// Grados de libertad
+    pub degrees: usize,
+    /// Valor p, P(χ² >= estadístico)
+    pub p_value: f64,
+    /// Si se rechaza la hipótesis de que el flujo es uniforme e independiente
+    pub reject: bool,
+}
+
+/// Prueba de frecuencia (equidistribución) de los primeros dígitos
+/// decimales: con k dígitos, cada número ⌊u·10ᵏ⌋ entre 0 y 10ᵏ - 1 debe
+/// aparecer con la misma frecuencia
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DigitFrequency {
+    /// Cantidad de dígitos analizados
+    pub digits: u32,
+    /// Frecuencia observada de cada número de k dígitos, sólo si son 100 o
+    /// menos
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub observed: Option<Vec<u64>>,
+    /// Frecuencia esperada de cada número, n/10ᵏ
+    pub expected: f64,
+    /// Prueba de chi cuadrado, con 10ᵏ - 1 grados de libertad
+    pub test: ChiSquaredTest,
+}
+
+/// Prueba de colisiones de Knuth: los puntos formados por valores
+/// consecutivos, sin solaparse, se ubican en celdas, y se cuentan los que
+/// caen en una celda ya ocupada. Un generador con un estado demasiado chico
+/// repite puntos y produce colisiones de más; uno que evita repetirlos, de
+/// menos
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CollisionTest {
+    /// Cantidad de valores consecutivos de cada punto
+    pub dimension: u32,
+    /// Cantidad de divisiones de cada coordenada
+    pub divisions: u64,
+    /// Cantidad de celdas, divisionesᵈⁱᵐᵉⁿˢⁱᵒⁿ
+    pub cells: u64,
+    /// Cantidad de puntos ubicados, a lo sumo una cada `CELLS_PER_BALL` celdas
+    pub points: u64,
+    /// Colisiones observadas
+    pub observed: u64,
+    /// Colisiones esperadas, n - m + m(1 - 1/m)ⁿ con n puntos y m celdas
+    pub expected: f64,
+    /// Valor p bilateral, con la distribución exacta de las colisiones
+    pub p_value: f64,
+    /// Si se rechaza la hipótesis de que el flujo es uniforme e independiente
+    pub reject: bool,
+}
+
+/// Prueba de permutaciones: en cada grupo de k valores consecutivos, sin
+/// solaparse, los k! órdenes relativos posibles deben ser igualmente
+/// probables
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PermutationTest {
+    /// Cantidad de valores de cada grupo, k
+    pub length: usize,
+    /// Cantidad de grupos
+    pub groups: u64,
+    /// Frecuencia observada de cada orden, sólo si son 100 o menos
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub observed: Option<Vec<u64>>,
+    /// Frecuencia esperada de cada orden, grupos/k!
+    pub expected: f64,
+    /// Prueba de chi cuadrado, con k! - 1 grados de libertad
+    pub test: ChiSquaredTest,
+}
+
+/// Estimación de la entropía del flujo, con cada número reemplazado por el
+/// intervalo de [0, 1) en el que cae. No es una prueba, sino un indicador:
+/// un flujo uniforme e independiente tiene la entropía máxima
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Entropy {
+    /// Cantidad de intervalos, b
+    pub bins: u64,
+    /// Entropía de Shannon de los intervalos, -Σ p·log₂ p, en bits
+    pub shannon: f64,
+    /// Entropía aproximada con bloques de 2, φ(2) - φ(3), en bits: la
+    /// información que agrega cada intervalo dados los dos anteriores. Baja
+    /// si los valores dependen de los anteriores
+    pub approximate: f64,
+    /// Entropía máxima, log₂ b
+    pub max: f64,
+    /// Indicador entre 0 y 1, la entropía aproximada sobre la máxima
+    pub score: f64,
+}
+
+/// Resultado de la batería de pruebas del flujo uniforme. Las pruebas que
+/// no se pueden aplicar por el tamaño de la muestra son None
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Battery {
+    /// Cantidad de números uniformes
+    pub n: u64,
+    /// Nivel de significancia utilizado
+    pub alpha: f64,
+    /// Prueba de frecuencia de los primeros dígitos, si la frecuencia
+    /// esperada es al menos `MIN_EXPECTED`
+    pub frequency: Option<DigitFrequency>,
+    /// Prueba de Kolmogorov-Smirnov contra la Uniforme en [0, 1), exacta
+    /// con hasta `ks::EXACT_MAX_N` valores (ver `ks::uniformity`), si la
+    /// muestra no está vacía
+    pub kolmogorov_smirnov: Option<UniformityTest>,
+    /// Prueba de colisiones, si alcanzan los valores para dos puntos
+    pub collision: Option<CollisionTest>,
+    /// Prueba de permutaciones, si la frecuencia esperada de cada orden es
+    /// al menos `MIN_EXPECTED`
+    pub permutation: Option<PermutationTest>,
+    /// Estimación de la entropía, si hay al menos b³ números
+    pub entropy: Option<Entropy>,
+}
+
+/// Aplica la batería de pruebas a un flujo de números uniformes en [0, 1),
+/// por ejemplo los números crudos de un generador, en el orden en que
+/// fueron generados. Las pruebas que necesitan ordenar los valores los
+/// copian en memoria. Devuelve un error si las opciones no son válidas
+///
+/// # Argumentos
+///
+/// * `nums` números uniformes, leídos por bloques
+/// * `options` significancia y parámetros de las pruebas
+pub fn battery<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    options: BatteryOptions,
+) -> Result<Battery, String> {
+    if !(options.alpha > 0f64 && options.alpha < 1f64) {
+        return Err("alpha debe estar entre 0 y 1".into());
+    }
+    if !(1..=MAX_DIGITS).contains(&options.digits) {
+        return Err(format!("digits debe estar entre 1 y {}", MAX_DIGITS));
+    }
+    if !(2..=MAX_PERMUTATION).contains(&options.permutation_length) {
+        return Err(format!("permutation_length debe estar entre 2 y {}", MAX_PERMUTATION));
+    }
+    if !(2..=MAX_ENTROPY_BINS).contains(&options.entropy_bins) {
+        return Err(format!("entropy_bins debe estar entre 2 y {}", MAX_ENTROPY_BINS));
+    }
+    let cells = options
+        .collision_divisions
+        .checked_pow(options.collision_dimension)
+        .filter(|cells| (MIN_CELLS..=MAX_CELLS).contains(cells));
+    let Some(cells) = cells else {
+        return Err(format!(
+            "la prueba de colisiones debe tener entre {} y {} celdas",
+            MIN_CELLS, MAX_CELLS
+        ));
+    };
+    Ok(Battery {
+        n: nums.len() as u64,
+        alpha: options.alpha,
+        frequency: digit_frequency(nums, options.digits, options.alpha),
+        kolmogorov_smirnov: (nums.len() > 0).then(|| {
+            let values: Vec<f64> = chunked::values(nums).map(|u| u.to_f64()).collect();
+            ks::uniformity(&values, options.alpha)
+        }),
+        collision: collision(
+            nums,
+            options.collision_dimension,
+            options.collision_divisions,
+            cells,
+            options.alpha,
+        ),
+        permutation: permutation(nums, options.permutation_length, options.alpha),
+        entropy: entropy(nums, options.entropy_bins),
+    })
+}
+
+/// Prueba de frecuencia de los primeros `digits` dígitos decimales. None si
+/// la frecuencia esperada de cada número es menor a `MIN_EXPECTED`
+///
+/// # Argumentos
+///
+/// * `nums` números uniformes en [0, 1)
+/// * `digits` cantidad de dígitos, entre 1 y `MAX_DIGITS`
+/// * `alpha` nivel de significancia
+pub fn digit_frequency<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    digits: u32,
+    alpha: f64,
+) -> Option<DigitFrequency> {
+    let cells = 10usize.pow(digits);
+    let expected = nums.len() as f64 / cells as f64;
+    if expected < MIN_EXPECTED {
+        return None;
+    }
+    let mut observed = vec![0u64; cells];
+    for u in chunked::values(nums) {
+        // Un valor redondeado a 1 al guardarlo en f32 cae en la última
+        let cell = ((u.to_f64() * cells as f64) as usize).min(cells - 1);
+        observed[cell] += 1;
+    }
+    let test = chi_squared_test(&observed, expected, alpha);
+    Some(DigitFrequency {
+        digits,
+        observed: (cells <= 100).then_some(observed),
+        expected,
+        test,
+    })
+}
+
+/// Prueba de permutaciones de grupos de `length` valores. None si la
+/// frecuencia esperada de cada orden es menor a `MIN_EXPECTED`
+///
+/// # Argumentos
+///
+/// * `nums` números uniformes, en el orden en que fueron generados
+/// * `length` cantidad de valores de cada grupo, entre 2 y `MAX_PERMUTATION`
+/// * `alpha` nivel de significancia
+pub fn permutation<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    length: usize,
+    alpha: f64,
+) -> Option<PermutationTest> {
+    let cells: usize = (1..=length).product();
+    let groups = nums.len() / length;
+    let expected = groups as f64 / cells as f64;
+    if expected < MIN_EXPECTED {
+        return None;
+    }
+    let mut observed = vec![0u64; cells];
+    let mut group = vec![0f64; length];
+    let mut values = chunked::values(nums);
+    for _ in 0..groups {
+        group.iter_mut().for_each(|u| *u = values.next().unwrap().to_f64());
+        // Índice del orden, entre 0 y k! - 1 (algoritmo P de Knuth): se
+        // lleva el máximo de los primeros r valores al final
+        let mut index = 0;
+        for r in (2..=length).rev() {
+            let max = (0..r).fold(0, |max, i| if group[i] > group[max] { i } else { max });
+            index = r * index + max;
+            group.swap(r - 1, max);
+        }
+        observed[index] += 1;
+    }
+    let test = chi_squared_test(&observed, expected, alpha);
+    Some(PermutationTest {
+        length,
+        groups: groups as u64,
+        observed: (cells <= 100).then_some(observed),
+        expected,
+        test,
+    })
+}
+
+/// Estimación de la entropía de Shannon y de la entropía aproximada de los
+/// números agrupados en `bins` intervalos. Los bloques de la entropía
+/// aproximada se cuentan en forma circular, como en la prueba del NIST,
+/// para que haya n de cada largo. None si hay menos de b³ números
+///
+/// # Argumentos
+///
+/// * `nums` números uniformes, en el orden en que fueron generados
+/// * `bins` cantidad de intervalos, entre 2 y `MAX_ENTROPY_BINS`
+pub fn entropy<T: Float, S: SampleChunks<T> + ?Sized>(nums: &S, bins: u64) -> Option<Entropy> {
+    let n = nums.len();
+    let b = bins as usize;
+    if n < b * b * b {
+        return None;
+    }
+    let symbol = |u: T| ((u.to_f64() * bins as f64) as usize).min(b - 1);
+    let mut singles = vec![0u64; b];
+    let mut doubles = vec![0u64; b * b];
+    let mut triples = vec![0u64; b * b * b];
+    // La secuencia sigue con sus dos primeros valores para cerrar el círculo
+    let first: Vec<usize> = chunked::values(nums).take(2).map(symbol).collect();
+    let sequence = chunked::values(nums).map(symbol).chain(first);
+    let (mut previous, mut before) = (0, 0);
+    for (j, x) in sequence.enumerate() {
+        if j < n {
+            singles[x] += 1;
+        }
+        if (1..=n).contains(&j) {
+            doubles[previous * b + x] += 1;
+        }
+        if j >= 2 {
+            triples[(before * b + previous) * b + x] += 1;
+        }
+        (before, previous) = (previous, x);
+    }
+    // Σ p·log₂ p de las frecuencias
+    let phi = |counts: &[u64]| -> f64 {
+        counts
+            .iter()
+            .filter(|&&c| c > 0)
+            .map(|&c| c as f64 / n as f64)
+            .map(|p| p * p.log2())
+            .sum()
+    };
+    let shannon = -phi(&singles);
+    let approximate = phi(&doubles) - phi(&triples);
+    let max = (bins as f64).log2();
+    Some(Entropy {
+        bins,
+        shannon,
+        approximate,
+        max,
+        score: (approximate / max).clamp(0f64, 1f64),
+    })
+}
+
+// Función privada, prueba de colisiones con las celdas ya validadas. Se usan
+// a lo sumo cells / CELLS_PER_BALL puntos, y la distribución exacta de las
+// colisiones se calcula agregando un punto a la vez, hasta la cantidad a
+// partir de la cual la probabilidad es despreciable
+fn collision<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    dimension: u32,
+    divisions: u64,
+    cells: u64,
+    alpha: f64,
+) -> Option<CollisionTest> {
+    let points = (nums.len() as u64 / dimension as u64).min(cells / CELLS_PER_BALL);
+    if points < 2 {
+        return None;
+    }
+    let mut occupied = vec![false; cells as usize];
+    let mut observed = 0u64;
+    let mut values = chunked::values(nums);
+    for _ in 0..points {
+        let mut cell = 0u64;
+        for _ in 0..dimension {
+            let u = values.next().unwrap().to_f64();
+            let digit = ((u * divisions as f64) as u64).min(divisions - 1);
+            cell = cell * divisions + digit;
+        }
+        if std::mem::replace(&mut occupied[cell as usize], true) {
+            observed += 1;
+        }
+    }
+    let (n, m) = (points as f64, cells as f64);
+    let expected = n - m + m * (1f64 - 1f64 / m).powf(n);
+    // probabilities[c], la de c colisiones con los puntos ubicados hasta
+    // el momento; el punto j choca con probabilidad (j - c)/m
+    let max = ((expected + 10f64 * expected.sqrt() + 20f64) as u64).min(points) as usize;
+    let mut probabilities = vec![0f64; max + 1];
+    probabilities[0] = 1f64;
+    for j in 1..points as usize {
+        for c in (0..=j.min(max)).rev() {
+            let stay = probabilities[c] * (1f64 - (j - c) as f64 / m);
+            let from = if c > 0 { probabilities[c - 1] * (j + 1 - c) as f64 / m } else { 0f64 };
+            probabilities[c] = stay + from;
+        }
+    }
+    let observed_index = observed as usize;
+    let lower: f64 = probabilities.iter().take(observed_index + 1).sum();
+    let below: f64 = probabilities.iter().take(observed_index).sum();
+    let p_value = (2f64 * lower.min(1f64 - below)).clamp(0f64, 1f64);
+    Some(CollisionTest {
+        dimension,
+        divisions,
+        cells,
+        points,
+        observed,
+        expected,
+        p_value,
+        reject: p_value < alpha,
+    })
+}
+
+// Función privada, prueba de chi cuadrado con la misma frecuencia esperada
+// en todas las categorías
+fn chi_squared_test(observed: &[u64], expected: f64, alpha: f64) -> ChiSquaredTest {
+    let statistic: f64 = observed
+        .iter()
+        .map(|&fo| (fo as f64 - expected).powi(2) / expected)
+        .sum();
+    let degrees = observed.len() - 1;
+    let p_value = math::gamma_q(degrees as f64 / 2f64, statistic / 2f64);
+    ChiSquaredTest { statistic, degrees, p_value, reject: p_value < alpha }
+}
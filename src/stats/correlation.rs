@@ -0,0 +1,135 @@
+use serde::Serialize;
+
+use crate::chunked::{self, SampleChunks};
+use crate::float::Float;
+use crate::math;
+
+/// Prueba de que un coeficiente de correlación es 0
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CorrelationTest {
+    /// Coeficiente de correlación, entre -1 y 1
+    pub coefficient: f64,
+    /// Estadístico t = r·√((n-2)/(1-r²)), con n-2 grados de libertad; infinito
+    /// (null en Json) si |r| = 1
+    pub statistic: f64,
+    /// Valor p bilateral, P(|T| >= |t|) con la t de Student
+    pub p_value: f64,
+    /// Si se rechaza la hipótesis de que las muestras no están correlacionadas
+    pub reject: bool,
+}
+
+/// Correlación entre dos muestras de la misma longitud, emparejadas por
+/// posición
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Correlation {
+    /// Cantidad de pares
+    pub n: u64,
+    /// Correlación lineal de Pearson
+    pub pearson: CorrelationTest,
+    /// Correlación de rangos de Spearman, la de Pearson entre los rangos,
+    /// con el rango promedio para los empates
+    pub spearman: CorrelationTest,
+    /// Nivel de significancia utilizado
+    pub alpha: f64,
+}
+
+/// Calcula las correlaciones de Pearson y de Spearman entre dos muestras,
+/// emparejando los valores por posición, con la prueba de que cada una es
+/// 0. Sirve para verificar la independencia de dos flujos generados con
+/// semillas o subflujos distintos. Los rangos se calculan en memoria.
+/// Devuelve un error si las muestras no tienen la misma longitud o tienen
+/// menos de 3 valores
+///
+/// # Argumentos
+///
+/// * `first` primera muestra
+/// * `second` segunda muestra
+/// * `alpha` nivel de significancia, por ejemplo 0.05
+pub fn correlation<T, U, A, B>(first: &A, second: &B, alpha: f64) -> Result<Correlation, String>
+where
+    T: Float,
+    U: Float,
+    A: SampleChunks<T> + ?Sized,
+    B: SampleChunks<U> + ?Sized,
+{
+    if first.len() != second.len() {
+        return Err(format!(
+            "las muestras deben tener la misma longitud: {} y {} valores",
+            first.len(),
+            second.len()
+        ));
+    }
+    if first.len() < 3 {
+        return Err("se necesitan al menos 3 pares de valores".into());
+    }
+    let n = first.len();
+    let linear = pearson(
+        chunked::values(first).map(|x| x.to_f64()),
+        chunked::values(second).map(|y| y.to_f64()),
+    );
+    let first_ranks = ranks(chunked::values(first).map(|x| x.to_f64()).collect());
+    let second_ranks = ranks(chunked::values(second).map(|y| y.to_f64()).collect());
+    let rank = pearson(first_ranks.into_iter(), second_ranks.into_iter());
+    Ok(Correlation {
+        n: n as u64,
+        pearson: test(linear, n, alpha),
+        spearman: test(rank, n, alpha),
+        alpha,
+    })
+}
+
+// Función privada, coeficiente de Pearson en una sola pasada, con la versión
+// del algoritmo de Welford para la covarianza
+fn pearson(first: impl Iterator<Item = f64>, second: impl Iterator<Item = f64>) -> f64 {
+    let mut count = 0f64;
+    let (mut mean_x, mut mean_y) = (0f64, 0f64);
+    let (mut m2_x, mut m2_y, mut co) = (0f64, 0f64, 0f64);
+    for (x, y) in first.zip(second) {
+        count += 1f64;
+        let dx = x - mean_x;
+        mean_x += dx / count;
+        let dy = y - mean_y;
+        mean_y += dy / count;
+        m2_x += dx * (x - mean_x);
+        m2_y += dy * (y - mean_y);
+        co += dx * (y - mean_y);
+    }
+    if m2_x <= 0f64 || m2_y <= 0f64 {
+        // Una muestra constante no está correlacionada con nada
+        return 0f64;
+    }
+    (co / (m2_x * m2_y).sqrt()).clamp(-1f64, 1f64)
+}
+
+// Función privada, rango de cada valor en la muestra, empezando en 1; los
+// valores iguales reciben el promedio de sus rangos
+fn ranks(values: Vec<f64>) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_unstable_by(|&i, &j| values[i].total_cmp(&values[j]));
+    let mut res = vec![0f64; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        // Rangos start + 1 a end, su promedio
+        let rank = (start + end + 1) as f64 / 2f64;
+        order[start..end].iter().for_each(|&i| res[i] = rank);
+        start = end;
+    }
+    res
+}
+
+// Función privada, prueba t del coeficiente con n - 2 grados de libertad
+fn test(coefficient: f64, n: usize, alpha: f64) -> CorrelationTest {
+    let df = (n - 2) as f64;
+    let (statistic, p_value) = if coefficient.abs() >= 1f64 {
+        (coefficient.signum() * f64::INFINITY, 0f64)
+    } else {
+        let t = coefficient * (df / (1f64 - coefficient * coefficient)).sqrt();
+        // P(|T| >= |t|) = I_{df/(df+t²)}(df/2, 1/2)
+        (t, math::beta_inc(df / 2f64, 0.5, df / (df + t * t)))
+    };
+    CorrelationTest { coefficient, statistic, p_value, reject: p_value < alpha }
+}
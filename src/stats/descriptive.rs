@@ -0,0 +1,283 @@
+use serde::{Deserialize, Serialize};
+
+use crate::chunked::{self, SampleChunks};
+use crate::float::Float;
+
+/// Estadísticos descriptivos de una muestra
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Summary {
+    /// Cantidad de valores
+    pub count: u64,
+    /// Media muestral
+    pub mean: f64,
+    /// Varianza muestral (con n-1)
+    pub variance: f64,
+    /// Valor mínimo
+    pub min: f64,
+    /// Valor máximo
+    pub max: f64,
+}
+
+/// Calcula los estadísticos descriptivos en una sola pasada, usando el
+/// algoritmo de Welford para la varianza. Los valores se leen por bloques
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra
+pub fn summary<T: Float, S: SampleChunks<T> + ?Sized>(nums: &S) -> Summary {
+    let mut count = 0u64;
+    let mut mean = 0f64;
+    let mut m2 = 0f64;
+    let mut min = f64::INFINITY;
+    let mut max = f64::NEG_INFINITY;
+    for x in chunked::values(nums) {
+        let x = x.to_f64();
+        count += 1;
+        let delta = x - mean;
+        mean += delta / count as f64;
+        m2 += delta * (x - mean);
+        min = min.min(x);
+        max = max.max(x);
+    }
+    if count == 0 {
+        return Summary { count, mean: 0f64, variance: 0f64, min: 0f64, max: 0f64 };
+    }
+    let variance = if count > 1 { m2 / (count - 1) as f64 } else { 0f64 };
+    Summary { count, mean, variance, min, max }
+}
+
+/// Calcula los estadísticos descriptivos de una muestra con pesos, por
+/// ejemplo del muestreo por importancia, en una sola pasada con la versión
+/// ponderada del algoritmo de Welford. La media es la media ponderada y la
+/// varianza usa pesos de confiabilidad, Σw(x-m)² / (Σw - Σw²/Σw), que con
+/// pesos iguales es la varianza con n-1. `count`, `min` y `max` son los de
+/// todos los valores
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra
+/// * `weights` peso de cada valor, en el mismo orden (ver `check_weights`)
+pub fn weighted_summary<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    weights: &[f64],
+) -> Summary {
+    let mut count = 0u64;
+    let mut total = 0f64;
+    let mut total_sq = 0f64;
+    let mut mean = 0f64;
+    let mut m2 = 0f64;
+    let mut min = f64::INFINITY;
+    let mut max = f64::NEG_INFINITY;
+    for (x, &w) in chunked::values(nums).zip(weights) {
+        let x = x.to_f64();
+        count += 1;
+        min = min.min(x);
+        max = max.max(x);
+        if w == 0f64 {
+            continue;
+        }
+        total += w;
+        total_sq += w * w;
+        let delta = x - mean;
+        mean += delta * w / total;
+        m2 += w * delta * (x - mean);
+    }
+    if count == 0 {
+        return Summary { count, mean: 0f64, variance: 0f64, min: 0f64, max: 0f64 };
+    }
+    let denominator = total - total_sq / total;
+    let variance = if denominator > 0f64 { m2 / denominator } else { 0f64 };
+    Summary { count, mean, variance, min, max }
+}
+
+/// Primer y tercer cuartil de la muestra, interpolando linealmente entre
+/// los valores ordenados (ver `quantiles`). Con la muestra vacía devuelve
+/// (0, 0)
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra
+pub fn quartiles<T: Float, S: SampleChunks<T> + ?Sized>(nums: &S) -> (f64, f64) {
+    match quantiles(nums, &[0.25, 0.75])[..] {
+        [q1, q3] => (q1, q3),
+        _ => (0f64, 0f64),
+    }
+}
+
+/// Cuantiles empíricos de la muestra en las probabilidades indicadas,
+/// interpolando linealmente entre los valores ordenados: el cuantil p está
+/// en la posición p·(n-1), contando desde 0. Los valores se copian en
+/// memoria para ordenarlos; con la muestra vacía devuelve 0 en todos
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra
+/// * `probabilities` probabilidades de los cuantiles, entre 0 y 1
+pub fn quantiles<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    probabilities: &[f64],
+) -> Vec<f64> {
+    let mut sorted: Vec<f64> = chunked::values(nums).map(|x| x.to_f64()).collect();
+    if sorted.is_empty() {
+        return vec![0f64; probabilities.len()];
+    }
+    sorted.sort_unstable_by(f64::total_cmp);
+    let quantile = |p: f64| {
+        let h = p.clamp(0f64, 1f64) * (sorted.len() - 1) as f64;
+        let i = h.floor() as usize;
+        let next = sorted[(i + 1).min(sorted.len() - 1)];
+        sorted[i] + (h - i as f64) * (next - sorted[i])
+    };
+    probabilities.iter().map(|&p| quantile(p)).collect()
+}
+
+/// Media y varianza de los valores recorridos hasta una posición de la muestra
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RollingPoint {
+    /// Cantidad de valores recorridos, la posición siguiente al último valor usado
+    pub n: u64,
+    /// Media de los valores usados
+    pub mean: f64,
+    /// Varianza muestral (con n-1) de los valores usados
+    pub variance: f64,
+}
+
+/// Media y varianza a lo largo de la secuencia, en `points` posiciones
+/// equiespaciadas. Sin ventana se usan todos los valores recorridos, para
+/// ver la convergencia de la media muestral a medida que crece n; con
+/// ventana, sólo los últimos `window` valores. Se calcula en una sola pasada
+/// con el algoritmo de Welford, quitando los valores que salen de la ventana
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra, en el orden en que fueron generados
+/// * `window` cantidad de valores de la ventana, mayor a 0, None para usar todos
+/// * `points` cantidad de posiciones a devolver
+pub fn rolling<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    window: Option<usize>,
+    points: usize,
+) -> Vec<RollingPoint> {
+    let len = nums.len();
+    let points = points.min(len);
+    let mut res = Vec::with_capacity(points);
+    if points == 0 {
+        return res;
+    }
+    let mut leaving = chunked::values(nums);
+    let mut count = 0u64;
+    let mut mean = 0f64;
+    let mut m2 = 0f64;
+    // Posición del próximo punto a devolver, la k-ésima de points
+    let mut k = 1;
+    let mut next = len.div_ceil(points);
+    for (i, x) in chunked::values(nums).enumerate() {
+        let x = x.to_f64();
+        count += 1;
+        let delta = x - mean;
+        mean += delta / count as f64;
+        m2 += delta * (x - mean);
+        if window.is_some_and(|window| i >= window) {
+            let y = leaving.next().unwrap().to_f64();
+            count -= 1;
+            let delta = y - mean;
+            mean -= delta / count as f64;
+            m2 -= delta * (y - mean);
+        }
+        if i + 1 == next {
+            let variance = if count > 1 { m2.max(0f64) / (count - 1) as f64 } else { 0f64 };
+            res.push(RollingPoint { n: next as u64, mean, variance });
+            k += 1;
+            next = (k * len).div_ceil(points);
+        }
+    }
+    res
+}
+
+/// Media de los primeros n valores de la muestra
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RunningMean {
+    /// Cantidad de valores recorridos
+    pub n: u64,
+    /// Media de los primeros n valores
+    pub mean: f64,
+}
+
+/// Promedio acumulado de la muestra, para ver la ley de los grandes números.
+/// Si la muestra tiene hasta `points` valores se devuelve la media en cada
+/// posición; si no, en `points` posiciones espaciadas geométricamente entre
+/// 1 y la cantidad de valores, para que el gráfico en escala logarítmica
+/// muestre tanto el comienzo, donde la media oscila más, como el final
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra, en el orden en que fueron generados
+/// * `points` cantidad máxima de posiciones a devolver
+pub fn running_means<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    points: usize,
+) -> Vec<RunningMean> {
+    let len = nums.len();
+    let positions: Vec<usize> = if len <= points {
+        (1..=len).collect()
+    } else if points == 1 {
+        vec![len]
+    } else {
+        // n = len^(k/(points-1)), sin repetir las posiciones que redondean igual
+        let mut positions: Vec<usize> = (0..points)
+            .map(|k| (len as f64).powf(k as f64 / (points - 1) as f64).round() as usize)
+            .map(|n| n.clamp(1, len))
+            .collect();
+        positions.dedup();
+        positions
+    };
+    let mut res = Vec::with_capacity(positions.len());
+    let mut next = positions.iter().copied().peekable();
+    let mut mean = 0f64;
+    for (i, x) in chunked::values(nums).enumerate() {
+        let Some(&position) = next.peek() else { break };
+        mean += (x.to_f64() - mean) / (i + 1) as f64;
+        if i + 1 == position {
+            res.push(RunningMean { n: position as u64, mean });
+            next.next();
+        }
+    }
+    res
+}
+
+/// Tamaño efectivo de una muestra con pesos, (Σw)² / Σw², la cantidad de
+/// valores sin pesos que daría la misma precisión. Con pesos iguales es la
+/// cantidad de valores
+///
+/// # Argumentos
+///
+/// * `weights` peso de cada valor
+pub fn effective_size(weights: &[f64]) -> f64 {
+    let total: f64 = weights.iter().sum();
+    let total_sq: f64 = weights.iter().map(|w| w * w).sum();
+    if total_sq > 0f64 { total * total / total_sq } else { 0f64 }
+}
+
+/// Verifica que los pesos de una muestra sean válidos: uno por valor,
+/// finitos, no negativos y con suma mayor a 0
+///
+/// # Argumentos
+///
+/// * `weights` peso de cada valor
+/// * `len` cantidad de valores de la muestra
+pub fn check_weights(weights: &[f64], len: usize) -> Result<(), String> {
+    if weights.len() != len {
+        return Err(format!(
+            "se necesita un peso por valor: {} pesos para {} valores",
+            weights.len(),
+            len
+        ));
+    }
+    if weights.iter().any(|w| !(w.is_finite() && *w >= 0f64)) {
+        return Err("los pesos deben ser números finitos no negativos".into());
+    }
+    if weights.iter().sum::<f64>() <= 0f64 {
+        return Err("la suma de los pesos debe ser mayor a 0".into());
+    }
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use serde::Serialize;
+
+use crate::chunked::{self, SampleChunks};
+use crate::dist::Distribution;
+use crate::float::Float;
+
+/// Cantidad máxima de enteros distintos entre el mínimo y el máximo de la
+/// muestra para los que se calculan las frecuencias
+pub const MAX_VALUES: usize = 100_000;
+
+/// Frecuencia observada y esperada de un valor entero
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct IntegerFrequency {
+    /// Valor entero
+    pub value: i64,
+    /// Cantidad de valores de la muestra iguales a `value`
+    pub observed: u64,
+    /// Frecuencia esperada, n·P(X = value)
+    pub expected: f64,
+}
+
+/// Frecuencias observadas y esperadas de cada valor entero de una muestra de
+/// una distribución discreta, sin agrupar en intervalos
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DiscreteFrequencies {
+    /// Frecuencias de cada entero entre el mínimo y el máximo de la muestra,
+    /// incluidos los que no aparecen en la muestra
+    pub values: Vec<IntegerFrequency>,
+    /// Frecuencia esperada de los valores menores al mínimo de la muestra
+    pub expected_below: f64,
+    /// Frecuencia esperada de los valores mayores al máximo de la muestra
+    pub expected_above: f64,
+}
+
+/// Cuenta cuántas veces aparece cada valor entero de la muestra y calcula su
+/// frecuencia esperada con la distribución, n·(F(k) - F(k-1)). Devuelve un
+/// error si la distribución no es discreta, si algún valor no es entero o si
+/// la muestra abarca más de MAX_VALUES enteros
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra
+/// * `dist` distribución discreta contra la que se comparan
+pub fn discrete_frequencies<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    dist: &dyn Distribution,
+) -> Result<DiscreteFrequencies, String> {
+    if !dist.is_discrete() {
+        return Err("la distribución de los datos no es discreta".into());
+    }
+    let mut min = i64::MAX;
+    let mut max = i64::MIN;
+    for x in chunked::values(nums) {
+        let x = x.to_f64();
+        if !x.is_finite() || x.fract() != 0f64 {
+            return Err(format!("los valores deben ser enteros, se encontró {}", x));
+        }
+        min = min.min(x as i64);
+        max = max.max(x as i64);
+    }
+    if min > max {
+        return Ok(DiscreteFrequencies { values: vec![], expected_below: 0f64, expected_above: 0f64 });
+    }
+    let width = max.abs_diff(min).saturating_add(1);
+    if width > MAX_VALUES as u64 {
+        return Err(format!("los valores abarcan {} enteros, el máximo es {}", width, MAX_VALUES));
+    }
+    let mut observed = vec![0u64; width as usize];
+    for x in chunked::values(nums) {
+        observed[(x.to_f64() as i64 - min) as usize] += 1;
+    }
+    let n = nums.len() as f64;
+    let mut previous = dist.cdf(min.saturating_sub(1) as f64);
+    let expected_below = n * previous;
+    let values = observed
+        .into_iter()
+        .zip(min..=max)
+        .map(|(observed, value)| {
+            let acc = dist.cdf(value as f64);
+            let expected = n * (acc - previous);
+            previous = acc;
+            IntegerFrequency { value, observed, expected }
+        })
+        .collect();
+    Ok(DiscreteFrequencies { values, expected_below, expected_above: n * (1f64 - previous) })
+}
@@ -0,0 +1,134 @@
+use serde::Serialize;
+
+use crate::dist::Distribution;
+
+/// Probabilidad de cada cola de la distribución que queda fuera de la
+/// integral de la distancia de Wasserstein, más allá de los valores de la
+/// muestra
+pub const TAIL_PROBABILITY: f64 = 1e-9;
+
+/// Cantidad de subintervalos de la regla de Simpson en cada cola
+const TAIL_PANELS: usize = 100;
+
+/// Distancias entre la distribución empírica de la muestra y la teórica.
+/// A diferencia de las pruebas de hipótesis, que con muestras grandes
+/// rechazan desvíos mínimos, miden el tamaño del desvío
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Distances {
+    /// Distancia de variación total entre las proporciones de los intervalos
+    /// del histograma y sus probabilidades, entre 0 y 1 (ver `total_variation`)
+    pub total_variation: f64,
+    /// Distancia de Wasserstein-1, ∫|Fₙ(x) - F(x)|dx, en las unidades de los
+    /// valores
+    pub wasserstein: f64,
+    /// Máxima diferencia entre las funciones de distribución,
+    /// supₓ|Fₙ(x) - F(x)|, el estadístico de Kolmogorov-Smirnov
+    pub max_cdf_gap: f64,
+}
+
+/// Distancia de variación total entre dos distribuciones sobre los mismos
+/// intervalos, ½·Σ|p̂ᵢ - pᵢ|. La probabilidad que queda fuera de los
+/// intervalos cuenta como un intervalo más, sin valores de la muestra
+///
+/// # Argumentos
+///
+/// * `observed` proporción de la muestra en cada intervalo, que suman 1
+/// * `expected` probabilidad de cada intervalo según la distribución
+pub fn total_variation(observed: &[f64], expected: &[f64]) -> f64 {
+    let outside = 1f64 - expected.iter().sum::<f64>();
+    let inside: f64 = observed.iter().zip(expected).map(|(o, e)| (o - e).abs()).sum();
+    (inside + outside.max(0f64)) / 2f64
+}
+
+/// Distancia de Wasserstein-1 y máxima diferencia entre la función de
+/// distribución empírica de la muestra y la de la distribución, en ese
+/// orden. En las distribuciones discretas la integral es la suma de las
+/// diferencias en cada entero; en las continuas se aproxima por la regla de
+/// Simpson entre valores consecutivos de la muestra, y en las colas hasta
+/// los cuantiles `TAIL_PROBABILITY` y 1 - `TAIL_PROBABILITY`. Con una
+/// muestra vacía ambas son 0
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra, en cualquier orden
+/// * `weights` peso de cada valor, en el mismo orden, None si no tiene pesos
+/// * `dist` distribución contra la que se compara
+pub fn cdf_distances(
+    nums: &[f64],
+    weights: Option<&[f64]>,
+    dist: &dyn Distribution,
+) -> (f64, f64) {
+    let mut points: Vec<(f64, f64)> = match weights {
+        Some(weights) => nums.iter().copied().zip(weights.iter().copied()).collect(),
+        None => nums.iter().map(|&x| (x, 1f64)).collect(),
+    };
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let total: f64 = points.iter().map(|p| p.1).sum();
+    if points.is_empty() || total <= 0f64 {
+        return (0f64, 0f64);
+    }
+
+    // Cada valor distinto, con la función empírica después de su salto
+    let mut steps: Vec<(f64, f64)> = Vec::new();
+    let mut cumulative = 0f64;
+    for (x, weight) in points {
+        cumulative += weight;
+        match steps.last_mut() {
+            Some(last) if last.0 == x => last.1 = cumulative / total,
+            _ => steps.push((x, cumulative / total)),
+        }
+    }
+
+    // La máxima diferencia se da antes o después de algún salto, como en
+    // `ks::one_sample`
+    let mut gap = 0f64;
+    let mut before = 0f64;
+    for &(x, after) in &steps {
+        let left = (dist.cdf(x.next_down()) - before).abs();
+        gap = gap.max(left).max((after - dist.cdf(x)).abs());
+        before = after;
+    }
+
+    let (first, last) = (steps[0].0, steps[steps.len() - 1].0);
+    let quantile = |p: f64| Some(dist.quantile(p)).filter(|q| q.is_finite());
+    let lower = quantile(TAIL_PROBABILITY).map_or(first, |q| q.min(first));
+    let upper = quantile(1f64 - TAIL_PROBABILITY).map_or(last, |q| q.max(last));
+    let wasserstein = if dist.is_discrete() {
+        // Las dos funciones son constantes entre enteros consecutivos
+        let (mut sum, mut empirical, mut j) = (0f64, 0f64, 0);
+        let mut k = lower.floor();
+        while k < upper {
+            while j < steps.len() && steps[j].0 <= k {
+                empirical = steps[j].1;
+                j += 1;
+            }
+            sum += (empirical - dist.cdf(k)).abs();
+            k += 1f64;
+        }
+        sum
+    } else {
+        let below = simpson(|x| dist.cdf(x), lower, first, TAIL_PANELS);
+        let above = simpson(|x| 1f64 - dist.cdf(x), last, upper, TAIL_PANELS);
+        let inside: f64 = steps
+            .windows(2)
+            .map(|w| simpson(|x| (w[0].1 - dist.cdf(x)).abs(), w[0].0, w[1].0, 1))
+            .sum();
+        below + inside + above
+    };
+    (wasserstein, gap)
+}
+
+// Función privada, integral de f entre a y b por la regla de Simpson
+// compuesta, con `panels` subintervalos
+fn simpson(f: impl Fn(f64) -> f64, a: f64, b: f64, panels: usize) -> f64 {
+    if b <= a {
+        return 0f64;
+    }
+    let h = (b - a) / panels as f64;
+    (0..panels)
+        .map(|i| {
+            let x = a + i as f64 * h;
+            (f(x) + 4f64 * f(x + h / 2f64) + f(x + h)) * h / 6f64
+        })
+        .sum()
+}
@@ -0,0 +1,298 @@
+use serde::Serialize;
+
+use crate::dist::uniform::Uniform;
+use crate::dist::Distribution;
+
+/// Resultado de la prueba de Kolmogorov-Smirnov para dos muestras
+#[derive(Serialize)]
+pub struct TwoSampleTest {
+    /// Máxima diferencia entre las funciones de distribución empíricas
+    pub statistic: f64,
+    /// Valor crítico para el nivel de significancia indicado
+    pub critical: f64,
+    /// Nivel de significancia utilizado
+    pub alpha: f64,
+    /// Si se rechaza la hipótesis de que ambas muestras provienen de la
+    /// misma distribución
+    pub reject: bool,
+}
+
+/// Prueba de Kolmogorov-Smirnov para dos muestras, con el valor crítico
+/// asintótico c(α)·√((n+m)/(n·m))
+///
+/// # Argumentos
+///
+/// * `first` primera muestra
+/// * `second` segunda muestra
+/// * `alpha` nivel de significancia, por ejemplo 0.05
+pub fn two_sample(first: &[f64], second: &[f64], alpha: f64) -> TwoSampleTest {
+    let mut a = first.to_vec();
+    let mut b = second.to_vec();
+    a.sort_by(|x, y| x.total_cmp(y));
+    b.sort_by(|x, y| x.total_cmp(y));
+    let n = a.len() as f64;
+    let m = b.len() as f64;
+
+    // Recorrer ambas muestras ordenadas, avanzando por el menor valor
+    // (y por ambas en caso de empate) y midiendo la diferencia
+    let (mut i, mut j) = (0, 0);
+    let mut statistic = 0f64;
+    while i < a.len() && j < b.len() {
+        let x = a[i].min(b[j]);
+        while i < a.len() && a[i] <= x {
+            i += 1;
+        }
+        while j < b.len() && b[j] <= x {
+            j += 1;
+        }
+        statistic = statistic.max((i as f64 / n - j as f64 / m).abs());
+    }
+
+    let critical = if a.is_empty() || b.is_empty() {
+        f64::INFINITY
+    } else {
+        (-(alpha / 2f64).ln() / 2f64).sqrt() * ((n + m) / (n * m)).sqrt()
+    };
+    TwoSampleTest {
+        statistic,
+        critical,
+        alpha,
+        reject: statistic > critical,
+    }
+}
+
+/// Resultado de la prueba de Kolmogorov-Smirnov de una muestra contra una
+/// distribución
+#[derive(Serialize)]
+pub struct OneSampleTest {
+    /// Máxima diferencia entre la función de distribución empírica y la
+    /// de la distribución
+    pub statistic: f64,
+    /// Valor crítico para el nivel de significancia indicado
+    pub critical: f64,
+    /// Nivel de significancia utilizado
+    pub alpha: f64,
+    /// Si se rechaza la hipótesis de que la muestra proviene de la distribución
+    pub reject: bool,
+}
+
+/// Prueba de Kolmogorov-Smirnov de una muestra, con el valor crítico
+/// asintótico c(α)/√n. En distribuciones discretas la prueba es conservadora
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra
+/// * `dist` distribución contra la que se prueba
+/// * `alpha` nivel de significancia, por ejemplo 0.05
+pub fn one_sample(nums: &[f64], dist: &dyn Distribution, alpha: f64) -> OneSampleTest {
+    let mut a = nums.to_vec();
+    a.sort_by(|x, y| x.total_cmp(y));
+    let n = a.len() as f64;
+
+    // La función empírica salta en cada valor distinto: se compara con F
+    // antes del salto (límite por izquierda, para los saltos de las
+    // distribuciones discretas) y después
+    let mut statistic = 0f64;
+    let mut i = 0;
+    while i < a.len() {
+        let x = a[i];
+        let before = i as f64 / n;
+        while i < a.len() && a[i] <= x {
+            i += 1;
+        }
+        let after = i as f64 / n;
+        let left = (dist.cdf(x.next_down()) - before).abs();
+        statistic = statistic.max(left).max((after - dist.cdf(x)).abs());
+    }
+
+    let critical = if a.is_empty() {
+        f64::INFINITY
+    } else {
+        (-(alpha / 2f64).ln() / 2f64).sqrt() / n.sqrt()
+    };
+    OneSampleTest {
+        statistic,
+        critical,
+        alpha,
+        reject: statistic > critical,
+    }
+}
+
+/// Tamaño máximo de la muestra para el que `uniformity` usa la distribución
+/// exacta del estadístico
+pub const EXACT_MAX_N: usize = 40;
+
+/// Resultado de la prueba de Kolmogorov-Smirnov de uniformidad en [0, 1)
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct UniformityTest {
+    /// Cantidad de valores
+    pub n: u64,
+    /// Máxima diferencia entre la función de distribución empírica y la
+    /// de la Uniforme
+    pub statistic: f64,
+    /// Valor crítico para el nivel de significancia indicado
+    pub critical: f64,
+    /// Valor p, P(D >= estadístico)
+    pub p_value: f64,
+    /// Si el valor crítico y el valor p son exactos, con hasta
+    /// `EXACT_MAX_N` valores, o asintóticos
+    pub exact: bool,
+    /// Nivel de significancia utilizado
+    pub alpha: f64,
+    /// Si se rechaza la hipótesis de que la muestra es uniforme
+    pub reject: bool,
+}
+
+/// Prueba de Kolmogorov-Smirnov de una muestra contra la Uniforme en
+/// [0, 1), para los números crudos de un generador. Con hasta `EXACT_MAX_N`
+/// valores el valor crítico y el valor p salen de la distribución exacta
+/// del estadístico (ver `kolmogorov_cdf`); con más, de la asintótica de
+/// Kolmogorov, con el valor crítico c(α)/√n de `one_sample`
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra, al menos uno
+/// * `alpha` nivel de significancia, por ejemplo 0.05
+pub fn uniformity(nums: &[f64], alpha: f64) -> UniformityTest {
+    let uniform = Uniform { lower: 0f64, upper: 1f64 };
+    let OneSampleTest { statistic, critical, .. } = one_sample(nums, &uniform, alpha);
+    let n = nums.len();
+    let exact = n <= EXACT_MAX_N;
+    let (critical, p_value) = if exact {
+        (exact_critical(n, alpha), 1f64 - kolmogorov_cdf(n, statistic))
+    } else {
+        (critical, kolmogorov_q((n as f64).sqrt() * statistic))
+    };
+    UniformityTest {
+        n: n as u64,
+        statistic,
+        critical,
+        p_value: p_value.clamp(0f64, 1f64),
+        exact,
+        alpha,
+        reject: statistic > critical,
+    }
+}
+
+/// Función de distribución exacta del estadístico de Kolmogorov-Smirnov de
+/// una muestra de n valores, P(D < d), por el método matricial de
+/// Marsaglia, Tsang y Wang. Sin escalar los exponentes, es precisa para
+/// muestras de hasta unos cientos de valores
+///
+/// # Argumentos
+///
+/// * `n` cantidad de valores, mayor a 0
+/// * `d` valor del estadístico
+pub fn kolmogorov_cdf(n: usize, d: f64) -> f64 {
+    let nd = n as f64 * d;
+    if nd <= 0.5 {
+        return 0f64;
+    }
+    if d >= 1f64 {
+        return 1f64;
+    }
+    let k = nd as usize + 1;
+    let m = 2 * k - 1;
+    let h = k as f64 - nd;
+    let mut matrix = vec![vec![0f64; m]; m];
+    for (i, row) in matrix.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            if i + 1 >= j {
+                *cell = 1f64;
+            }
+        }
+    }
+    for (i, row) in matrix.iter_mut().enumerate() {
+        row[0] -= h.powi(i as i32 + 1);
+    }
+    for (i, cell) in matrix[m - 1].iter_mut().enumerate() {
+        *cell -= h.powi((m - i) as i32);
+    }
+    if 2f64 * h - 1f64 > 0f64 {
+        matrix[m - 1][0] += (2f64 * h - 1f64).powi(m as i32);
+    }
+    for (i, row) in matrix.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            // Se divide por (i - j + 1)! debajo de la superdiagonal
+            if i + 1 > j {
+                *cell /= (1..=i + 1 - j).map(|g| g as f64).product::<f64>();
+            }
+        }
+    }
+    let power = matrix_power(&matrix, n);
+    // P = n!/nⁿ · (Hⁿ)ₖₖ, multiplicando de a un factor para no desbordar
+    let mut p = power[k - 1][k - 1];
+    for i in 1..=n {
+        p *= i as f64 / n as f64;
+    }
+    p.clamp(0f64, 1f64)
+}
+
+// Función privada, valor crítico exacto: el d con P(D < d) = 1 - α, por
+// bisección
+fn exact_critical(n: usize, alpha: f64) -> f64 {
+    let (mut lower, mut upper) = (0f64, 1f64);
+    while upper - lower > 1e-10 {
+        let middle = (lower + upper) / 2f64;
+        if kolmogorov_cdf(n, middle) < 1f64 - alpha {
+            lower = middle;
+        } else {
+            upper = middle;
+        }
+    }
+    upper
+}
+
+// Función privada, distribución asintótica de Kolmogorov,
+// Q(λ) = 2·Σ(-1)ʲ⁻¹·e^(-2j²λ²), P(√n·D > λ) para n grande
+fn kolmogorov_q(lambda: f64) -> f64 {
+    // La serie converge lento cerca de 0, donde Q es 1
+    if lambda < 0.2 {
+        return 1f64;
+    }
+    let mut sum = 0f64;
+    for j in 1..=100 {
+        let term = (-2f64 * (j * j) as f64 * lambda * lambda).exp();
+        sum += if j % 2 == 1 { term } else { -term };
+        if term < 1e-16 {
+            break;
+        }
+    }
+    2f64 * sum
+}
+
+// Función privada, potencia de una matriz cuadrada por duplicación
+fn matrix_power(matrix: &[Vec<f64>], mut exponent: usize) -> Vec<Vec<f64>> {
+    let m = matrix.len();
+    let mut res: Vec<Vec<f64>> =
+        (0..m).map(|i| (0..m).map(|j| if i == j { 1f64 } else { 0f64 }).collect()).collect();
+    let mut base = matrix.to_vec();
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            res = multiply(&res, &base);
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            base = multiply(&base, &base);
+        }
+    }
+    res
+}
+
+// Función privada, producto de dos matrices cuadradas
+fn multiply(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let m = a.len();
+    let mut res = vec![vec![0f64; m]; m];
+    for i in 0..m {
+        for l in 0..m {
+            let x = a[i][l];
+            if x == 0f64 {
+                continue;
+            }
+            for j in 0..m {
+                res[i][j] += x * b[l][j];
+            }
+        }
+    }
+    res
+}
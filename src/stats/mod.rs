@@ -1,14 +1,309 @@
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
 use std::sync::Arc;
 
+use crate::chunked::{self, SampleChunks, CHUNK_LEN};
 use crate::dist::Distribution;
+use crate::float::Float;
+use crate::stats::autocorrelation::AutocorrelationTest;
+use crate::stats::distance::Distances;
+use crate::stats::runs::RunsTest;
+
+pub mod autocorrelation;
+pub mod battery;
+pub mod correlation;
+pub mod descriptive;
+pub mod distance;
+pub mod discrete;
+pub mod ks;
+pub mod normality;
+pub mod runs;
+pub mod streaming;
+pub mod suggest;
+pub mod table;
 
 /// Datos necesarios para calcular estadísticas
 #[derive(Deserialize)]
 pub struct StatisticsInput {
     /// Cantidad de intervalos a utilizar para los cálculos
     pub intervals: usize,
-    pub significance: usize,
+    /// Índice del nivel de significancia en la tabla de chi cuadrado (ver
+    /// `ALPHAS`), si no se indica se usa `DEFAULT_SIGNIFICANCE`
+    pub significance: Option<usize>,
+    /// Intervalo final, cerrado si no se indica (ver `FinalBin`)
+    #[serde(default)]
+    pub final_bin: FinalBin,
+    /// Posición del primer valor a usar, el primero de la muestra si no se indica
+    #[serde(default)]
+    pub from: Option<usize>,
+    /// Posición siguiente al último valor a usar, el final de la muestra si
+    /// no se indica
+    #[serde(default)]
+    pub to: Option<usize>,
+    /// Escala del histograma y de la prueba, lineal si no se indica (ver `Scale`)
+    #[serde(default)]
+    pub scale: Scale,
+    /// Recorte de los valores extremos antes de armar los intervalos, None
+    /// para usar todos (ver `Trim`)
+    #[serde(default)]
+    pub trim: Option<Trim>,
+    /// Forma de armar los intervalos, de igual ancho si no se indica (ver
+    /// `Binning`)
+    #[serde(default)]
+    pub binning: Binning,
+    /// Niveles de significancia adicionales, por ejemplo [0.10, 0.05, 0.01],
+    /// para obtener el valor crítico y el resultado de la prueba con cada
+    /// uno en la misma petición; deben estar en `ALPHAS`
+    #[serde(default)]
+    pub alphas: Option<Vec<f64>>,
+    /// Si los parámetros de la distribución se estimaron a partir de la
+    /// muestra. En ese caso los grados de libertad restan uno por parámetro
+    /// (ver `Distribution::get_degrees`); si se fijaron de antemano, como al
+    /// generar los valores, son intervalos - 1. Falso si no se indica
+    #[serde(default)]
+    pub parameters_estimated: bool,
+    /// Si se agregan las estadísticas extendidas, las distancias entre la
+    /// distribución empírica y la teórica (ver `distance::Distances`). Falso
+    /// si no se indica
+    #[serde(default)]
+    pub extended: bool,
+}
+
+impl StatisticsInput {
+    /// Verifica que las posiciones `from` y `to` estén dentro de la muestra
+    ///
+    /// # Argumentos
+    ///
+    /// * `len` cantidad de valores de la muestra
+    pub fn check_range(&self, len: usize) -> Result<(), String> {
+        let to = self.to.unwrap_or(len);
+        if to > len {
+            return Err(format!("to no puede superar la cantidad de valores, {}", len));
+        }
+        if self.from.unwrap_or(0) > to {
+            return Err("from no puede ser mayor a to".into());
+        }
+        Ok(())
+    }
+
+    /// Posiciones (from, to) de los valores a usar, limitadas a la muestra
+    ///
+    /// # Argumentos
+    ///
+    /// * `len` cantidad de valores de la muestra
+    pub fn bounds(&self, len: usize) -> (usize, usize) {
+        let to = self.to.unwrap_or(len).min(len);
+        (self.from.unwrap_or(0).min(to), to)
+    }
+
+    /// Verifica que los valores admitan la escala pedida: con la escala
+    /// logarítmica, la distribución no puede ser discreta y los valores a
+    /// usar deben ser finitos y mayores a 0. Con la escala lineal no recorre los valores
+    ///
+    /// # Argumentos
+    ///
+    /// * `nums` valores de la muestra
+    /// * `dist` distribución contra la que se prueba
+    pub fn check_scale<T: Float, S: SampleChunks<T> + ?Sized>(
+        &self,
+        nums: &S,
+        dist: &dyn Distribution,
+    ) -> Result<(), String> {
+        if self.scale == Scale::Linear {
+            return Ok(());
+        }
+        if dist.is_discrete() {
+            return Err("la escala logarítmica no admite distribuciones discretas".into());
+        }
+        let (from, to) = self.bounds(nums.len());
+        let mut values = chunked::values(nums).skip(from).take(to - from).map(|x| x.to_f64());
+        match values.find(|x| *x <= 0f64 || !x.is_finite()) {
+            Some(x) => Err(format!(
+                "la escala logarítmica necesita valores finitos mayores a 0, se encontró {}",
+                x
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Verifica que los niveles de significancia adicionales, si los hay,
+    /// estén tabulados en `ALPHAS`
+    pub fn check_alphas(&self) -> Result<(), String> {
+        let alphas = self.alphas.iter().flatten();
+        match alphas.copied().find(|&alpha| significance_for_alpha(alpha).is_none()) {
+            Some(alpha) => Err(format!(
+                "el nivel de significancia {} no está tabulado ({:?})",
+                alpha, ALPHAS
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Verifica que haya al menos un intervalo
+    pub fn check_intervals(&self) -> Result<(), String> {
+        if self.intervals == 0 {
+            return Err("intervals debe ser mayor a 0".into());
+        }
+        Ok(())
+    }
+
+    /// Verifica que el índice de significancia, si se indicó, sea una fila de
+    /// la tabla de chi cuadrado, entre 1 y la cantidad de `ALPHAS`
+    pub fn check_significance(&self) -> Result<(), String> {
+        match self.significance {
+            Some(significance) if !(1..=ALPHAS.len()).contains(&significance) => Err(format!(
+                "significance debe estar entre 1 y {}",
+                ALPHAS.len()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Verifica los parámetros del recorte, si lo hay, y que la distribución
+    /// no sea discreta. No recorre los valores
+    ///
+    /// # Argumentos
+    ///
+    /// * `dist` distribución contra la que se prueba
+    pub fn check_trim(&self, dist: &dyn Distribution) -> Result<(), String> {
+        let Some(trim) = self.trim else { return Ok(()) };
+        trim.validate()?;
+        if dist.is_discrete() {
+            return Err("el recorte no admite distribuciones discretas".into());
+        }
+        Ok(())
+    }
+}
+
+/// Recorte de los valores extremos de la muestra. Un solo valor extremo, por
+/// ejemplo de Box-Müller, estira todos los intervalos del histograma; los
+/// valores recortados no se cuentan, y la prueba usa la distribución
+/// truncada a los límites del recorte
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Trim {
+    /// Excluye los valores fuera de [Q1 - k·IQR, Q3 + k·IQR], con los
+    /// cuartiles de la muestra sin pesos; k = 1.5 es el criterio de Tukey
+    Iqr { k: f64 },
+    /// Excluye los valores fuera de [lower, upper]; sin uno de los límites,
+    /// no se recorta de ese lado
+    Bounds { lower: Option<f64>, upper: Option<f64> },
+}
+
+impl Trim {
+    /// Verifica que k sea un número finito no negativo y que los límites
+    /// sean finitos, con lower < upper
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Trim::Iqr { k } if !(k.is_finite() && k >= 0f64) => {
+                Err("k debe ser un número finito mayor o igual a 0".into())
+            }
+            Trim::Bounds { lower, upper } => {
+                if lower.into_iter().chain(upper).any(|x| !x.is_finite()) {
+                    return Err("los límites del recorte deben ser números finitos".into());
+                }
+                match (lower, upper) {
+                    (Some(lower), Some(upper)) if lower >= upper => {
+                        Err("el límite inferior del recorte debe ser menor al superior".into())
+                    }
+                    _ => Ok(()),
+                }
+            }
+            Trim::Iqr { .. } => Ok(()),
+        }
+    }
+
+    /// Límites del recorte para la muestra, None del lado que no se recorta
+    ///
+    /// # Argumentos
+    ///
+    /// * `nums` valores de la muestra
+    pub fn limits<T: Float, S: SampleChunks<T> + ?Sized>(
+        &self,
+        nums: &S,
+    ) -> (Option<f64>, Option<f64>) {
+        match *self {
+            Trim::Iqr { k } => {
+                let (q1, q3) = descriptive::quartiles(nums);
+                (Some(q1 - k * (q3 - q1)), Some(q3 + k * (q3 - q1)))
+            }
+            Trim::Bounds { lower, upper } => (lower, upper),
+        }
+    }
+}
+
+/// Resultado del recorte de los valores extremos
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Trimmed {
+    /// Límite inferior usado, None si no se recortó de ese lado
+    pub lower: Option<f64>,
+    /// Límite superior usado, None si no se recortó de ese lado
+    pub upper: Option<f64>,
+    /// Cantidad de valores excluidos
+    pub excluded: u64,
+}
+
+/// Escala en la que se calculan el histograma y la prueba de chi cuadrado
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Scale {
+    /// Sobre los valores de la muestra
+    #[default]
+    Linear,
+    /// Sobre el logaritmo natural de los valores, para muestras con cola
+    /// pesada (lognormal, Pareto) que en escala lineal quedan en un solo
+    /// intervalo. Los límites y las marcas de clase son logaritmos, y la
+    /// frecuencia esperada de [a, b) es n·(F(eᵇ) - F(eᵃ))
+    Log,
+}
+
+/// Forma de armar los intervalos del histograma y de la prueba de chi cuadrado
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Binning {
+    /// Intervalos de igual ancho entre los límites
+    #[default]
+    EqualWidth,
+    /// Intervalos con aproximadamente n/k valores cada uno, con límites en
+    /// los cuantiles empíricos de la muestra y anchos variables. En las
+    /// muestras con cola pesada, con igual ancho casi todos los valores caen
+    /// en los primeros intervalos. La frecuencia esperada de [a, b) es
+    /// n·(F(b) - F(a)). Las distribuciones discretas usan siempre intervalos
+    /// de igual ancho
+    Quantile,
+}
+
+/// Intervalo final del histograma y de la prueba de chi cuadrado. Los demás
+/// intervalos son cerrados a izquierda y abiertos a derecha, [lower, upper),
+/// por lo que un valor que cae justo en un límite se cuenta en el intervalo
+/// que empieza en él
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum FinalBin {
+    /// [lower, upper], incluye el límite superior, donde cae el máximo de la
+    /// muestra
+    #[default]
+    Closed,
+    /// [lower, ∞), la clase abierta que incluye toda la cola superior de la
+    /// distribución, con frecuencia esperada 1 - F(lower). Las distribuciones
+    /// discretas la usan siempre
+    Open,
+}
+
+/// Niveles de significancia tabulados, en el orden de las filas de la tabla
+/// de chi_squared_critical_value()
+pub const ALPHAS: [f64; 10] = [0.995, 0.99, 0.975, 0.95, 0.90, 0.10, 0.05, 0.025, 0.01, 0.001];
+
+/// Índice de significancia por defecto, correspondiente a α = 0.05
+pub const DEFAULT_SIGNIFICANCE: usize = 7;
+
+/// Devuelve el índice de la tabla de chi cuadrado correspondiente al nivel de
+/// significancia, o None si no está tabulado
+///
+/// # Argumentos
+///
+/// * `alpha` nivel de significancia, por ejemplo 0.05
+pub fn significance_for_alpha(alpha: f64) -> Option<usize> {
+    ALPHAS
+        .iter()
+        .position(|a| (a - alpha).abs() < 1e-9)
+        .map(|i| i + 1)
 }
 
 /// Datos a devolver para la generación del histograma
@@ -18,7 +313,43 @@ pub struct HistogramData {
     pub y: Vec<u64>,
     pub lower: f64,
     pub upper: f64,
+    /// Ancho de los intervalos; con intervalos por cuantiles, el promedio
     pub size: f64,
+    /// Ancho de cada intervalo, sólo con intervalos por cuantiles; x son
+    /// los puntos medios
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub widths: Option<Vec<f64>>,
+    /// Forma de armar los intervalos, de igual ancho siempre en las
+    /// distribuciones discretas
+    pub binning: Binning,
+    /// Suma de los pesos de los valores de cada intervalo, si la muestra
+    /// tiene pesos
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weights: Option<Vec<f64>>,
+    /// Intervalo final utilizado, abierto siempre en las distribuciones
+    /// discretas
+    pub final_bin: FinalBin,
+    /// Escala de los intervalos; en la logarítmica, x, lower, upper y size
+    /// están en logaritmos
+    pub scale: Scale,
+    /// Cambio de los límites o de la cantidad de intervalos por la
+    /// distribución, si los cambió (ver `Distribution::get_intervals`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adjustment: Option<IntervalAdjustment>,
+}
+
+/// Límites y cantidad de intervalos que la distribución cambió, por ejemplo
+/// la Poisson, que usa un intervalo por valor entero sin importar la
+/// cantidad pedida
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct IntervalAdjustment {
+    /// Límites calculados con la muestra, el mínimo y el máximo redondeados
+    /// hacia afuera, y la cantidad de intervalos pedida
+    pub requested: DistributionLimits,
+    /// Límites y cantidad de intervalos usados
+    pub applied: DistributionLimits,
+    /// Por qué la distribución los cambió
+    pub reason: &'static str,
 }
 
 /// Datos a devolver como resultado del test de chi cuadrado
@@ -30,19 +361,47 @@ pub struct TestResult {
     pub calculated: f64,
     /// valor crítico, chi cuadrado tabulado
     pub critical: f64,
+    /// Si se rechaza la hipótesis de que los valores siguen la distribución,
+    /// calculado > crítico
+    pub reject: bool,
+    /// Nivel de significancia utilizado
+    pub alpha: f64,
+    /// Grados de libertad del valor crítico, según los intervalos que
+    /// quedan después de combinar los de fe < 5
+    pub degrees: usize,
+    /// Conclusión de la prueba, en texto
+    pub conclusion: String,
+    /// Tamaño efectivo de la muestra, si tiene pesos (ver
+    /// `descriptive::effective_size`); las frecuencias de la prueba se
+    /// escalan a él
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_size: Option<f64>,
+    /// Valor crítico y resultado con cada nivel de significancia adicional
+    /// pedido, en el mismo orden (ver `StatisticsInput::alphas`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub levels: Option<Vec<SignificanceLevel>>,
 }
 
+/// Resultado de la prueba de chi cuadrado con un nivel de significancia
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SignificanceLevel {
+    /// Nivel de significancia
+    pub alpha: f64,
+    /// Valor crítico, chi cuadrado tabulado
+    pub critical: f64,
+    /// Si se rechaza la hipótesis de que los valores siguen la distribución
+    pub reject: bool,
+}
+
+/// Intervalo [lower, upper), o [lower, upper] si `upper_closed`
 #[derive(Serialize)]
 pub struct Interval {
     pub lower: f64,
     pub upper: f64,
+    pub upper_closed: bool,
 }
 
-pub struct DistributionLimits {
-    pub lower: f64,
-    pub upper: f64,
-    pub intervals: usize,
-}
+pub use crate::dist::DistributionLimits;
 
 /// Fila de la tabla del cálculo de Chi Cuadrado
 #[derive(Serialize)]
@@ -51,15 +410,27 @@ pub struct ChiInterval {
     pub upper: f64,
     /// frecuencia observada
     pub fo: u64,
+    /// frecuencia observada ponderada, si la muestra tiene pesos: la suma
+    /// de los pesos del intervalo, escalada al tamaño efectivo de la muestra.
+    /// Se usa en lugar de fo en la prueba
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fo_weighted: Option<f64>,
     /// frecuencia esperada
     pub fe: f64,
     pub c: Option<f64>,
     pub c_ac: Option<f64>,
+    /// Si el intervalo incluye su límite superior, sólo en el intervalo
+    /// final cerrado. El límite inferior se incluye siempre
+    pub upper_closed: bool,
+    /// Si el intervalo es la clase abierta "≥ lower" de una distribución
+    /// discreta, que incluye toda la cola superior
+    pub open_upper: bool,
 }
 
 impl ChiInterval {
     fn set_c(&mut self, cumulative: f64) -> f64 {
-        let c = (self.fo as f64 - self.fe).powi(2) / self.fe;
+        let fo = self.fo_weighted.unwrap_or(self.fo as f64);
+        let c = (fo - self.fe).powi(2) / self.fe;
         self.c = Some(c);
         self.c_ac = Some(cumulative + c);
         c
@@ -70,7 +441,12 @@ impl ChiInterval {
         self.lower = self.lower.min(other.lower);
         self.upper = self.upper.max(other.upper);
         self.fo += other.fo;
+        if let (Some(fo), Some(other)) = (&mut self.fo_weighted, other.fo_weighted) {
+            *fo += other;
+        }
         self.fe += other.fe;
+        self.upper_closed |= other.upper_closed;
+        self.open_upper |= other.open_upper;
     }
 }
 
@@ -79,147 +455,831 @@ impl ChiInterval {
 pub struct StatisticsResponse {
     pub histogram: HistogramData,
     pub test: TestResult,
+    /// Prueba de independencia de Ljung-Box, None si la muestra es muy chica
+    pub autocorrelation: Option<AutocorrelationTest>,
+    /// Prueba de rachas por encima y por debajo de la mediana, con la
+    /// distribución de sus largos, None si no alcanzan las rachas
+    pub runs: Option<RunsTest>,
+    /// Recorte de los valores extremos, si se pidió
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trimmed: Option<Trimmed>,
+    /// Estadísticas extendidas, si se pidieron (ver
+    /// `StatisticsInput::extended`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extended: Option<Distances>,
 }
 
-/// Método que recibe la última distribución generada, la cantidad de intervalos
-/// y devuelve la respuesta con el test de chi-cuadrado y los datos del histograma
-pub async fn full_statistics(
+/// Versión asíncrona de weighted_statistics(), para el servidor: el cálculo
+/// se hace en un hilo bloqueante, sin ocupar el runtime
+#[cfg(feature = "server")]
+pub async fn full_statistics<T: Float, S: SampleChunks<T> + Send + 'static>(
     input: StatisticsInput,
-    nums: Arc<Vec<f64>>,
+    nums: Arc<S>,
+    weights: Option<Arc<Vec<f64>>>,
     dist: Arc<Box<dyn Distribution + Send + Sync>>,
 ) -> StatisticsResponse {
-    // Tomar el límite inferior y superior de la distribución
-    let lower = nums
-        .iter()
-        .min_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap_or(&0f64)
-        .floor();
-    let upper = nums
-        .iter()
-        .max_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap_or(&0f64)
-        .ceil();
-    // Tomar la cantidad de intervalos y el tamaño de cada uno
-    let intervals = input.intervals;
-    let limits = DistributionLimits { lower, upper, intervals };
-    let limits = dist.get_intervals(limits);
-    let upper = limits.upper;
-    let lower = limits.lower;
-    let intervals = limits.intervals;
-
-    let significance = input.significance;
-    let size = (upper - lower) / intervals as f64;
+    tokio::task::spawn_blocking(move || {
+        let weights = weights.as_deref().map(Vec::as_slice);
+        weighted_statistics(input, nums.as_ref(), weights, dist.as_ref().as_ref())
+    })
+    .await
+    .unwrap()
+}
+
+/// Método que recibe la última distribución generada, la cantidad de intervalos
+/// y devuelve la respuesta con el test de chi-cuadrado y los datos del histograma.
+/// Los valores pueden estar guardados en f32 o f64 (ver `Float`), y se leen
+/// por bloques (ver `SampleChunks`)
+pub fn statistics<T: Float, S: SampleChunks<T> + ?Sized>(
+    input: StatisticsInput,
+    nums: &S,
+    dist: &dyn Distribution,
+) -> StatisticsResponse {
+    weighted_statistics(input, nums, None, dist)
+}
+
+/// Versión de statistics() para una muestra con pesos, por ejemplo del
+/// muestreo por importancia. El histograma cuenta los valores y suma los
+/// pesos de cada intervalo; la prueba de chi cuadrado compara la
+/// proporción de peso de cada intervalo con su probabilidad, con las
+/// frecuencias escaladas al tamaño efectivo de la muestra. La prueba de
+/// independencia no usa los pesos. Sin pesos es igual a statistics(). Con
+/// `from` o `to`, las estadísticas se calculan sólo con esa parte de la
+/// muestra, copiada en memoria. Con la escala logarítmica se calculan sobre
+/// el logaritmo de los valores, también copiados en memoria; los valores
+/// deben cumplir `StatisticsInput::check_scale`. Con `trim`, antes se
+/// excluyen los valores extremos, también en memoria (ver `Trim`)
+///
+/// # Argumentos
+///
+/// * `input` cantidad de intervalos, significancia e intervalo final
+/// * `nums` valores de la muestra
+/// * `weights` peso de cada valor, en el mismo orden (ver
+///   `descriptive::check_weights`), None si no tiene pesos
+/// * `dist` distribución contra la que se prueba
+pub fn weighted_statistics<T: Float, S: SampleChunks<T> + ?Sized>(
+    input: StatisticsInput,
+    nums: &S,
+    weights: Option<&[f64]>,
+    dist: &dyn Distribution,
+) -> StatisticsResponse {
+    if input.from.is_some() || input.to.is_some() {
+        let (from, to) = input.bounds(nums.len());
+        let window = nums.range(from, to);
+        let weights = weights.map(|weights| &weights[from..to]);
+        let input = StatisticsInput { from: None, to: None, ..input };
+        return weighted_statistics(input, &window, weights, dist);
+    }
+    if let Some(trim) = input.trim {
+        let (lower, upper) = trim.limits(nums);
+        let (kept, kept_weights) = trim_values(nums, weights, lower, upper);
+        let input = StatisticsInput { trim: None, ..input };
+        let truncated = Truncated { dist, lower, upper };
+        let mut res = weighted_statistics(input, &kept, kept_weights.as_deref(), &truncated);
+        let excluded = (nums.len() - kept.len()) as u64;
+        res.trimmed = Some(Trimmed { lower, upper, excluded });
+        return res;
+    }
+    if input.scale == Scale::Log {
+        let logs: Vec<f64> = chunked::values(nums).map(|x| x.to_f64().ln()).collect();
+        let input = StatisticsInput { scale: Scale::Linear, ..input };
+        let mut res = weighted_statistics(input, &logs, weights, &LogScale(dist));
+        res.histogram.scale = Scale::Log;
+        return res;
+    }
+    let Bins { lower, upper, intervals, size, final_bin, binning, edges, adjustment } =
+        bins(&input, nums, dist);
+    let significance = input.significance.unwrap_or(DEFAULT_SIGNIFICANCE);
+    let width = (binning == Binning::EqualWidth).then_some(size);
 
     // Crear listas necesarias
     let mut interval_list: Vec<Interval> = Vec::with_capacity(intervals);
     let mut classmark_list: Vec<f64> = Vec::with_capacity(intervals);
-    let mut interval_min = lower;
 
     // Agregar intervalos y marcas de clases a las listas
-    for _ in 0..intervals {
+    for (i, bounds) in edges.windows(2).enumerate() {
         interval_list.push(Interval {
-            lower: interval_min,
-            upper: interval_min + size,
+            lower: bounds[0],
+            upper: bounds[1],
+            upper_closed: i == intervals - 1 && final_bin == FinalBin::Closed,
+        });
+        classmark_list.push(match width {
+            Some(size) => bounds[0] + size / 2f64,
+            None => (bounds[0] + bounds[1]) / 2f64,
         });
-        classmark_list.push(interval_min + size / 2f64);
-        interval_min += size;
     }
 
-    // Cantidad de hilos del CPU
-    let threads: usize = std::thread::available_parallelism().unwrap().into();
-    // Tamaño de cada slice del vector
-    let slice_size = (nums.len() as f64 / (threads - 2) as f64).ceil() as usize;
-    // Vector de frecuencias por intervalo
+    // Cantidad de hilos del CPU, dejando 2 hilos sin utilizar (al menos uno)
+    let threads: usize = std::thread::available_parallelism().map_or(1, |n| n.into());
+    let workers = threads.saturating_sub(2).max(1);
+    // Cada bloque se divide en una parte por hilo, y cada hilo cuenta su
+    // parte de todos los bloques
+    let chunks: Vec<&[T]> = (0..nums.chunk_count()).map(|i| nums.chunk(i)).collect();
+    // Vector de frecuencias por intervalo, y de suma de pesos si los hay
     let mut data_list: Vec<u64> = vec![0; intervals];
+    let mut weight_list: Vec<f64> = vec![0f64; if weights.is_some() { intervals } else { 0 }];
 
-    // Vector con las frecuencias parciales
-    let mut results_slice: Vec<Vec<u64>> = Vec::with_capacity(threads - 2);
-    // Vector de tareas a iniciar, una por hilo del CPU, dejando 2 hilos sin utilizar
-    let mut tasks = Vec::with_capacity(threads - 2);
-
-    // Copiar el contador de referencias del vector de números generados
-    let nums_clone = Arc::clone(&nums);
-    // Por cada tarea a iniciar, iniciarla pasando como parámetro el vector entero,
-    // el índice por donde debe empezar y terminar de procesar
-    for i in 0..threads - 2 {
-        let start = 0 + i * slice_size;
-        let end = start + slice_size.min(nums.len() - start);
-        let task = tokio::task::spawn(parse_intervals(
-            nums_clone.clone(),
-            intervals,
-            lower,
-            size,
-            start,
-            end,
-        ));
-        tasks.push(task);
-    }
-
-    // Obtener los resultados de las tareas una vez que terminen
-    for task in tasks {
-        let result = task.await.unwrap();
-        results_slice.push(result);
-    }
+    // Iniciar un hilo por parte de los bloques, y otros para las pruebas de
+    // independencia, que se calculan en paralelo con las frecuencias
+    let (results_slice, autocorrelation, runs) = std::thread::scope(|scope| {
+        let autocorrelation =
+            scope.spawn(|| autocorrelation::ljung_box(nums, significance));
+        let runs = scope.spawn(|| runs::runs(nums, significance));
+        let tasks: Vec<_> = (0..workers)
+            .map(|i| {
+                let chunks = &chunks;
+                let edges = &edges;
+                scope.spawn(move || {
+                    let mut counts = vec![0u64; intervals];
+                    let mut sums = vec![0f64; if weights.is_some() { intervals } else { 0 }];
+                    for (c, chunk) in chunks.iter().enumerate() {
+                        let part = chunk.len().div_ceil(workers);
+                        let start = (i * part).min(chunk.len());
+                        let end = (start + part).min(chunk.len());
+                        // Pesos de la misma parte del bloque; todos los
+                        // bloques tienen CHUNK_LEN valores salvo el último
+                        let offset = c * CHUNK_LEN;
+                        let part_weights = weights.map(|w| &w[offset + start..offset + end]);
+                        let values = &chunk[start..end];
+                        parse_intervals(values, part_weights, &mut counts, &mut sums, edges, width);
+                    }
+                    (counts, sums)
+                })
+            })
+            .collect();
+        // Obtener los resultados de los hilos una vez que terminen
+        let results: Vec<(Vec<u64>, Vec<f64>)> =
+            tasks.into_iter().map(|t| t.join().unwrap()).collect();
+        (results, autocorrelation.join().unwrap(), runs.join().unwrap())
+    });
 
     // Guardar los resultados en la lista final
-    for vec in results_slice {
-        for (i, &x) in vec.iter().enumerate() {
+    for (counts, sums) in results_slice {
+        for (i, &x) in counts.iter().enumerate() {
             data_list[i] += x;
         }
+        for (i, &w) in sums.iter().enumerate() {
+            weight_list[i] += w;
+        }
     }
 
+    // Con pesos, la prueba usa el tamaño efectivo de la muestra, y la
+    // frecuencia observada de cada intervalo es su proporción del peso total
+    let effective_size = weights.map(descriptive::effective_size);
+    let sample_size = effective_size.unwrap_or(nums.len() as f64);
+    let total_weight: f64 = weight_list.iter().sum();
+
     // Obtener las frecuencias esperadas según la distribución
-    let exp_list: Vec<f64> = dist
-        .get_expected(intervals, lower, upper)
+    let exp_list: Vec<f64> = probabilities(dist, &edges, final_bin, binning)
         .iter()
-        .map(|n| n * nums.len() as f64)
+        .map(|p| p * sample_size)
         .collect();
 
     // Unir la lista de intervalos, de frecuencias esperadas y de frecuencias observadas
     // en una lista de ChiInterval
-    let intervals: Vec<ChiInterval> = data_list
+    let mut intervals: Vec<ChiInterval> = data_list
         .iter()
         // Iterar sobre las tres listas a la vez
         .zip(exp_list)
         .zip(interval_list)
+        .enumerate()
         // por cada intervalo, crear el objeto necesario
-        .map(|((fo, fe), int)| ChiInterval {
+        .map(|(i, ((fo, fe), int))| ChiInterval {
             lower: int.lower,
             upper: int.upper,
             fo: *fo,
+            fo_weighted: effective_size.map(|_| weight_list[i] / total_weight * sample_size),
             fe,
             c: None,
             c_ac: None,
+            upper_closed: int.upper_closed,
+            open_upper: false,
         })
         .collect();
-
-    let mut merged_intervals = merge_intervals(intervals);
-
-    // Sumatoria de (fo-fe)²/fe
-    let mut calculated = 0f64;
-    for interval in merged_intervals.iter_mut() {
-        calculated += interval.set_c(calculated);
+    if final_bin == FinalBin::Open {
+        if let Some(last) = intervals.last_mut() {
+            last.open_upper = true;
+        }
     }
 
-    // Valor crítico del test de chi cuadrado
-    let critical =
-        chi_squared_critical_value(dist.get_degrees(merged_intervals.len()), significance);
+    // Distancias a la distribución, con los valores copiados en memoria
+    let extended = input.extended.then(|| {
+        let proportion = |i: &ChiInterval| i.fo_weighted.unwrap_or(i.fo as f64) / sample_size;
+        let observed: Vec<f64> = intervals.iter().map(proportion).collect();
+        let expected: Vec<f64> = intervals.iter().map(|i| i.fe / sample_size).collect();
+        let values: Vec<f64> = chunked::values(nums).map(|x| x.to_f64()).collect();
+        let (wasserstein, max_cdf_gap) = distance::cdf_distances(&values, weights, dist);
+        Distances {
+            total_variation: distance::total_variation(&observed, &expected),
+            wasserstein,
+            max_cdf_gap,
+        }
+    });
 
-    // Valores a devolver
-    let test = TestResult {
-        intervals: merged_intervals,
-        calculated,
-        critical,
-    };
+    let estimated = input.parameters_estimated;
+    let test = test_result(intervals, dist, significance, estimated, input.alphas, effective_size);
     let histogram = HistogramData {
         x: classmark_list,
         y: data_list,
         lower,
         upper,
         size,
+        widths: width.is_none().then(|| edges.windows(2).map(|b| b[1] - b[0]).collect()),
+        binning,
+        weights: weights.map(|_| weight_list),
+        final_bin,
+        scale: Scale::Linear,
+        adjustment,
+    };
+    StatisticsResponse { histogram, test, autocorrelation, runs, trimmed: None, extended }
+}
+
+/// Tamaño del primer prefijo de la traza de chi cuadrado; los siguientes
+/// son 10 veces el anterior
+pub const TRACE_START: usize = 1000;
+
+/// Prueba de chi cuadrado sobre un prefijo de la muestra
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TracePoint {
+    /// Cantidad de valores del prefijo
+    pub n: u64,
+    /// Estadístico de chi cuadrado calculado
+    pub calculated: f64,
+    /// Valor crítico, según los intervalos que quedan después de combinar
+    pub critical: f64,
+    /// Cantidad de intervalos de la prueba, después de combinar los de fe < 5
+    pub intervals: usize,
+    /// Si se rechaza la hipótesis de que los valores siguen la distribución
+    pub reject: bool,
+}
+
+/// Traza del estadístico de chi cuadrado a medida que crece la muestra
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ChiSquaredTrace {
+    /// Límite inferior de los intervalos, los mismos para todos los prefijos
+    pub lower: f64,
+    /// Límite superior de los intervalos
+    pub upper: f64,
+    /// Prueba de cada prefijo, del más corto a la muestra completa
+    pub points: Vec<TracePoint>,
+    /// Recorte de los valores extremos, si se pidió
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trimmed: Option<Trimmed>,
+}
+
+/// Calcula la prueba de chi cuadrado sobre prefijos de la muestra con
+/// tamaños espaciados logarítmicamente (TRACE_START, 10 veces más, ...,
+/// hasta la muestra completa), para ver cómo crece la potencia de la prueba
+/// con el tamaño de la muestra. Todos los prefijos usan los intervalos de
+/// la muestra completa, y las frecuencias se acumulan en una sola pasada.
+/// No usa pesos; con `from` o `to` se usa sólo esa parte de la muestra, y
+/// con la escala logarítmica los límites son logaritmos. Con `trim`, los
+/// prefijos son de los valores que quedan después del recorte
+///
+/// # Argumentos
+///
+/// * `input` cantidad de intervalos, significancia, intervalo final y parte de la muestra
+/// * `nums` valores de la muestra, en el orden en que fueron generados
+/// * `dist` distribución contra la que se prueba
+pub fn chi_squared_trace<T: Float, S: SampleChunks<T> + ?Sized>(
+    input: StatisticsInput,
+    nums: &S,
+    dist: &dyn Distribution,
+) -> ChiSquaredTrace {
+    if input.from.is_some() || input.to.is_some() {
+        let (from, to) = input.bounds(nums.len());
+        let window = nums.range(from, to);
+        let input = StatisticsInput { from: None, to: None, ..input };
+        return chi_squared_trace(input, &window, dist);
+    }
+    if let Some(trim) = input.trim {
+        let (lower, upper) = trim.limits(nums);
+        let (kept, _) = trim_values(nums, None, lower, upper);
+        let input = StatisticsInput { trim: None, ..input };
+        let mut trace = chi_squared_trace(input, &kept, &Truncated { dist, lower, upper });
+        let excluded = (nums.len() - kept.len()) as u64;
+        trace.trimmed = Some(Trimmed { lower, upper, excluded });
+        return trace;
+    }
+    if input.scale == Scale::Log {
+        let logs: Vec<f64> = chunked::values(nums).map(|x| x.to_f64().ln()).collect();
+        let input = StatisticsInput { scale: Scale::Linear, ..input };
+        return chi_squared_trace(input, &logs, &LogScale(dist));
+    }
+    let Bins { lower, upper, intervals, size, final_bin, binning, edges, .. } =
+        bins(&input, nums, dist);
+    let significance = input.significance.unwrap_or(DEFAULT_SIGNIFICANCE);
+    let width = (binning == Binning::EqualWidth).then_some(size);
+    let probs = probabilities(dist, &edges, final_bin, binning);
+    let len = nums.len();
+    let mut checkpoints: Vec<usize> =
+        std::iter::successors(Some(TRACE_START), |n| n.checked_mul(10))
+            .take_while(|&n| n < len)
+            .collect();
+    checkpoints.push(len);
+
+    let mut counts = vec![0u64; intervals];
+    let mut points = Vec::with_capacity(checkpoints.len());
+    let mut checkpoints = checkpoints.into_iter().peekable();
+    let mut pos = 0;
+    // Prueba con las frecuencias acumuladas hasta pos
+    let mut trace = |counts: &[u64], n: usize| {
+        let chi_intervals = count_intervals(counts, &probs, &edges, final_bin, n);
+        let (merged, calculated, critical, _) =
+            chi_squared(chi_intervals, dist, significance, input.parameters_estimated);
+        points.push(TracePoint {
+            n: n as u64,
+            calculated,
+            critical,
+            intervals: merged.len(),
+            reject: calculated > critical,
+        });
+    };
+    for c in 0..nums.chunk_count() {
+        let mut chunk = nums.chunk(c);
+        // Un bloque puede contener varios prefijos
+        while let Some(&next) = checkpoints.peek() {
+            let take = (next - pos).min(chunk.len());
+            parse_intervals(&chunk[..take], None, &mut counts, &mut [], &edges, width);
+            pos += take;
+            chunk = &chunk[take..];
+            if pos < next {
+                break;
+            }
+            trace(&counts, pos);
+            checkpoints.next();
+        }
+    }
+    ChiSquaredTrace { lower, upper, points, trimmed: None }
+}
+
+/// Prueba de chi cuadrado con una cantidad de intervalos
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SensitivityPoint {
+    /// Cantidad de intervalos pedida, antes de combinar los de fe < 5
+    pub requested: usize,
+    /// Cantidad de intervalos de la prueba, después de combinar
+    pub intervals: usize,
+    /// Estadístico de chi cuadrado calculado
+    pub calculated: f64,
+    /// Grados de libertad del valor crítico
+    pub degrees: usize,
+    /// Valor crítico, chi cuadrado tabulado
+    pub critical: f64,
+    /// Si se rechaza la hipótesis de que los valores siguen la distribución
+    pub reject: bool,
+}
+
+/// Resultado de la prueba de chi cuadrado con cada cantidad de intervalos
+/// de un rango, para ver cuánto depende la decisión del agrupamiento
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct IntervalSensitivity {
+    /// Nivel de significancia utilizado
+    pub alpha: f64,
+    /// Prueba con cada cantidad de intervalos, de menor a mayor
+    pub points: Vec<SensitivityPoint>,
+    /// Cantidad de pruebas que rechazan la hipótesis
+    pub rejections: usize,
+    /// Recorte de los valores extremos, si se pidió
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trimmed: Option<Trimmed>,
+}
+
+/// Calcula la prueba de chi cuadrado con cada cantidad de intervalos entre
+/// `min_intervals` y `max_intervals`, con los demás datos de `input` (se
+/// ignora `input.intervals`), para ver si la decisión de rechazar depende
+/// de cómo se agrupan los valores. Las frecuencias de todas las cantidades
+/// se cuentan en una sola pasada por los bloques. Como la traza, no usa
+/// pesos ni los niveles de significancia adicionales. En las distribuciones
+/// discretas la cantidad de intervalos la fija la distribución, por lo que
+/// todas las pruebas son iguales
+///
+/// # Argumentos
+///
+/// * `input` significancia, intervalo final, agrupamiento y parte de la muestra
+/// * `nums` valores de la muestra
+/// * `dist` distribución contra la que se prueba
+/// * `min_intervals` menor cantidad de intervalos, mayor a 0
+/// * `max_intervals` mayor cantidad de intervalos
+pub fn interval_sensitivity<T: Float, S: SampleChunks<T> + ?Sized>(
+    input: StatisticsInput,
+    nums: &S,
+    dist: &dyn Distribution,
+    min_intervals: usize,
+    max_intervals: usize,
+) -> IntervalSensitivity {
+    if input.from.is_some() || input.to.is_some() {
+        let (from, to) = input.bounds(nums.len());
+        let window = nums.range(from, to);
+        let input = StatisticsInput { from: None, to: None, ..input };
+        return interval_sensitivity(input, &window, dist, min_intervals, max_intervals);
+    }
+    if let Some(trim) = input.trim {
+        let (lower, upper) = trim.limits(nums);
+        let (kept, _) = trim_values(nums, None, lower, upper);
+        let input = StatisticsInput { trim: None, ..input };
+        let truncated = Truncated { dist, lower, upper };
+        let mut res = interval_sensitivity(input, &kept, &truncated, min_intervals, max_intervals);
+        let excluded = (nums.len() - kept.len()) as u64;
+        res.trimmed = Some(Trimmed { lower, upper, excluded });
+        return res;
+    }
+    if input.scale == Scale::Log {
+        let logs: Vec<f64> = chunked::values(nums).map(|x| x.to_f64().ln()).collect();
+        let input = StatisticsInput { scale: Scale::Linear, ..input };
+        return interval_sensitivity(input, &logs, &LogScale(dist), min_intervals, max_intervals);
+    }
+    let significance = input.significance.unwrap_or(DEFAULT_SIGNIFICANCE);
+    let mut input = StatisticsInput { alphas: None, ..input };
+    let all_bins: Vec<Bins> = (min_intervals..=max_intervals)
+        .map(|intervals| {
+            input.intervals = intervals;
+            bins(&input, nums, dist)
+        })
+        .collect();
+    let mut counts: Vec<Vec<u64>> = all_bins.iter().map(|b| vec![0u64; b.intervals]).collect();
+    for c in 0..nums.chunk_count() {
+        let chunk = nums.chunk(c);
+        for (bins, counts) in all_bins.iter().zip(&mut counts) {
+            let width = (bins.binning == Binning::EqualWidth).then_some(bins.size);
+            parse_intervals(chunk, None, counts, &mut [], &bins.edges, width);
+        }
+    }
+    let points: Vec<SensitivityPoint> = (min_intervals..=max_intervals)
+        .zip(all_bins.iter().zip(&counts))
+        .map(|(requested, (bins, counts))| {
+            let probs = probabilities(dist, &bins.edges, bins.final_bin, bins.binning);
+            let chi_intervals =
+                count_intervals(counts, &probs, &bins.edges, bins.final_bin, nums.len());
+            let (merged, calculated, critical, degrees) =
+                chi_squared(chi_intervals, dist, significance, input.parameters_estimated);
+            SensitivityPoint {
+                requested,
+                intervals: merged.len(),
+                calculated,
+                degrees,
+                critical,
+                reject: calculated > critical,
+            }
+        })
+        .collect();
+    IntervalSensitivity {
+        alpha: ALPHAS[significance - 1],
+        rejections: points.iter().filter(|p| p.reject).count(),
+        points,
+        trimmed: None,
+    }
+}
+
+// Función privada, tabla de la prueba sin pesos a partir de las
+// frecuencias observadas de cada intervalo y las probabilidades, con las
+// frecuencias esperadas para n valores
+fn count_intervals(
+    counts: &[u64],
+    probs: &[f64],
+    edges: &[f64],
+    final_bin: FinalBin,
+    n: usize,
+) -> Vec<ChiInterval> {
+    let last = counts.len() - 1;
+    counts
+        .iter()
+        .zip(probs)
+        .zip(edges.windows(2))
+        .enumerate()
+        .map(|(i, ((&fo, p), bounds))| ChiInterval {
+            lower: bounds[0],
+            upper: bounds[1],
+            fo,
+            fo_weighted: None,
+            fe: p * n as f64,
+            c: None,
+            c_ac: None,
+            upper_closed: i == last && final_bin == FinalBin::Closed,
+            open_upper: i == last && final_bin == FinalBin::Open,
+        })
+        .collect()
+}
+
+/// Distribución del logaritmo natural de una variable positiva, para las
+/// estadísticas en escala logarítmica: P(ln X <= y) = F(eʸ)
+struct LogScale<'a>(&'a dyn Distribution);
+
+impl Distribution for LogScale<'_> {
+    fn get_expected(&self, intervals: usize, lower: f64, upper: f64) -> Vec<f64> {
+        expected_from_cdf(self, intervals, lower, upper)
+    }
+
+    fn get_degrees(&self, intervals: usize) -> usize {
+        self.0.get_degrees(intervals)
+    }
+
+    fn get_intervals(&self, limits: DistributionLimits) -> DistributionLimits {
+        // Los límites que fija la distribución, como los de la Uniforme, se
+        // pasan a logaritmos; los que no son positivos no tienen logaritmo
+        let intervals = self.0.get_intervals(DistributionLimits {
+            lower: limits.lower.exp(),
+            upper: limits.upper.exp(),
+            intervals: limits.intervals,
+        });
+        DistributionLimits {
+            lower: if intervals.lower > 0f64 { intervals.lower.ln() } else { limits.lower },
+            upper: if intervals.upper > 0f64 { intervals.upper.ln() } else { limits.upper },
+            intervals: intervals.intervals,
+        }
+    }
+
+    fn intervals_reason(&self) -> Option<&'static str> {
+        self.0.intervals_reason()
+    }
+
+    fn quantile(&self, p: f64) -> f64 {
+        self.0.quantile(p).ln()
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        self.0.cdf(x.exp())
+    }
+}
+
+/// Distribución truncada a los límites del recorte de los valores extremos:
+/// P(X <= x | lower <= X <= upper)
+pub(crate) struct Truncated<'a> {
+    pub(crate) dist: &'a dyn Distribution,
+    pub(crate) lower: Option<f64>,
+    pub(crate) upper: Option<f64>,
+}
+
+impl Truncated<'_> {
+    /// Probabilidad acumulada en los límites del recorte
+    pub(crate) fn mass(&self) -> (f64, f64) {
+        let below = self.lower.map_or(0f64, |lower| self.dist.cdf(lower));
+        let above = self.upper.map_or(1f64, |upper| self.dist.cdf(upper));
+        (below, above)
+    }
+}
+
+impl Distribution for Truncated<'_> {
+    fn get_expected(&self, intervals: usize, lower: f64, upper: f64) -> Vec<f64> {
+        expected_from_cdf(self, intervals, lower, upper)
+    }
+
+    fn get_degrees(&self, intervals: usize) -> usize {
+        self.dist.get_degrees(intervals)
+    }
+
+    fn get_intervals(&self, limits: DistributionLimits) -> DistributionLimits {
+        self.dist.get_intervals(limits)
+    }
+
+    fn intervals_reason(&self) -> Option<&'static str> {
+        self.dist.intervals_reason()
+    }
+
+    fn quantile(&self, p: f64) -> f64 {
+        let (below, above) = self.mass();
+        self.dist.quantile(below + p * (above - below))
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        let (below, above) = self.mass();
+        if above <= below {
+            return if self.lower.is_some_and(|lower| x < lower) { 0f64 } else { 1f64 };
+        }
+        ((self.dist.cdf(x) - below) / (above - below)).clamp(0f64, 1f64)
+    }
+}
+
+/// Probabilidad de cada intervalo por diferencias de la función acumulada,
+/// para las distribuciones derivadas de otra. La probabilidad por debajo de
+/// lower se suma al primer intervalo, y la de por encima de upper al último
+///
+/// # Argumentos
+///
+/// * `dist` distribución de la que se toma la función acumulada
+/// * `intervals` cantidad de intervalos
+/// * `lower` límite inferior de los intervalos
+/// * `upper` límite superior de los intervalos
+pub(crate) fn expected_from_cdf(
+    dist: &dyn Distribution,
+    intervals: usize,
+    lower: f64,
+    upper: f64,
+) -> Vec<f64> {
+    let size = (upper - lower) / intervals as f64;
+    let mut acc_prev = 0f64;
+    (1..=intervals)
+        .map(|i| {
+            let acc = if i == intervals { 1f64 } else { dist.cdf(lower + i as f64 * size) };
+            let prob = acc - acc_prev;
+            acc_prev = acc;
+            prob
+        })
+        .collect()
+}
+
+// Función privada, copia los valores dentro de los límites del recorte, y
+// sus pesos si los hay, en el orden de la muestra
+fn trim_values<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    weights: Option<&[f64]>,
+    lower: Option<f64>,
+    upper: Option<f64>,
+) -> (Vec<T>, Option<Vec<f64>>) {
+    let keep = |x: f64| {
+        lower.is_none_or(|lower| x >= lower) && upper.is_none_or(|upper| x <= upper)
     };
-    let res = StatisticsResponse { histogram, test };
-    res
+    let mut kept = Vec::new();
+    let mut kept_weights = weights.map(|_| Vec::new());
+    for (i, x) in chunked::values(nums).enumerate() {
+        if keep(x.to_f64()) {
+            kept.push(x);
+            if let (Some(kept_weights), Some(weights)) = (&mut kept_weights, weights) {
+                kept_weights.push(weights[i]);
+            }
+        }
+    }
+    (kept, kept_weights)
+}
+
+/// Límites de los intervalos del histograma y de la prueba
+struct Bins {
+    lower: f64,
+    upper: f64,
+    intervals: usize,
+    size: f64,
+    final_bin: FinalBin,
+    binning: Binning,
+    /// Límites de cada intervalo, intervals + 1 valores
+    edges: Vec<f64>,
+    /// Cambio de los límites calculados con la muestra por la distribución
+    adjustment: Option<IntervalAdjustment>,
+}
+
+// Función privada, límites de los intervalos de la muestra: los redondeados
+// del mínimo y el máximo, salvo que la distribución fije otros. Con
+// intervalos por cuantiles, los límites intermedios son los valores de la
+// muestra ordenada en las posiciones i·n/k, de modo que cada intervalo
+// [edges[i], edges[i+1]) tiene n/k valores, salvo por los repetidos
+fn bins<T: Float, S: SampleChunks<T> + ?Sized>(
+    input: &StatisticsInput,
+    nums: &S,
+    dist: &dyn Distribution,
+) -> Bins {
+    // Tomar el límite inferior y superior de la distribución
+    let lower = chunked::values(nums)
+        .min_by(|a, b| a.partial_cmp(b).unwrap())
+        .map_or(0f64, |x| x.to_f64())
+        .floor();
+    let upper = chunked::values(nums)
+        .max_by(|a, b| a.partial_cmp(b).unwrap())
+        .map_or(0f64, |x| x.to_f64())
+        .ceil();
+    // Tomar la cantidad de intervalos y el tamaño de cada uno
+    let requested = DistributionLimits { lower, upper, intervals: input.intervals };
+    let applied = dist.get_intervals(requested);
+    let adjustment = (applied != requested).then(|| IntervalAdjustment {
+        requested,
+        applied,
+        reason: dist.intervals_reason().unwrap_or("la distribución fija los intervalos"),
+    });
+    let DistributionLimits { lower, upper, intervals } = applied;
+    let size = (upper - lower) / intervals as f64;
+    // En las distribuciones discretas el último intervalo es la clase
+    // abierta; los intervalos de la cola con fe < 5 se unen a ella al combinar
+    let final_bin = if dist.is_discrete() { FinalBin::Open } else { input.final_bin };
+    let binning = match input.binning {
+        Binning::Quantile if !dist.is_discrete() && !nums.is_empty() => Binning::Quantile,
+        _ => Binning::EqualWidth,
+    };
+    let edges: Vec<f64> = if binning == Binning::Quantile {
+        let mut sorted: Vec<f64> = chunked::values(nums).map(|x| x.to_f64()).collect();
+        sorted.sort_unstable_by(f64::total_cmp);
+        let n = sorted.len();
+        (0..=intervals)
+            .map(|i| match i {
+                0 => lower,
+                i if i == intervals => upper,
+                i => sorted[i * n / intervals].clamp(lower, upper),
+            })
+            .collect()
+    } else {
+        // Límites de los intervalos, calculados a partir de lower para no
+        // acumular errores de redondeo; el último es exactamente upper
+        (0..=intervals)
+            .map(|i| if i == intervals { upper } else { lower + i as f64 * size })
+            .collect()
+    };
+    Bins { lower, upper, intervals, size, final_bin, binning, edges, adjustment }
+}
+
+// Función privada, probabilidad de cada intervalo según la distribución.
+// Con intervalos por cuantiles se calcula con la función acumulada en cada
+// límite; la probabilidad por debajo del primero se suma al primer
+// intervalo, y la de por encima del último al último
+fn probabilities(
+    dist: &dyn Distribution,
+    edges: &[f64],
+    final_bin: FinalBin,
+    binning: Binning,
+) -> Vec<f64> {
+    let intervals = edges.len() - 1;
+    let mut probs = match binning {
+        Binning::EqualWidth => dist.get_expected(intervals, edges[0], edges[intervals]),
+        Binning::Quantile => {
+            let mut acc_prev = 0f64;
+            (1..=intervals)
+                .map(|i| {
+                    let acc = if i == intervals { 1f64 } else { dist.cdf(edges[i]) };
+                    let prob = acc - acc_prev;
+                    acc_prev = acc;
+                    prob
+                })
+                .collect()
+        }
+    };
+    // La clase abierta de una distribución continua incluye toda la cola
+    // superior; las discretas ya la calculan así en get_expected
+    if final_bin == FinalBin::Open && !dist.is_discrete() {
+        if let Some(last) = probs.last_mut() {
+            *last = 1f64 - dist.cdf(edges[intervals - 1]);
+        }
+    }
+    probs
+}
+
+// Función privada, prueba de chi cuadrado sobre los intervalos, después de
+// unir los de fe < 5, con la decisión y los niveles de significancia
+// adicionales pedidos
+fn test_result(
+    intervals: Vec<ChiInterval>,
+    dist: &dyn Distribution,
+    significance: usize,
+    estimated: bool,
+    alphas: Option<Vec<f64>>,
+    effective_size: Option<f64>,
+) -> TestResult {
+    let (merged_intervals, calculated, critical, degrees) =
+        chi_squared(intervals, dist, significance, estimated);
+    // Los niveles adicionales usan los mismos grados de libertad
+    let (reject, alpha) = (calculated > critical, ALPHAS[significance - 1]);
+    let levels = alphas.map(|alphas| {
+        alphas
+            .into_iter()
+            .filter_map(|alpha| Some((alpha, significance_for_alpha(alpha)?)))
+            .map(|(alpha, index)| {
+                let critical = chi_squared_critical_value(degrees, index);
+                SignificanceLevel { alpha, critical, reject: calculated > critical }
+            })
+            .collect()
+    });
+
+    TestResult {
+        intervals: merged_intervals,
+        calculated,
+        critical,
+        reject,
+        alpha,
+        degrees,
+        conclusion: conclusion(reject, alpha, degrees, calculated, critical),
+        effective_size,
+        levels,
+    }
+}
+
+// Función privada, conclusión de la prueba de chi cuadrado en texto
+fn conclusion(reject: bool, alpha: f64, degrees: usize, calculated: f64, critical: f64) -> String {
+    let (verdict, compare) = if reject {
+        ("Se rechaza", "supera")
+    } else {
+        ("No se rechaza", "no supera")
+    };
+    format!(
+        "{} la hipótesis de que los valores siguen la distribución: con α = {} y {} grados de \
+         libertad, el estadístico {:.4} {} el valor crítico {:.4}",
+        verdict, alpha, degrees, calculated, compare, critical
+    )
+}
+
+// Función privada, une los intervalos con fe < 5 y devuelve los intervalos
+// resultantes, el estadístico Σ(fo-fe)²/fe, su valor crítico y los grados de
+// libertad. Si los parámetros no se estimaron, los grados de libertad son
+// intervalos - 1, sin restar los parámetros de la distribución
+fn chi_squared(
+    intervals: Vec<ChiInterval>,
+    dist: &dyn Distribution,
+    significance: usize,
+    estimated: bool,
+) -> (Vec<ChiInterval>, f64, f64, usize) {
+    let mut merged_intervals = merge_intervals(intervals);
+    let mut calculated = 0f64;
+    for interval in merged_intervals.iter_mut() {
+        calculated += interval.set_c(calculated);
+    }
+    let k = merged_intervals.len();
+    let degrees = if estimated { dist.get_degrees(k) } else { k.saturating_sub(1).max(1) };
+    let critical = chi_squared_critical_value(degrees, significance);
+    (merged_intervals, calculated, critical, degrees)
 }
 
 fn merge_intervals(intervals: Vec<ChiInterval>) -> Vec<ChiInterval> {
@@ -228,7 +1288,7 @@ fn merge_intervals(intervals: Vec<ChiInterval>) -> Vec<ChiInterval> {
     let mut pending: Option<ChiInterval> = None;
     for mut interval in intervals {
         if let Some(int) = &mut pending {
-            interval.merge(&int);
+            interval.merge(int);
             pending = None;
         }
         if interval.fe >= 5f64 {
@@ -241,7 +1301,7 @@ fn merge_intervals(intervals: Vec<ChiInterval>) -> Vec<ChiInterval> {
     if let Some(int) = &mut pending {
         match merged_intervals.last_mut() {
             Some(interval) => {
-                interval.merge(&int);
+                interval.merge(int);
             }
             None => {
                 merged_intervals.push(pending.take().unwrap());
@@ -251,24 +1311,46 @@ fn merge_intervals(intervals: Vec<ChiInterval>) -> Vec<ChiInterval> {
     merged_intervals
 }
 
-async fn parse_intervals(
-    nums: Arc<Vec<f64>>,
-    intervals: usize,
-    lower: f64,
-    size: f64,
-    start: usize,
-    end: usize,
-) -> Vec<u64> {
-    let mut data_list: Vec<u64> = vec![0; intervals];
-    let opt = nums.get(start..end);
-    if let Some(nums) = opt {
-        for num in nums.iter() {
-            let ind = ((num - lower) / size) as usize;
-            let ind = ind.min(intervals - 1);
-            data_list[ind] += 1;
+// Función privada, cuenta los valores de cada intervalo [edges[i], edges[i+1]),
+// y si hay pesos los suma en weight_list. Los límites los fija la
+// distribución (ver get_intervals), por lo que puede haber valores fuera de
+// ellos, que se cuentan en el primer o el último intervalo; el máximo, igual
+// a upper, cae en el último. Con `width` None los intervalos tienen anchos
+// variables y se buscan por bisección
+fn parse_intervals<T: Float>(
+    nums: &[T],
+    weights: Option<&[f64]>,
+    data_list: &mut [u64],
+    weight_list: &mut [f64],
+    edges: &[f64],
+    width: Option<f64>,
+) {
+    let intervals = data_list.len();
+    let lower = edges[0];
+    for (j, num) in nums.iter().enumerate() {
+        let x = num.to_f64();
+        let ind = match width {
+            // Cantidad de límites intermedios menores o iguales a x
+            None => edges[1..intervals].partition_point(|&e| e <= x),
+            Some(size) => {
+                let mut ind = if x <= lower { 0 } else { ((x - lower) / size) as usize };
+                ind = ind.min(intervals - 1);
+                // La división puede redondear al intervalo vecino cuando el
+                // valor está en un límite; se corrige comparando con los
+                // límites reales
+                if ind > 0 && x < edges[ind] {
+                    ind -= 1;
+                } else if ind + 1 < intervals && x >= edges[ind + 1] {
+                    ind += 1;
+                }
+                ind
+            }
+        };
+        data_list[ind] += 1;
+        if let Some(weights) = weights {
+            weight_list[ind] += weights[j];
         }
     }
-    data_list
 }
 
 pub fn chi_squared_critical_value(df: usize, alpha: usize) -> f64 {
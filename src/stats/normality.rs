@@ -0,0 +1,66 @@
+use serde::Serialize;
+
+use crate::chunked::{self, SampleChunks};
+use crate::float::Float;
+
+/// Prueba de normalidad de Jarque-Bera, a partir de la asimetría y la curtosis
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct JarqueBera {
+    /// Cantidad de valores
+    pub n: u64,
+    /// Coeficiente de asimetría muestral, 0 en la Normal
+    pub skewness: f64,
+    /// Exceso de curtosis muestral, 0 en la Normal
+    pub kurtosis: f64,
+    /// Estadístico JB = n/6·(S² + K²/4)
+    pub statistic: f64,
+    /// Valor p con la chi cuadrado de 2 grados de libertad, exp(-JB/2).
+    /// Es asintótico: con pocos valores la prueba es conservadora
+    pub p_value: f64,
+    /// Nivel de significancia utilizado
+    pub alpha: f64,
+    /// Si se rechaza la hipótesis de que la muestra proviene de una Normal
+    pub reject: bool,
+}
+
+/// Prueba de Jarque-Bera de que la muestra proviene de una Normal, con
+/// cualquier media y varianza. Recorre los valores dos veces, por bloques.
+/// Devuelve None si hay menos de 2 valores o todos son iguales
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra
+/// * `alpha` nivel de significancia, por ejemplo 0.05
+pub fn jarque_bera<T: Float, S: SampleChunks<T> + ?Sized>(nums: &S, alpha: f64) -> Option<JarqueBera> {
+    if nums.len() < 2 {
+        return None;
+    }
+    let n = nums.len() as f64;
+    let mean = chunked::values(nums).map(|x| x.to_f64()).sum::<f64>() / n;
+    // Momentos centrales 2, 3 y 4, con n en el denominador
+    let (mut m2, mut m3, mut m4) = (0f64, 0f64, 0f64);
+    for x in chunked::values(nums) {
+        let d = x.to_f64() - mean;
+        let d2 = d * d;
+        m2 += d2;
+        m3 += d2 * d;
+        m4 += d2 * d2;
+    }
+    let (m2, m3, m4) = (m2 / n, m3 / n, m4 / n);
+    if m2 <= 0f64 {
+        return None;
+    }
+    let skewness = m3 / m2.powf(1.5);
+    let kurtosis = m4 / (m2 * m2) - 3f64;
+    let statistic = n / 6f64 * (skewness * skewness + kurtosis * kurtosis / 4f64);
+    let p_value = (-statistic / 2f64).exp();
+    Some(JarqueBera {
+        n: nums.len() as u64,
+        skewness,
+        kurtosis,
+        statistic,
+        p_value,
+        alpha,
+        reject: p_value < alpha,
+    })
+}
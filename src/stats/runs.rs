@@ -0,0 +1,160 @@
+use serde::Serialize;
+
+use crate::chunked::{self, SampleChunks};
+use crate::float::Float;
+use crate::stats::battery::MIN_EXPECTED;
+use crate::stats::chi_squared_critical_value;
+
+/// Frecuencia observada y esperada de las rachas de un largo
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RunLength {
+    /// Largo de las rachas
+    pub length: usize,
+    /// Si es la clase abierta, la de las rachas de ese largo o más
+    pub open: bool,
+    /// Cantidad de rachas observadas
+    pub observed: u64,
+    /// Cantidad de rachas esperadas, R·(1/2)ᵏ, o R·(1/2)ᵏ⁻¹ en la clase
+    /// abierta, con R rachas
+    pub expected: f64,
+}
+
+/// Prueba de rachas por encima y por debajo de la mediana: con valores
+/// independientes, cada valor queda del mismo lado que el anterior con
+/// probabilidad 1/2, así que el largo de las rachas es geométrico. Además
+/// de la cantidad de rachas, compara la distribución de sus largos con la
+/// geométrica, lo que muestra si sobran rachas largas (valores
+/// correlacionados) o cortas (valores que alternan)
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RunsTest {
+    /// Mediana de la muestra; los valores iguales a ella no se cuentan
+    pub median: f64,
+    /// Cantidad de valores por encima de la mediana, n₁
+    pub above: u64,
+    /// Cantidad de valores por debajo de la mediana, n₂
+    pub below: u64,
+    /// Cantidad de rachas, R
+    pub runs: u64,
+    /// Cantidad de rachas esperada, 2·n₁·n₂/n + 1
+    pub expected_runs: f64,
+    /// Estadístico z de la cantidad de rachas, (R - E[R]) / σ, con
+    /// distribución aproximadamente normal estándar
+    pub z: f64,
+    /// Rachas de cada largo, la última como clase abierta. Se agrupan
+    /// desde el largo en el que la frecuencia esperada de la clase abierta
+    /// sería menor a `battery::MIN_EXPECTED`
+    pub lengths: Vec<RunLength>,
+    /// Chi cuadrado de los largos, Σ(fo - fe)²/fe
+    pub calculated: f64,
+    /// Grados de libertad, clases - 1
+    pub degrees: usize,
+    /// Valor crítico, chi cuadrado tabulado con esos grados de libertad
+    pub critical: f64,
+}
+
+/// Prueba de rachas por encima y por debajo de la mediana, con la
+/// distribución de los largos. Copia los valores en memoria para calcular
+/// la mediana. Devuelve None si no hay valores de los dos lados o si las
+/// rachas no alcanzan para dos clases con la frecuencia esperada mínima
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra, en el orden en que fueron generados
+/// * `significance` índice del nivel de significancia en la tabla de chi cuadrado
+pub fn runs<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    significance: usize,
+) -> Option<RunsTest> {
+    let mut sorted: Vec<f64> = chunked::values(nums)
+        .map(|x| x.to_f64())
+        .filter(|x| !x.is_nan())
+        .collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    let median = median(&mut sorted);
+    // Largo de cada racha, en orden
+    let mut lengths: Vec<usize> = Vec::new();
+    let (mut above, mut below) = (0u64, 0u64);
+    let mut previous = None;
+    for x in chunked::values(nums).map(|x| x.to_f64()) {
+        let side = if x > median {
+            above += 1;
+            true
+        } else if x < median {
+            below += 1;
+            false
+        } else {
+            continue;
+        };
+        match lengths.last_mut() {
+            Some(length) if previous == Some(side) => *length += 1,
+            _ => lengths.push(1),
+        }
+        previous = Some(side);
+    }
+    if above == 0 || below == 0 {
+        return None;
+    }
+    let runs = lengths.len() as u64;
+    // Clases 1, 2, ..., k - 1 y la abierta "≥ k", con k el mayor largo cuya
+    // clase abierta tiene la frecuencia esperada mínima, R·(1/2)ᵏ⁻¹ >= 5
+    let r = runs as f64;
+    let classes = ((r / MIN_EXPECTED).log2().floor() + 1f64).max(0f64) as usize;
+    if classes < 2 {
+        return None;
+    }
+    let mut observed = vec![0u64; classes];
+    for &length in &lengths {
+        observed[length.min(classes) - 1] += 1;
+    }
+    let lengths: Vec<RunLength> = observed
+        .into_iter()
+        .enumerate()
+        .map(|(i, observed)| {
+            let open = i == classes - 1;
+            let exponent = if open { i } else { i + 1 };
+            RunLength {
+                length: i + 1,
+                open,
+                observed,
+                expected: r * 0.5f64.powi(exponent as i32),
+            }
+        })
+        .collect();
+    let calculated = lengths
+        .iter()
+        .map(|l| (l.observed as f64 - l.expected).powi(2) / l.expected)
+        .sum();
+    let degrees = classes - 1;
+    // Media y varianza de la cantidad de rachas (Wald-Wolfowitz)
+    let (n1, n2) = (above as f64, below as f64);
+    let n = n1 + n2;
+    let expected_runs = 2f64 * n1 * n2 / n + 1f64;
+    let variance = 2f64 * n1 * n2 * (2f64 * n1 * n2 - n) / (n * n * (n - 1f64));
+    let z = if variance > 0f64 { (r - expected_runs) / variance.sqrt() } else { 0f64 };
+    Some(RunsTest {
+        median,
+        above,
+        below,
+        runs,
+        expected_runs,
+        z,
+        lengths,
+        calculated,
+        degrees,
+        critical: chi_squared_critical_value(degrees, significance),
+    })
+}
+
+// Función privada, mediana de los valores, que reordena. Con una cantidad
+// par, el promedio de los dos del medio
+fn median(values: &mut [f64]) -> f64 {
+    let len = values.len();
+    let (left, &mut upper, _) = values.select_nth_unstable_by(len / 2, f64::total_cmp);
+    if len % 2 == 1 {
+        return upper;
+    }
+    let lower = left.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    (lower + upper) / 2f64
+}
@@ -0,0 +1,462 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::dist::Distribution;
+use crate::stats::descriptive::Summary;
+use crate::stats::{
+    test_result, Binning, ChiInterval, FinalBin, HistogramData, Scale, TestResult,
+};
+
+/// Intervalos de igual ancho del histograma, acordados antes de leer los
+/// valores: en una sola pasada no se conocen el mínimo y el máximo
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct StreamingBins {
+    /// Límite inferior del primer intervalo
+    pub lower: f64,
+    /// Límite superior del último intervalo, que lo incluye
+    pub upper: f64,
+    /// Cantidad de intervalos
+    pub intervals: usize,
+}
+
+impl StreamingBins {
+    /// Verifica que los límites sean finitos y estén en orden, y que haya
+    /// al menos un intervalo
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.lower.is_finite() || !self.upper.is_finite() || self.lower >= self.upper {
+            return Err("lower y upper deben ser finitos, con lower < upper".into());
+        }
+        if self.intervals == 0 {
+            return Err("se necesita al menos un intervalo".into());
+        }
+        Ok(())
+    }
+
+    // Función privada, ancho de cada intervalo
+    fn size(&self) -> f64 {
+        (self.upper - self.lower) / self.intervals as f64
+    }
+
+    // Función privada, límites de los intervalos, de lower a upper
+    fn edges(&self) -> Vec<f64> {
+        let size = self.size();
+        (0..=self.intervals)
+            .map(|i| if i == self.intervals { self.upper } else { self.lower + i as f64 * size })
+            .collect()
+    }
+}
+
+/// Estadísticas de una muestra leída en una sola pasada, sin guardar los
+/// valores
+#[derive(Serialize)]
+pub struct StreamingStatistics {
+    /// Cantidad, media, varianza, mínimo y máximo de todos los valores
+    pub summary: Summary,
+    /// Coeficiente de asimetría, m₃/m₂^(3/2)
+    pub skewness: f64,
+    /// Exceso de curtosis, m₄/m₂² - 3
+    pub kurtosis: f64,
+    /// Histograma con los intervalos acordados
+    pub histogram: HistogramData,
+    /// Cantidad de valores fuera de [lower, upper], que no cuentan en el
+    /// histograma ni en la prueba
+    pub outside: u64,
+    /// Prueba de chi cuadrado de los valores dentro de los intervalos, si se
+    /// indicó una distribución (ver `StreamingAccumulator::finish`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test: Option<TestResult>,
+}
+
+/// Acumula los valores de una muestra de a uno, con memoria proporcional a
+/// la cantidad de intervalos y no a la de valores. Los momentos se
+/// actualizan con las fórmulas de Welford y Terriberry
+#[derive(Clone, Debug)]
+pub struct StreamingAccumulator {
+    bins: StreamingBins,
+    count: u64,
+    mean: f64,
+    m2: f64,
+    m3: f64,
+    m4: f64,
+    min: f64,
+    max: f64,
+    counts: Vec<u64>,
+    outside: u64,
+}
+
+impl StreamingAccumulator {
+    /// Crea un acumulador vacío con los intervalos indicados
+    ///
+    /// # Argumentos
+    ///
+    /// * `bins` intervalos acordados (ver `StreamingBins::validate`)
+    pub fn new(bins: StreamingBins) -> Self {
+        StreamingAccumulator {
+            bins,
+            count: 0,
+            mean: 0f64,
+            m2: 0f64,
+            m3: 0f64,
+            m4: 0f64,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            counts: vec![0; bins.intervals],
+            outside: 0,
+        }
+    }
+
+    /// Agrega un valor
+    ///
+    /// # Argumentos
+    ///
+    /// * `x` valor de la muestra
+    pub fn push(&mut self, x: f64) {
+        let n1 = self.count as f64;
+        self.count += 1;
+        let n = self.count as f64;
+        let delta = x - self.mean;
+        let delta_n = delta / n;
+        let delta_n2 = delta_n * delta_n;
+        let term = delta * delta_n * n1;
+        self.mean += delta_n;
+        self.m4 += term * delta_n2 * (n * n - 3f64 * n + 3f64) + 6f64 * delta_n2 * self.m2
+            - 4f64 * delta_n * self.m3;
+        self.m3 += term * delta_n * (n - 2f64) - 3f64 * delta_n * self.m2;
+        self.m2 += term;
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+
+        let StreamingBins { lower, upper, intervals } = self.bins;
+        if x < lower || x > upper {
+            self.outside += 1;
+        } else {
+            // El valor upper cae en el último intervalo, que es cerrado
+            let i = ((x - lower) / self.bins.size()) as usize;
+            self.counts[i.min(intervals - 1)] += 1;
+        }
+    }
+
+    /// Devuelve las estadísticas de los valores agregados. Con una
+    /// distribución, agrega la prueba de chi cuadrado de los valores dentro
+    /// de [lower, upper] contra la distribución truncada a ese rango, como
+    /// con el recorte de los valores extremos
+    ///
+    /// # Argumentos
+    ///
+    /// * `dist` distribución contra la que se prueba, None para no probar
+    /// * `significance` índice del nivel de significancia (ver `ALPHAS`)
+    /// * `estimated` si los parámetros de la distribución se estimaron a
+    ///   partir de la muestra (ver `StatisticsInput::parameters_estimated`)
+    pub fn finish(
+        self,
+        dist: Option<&dyn Distribution>,
+        significance: usize,
+        estimated: bool,
+    ) -> StreamingStatistics {
+        let n = self.count as f64;
+        let summary = if self.count == 0 {
+            Summary { count: 0, mean: 0f64, variance: 0f64, min: 0f64, max: 0f64 }
+        } else {
+            let variance = if self.count > 1 { self.m2 / (n - 1f64) } else { 0f64 };
+            Summary { count: self.count, mean: self.mean, variance, min: self.min, max: self.max }
+        };
+        let (skewness, kurtosis) = if self.m2 > 0f64 {
+            (n.sqrt() * self.m3 / self.m2.powf(1.5), n * self.m4 / (self.m2 * self.m2) - 3f64)
+        } else {
+            (0f64, 0f64)
+        };
+
+        let size = self.bins.size();
+        let edges = self.bins.edges();
+        let test = dist.map(|dist| {
+            // Probabilidad de cada intervalo [a, b) dada la de [lower, upper]
+            let left = |x: f64| dist.cdf(x.next_down());
+            let last = edges.len() - 2;
+            let mass = dist.cdf(self.bins.upper) - left(self.bins.lower);
+            let inside: u64 = self.counts.iter().sum();
+            let intervals: Vec<ChiInterval> = edges
+                .windows(2)
+                .enumerate()
+                .map(|(i, bounds)| {
+                    let acc = if i == last { dist.cdf(bounds[1]) } else { left(bounds[1]) };
+                    ChiInterval {
+                        lower: bounds[0],
+                        upper: bounds[1],
+                        fo: self.counts[i],
+                        fo_weighted: None,
+                        fe: (acc - left(bounds[0])) / mass * inside as f64,
+                        c: None,
+                        c_ac: None,
+                        upper_closed: i == last,
+                        open_upper: false,
+                    }
+                })
+                .collect();
+            test_result(intervals, dist, significance, estimated, None, None)
+        });
+
+        let histogram = HistogramData {
+            x: edges.windows(2).map(|b| b[0] + size / 2f64).collect(),
+            y: self.counts,
+            lower: self.bins.lower,
+            upper: self.bins.upper,
+            size,
+            widths: None,
+            binning: Binning::EqualWidth,
+            weights: None,
+            final_bin: FinalBin::Closed,
+            scale: Scale::Linear,
+            adjustment: None,
+        };
+        StreamingStatistics { summary, skewness, kurtosis, histogram, outside: self.outside, test }
+    }
+
+    /// Agrega los valores de otro acumulador con los mismos intervalos, por
+    /// ejemplo de otra parte de la muestra leída en paralelo, combinando los
+    /// momentos con las fórmulas de Pébay. El resultado es el mismo que
+    /// agregando los valores de a uno, salvo por redondeo
+    ///
+    /// # Argumentos
+    ///
+    /// * `other` acumulador con los valores a agregar
+    pub fn merge(&mut self, other: &StreamingAccumulator) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = other.clone();
+            return;
+        }
+        let (na, nb) = (self.count as f64, other.count as f64);
+        let n = na + nb;
+        let delta = other.mean - self.mean;
+        let (delta2, delta3) = (delta * delta, delta * delta * delta);
+        self.m4 += other.m4
+            + delta2 * delta2 * na * nb * (na * na - na * nb + nb * nb) / (n * n * n)
+            + 6f64 * delta2 * (na * na * other.m2 + nb * nb * self.m2) / (n * n)
+            + 4f64 * delta * (na * other.m3 - nb * self.m3) / n;
+        self.m3 += other.m3
+            + delta3 * na * nb * (na - nb) / (n * n)
+            + 3f64 * delta * (na * other.m2 - nb * self.m2) / n;
+        self.m2 += other.m2 + delta2 * na * nb / n;
+        self.mean += delta * nb / n;
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.outside += other.outside;
+    }
+}
+
+impl Extend<f64> for StreamingAccumulator {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|x| self.push(x));
+    }
+}
+
+/// Cantidad aproximada de bytes de cada parte del CSV que se interpreta en
+/// un hilo, cortada en un salto de línea
+pub const PARSE_BLOCK: usize = 1 << 20;
+
+/// Cantidad de bytes que `CsvValues::feed_into` junta antes de interpretar
+/// las líneas completas, repartidas en partes de PARSE_BLOCK bytes
+pub const PARSE_BATCH: usize = 16 * PARSE_BLOCK;
+
+/// Lector incremental de valores en CSV: recibe el archivo por partes, de
+/// cualquier tamaño, y entrega los valores de la primera columna de cada
+/// línea completa. Se saltean las líneas vacías, y la primera si no es un
+/// número, como el encabezado "value" de `/export`
+#[derive(Default, Debug)]
+pub struct CsvValues {
+    /// Bytes leídos y todavía no interpretados, desde el comienzo de una línea
+    pending: Vec<u8>,
+    /// Cantidad de líneas interpretadas
+    line: u64,
+}
+
+impl CsvValues {
+    /// Lee una parte del archivo, llamando a `f` con cada valor de las
+    /// líneas completas. Devuelve un error con el número de línea si alguna
+    /// no es un número finito
+    ///
+    /// # Argumentos
+    ///
+    /// * `bytes` siguiente parte del archivo
+    /// * `f` función que recibe cada valor
+    pub fn feed(&mut self, bytes: &[u8], mut f: impl FnMut(f64)) -> Result<(), String> {
+        let mut rest = bytes;
+        while let Some(end) = rest.iter().position(|&b| b == b'\n') {
+            self.pending.extend_from_slice(&rest[..end]);
+            rest = &rest[end + 1..];
+            let line = std::mem::take(&mut self.pending);
+            self.parse(&line, &mut f)?;
+        }
+        self.pending.extend_from_slice(rest);
+        Ok(())
+    }
+
+    /// Termina la lectura, con la última línea si no termina en salto de
+    /// línea
+    ///
+    /// # Argumentos
+    ///
+    /// * `f` función que recibe el último valor
+    pub fn finish(mut self, mut f: impl FnMut(f64)) -> Result<(), String> {
+        let line = std::mem::take(&mut self.pending);
+        self.parse(&line, &mut f)
+    }
+
+    /// Como `feed`, pero agrega los valores a un acumulador. Junta
+    /// PARSE_BATCH bytes y divide sus líneas completas en partes de
+    /// PARSE_BLOCK bytes, que con el feature `parallel` se interpretan en
+    /// paralelo, cada una con su acumulador; los acumuladores se combinan
+    /// en orden (ver `StreamingAccumulator::merge`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `bytes` siguiente parte del archivo
+    /// * `acc` acumulador al que se agregan los valores
+    pub fn feed_into(
+        &mut self,
+        bytes: &[u8],
+        acc: &mut StreamingAccumulator,
+    ) -> Result<(), String> {
+        self.pending.extend_from_slice(bytes);
+        if self.pending.len() < PARSE_BATCH {
+            return Ok(());
+        }
+        let Some(end) = self.pending.iter().rposition(|&b| b == b'\n') else {
+            return Ok(());
+        };
+        let rest = self.pending.split_off(end + 1);
+        let mut block = std::mem::replace(&mut self.pending, rest);
+        block.pop();
+        self.parse_block(&block, acc)
+    }
+
+    /// Como `finish`, pero agrega los valores que quedan a un acumulador
+    ///
+    /// # Argumentos
+    ///
+    /// * `acc` acumulador al que se agregan los valores
+    pub fn finish_into(mut self, acc: &mut StreamingAccumulator) -> Result<(), String> {
+        let block = std::mem::take(&mut self.pending);
+        self.parse_block(&block, acc)
+    }
+
+    // Función privada, interpreta una línea completa
+    fn parse(&mut self, line: &[u8], f: &mut impl FnMut(f64)) -> Result<(), String> {
+        self.line += 1;
+        match parse_line(line, self.line == 1) {
+            Ok(Some(x)) => {
+                f(x);
+                Ok(())
+            }
+            Ok(None) => Ok(()),
+            Err(field) => Err(invalid_line(self.line, &field)),
+        }
+    }
+
+    // Función privada, interpreta líneas completas, separadas por saltos de
+    // línea, dividiéndolas en partes de PARSE_BLOCK bytes
+    fn parse_block(&mut self, block: &[u8], acc: &mut StreamingAccumulator) -> Result<(), String> {
+        let mut parts = Vec::with_capacity(block.len() / PARSE_BLOCK + 1);
+        let mut rest = block;
+        while rest.len() > PARSE_BLOCK {
+            match rest[PARSE_BLOCK..].iter().position(|&b| b == b'\n') {
+                Some(i) => {
+                    parts.push(&rest[..PARSE_BLOCK + i]);
+                    rest = &rest[PARSE_BLOCK + i + 1..];
+                }
+                None => break,
+            }
+        }
+        parts.push(rest);
+
+        let header = self.line == 0;
+        let bins = acc.bins;
+        let parse = |(i, part): (usize, &&[u8])| parse_part(part, bins, header && i == 0);
+        #[cfg(feature = "parallel")]
+        let results: Vec<_> = parts.par_iter().enumerate().map(parse).collect();
+        #[cfg(not(feature = "parallel"))]
+        let results: Vec<_> = parts.iter().enumerate().map(parse).collect();
+        for result in results {
+            match result {
+                Ok((part, lines)) => {
+                    acc.merge(&part);
+                    self.line += lines;
+                }
+                Err((line, field)) => return Err(invalid_line(self.line + line, &field)),
+            }
+        }
+        Ok(())
+    }
+}
+
+// Función privada, valor de la primera columna de una línea, None si está
+// vacía o es el encabezado, o el campo si no es un número finito
+fn parse_line(line: &[u8], first: bool) -> Result<Option<f64>, String> {
+    let text = String::from_utf8_lossy(line);
+    let field = text.split(',').next().unwrap_or_default().trim();
+    if field.is_empty() {
+        return Ok(None);
+    }
+    match field.parse::<f64>() {
+        Ok(x) if x.is_finite() => Ok(Some(x)),
+        Err(_) if first => Ok(None),
+        _ => Err(field.to_string()),
+    }
+}
+
+// Función privada, acumula los valores de una parte del CSV. Devuelve el
+// acumulador y la cantidad de líneas, o la línea de la parte, contando
+// desde 1, y el campo que no es un número
+fn parse_part(
+    part: &[u8],
+    bins: StreamingBins,
+    header: bool,
+) -> Result<(StreamingAccumulator, u64), (u64, String)> {
+    let mut acc = StreamingAccumulator::new(bins);
+    let mut lines = 0;
+    for line in part.split(|&b| b == b'\n') {
+        lines += 1;
+        match parse_line(line, header && lines == 1) {
+            Ok(Some(x)) => acc.push(x),
+            Ok(None) => {}
+            Err(field) => return Err((lines, field)),
+        }
+    }
+    Ok((acc, lines))
+}
+
+// Función privada, error de una línea que no es un número
+fn invalid_line(line: u64, field: &str) -> String {
+    format!("la línea {} no es un número finito: {}", line, field)
+}
+
+/// Lee un CSV de valores de un `AsyncRead`, por ejemplo un archivo, en una
+/// sola pasada y con memoria acotada (ver `CsvValues::feed_into`)
+///
+/// # Argumentos
+///
+/// * `reader` origen del CSV
+/// * `acc` acumulador al que se agregan los valores
+#[cfg(feature = "server")]
+pub async fn read_csv<R: tokio::io::AsyncRead + Unpin>(
+    mut reader: R,
+    acc: &mut StreamingAccumulator,
+) -> Result<(), String> {
+    use tokio::io::AsyncReadExt;
+    let mut csv = CsvValues::default();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer).await.map_err(|e| e.to_string())?;
+        if read == 0 {
+            return csv.finish_into(acc);
+        }
+        csv.feed_into(&buffer[..read], acc)?;
+    }
+}
@@ -0,0 +1,202 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::chunked::{self, SampleChunks};
+use crate::float::Float;
+use crate::stats::descriptive::{summary, Summary};
+
+/// Forma de la muestra: los estadísticos descriptivos con los que `suggest`
+/// elige las familias de distribuciones candidatas
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Shape {
+    /// Cantidad, media, varianza, mínimo y máximo
+    pub summary: Summary,
+    /// Coeficiente de asimetría muestral, 0 en las distribuciones simétricas
+    pub skewness: f64,
+    /// Exceso de curtosis muestral, 0 en la Normal y -1.2 en la Uniforme
+    pub kurtosis: f64,
+    /// Coeficiente de variación, desvío / media, None si la media no es
+    /// positiva
+    pub cv: Option<f64>,
+    /// Si todos los valores son enteros
+    pub integer: bool,
+}
+
+/// Familia de distribuciones candidata. Beta y Lognormal no pueden
+/// generarse en simrng, pero se sugieren igual para orientar el ajuste
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Family {
+    Uniform,
+    Beta,
+    Normal,
+    Exponential,
+    Gamma,
+    Lognormal,
+    Poisson,
+}
+
+/// Familia candidata, con los parámetros estimados por el método de los
+/// momentos y la asimetría y curtosis que tendría con esos parámetros
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Candidate {
+    /// Familia de distribuciones
+    pub family: Family,
+    /// Parámetros estimados, con los nombres de los parámetros Json de la
+    /// distribución cuando simrng la genera
+    pub parameters: BTreeMap<&'static str, f64>,
+    /// Coeficiente de asimetría de la familia con esos parámetros
+    pub skewness: f64,
+    /// Exceso de curtosis de la familia con esos parámetros
+    pub kurtosis: f64,
+    /// Distancia euclídea entre la asimetría y curtosis de la muestra y las
+    /// de la familia; cuanto menor, más parecida es la forma
+    pub distance: f64,
+    /// Por qué se sugiere la familia
+    pub reason: String,
+}
+
+/// Calcula la forma de la muestra, recorriendo los valores dos veces por
+/// bloques. Devuelve None si hay menos de 2 valores o todos son iguales
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra
+pub fn shape<T: Float, S: SampleChunks<T> + ?Sized>(nums: &S) -> Option<Shape> {
+    let summary = summary(nums);
+    if summary.count < 2 || summary.min == summary.max {
+        return None;
+    }
+    // Momentos centrales 2, 3 y 4, con n en el denominador, como en
+    // `normality::jarque_bera`
+    let (mut m2, mut m3, mut m4) = (0f64, 0f64, 0f64);
+    let mut integer = true;
+    for x in chunked::values(nums) {
+        let x = x.to_f64();
+        integer &= x.fract() == 0f64;
+        let d = x - summary.mean;
+        let d2 = d * d;
+        m2 += d2;
+        m3 += d2 * d;
+        m4 += d2 * d2;
+    }
+    let n = summary.count as f64;
+    let (m2, m3, m4) = (m2 / n, m3 / n, m4 / n);
+    let cv = (summary.mean > 0f64).then(|| summary.variance.sqrt() / summary.mean);
+    Some(Shape {
+        skewness: m3 / m2.powf(1.5),
+        kurtosis: m4 / (m2 * m2) - 3f64,
+        cv,
+        integer,
+        summary,
+    })
+}
+
+/// Sugiere familias de distribuciones para la muestra a partir de su forma,
+/// como un gráfico de Cullen y Frey: cada familia compatible con el soporte
+/// de los valores (no negativos, positivos, enteros) se ajusta por el
+/// método de los momentos y se ordenan por la distancia entre su asimetría
+/// y curtosis y las de la muestra. Es una orientación previa al ajuste, no
+/// una prueba: la asimetría y sobre todo la curtosis muestrales varían
+/// mucho con pocos valores
+///
+/// # Argumentos
+///
+/// * `shape` forma de la muestra (ver `shape`)
+pub fn suggest(shape: &Shape) -> Vec<Candidate> {
+    let Summary { mean, variance, min, max, .. } = shape.summary;
+    let sd = variance.sqrt();
+    let mut candidates = Vec::new();
+    let mut push = |family: Family,
+                    parameters: &[(&'static str, f64)],
+                    skewness: f64,
+                    kurtosis: f64,
+                    reason: String| {
+        let distance = (shape.skewness - skewness).hypot(shape.kurtosis - kurtosis);
+        candidates.push(Candidate {
+            family,
+            parameters: parameters.iter().copied().collect(),
+            skewness,
+            kurtosis,
+            distance,
+            reason,
+        });
+    };
+
+    push(
+        Family::Uniform,
+        &[("lower", min), ("upper", max)],
+        0f64,
+        -1.2,
+        "acotada y simétrica, con colas livianas".to_string(),
+    );
+    push(
+        Family::Normal,
+        &[("mean", mean), ("sd", sd)],
+        0f64,
+        0f64,
+        "simétrica, sin exceso de curtosis".to_string(),
+    );
+    // Beta en [min, max] con la media y la varianza de la muestra
+    let width = max - min;
+    let (m, v) = ((mean - min) / width, variance / width / width);
+    let common = m * (1f64 - m) / v - 1f64;
+    if common > 0f64 {
+        let (a, b) = (m * common, (1f64 - m) * common);
+        let skewness =
+            2f64 * (b - a) * (a + b + 1f64).sqrt() / ((a + b + 2f64) * (a * b).sqrt());
+        let kurtosis = 6f64 * ((a - b).powi(2) * (a + b + 1f64) - a * b * (a + b + 2f64))
+            / (a * b * (a + b + 2f64) * (a + b + 3f64));
+        push(
+            Family::Beta,
+            &[("alpha", a), ("beta", b), ("lower", min), ("upper", max)],
+            skewness,
+            kurtosis,
+            "acotada, simétrica o asimétrica según alpha y beta".to_string(),
+        );
+    }
+    if let (Some(cv), true) = (shape.cv, min >= 0f64) {
+        push(
+            Family::Exponential,
+            &[("lambda", 1f64 / mean)],
+            2f64,
+            6f64,
+            format!(
+                "valores no negativos, coeficiente de variación {:.2} (1 en la Exponencial)",
+                cv
+            ),
+        );
+        let k = 1f64 / (cv * cv);
+        push(
+            Family::Gamma,
+            &[("shape", k), ("scale", variance / mean)],
+            2f64 * cv,
+            6f64 * cv * cv,
+            format!("valores no negativos y asimetría positiva, forma {:.2}", k),
+        );
+        if min > 0f64 {
+            let w = 1f64 + cv * cv;
+            let sigma = w.ln().sqrt();
+            push(
+                Family::Lognormal,
+                &[("mu", mean.ln() - sigma * sigma / 2f64), ("sigma", sigma)],
+                (w + 2f64) * (w - 1f64).sqrt(),
+                w.powi(4) + 2f64 * w.powi(3) + 3f64 * w * w - 6f64,
+                "valores positivos con cola derecha pesada".to_string(),
+            );
+        }
+        if shape.integer {
+            push(
+                Family::Poisson,
+                &[("lambda", mean)],
+                1f64 / mean.sqrt(),
+                1f64 / mean,
+                format!(
+                    "valores enteros no negativos, varianza / media = {:.2} (1 en la Poisson)",
+                    variance / mean
+                ),
+            );
+        }
+    }
+    candidates.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    candidates
+}
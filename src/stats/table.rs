@@ -0,0 +1,114 @@
+use std::fmt::Write;
+
+use crate::stats::{ChiInterval, TestResult};
+
+impl TestResult {
+    /// Tabla de cálculo de la prueba en CSV, con una fila por intervalo y las
+    /// columnas lower, upper, fo, fe, c y c_ac. Si la muestra tiene pesos se
+    /// agrega fo_weighted después de fo. El límite superior de la clase
+    /// abierta es inf, y los valores no calculados quedan vacíos
+    pub fn to_csv(&self) -> String {
+        let weighted = self.effective_size.is_some();
+        let mut res = String::from("lower,upper,fo,");
+        if weighted {
+            res.push_str("fo_weighted,");
+        }
+        res.push_str("fe,c,c_ac\n");
+        for interval in &self.intervals {
+            let upper = if interval.open_upper { "inf".into() } else { interval.upper.to_string() };
+            write!(res, "{},{},{},", interval.lower, upper, interval.fo).unwrap();
+            if weighted {
+                write!(res, "{},", optional(interval.fo_weighted, |x| x.to_string())).unwrap();
+            }
+            let (c, c_ac) = (interval.c, interval.c_ac);
+            let (c, c_ac) = (optional(c, |x| x.to_string()), optional(c_ac, |x| x.to_string()));
+            writeln!(res, "{},{},{}", interval.fe, c, c_ac).unwrap();
+        }
+        res
+    }
+
+    /// Tabla de cálculo de la prueba como un entorno tabular de LaTeX, para
+    /// pegar en un informe, con los valores redondeados a 4 decimales y el
+    /// estadístico calculado y el valor crítico al final. Los intervalos se
+    /// escriben con corchetes y paréntesis, [lower, upper), y la clase abierta
+    /// hasta ∞. Si la muestra tiene pesos, fo es la frecuencia ponderada que
+    /// usa la prueba
+    pub fn to_latex(&self) -> String {
+        let mut res = String::from("\\begin{tabular}{rrrrrr}\n\\hline\n");
+        res.push_str("Desde & Hasta & $f_o$ & $f_e$ & $C$ & $C_{ac}$ \\\\\n\\hline\n");
+        for interval in &self.intervals {
+            writeln!(
+                res,
+                "[{:.4} & {} & {} & {:.4} & {} & {} \\\\",
+                interval.lower,
+                upper_bound(interval, "$\\infty$"),
+                match interval.fo_weighted {
+                    Some(fo) => format!("{:.4}", fo),
+                    None => interval.fo.to_string(),
+                },
+                interval.fe,
+                optional(interval.c, |x| format!("{:.4}", x)),
+                optional(interval.c_ac, |x| format!("{:.4}", x)),
+            )
+            .unwrap();
+        }
+        res.push_str("\\hline\n");
+        for (name, value) in [("calculado", self.calculated), ("crítico", self.critical)] {
+            writeln!(res, "\\multicolumn{{5}}{{r}}{{$\\chi^2$ {}}} & {:.4} \\\\", name, value)
+                .unwrap();
+        }
+        res.push_str("\\hline\n\\end{tabular}\n");
+        res
+    }
+
+    /// Tabla de cálculo de la prueba como tabla HTML, para incluir en un
+    /// informe, con los mismos valores redondeados e intervalos que
+    /// `to_latex` y el estadístico calculado y el valor crítico al pie
+    pub fn to_html(&self) -> String {
+        let mut res = String::from("<table>\n<thead>\n");
+        res.push_str(
+            "<tr><th>Desde</th><th>Hasta</th><th>f<sub>o</sub></th><th>f<sub>e</sub></th>\
+             <th>C</th><th>C<sub>ac</sub></th></tr>\n</thead>\n<tbody>\n",
+        );
+        for interval in &self.intervals {
+            writeln!(
+                res,
+                "<tr><td>[{:.4}</td><td>{}</td><td>{}</td><td>{:.4}</td><td>{}</td><td>{}</td></tr>",
+                interval.lower,
+                upper_bound(interval, "∞"),
+                match interval.fo_weighted {
+                    Some(fo) => format!("{:.4}", fo),
+                    None => interval.fo.to_string(),
+                },
+                interval.fe,
+                optional(interval.c, |x| format!("{:.4}", x)),
+                optional(interval.c_ac, |x| format!("{:.4}", x)),
+            )
+            .unwrap();
+        }
+        res.push_str("</tbody>\n<tfoot>\n");
+        for (name, value) in [("calculado", self.calculated), ("crítico", self.critical)] {
+            writeln!(res, "<tr><th colspan=\"5\">χ² {}</th><td>{:.4}</td></tr>", name, value)
+                .unwrap();
+        }
+        res.push_str("</tfoot>\n</table>\n");
+        res
+    }
+}
+
+// Función privada, texto de un valor opcional, vacío si es None
+fn optional(value: Option<f64>, text: impl Fn(f64) -> String) -> String {
+    value.map_or(String::new(), text)
+}
+
+// Función privada, límite superior de un intervalo: `infinity` en la clase
+// abierta, cerrado en el intervalo final cerrado y abierto en los demás
+fn upper_bound(interval: &ChiInterval, infinity: &str) -> String {
+    if interval.open_upper {
+        format!("{})", infinity)
+    } else if interval.upper_closed {
+        format!("{:.4}]", interval.upper)
+    } else {
+        format!("{:.4})", interval.upper)
+    }
+}
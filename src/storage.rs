@@ -0,0 +1,739 @@
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Serialize, Serializer};
+use simrng::dist::exponential::Exponential;
+use simrng::dist::gamma::Gamma;
+use simrng::dist::normal::{Algorithm, Normal};
+use simrng::dist::poisson::Poisson;
+use simrng::dist::table::TabulatedCdf;
+use simrng::dist::uniform::Uniform;
+use simrng::dist::Distribution;
+use simrng::rng::{LinearCongruentialGenerator, LCG_A, LCG_C, LCG_M};
+use simrng::sampling::{self, Stratum};
+use simrng::transform::{Operation, TransformedDistribution, TruncatedDistribution};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::controllers::DistributionType;
+use crate::datasets::{Precision, Samples};
+use crate::history::Metadata;
+
+/// Distribución con sus parámetros, serializable para guardarse junto a los
+/// datos generados
+#[derive(Serialize, Deserialize, Clone)]
+pub enum StoredDistribution {
+    Normal(Normal),
+    Uniform(Uniform),
+    Exponential(Exponential),
+    Poisson(Poisson),
+    Gamma(Gamma),
+    Tabulated(TabulatedCdf),
+}
+
+impl StoredDistribution {
+    /// Interpreta los parámetros recibidos en Json según el tipo de distribución
+    ///
+    /// # Argumentos
+    ///
+    /// * `kind` tipo de distribución
+    /// * `data` parámetros de la distribución, en Json
+    pub fn from_parameters(
+        kind: &DistributionType,
+        data: &serde_json::Value,
+    ) -> Result<Self, serde_json::Error> {
+        let data = data.clone();
+        Ok(match kind {
+            DistributionType::Normal => StoredDistribution::Normal(serde_json::from_value(data)?),
+            DistributionType::Uniform => StoredDistribution::Uniform(serde_json::from_value(data)?),
+            DistributionType::Exponential => {
+                StoredDistribution::Exponential(serde_json::from_value(data)?)
+            }
+            DistributionType::Poisson => StoredDistribution::Poisson(serde_json::from_value(data)?),
+            DistributionType::Gamma => StoredDistribution::Gamma(serde_json::from_value(data)?),
+            DistributionType::Tabulated => {
+                StoredDistribution::Tabulated(serde_json::from_value(data)?)
+            }
+        })
+    }
+
+    /// Devuelve la distribución como objeto de interfaz, para el estado
+    pub fn boxed(self) -> Box<dyn Distribution + Send + Sync> {
+        match self {
+            StoredDistribution::Normal(d) => Box::new(d),
+            StoredDistribution::Uniform(d) => Box::new(d),
+            StoredDistribution::Exponential(d) => Box::new(d),
+            StoredDistribution::Poisson(d) => Box::new(d),
+            StoredDistribution::Gamma(d) => Box::new(d),
+            StoredDistribution::Tabulated(d) => Box::new(d),
+        }
+    }
+
+    /// Devuelve la media y la varianza de la distribución
+    pub fn moments(&self) -> (f64, f64) {
+        match self {
+            StoredDistribution::Normal(d) => (d.mean, d.sd.powi(2)),
+            StoredDistribution::Uniform(d) => ((d.lower + d.upper) / 2f64, (d.upper - d.lower).powi(2) / 12f64),
+            StoredDistribution::Exponential(d) => (d.mean(), 1f64 / d.lambda.powi(2)),
+            StoredDistribution::Poisson(d) => (d.lambda, d.lambda),
+            StoredDistribution::Gamma(d) => (d.mean(), d.variance()),
+            StoredDistribution::Tabulated(d) => d.moments(),
+        }
+    }
+
+    /// Nombre del tipo de distribución, el de `DistributionType`
+    pub fn name(&self) -> &'static str {
+        match self {
+            StoredDistribution::Normal(_) => "Normal",
+            StoredDistribution::Uniform(_) => "Uniform",
+            StoredDistribution::Exponential(_) => "Exponential",
+            StoredDistribution::Poisson(_) => "Poisson",
+            StoredDistribution::Gamma(_) => "Gamma",
+            StoredDistribution::Tabulated(_) => "Tabulated",
+        }
+    }
+
+    /// Si cada valor se genera con un único número uniforme, por la
+    /// transformada inversa, por lo que saltar n valores es saltar n uniformes
+    pub fn inverse_transform(&self) -> bool {
+        matches!(
+            self,
+            StoredDistribution::Uniform(_)
+                | StoredDistribution::Exponential(_)
+                | StoredDistribution::Tabulated(_)
+        )
+    }
+}
+
+/// Modo de generación de los valores
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub enum GenerationMode {
+    /// Valores independientes con la distribución indicada
+    #[default]
+    Independent,
+    /// Serie autorregresiva AR(1), Xₜ = φ·Xₜ₋₁ + εₜ, con innovaciones εₜ de
+    /// la distribución indicada
+    Autoregressive { phi: f64 },
+    /// Muestreo estratificado: los uniformes se dividen en `strata` estratos
+    /// de igual probabilidad, con asignación proporcional, antes de aplicar
+    /// la transformada inversa de la distribución
+    Stratified { strata: usize },
+}
+
+impl GenerationMode {
+    /// Verifica que los parámetros del modo sean válidos
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            GenerationMode::Autoregressive { phi } if phi.abs() >= 1f64 => {
+                Err("phi debe estar entre -1 y 1 para que la serie sea estacionaria".into())
+            }
+            GenerationMode::Stratified { strata: 0 } => {
+                Err("la cantidad de estratos debe ser mayor a 0".into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Transforma, en el lugar, los valores independientes generados con la
+    /// distribución en los valores del modo. Los valores pueden transformarse
+    /// por bloques, indicando el último valor del bloque anterior
+    ///
+    /// # Argumentos
+    ///
+    /// * `distribution` distribución de los valores generados
+    /// * `res` valores generados
+    /// * `previous` último valor transformado del bloque anterior, None en el primero
+    pub fn apply(&self, distribution: &StoredDistribution, res: &mut [f64], previous: Option<f64>) {
+        if let GenerationMode::Autoregressive { phi } = *self {
+            let Some(first) = res.first_mut() else { return };
+            match previous {
+                Some(previous) => *first += phi * previous,
+                None => {
+                    // El primer valor se lleva a la media y varianza
+                    // estacionarias, para que la serie no tenga período de
+                    // calentamiento
+                    let (mean, _) = distribution.moments();
+                    *first = mean / (1f64 - phi) + (*first - mean) / (1f64 - phi * phi).sqrt();
+                }
+            }
+            for t in 1..res.len() {
+                res[t] += phi * res[t - 1];
+            }
+        }
+    }
+
+    /// Estratos del muestreo estratificado, con la cantidad de valores
+    /// asignada a cada uno. None en los demás modos
+    ///
+    /// # Argumentos
+    ///
+    /// * `number` cantidad de valores generados
+    /// * `distribution` distribución de los valores
+    pub fn strata(&self, number: u64, distribution: &StoredDistribution) -> Option<Vec<Stratum>> {
+        match *self {
+            GenerationMode::Stratified { strata } => {
+                Some(sampling::strata(number as usize, strata, distribution.clone().boxed().as_ref()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Distribución de los valores generados, contra la que se hace la prueba
+    /// de chi cuadrado. En AR(1) es la distribución estacionaria: exacta para
+    /// innovaciones normales, y una Normal con la misma media y varianza para
+    /// las demás
+    ///
+    /// # Argumentos
+    ///
+    /// * `distribution` distribución de los valores independientes
+    pub fn tested_distribution(&self, distribution: &StoredDistribution) -> StoredDistribution {
+        match *self {
+            GenerationMode::Independent | GenerationMode::Stratified { .. } => distribution.clone(),
+            GenerationMode::Autoregressive { phi } => {
+                let (mean, variance) = distribution.moments();
+                StoredDistribution::Normal(Normal {
+                    algorithm: Algorithm::BoxMuller,
+                    mean: mean / (1f64 - phi),
+                    sd: (variance / (1f64 - phi * phi)).sqrt(),
+                })
+            }
+        }
+    }
+}
+
+/// Qué se hace con los valores generados que caen fuera de los límites
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum BoundsMethod {
+    /// Se llevan al límite más cercano, que acumula la probabilidad de
+    /// todos los valores de ese lado
+    #[default]
+    Clamp,
+    /// Se descartan y se generan otros en su lugar, hasta completar la
+    /// cantidad pedida: la distribución queda truncada
+    Reject,
+}
+
+/// Límites de los valores generados, por ejemplo para que una Normal no
+/// dé tiempos de servicio negativos. Sin uno de los límites, ese lado no
+/// se limita
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    /// Qué se hace con los valores fuera de los límites, llevarlos al
+    /// límite si no se indica
+    #[serde(default)]
+    pub method: BoundsMethod,
+}
+
+/// Menor probabilidad de caer dentro de los límites con la que se admite
+/// descartar y volver a generar los valores: con menos, se generarían más
+/// de cien valores por cada uno guardado
+pub const MIN_ACCEPTANCE: f64 = 0.01;
+
+impl Bounds {
+    /// Verifica que haya al menos un límite, que sean finitos y que min sea
+    /// menor a max
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min.is_none() && self.max.is_none() {
+            return Err("los límites deben indicar min, max o ambos".into());
+        }
+        if self.min.is_some_and(|min| !min.is_finite())
+            || self.max.is_some_and(|max| !max.is_finite())
+        {
+            return Err("los límites deben ser finitos".into());
+        }
+        if let (Some(min), Some(max)) = (self.min, self.max) {
+            if min >= max {
+                return Err("min debe ser menor a max".into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Si el valor está dentro de los límites, incluidos
+    pub fn contains(&self, x: f64) -> bool {
+        self.min.is_none_or(|min| x >= min) && self.max.is_none_or(|max| x <= max)
+    }
+
+    /// Distribución de los valores limitados, a partir de la de los valores
+    /// sin limitar: con masa en los límites al llevarlos al límite, o
+    /// truncada al descartarlos
+    ///
+    /// # Argumentos
+    ///
+    /// * `dist` distribución de los valores sin limitar
+    pub fn distribution(
+        &self,
+        dist: Box<dyn Distribution + Send + Sync>,
+    ) -> Box<dyn Distribution + Send + Sync> {
+        match self.method {
+            BoundsMethod::Clamp => Box::new(TransformedDistribution::new(
+                Arc::new(dist),
+                Operation::Clip { lower: self.min, upper: self.max },
+            )),
+            BoundsMethod::Reject => {
+                Box::new(TruncatedDistribution::new(Arc::new(dist), self.min, self.max))
+            }
+        }
+    }
+}
+
+/// Parámetros con los que se generó un conjunto de datos, para registrarlo
+/// y poder repetirlo
+#[derive(Clone)]
+pub struct Experiment {
+    pub seed: u64,
+    pub distribution: StoredDistribution,
+    pub mode: GenerationMode,
+    /// Cantidad de valores iniciales descartados antes de guardar los datos
+    pub discard: u64,
+    /// Generador de números aleatorios uniformes
+    pub generator: Generator,
+    /// Cantidad de decimales a los que se redondean los valores guardados,
+    /// None para no redondearlos
+    pub decimals: Option<u8>,
+    /// Límites de los valores guardados, None para no limitarlos
+    pub bounds: Option<Bounds>,
+}
+
+impl Experiment {
+    /// Verifica que los parámetros del modo y del generador sean válidos,
+    /// que el descarte inicial pueda aplicarse al modo, que la cantidad de
+    /// decimales no supere `transform::MAX_DECIMALS` y que los límites sean
+    /// válidos. Para descartar los valores fuera de los límites, el modo no
+    /// puede ser el muestreo estratificado y la probabilidad de caer dentro
+    /// debe ser al menos `MIN_ACCEPTANCE`
+    pub fn validate(&self) -> Result<(), String> {
+        self.mode.validate()?;
+        self.generator.validate()?;
+        if self.discard > 0 && matches!(self.mode, GenerationMode::Stratified { .. }) {
+            return Err("el muestreo estratificado no admite descartar valores iniciales".into());
+        }
+        if let Some(rounding) = self.rounding() {
+            rounding.validate()?;
+        }
+        if let Some(bounds) = self.bounds {
+            bounds.validate()?;
+        }
+        if self.rejection().is_some() {
+            if matches!(self.mode, GenerationMode::Stratified { .. }) {
+                return Err(
+                    "el muestreo estratificado no admite descartar valores fuera de los límites".into(),
+                );
+            }
+            let acceptance = self.acceptance();
+            if acceptance.is_nan() || acceptance < MIN_ACCEPTANCE {
+                return Err(format!(
+                    "la probabilidad de caer dentro de los límites es {}, menor a {}",
+                    acceptance, MIN_ACCEPTANCE
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Límites de los valores guardados si los que caen fuera se descartan
+    /// y se vuelven a generar
+    pub fn rejection(&self) -> Option<Bounds> {
+        self.bounds.filter(|bounds| bounds.method == BoundsMethod::Reject)
+    }
+
+    /// Proporción esperada de valores generados que se guardan: la
+    /// probabilidad de caer dentro de los límites si los de fuera se
+    /// descartan, 1 si no
+    pub fn acceptance(&self) -> f64 {
+        let Some(bounds) = self.rejection() else { return 1f64 };
+        let dist = self.mode.tested_distribution(&self.distribution).boxed();
+        TruncatedDistribution::new(Arc::new(dist), bounds.min, bounds.max).acceptance()
+    }
+
+    /// Termina los valores generados antes de guardarlos: los lleva a los
+    /// límites si se indicó (los descartados se quitan al generarlos, ver
+    /// `controllers::generate_bounded`) y los redondea
+    ///
+    /// # Argumentos
+    ///
+    /// * `values` valores a terminar, en el lugar
+    pub fn finish(&self, values: &mut [f64]) {
+        if let Some(Bounds { min, max, method: BoundsMethod::Clamp }) = self.bounds {
+            let clip = Operation::Clip { lower: min, upper: max };
+            values.iter_mut().for_each(|x| *x = clip.apply(*x));
+        }
+        self.round(values);
+    }
+
+    /// Operación con la que se redondean los valores guardados, si se
+    /// indicaron los decimales
+    pub fn rounding(&self) -> Option<Operation> {
+        self.decimals.map(|decimals| Operation::Round { decimals })
+    }
+
+    /// Redondea los valores a la cantidad de decimales indicada, si se indicó
+    ///
+    /// # Argumentos
+    ///
+    /// * `values` valores a redondear, en el lugar
+    pub fn round(&self, values: &mut [f64]) {
+        if let Some(rounding) = self.rounding() {
+            values.iter_mut().for_each(|x| *x = rounding.apply(*x));
+        }
+    }
+
+    /// Distribución contra la que se prueban los valores guardados: la del
+    /// modo (ver `GenerationMode::tested_distribution`), limitada si se
+    /// indicaron los límites (ver `Bounds::distribution`) y con el redondeo
+    /// si se indicaron los decimales, para que los intervalos tengan en
+    /// cuenta que los valores son discretos
+    pub fn tested(&self) -> Box<dyn Distribution + Send + Sync> {
+        let tested = self.mode.tested_distribution(&self.distribution).boxed();
+        let tested = match self.bounds {
+            Some(bounds) => bounds.distribution(tested),
+            None => tested,
+        };
+        match self.rounding() {
+            Some(rounding) => Box::new(TransformedDistribution::new(Arc::new(tested), rounding)),
+            None => tested,
+        }
+    }
+}
+
+/// Versión de simrng, que se registra con cada generación
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Generador de números aleatorios uniformes de la generación
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Generator {
+    /// xoshiro256++, con un subflujo por bloque en los pedidos grandes (ver
+    /// `rng::fill_chunked`)
+    #[default]
+    Xoshiro256PlusPlus,
+    /// Generador congruencial lineal con los parámetros indicados, por
+    /// ejemplo para repetir una secuencia calculada a mano. Los que no se
+    /// indican son los de `LinearCongruentialGenerator::with_seed`. Genera un
+    /// único flujo, sin subflujos por bloque
+    Lcg {
+        #[serde(default = "lcg_m")]
+        m: u64,
+        #[serde(default = "lcg_a")]
+        a: u64,
+        #[serde(default = "lcg_c")]
+        c: u64,
+    },
+}
+
+impl Generator {
+    /// Verifica los parámetros del generador: en el congruencial, m > 0,
+    /// a < m y c < m
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Generator::Lcg { m: 0, .. } => Err("m debe ser mayor a 0".into()),
+            Generator::Lcg { m, a, c } if a >= m || c >= m => {
+                Err("a y c deben ser menores a m".into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Generador congruencial con la semilla indicada, None si el generador
+    /// no es congruencial
+    ///
+    /// # Argumentos
+    ///
+    /// * `seed` semilla del generador, x0
+    pub fn lcg(&self, seed: u64) -> Option<LinearCongruentialGenerator> {
+        match *self {
+            Generator::Lcg { m, a, c } => Some(LinearCongruentialGenerator::new(seed, m, a, c)),
+            Generator::Xoshiro256PlusPlus => None,
+        }
+    }
+}
+
+// Funciones privadas, parámetros por defecto del generador congruencial
+fn lcg_m() -> u64 {
+    LCG_M
+}
+
+fn lcg_a() -> u64 {
+    LCG_A
+}
+
+fn lcg_c() -> u64 {
+    LCG_C
+}
+
+/// Manifiesto de reproducibilidad de una generación: todo lo necesario para
+/// volver a generar exactamente los mismos valores. Se devuelve al generar y
+/// se acepta en `/reproduce` para repetir la generación
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Reproducibility {
+    /// Semilla del generador
+    pub seed: u64,
+    /// Generador de números aleatorios uniformes
+    #[serde(default)]
+    pub generator: Generator,
+    /// Distribución con sus parámetros
+    pub distribution: StoredDistribution,
+    /// Modo de generación
+    #[serde(default)]
+    pub mode: GenerationMode,
+    /// Cantidad de valores iniciales descartados
+    #[serde(default)]
+    pub discard: u64,
+    /// Cantidad de valores generados
+    pub number: u64,
+    /// Precisión con la que se guardaron los valores
+    #[serde(default)]
+    pub precision: Precision,
+    /// Cantidad de decimales a los que se redondearon los valores
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+    /// Límites de los valores
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<Bounds>,
+    /// Versión de simrng con la que se generaron los valores. Con otra
+    /// versión los valores pueden no ser idénticos
+    pub version: String,
+}
+
+impl Reproducibility {
+    /// Manifiesto de una generación con esta versión de simrng
+    ///
+    /// # Argumentos
+    ///
+    /// * `experiment` semilla, distribución y modo de generación
+    /// * `number` cantidad de valores generados
+    /// * `precision` precisión con la que se guardaron los valores
+    pub fn new(experiment: &Experiment, number: u64, precision: Precision) -> Self {
+        Reproducibility {
+            seed: experiment.seed,
+            generator: experiment.generator,
+            distribution: experiment.distribution.clone(),
+            mode: experiment.mode,
+            discard: experiment.discard,
+            number,
+            precision,
+            decimals: experiment.decimals,
+            bounds: experiment.bounds,
+            version: VERSION.into(),
+        }
+    }
+
+    /// Parámetros con los que se repite la generación
+    pub fn experiment(&self) -> Experiment {
+        Experiment {
+            seed: self.seed,
+            distribution: self.distribution.clone(),
+            mode: self.mode,
+            discard: self.discard,
+            generator: self.generator,
+            decimals: self.decimals,
+            bounds: self.bounds,
+        }
+    }
+}
+
+/// Último experimento leído del disco
+pub struct Snapshot {
+    /// Semilla con la que se generaron los datos
+    pub seed: u64,
+    /// Distribución del modo de generación (ver
+    /// `GenerationMode::tested_distribution`), sin los límites ni el redondeo
+    pub distribution: StoredDistribution,
+    /// Manifiesto de la generación, None si lo guardó una versión anterior
+    pub manifest: Option<Reproducibility>,
+    /// Etiqueta, descripción y etiquetas del conjunto de datos
+    pub metadata: Metadata,
+    /// Vector de números generados
+    pub data: Vec<f64>,
+}
+
+impl Snapshot {
+    /// Distribución contra la que se prueban los valores: la del manifiesto,
+    /// con los límites y el redondeo (ver `Experiment::tested`), o la del
+    /// modo si el experimento lo guardó una versión anterior
+    pub fn tested(&self) -> Box<dyn Distribution + Send + Sync> {
+        match &self.manifest {
+            Some(manifest) => manifest.experiment().tested(),
+            None => self.distribution.clone().boxed(),
+        }
+    }
+}
+
+impl Snapshot {
+    /// Experimento recuperado con su manifiesto
+    ///
+    /// # Argumentos
+    ///
+    /// * `manifest` manifiesto de la generación
+    /// * `metadata` etiqueta, descripción y etiquetas del conjunto de datos
+    /// * `data` valores generados
+    pub fn new(manifest: Reproducibility, metadata: Metadata, data: Vec<f64>) -> Self {
+        Snapshot {
+            seed: manifest.seed,
+            distribution: manifest.mode.tested_distribution(&manifest.distribution),
+            manifest: Some(manifest),
+            metadata,
+            data,
+        }
+    }
+}
+
+/// Comienzo de los archivos guardados con el manifiesto. Los de versiones
+/// anteriores empiezan directamente con la semilla
+const MAGIC: [u8; 8] = *b"SIMRNG\x00\x02";
+
+/// Formato de los experimentos guardados por las versiones anteriores, sin
+/// manifiesto ni metadatos
+#[derive(Deserialize)]
+struct LegacySnapshot {
+    seed: u64,
+    distribution: StoredDistribution,
+    data: Vec<f64>,
+}
+
+/// Experimento guardado después de `MAGIC`. El manifiesto y los metadatos
+/// van en JSON, porque bincode no admite los campos que se omiten al
+/// serializar ni los parámetros alternativos de las distribuciones
+#[derive(Deserialize)]
+struct StoredSnapshot {
+    manifest: String,
+    metadata: String,
+    data: Vec<f64>,
+}
+
+/// Versión por referencia de StoredSnapshot, para escribir sin copiar los datos
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    manifest: String,
+    metadata: String,
+    data: SamplesRef<'a>,
+}
+
+/// Valores del conjunto de datos, que se serializan en f64 leyéndolos por
+/// bloques, con el mismo formato que un Vec<f64>
+struct SamplesRef<'a>(&'a Samples);
+
+impl Serialize for SamplesRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for x in self.0.values_f64() {
+            seq.serialize_element(&x)?;
+        }
+        seq.end()
+    }
+}
+
+/// Guarda el experimento en el archivo indicado, con su manifiesto y sus
+/// metadatos para recuperarlo tal como estaba. Escribe primero a un archivo
+/// temporal, con un nombre distinto en cada guardado, y luego lo renombra,
+/// para no dejar un archivo a medio escribir aunque haya dos guardados a la vez
+///
+/// # Argumentos
+///
+/// * `path` ruta del archivo a escribir
+/// * `manifest` manifiesto de la generación
+/// * `metadata` etiqueta, descripción y etiquetas del conjunto de datos
+/// * `data` números generados, que se guardan en f64
+pub fn save(
+    path: &Path,
+    manifest: &Reproducibility,
+    metadata: &Metadata,
+    data: &Samples,
+) -> Result<(), bincode::Error> {
+    let tmp = temporary(path);
+    let res = (|| {
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        let (manifest, metadata) = encode_manifest(manifest, metadata)?;
+        writer.write_all(&MAGIC)?;
+        let snapshot = SnapshotRef { manifest, metadata, data: SamplesRef(data) };
+        bincode::serialize_into(&mut writer, &snapshot)?;
+        writer.flush()?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    })();
+    if res.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    res
+}
+
+// Función privada, ruta del archivo temporal de un guardado, junto al
+// archivo final y única para cada guardado del proceso
+fn temporary(path: &Path) -> PathBuf {
+    static SAVES: AtomicU64 = AtomicU64::new(0);
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        SAVES.fetch_add(1, Ordering::Relaxed)
+    ));
+    path.with_file_name(name)
+}
+
+/// Lee un experimento serializado por una versión anterior, sin manifiesto
+/// ni metadatos, como los de las bases SQLite de versiones anteriores
+///
+/// # Argumentos
+///
+/// * `bytes` experimento serializado
+pub fn decode(bytes: &[u8]) -> Result<Snapshot, bincode::Error> {
+    let LegacySnapshot { seed, distribution, data } = bincode::deserialize(bytes)?;
+    Ok(Snapshot { seed, distribution, manifest: None, metadata: Metadata::default(), data })
+}
+
+/// Lee el experimento guardado en el archivo indicado. Los archivos de
+/// versiones anteriores, sin manifiesto, se leen igual
+///
+/// # Argumentos
+///
+/// * `path` ruta del archivo a leer
+pub fn load(path: &Path) -> Result<Snapshot, bincode::Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        // Archivo de una versión anterior, que empieza con la semilla
+        let LegacySnapshot { seed, distribution, data } =
+            bincode::deserialize_from((&magic[..]).chain(reader))?;
+        let metadata = Metadata::default();
+        return Ok(Snapshot { seed, distribution, manifest: None, metadata, data });
+    }
+    let StoredSnapshot { manifest, metadata, data } = bincode::deserialize_from(reader)?;
+    let (manifest, metadata) = decode_manifest(&manifest, &metadata)?;
+    Ok(Snapshot::new(manifest, metadata, data))
+}
+
+/// Serializa en JSON el manifiesto y los metadatos, como se guardan junto a
+/// los valores
+///
+/// # Argumentos
+///
+/// * `manifest` manifiesto de la generación
+/// * `metadata` etiqueta, descripción y etiquetas del conjunto de datos
+pub fn encode_manifest(
+    manifest: &Reproducibility,
+    metadata: &Metadata,
+) -> Result<(String, String), bincode::Error> {
+    let json = |e: serde_json::Error| bincode::ErrorKind::Custom(e.to_string());
+    let manifest = serde_json::to_string(manifest).map_err(json)?;
+    Ok((manifest, serde_json::to_string(metadata).map_err(json)?))
+}
+
+/// Lee el manifiesto y los metadatos serializados con `encode_manifest`
+///
+/// # Argumentos
+///
+/// * `manifest` manifiesto en JSON
+/// * `metadata` metadatos en JSON
+pub fn decode_manifest(
+    manifest: &str,
+    metadata: &str,
+) -> Result<(Reproducibility, Metadata), bincode::Error> {
+    let json = |e: serde_json::Error| bincode::ErrorKind::Custom(e.to_string());
+    let manifest = serde_json::from_str(manifest).map_err(json)?;
+    Ok((manifest, serde_json::from_str(metadata).map_err(json)?))
+}
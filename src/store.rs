@@ -0,0 +1,692 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use simrng::chunked::CHUNK_LEN;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::auth::Client;
+use crate::config::Config;
+use crate::datasets::Samples;
+use crate::history::Metadata;
+use crate::storage::{self, Reproducibility, Snapshot};
+
+/// Error de un almacenamiento de experimentos
+pub type StoreError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Dónde se persiste el último experimento generado de cada cliente, para
+/// recuperarlo al reiniciar el servidor. Los conjuntos de datos en uso se
+/// mantienen en memoria (ver `Workspaces`); las estadísticas y el listado de
+/// conjuntos recuperan a través de este trait el experimento de un cliente
+/// que no tiene ninguno en memoria (ver `controllers::stored_datasets`)
+pub trait DatasetStore: Send + Sync {
+    /// Guarda el experimento como el último del cliente, reemplazando el
+    /// anterior, con su manifiesto para probarlo al recuperarlo contra la
+    /// misma distribución (ver `Snapshot::tested`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `client` cliente que realizó la generación
+    /// * `manifest` manifiesto de la generación
+    /// * `metadata` etiqueta, descripción y etiquetas del conjunto de datos
+    /// * `data` valores generados
+    fn save(
+        &self,
+        client: &Client,
+        manifest: &Reproducibility,
+        metadata: &Metadata,
+        data: &Samples,
+    ) -> Result<(), StoreError>;
+
+    /// Devuelve el último experimento guardado de cada cliente
+    fn load(&self) -> Result<Vec<(Client, Snapshot)>, StoreError>;
+
+    /// Devuelve el último experimento guardado del cliente, si lo hay
+    ///
+    /// # Argumentos
+    ///
+    /// * `client` cliente que realizó la generación
+    fn get(&self, client: &Client) -> Result<Option<Snapshot>, StoreError>;
+
+    /// Borra el experimento guardado del cliente, si lo hay
+    ///
+    /// # Argumentos
+    ///
+    /// * `client` cliente cuyos datos se descartan
+    fn remove(&self, client: &Client) -> Result<(), StoreError>;
+}
+
+/// Almacenamiento compartido por el servidor. Los experimentos se guardan en
+/// segundo plano, así que ordena los guardados y los borrados de cada
+/// cliente: cada guardado recibe un turno al pedirse (ver `ticket`) y se
+/// descarta si, al escribirse, ya se escribió uno posterior o se borraron los
+/// datos del cliente después de pedirlo. Los guardados y los borrados se
+/// hacen de a uno
+pub struct Persistence {
+    /// Almacenamiento configurado
+    store: Box<dyn DatasetStore>,
+    /// Turnos de cada cliente
+    turns: Mutex<HashMap<Client, Turns>>,
+}
+
+// Turnos de los guardados de un cliente
+#[derive(Default)]
+struct Turns {
+    // Último turno entregado
+    issued: u64,
+    // Turno del último guardado escrito, o el último entregado antes de un
+    // borrado
+    written: u64,
+}
+
+impl Persistence {
+    /// Crea el almacenamiento compartido
+    ///
+    /// # Argumentos
+    ///
+    /// * `store` almacenamiento configurado (ver `open`)
+    pub fn new(store: Box<dyn DatasetStore>) -> Self {
+        Self { store, turns: Mutex::new(HashMap::new()) }
+    }
+
+    /// Devuelve el turno de un guardado del cliente. Debe pedirse al
+    /// reemplazar sus datos en memoria, antes de pasar a segundo plano
+    ///
+    /// # Argumentos
+    ///
+    /// * `client` cliente que realizó la generación
+    pub fn ticket(&self, client: &Client) -> u64 {
+        let mut turns = self.turns.lock().unwrap();
+        let turns = turns.entry(client.clone()).or_default();
+        turns.issued += 1;
+        turns.issued
+    }
+
+    /// Guarda el experimento como el último del cliente, salvo que el turno
+    /// haya quedado viejo (ver `DatasetStore::save`)
+    ///
+    /// # Argumentos
+    ///
+    /// * `ticket` turno del guardado (ver `ticket`)
+    /// * `client` cliente que realizó la generación
+    /// * `manifest` manifiesto de la generación
+    /// * `metadata` etiqueta, descripción y etiquetas del conjunto de datos
+    /// * `data` valores generados
+    pub fn save(
+        &self,
+        ticket: u64,
+        client: &Client,
+        manifest: &Reproducibility,
+        metadata: &Metadata,
+        data: &Samples,
+    ) -> Result<(), StoreError> {
+        let mut turns = self.turns.lock().unwrap();
+        let turns = turns.entry(client.clone()).or_default();
+        if ticket <= turns.written {
+            return Ok(());
+        }
+        self.store.save(client, manifest, metadata, data)?;
+        turns.written = ticket;
+        Ok(())
+    }
+
+    /// Devuelve el último experimento guardado de cada cliente
+    pub fn load(&self) -> Result<Vec<(Client, Snapshot)>, StoreError> {
+        self.store.load()
+    }
+
+    /// Devuelve el último experimento guardado del cliente, si lo hay
+    ///
+    /// # Argumentos
+    ///
+    /// * `client` cliente que realizó la generación
+    pub fn get(&self, client: &Client) -> Result<Option<Snapshot>, StoreError> {
+        self.store.get(client)
+    }
+
+    /// Borra el experimento guardado del cliente, y descarta los guardados
+    /// pedidos antes que todavía no se escribieron
+    ///
+    /// # Argumentos
+    ///
+    /// * `client` cliente cuyos datos se descartan
+    pub fn remove(&self, client: &Client) -> Result<(), StoreError> {
+        let mut turns = self.turns.lock().unwrap();
+        let turns = turns.entry(client.clone()).or_default();
+        turns.written = turns.issued;
+        self.store.remove(client)
+    }
+}
+
+/// Tipo de almacenamiento de los experimentos
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreKind {
+    /// Sólo en memoria, no se recupera nada al reiniciar
+    Memory,
+    /// Un archivo con el último experimento del cliente anónimo
+    File,
+    /// Una base de datos SQLite con el último experimento de cada cliente
+    Sqlite,
+}
+
+/// Abre el almacenamiento configurado (ver `Config::store`)
+///
+/// # Argumentos
+///
+/// * `config` configuración del servidor, ya validada
+pub fn open(config: &Config) -> Result<Box<dyn DatasetStore>, StoreError> {
+    let path = || config.data_file.clone().ok_or("data_file no indicado");
+    Ok(match config.store() {
+        StoreKind::Memory => Box::new(MemoryStore),
+        StoreKind::File => Box::new(FileStore { path: path()? }),
+        StoreKind::Sqlite => Box::new(SqliteStore::open(&path()?)?),
+    })
+}
+
+/// Almacenamiento que no persiste nada: lo más rápido, pero los datos se
+/// pierden al reiniciar
+pub struct MemoryStore;
+
+impl DatasetStore for MemoryStore {
+    fn save(
+        &self,
+        _: &Client,
+        _: &Reproducibility,
+        _: &Metadata,
+        _: &Samples,
+    ) -> Result<(), StoreError> {
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Vec<(Client, Snapshot)>, StoreError> {
+        Ok(Vec::new())
+    }
+
+    fn get(&self, _: &Client) -> Result<Option<Snapshot>, StoreError> {
+        Ok(None)
+    }
+
+    fn remove(&self, _: &Client) -> Result<(), StoreError> {
+        Ok(())
+    }
+}
+
+/// Almacenamiento en un único archivo (ver `storage::save`). Como el
+/// archivo es uno solo, sólo se persisten los datos del cliente anónimo
+pub struct FileStore {
+    /// Ruta del archivo
+    path: PathBuf,
+}
+
+impl DatasetStore for FileStore {
+    fn save(
+        &self,
+        client: &Client,
+        manifest: &Reproducibility,
+        metadata: &Metadata,
+        data: &Samples,
+    ) -> Result<(), StoreError> {
+        if client.is_anonymous() {
+            storage::save(&self.path, manifest, metadata, data)?;
+        }
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Vec<(Client, Snapshot)>, StoreError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(vec![(Client::anonymous(), storage::load(&self.path)?)])
+    }
+
+    fn get(&self, client: &Client) -> Result<Option<Snapshot>, StoreError> {
+        if !client.is_anonymous() || !self.path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(storage::load(&self.path)?))
+    }
+
+    fn remove(&self, client: &Client) -> Result<(), StoreError> {
+        if client.is_anonymous() && self.path.exists() {
+            std::fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Almacenamiento en una base de datos SQLite, con el último experimento de
+/// cada cliente. Los valores se guardan en filas de hasta `CHUNK_LEN`
+/// valores, en f64, para no armar nunca un único BLOB con todo el conjunto
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// Abre (o crea) la base de datos. Los experimentos guardados por
+    /// versiones anteriores, en un único BLOB, se pasan a filas por bloque
+    ///
+    /// # Argumentos
+    ///
+    /// * `path` ruta del archivo SQLite
+    pub fn open(path: &Path) -> Result<Self, StoreError> {
+        let mut conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS experiments (
+                client TEXT PRIMARY KEY,
+                timestamp INTEGER NOT NULL,
+                seed INTEGER NOT NULL,
+                distribution TEXT NOT NULL,
+                length INTEGER NOT NULL,
+                manifest TEXT,
+                metadata TEXT
+            );
+            CREATE TABLE IF NOT EXISTS experiment_chunks (
+                client TEXT NOT NULL,
+                idx INTEGER NOT NULL,
+                vals BLOB NOT NULL,
+                PRIMARY KEY (client, idx)
+            );",
+        )?;
+        migrate(&mut conn)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+}
+
+impl DatasetStore for SqliteStore {
+    fn save(
+        &self,
+        client: &Client,
+        manifest: &Reproducibility,
+        metadata: &Metadata,
+        data: &Samples,
+    ) -> Result<(), StoreError> {
+        let distribution = manifest.mode.tested_distribution(&manifest.distribution);
+        let distribution = serde_json::to_string(&distribution)?;
+        let (manifest_json, metadata) = storage::encode_manifest(manifest, metadata)?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let mut conn = self.conn.lock().unwrap();
+        // Todo en una transacción, para que al reiniciar no se lea un
+        // experimento a medio escribir
+        let tx = conn.transaction()?;
+        remove_rows(&tx, client)?;
+        tx.execute(
+            "INSERT INTO experiments
+             (client, timestamp, seed, distribution, length, manifest, metadata)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                client.0,
+                timestamp as i64,
+                manifest.seed as i64,
+                distribution,
+                data.len() as i64,
+                manifest_json,
+                metadata
+            ],
+        )?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO experiment_chunks (client, idx, vals) VALUES (?1, ?2, ?3)",
+            )?;
+            // Se serializa un bloque por vez
+            let mut chunk = Vec::with_capacity(CHUNK_LEN.min(data.len()) * 8);
+            let mut idx = 0i64;
+            for x in data.values_f64() {
+                chunk.extend_from_slice(&x.to_le_bytes());
+                if chunk.len() == CHUNK_LEN * 8 {
+                    insert.execute(params![client.0, idx, chunk])?;
+                    chunk.clear();
+                    idx += 1;
+                }
+            }
+            if !chunk.is_empty() {
+                insert.execute(params![client.0, idx, chunk])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Vec<(Client, Snapshot)>, StoreError> {
+        query_snapshots(&self.conn.lock().unwrap(), None)
+    }
+
+    fn get(&self, client: &Client) -> Result<Option<Snapshot>, StoreError> {
+        let snapshots = query_snapshots(&self.conn.lock().unwrap(), Some(client))?;
+        Ok(snapshots.into_iter().next().map(|(_, snapshot)| snapshot))
+    }
+
+    fn remove(&self, client: &Client) -> Result<(), StoreError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        remove_rows(&tx, client)?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+// Función privada, lee los experimentos guardados, de todos los clientes o
+// sólo del indicado
+fn query_snapshots(
+    conn: &Connection,
+    client: Option<&Client>,
+) -> Result<Vec<(Client, Snapshot)>, StoreError> {
+    let mut experiments = conn.prepare(
+        "SELECT client, seed, distribution, length, manifest, metadata
+         FROM experiments WHERE ?1 IS NULL OR client = ?1 ORDER BY client",
+    )?;
+    let rows = experiments.query_map(params![client.map(|c| &c.0)], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, i64>(3)?,
+            row.get::<_, Option<String>>(4)?,
+            row.get::<_, Option<String>>(5)?,
+        ))
+    })?;
+    let mut chunks =
+        conn.prepare("SELECT vals FROM experiment_chunks WHERE client = ?1 ORDER BY idx")?;
+    let mut res = Vec::new();
+    for row in rows {
+        let (client, seed, distribution, length, manifest, metadata) = row?;
+        let mut data = Vec::with_capacity(length as usize);
+        let mut rows = chunks.query(params![client])?;
+        while let Some(row) = rows.next()? {
+            let vals = row.get_ref(0)?.as_blob()?;
+            if vals.len() % 8 != 0 {
+                return Err(format!("bloque inválido en los datos de {}", client).into());
+            }
+            let values = vals.chunks_exact(8).map(|b| b.try_into().unwrap());
+            data.extend(values.map(f64::from_le_bytes));
+        }
+        if data.len() != length as usize {
+            return Err(format!("faltan valores en los datos de {}", client).into());
+        }
+        let snapshot = match (manifest, metadata) {
+            (Some(manifest), Some(metadata)) => {
+                let (manifest, metadata) = storage::decode_manifest(&manifest, &metadata)?;
+                Snapshot::new(manifest, metadata, data)
+            }
+            // Experimento de una versión anterior, sin manifiesto
+            _ => Snapshot {
+                seed: seed as u64,
+                distribution: serde_json::from_str(&distribution)?,
+                manifest: None,
+                metadata: Metadata::default(),
+                data,
+            },
+        };
+        res.push((Client(client), snapshot));
+    }
+    Ok(res)
+}
+
+// Función privada, borra el experimento guardado del cliente
+fn remove_rows(conn: &Connection, client: &Client) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM experiments WHERE client = ?1", params![client.0])?;
+    conn.execute("DELETE FROM experiment_chunks WHERE client = ?1", params![client.0])?;
+    Ok(())
+}
+
+// Función privada, pasa los experimentos de la tabla `datasets` de las
+// versiones anteriores, con todo el experimento en un BLOB (ver
+// `storage::encode`), a las tablas por bloque, y la borra
+fn migrate(conn: &mut Connection) -> Result<(), StoreError> {
+    let legacy: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'datasets')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !legacy {
+        return Ok(());
+    }
+    let tx = conn.transaction()?;
+    let snapshots = {
+        let mut stmt = tx.prepare("SELECT client, timestamp, snapshot FROM datasets")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, Vec<u8>>(2)?))
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()?
+    };
+    for (client, timestamp, snapshot) in snapshots {
+        let snapshot = storage::decode(&snapshot)?;
+        let client = Client(client);
+        remove_rows(&tx, &client)?;
+        tx.execute(
+            "INSERT INTO experiments (client, timestamp, seed, distribution, length)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                client.0,
+                timestamp,
+                snapshot.seed as i64,
+                serde_json::to_string(&snapshot.distribution)?,
+                snapshot.data.len() as i64
+            ],
+        )?;
+        for (idx, chunk) in snapshot.data.chunks(CHUNK_LEN).enumerate() {
+            let vals: Vec<u8> = chunk.iter().flat_map(|x| x.to_le_bytes()).collect();
+            tx.execute(
+                "INSERT INTO experiment_chunks (client, idx, vals) VALUES (?1, ?2, ?3)",
+                params![client.0, idx as i64, vals],
+            )?;
+        }
+    }
+    tx.execute_batch("DROP TABLE datasets")?;
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasets::Precision;
+    use crate::storage::{
+        Bounds, BoundsMethod, Experiment, GenerationMode, Generator, StoredDistribution,
+    };
+    use simrng::dist::normal::{Algorithm, Normal};
+    use simrng::dist::uniform::Uniform;
+
+    // Función privada, ruta única en el directorio temporal para un test
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("simrng-store-{}-{}", std::process::id(), name))
+    }
+
+    // Función privada, distribución de los experimentos de los tests
+    fn uniform() -> StoredDistribution {
+        StoredDistribution::Uniform(Uniform { lower: 0f64, upper: 1f64 })
+    }
+
+    // Función privada, manifiesto de una generación Uniforme(0, 1)
+    fn manifest(seed: u64, bounds: Option<Bounds>) -> Reproducibility {
+        let experiment = Experiment {
+            seed,
+            distribution: uniform(),
+            mode: GenerationMode::Independent,
+            discard: 0,
+            generator: Generator::default(),
+            decimals: None,
+            bounds,
+        };
+        Reproducibility::new(&experiment, 1, Precision::F64)
+    }
+
+    // Función privada, verifica que un experimento leído sea el guardado
+    fn check_snapshot(snapshot: &Snapshot, seed: u64, data: &[f64]) {
+        assert_eq!(snapshot.seed, seed);
+        assert_eq!(snapshot.data, data);
+        assert!(matches!(
+            snapshot.distribution,
+            StoredDistribution::Uniform(Uniform { lower, upper }) if lower == 0f64 && upper == 1f64
+        ));
+    }
+
+    #[test]
+    fn test_file_store() {
+        let path = temp_path("file.bin");
+        let store = FileStore { path: path.clone() };
+        let data: Vec<f64> = (0..1000).map(|i| i as f64 / 1000f64).collect();
+        let (anonymous, client) = (Client::anonymous(), Client("a".into()));
+        let bounds = Bounds { min: Some(0.2), max: Some(0.5), method: BoundsMethod::Reject };
+        let metadata = Metadata { label: Some("x".into()), ..Metadata::default() };
+        let samples = Samples::from(data.clone());
+        store.save(&anonymous, &manifest(7, Some(bounds)), &metadata, &samples).unwrap();
+        // Sólo se persisten los datos del cliente anónimo
+        let other = Samples::from(vec![1f64]);
+        store.save(&client, &manifest(8, None), &Metadata::default(), &other).unwrap();
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded[0].0.is_anonymous());
+        let snapshot = &loaded[0].1;
+        check_snapshot(snapshot, 7, &data);
+        assert_eq!(snapshot.metadata, metadata);
+        assert_eq!(snapshot.manifest.as_ref().unwrap().bounds, Some(bounds));
+        // Se prueba contra la distribución truncada, no contra la Uniforme(0, 1)
+        assert!((snapshot.tested().cdf(0.35) - 0.5).abs() < 1e-12);
+        check_snapshot(&store.get(&anonymous).unwrap().unwrap(), 7, &data);
+        assert!(store.get(&client).unwrap().is_none());
+        store.remove(&anonymous).unwrap();
+        assert!(store.load().unwrap().is_empty());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_file_store_normal() {
+        // Los parámetros alternativos de la Normal (sd o variance) no se
+        // pueden leer con bincode, el manifiesto va en JSON
+        let path = temp_path("normal.bin");
+        let normal = Normal { algorithm: Algorithm::BoxMuller, mean: 1f64, sd: 2f64 };
+        let experiment = Experiment {
+            seed: 9,
+            distribution: StoredDistribution::Normal(normal),
+            mode: GenerationMode::Independent,
+            discard: 0,
+            generator: Generator::default(),
+            decimals: Some(1),
+            bounds: None,
+        };
+        let manifest = Reproducibility::new(&experiment, 2, Precision::F64);
+        let samples = Samples::from(vec![0.5f64, 1.5]);
+        FileStore { path: path.clone() }
+            .save(&Client::anonymous(), &manifest, &Metadata::default(), &samples)
+            .unwrap();
+        let snapshot = storage::load(&path).unwrap();
+        assert_eq!(snapshot.seed, 9);
+        assert_eq!(snapshot.data, [0.5, 1.5]);
+        assert_eq!(snapshot.manifest.as_ref().unwrap().decimals, Some(1));
+        let sd = match snapshot.distribution {
+            StoredDistribution::Normal(normal) => normal.sd,
+            _ => 0f64,
+        };
+        assert_eq!(sd, 2f64);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_file_store_legacy() {
+        // Archivo de una versión anterior, sin manifiesto ni metadatos
+        let path = temp_path("legacy-file.bin");
+        let data = vec![0.1f64, 0.2, 0.3];
+        std::fs::write(&path, bincode::serialize(&(4u64, uniform(), data.clone())).unwrap())
+            .unwrap();
+        let snapshot = storage::load(&path).unwrap();
+        check_snapshot(&snapshot, 4, &data);
+        assert!(snapshot.manifest.is_none());
+        assert!((snapshot.tested().cdf(0.35) - 0.35).abs() < 1e-12);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_sqlite_store() {
+        let path = temp_path("sqlite.db");
+        let _ = std::fs::remove_file(&path);
+        let store = SqliteStore::open(&path).unwrap();
+        // Más de un bloque, para que los valores ocupen varias filas
+        let data: Vec<f64> = (0..CHUNK_LEN + 3).map(|i| i as f64).collect();
+        let (anonymous, client) = (Client::anonymous(), Client("a".into()));
+        let metadata = Metadata::default();
+        store.save(&anonymous, &manifest(1, None), &metadata, &Samples::from(vec![0.5])).unwrap();
+        store.save(&client, &manifest(2, None), &metadata, &Samples::from(vec![0.25])).unwrap();
+        store.save(&client, &manifest(3, None), &metadata, &Samples::from(data.clone())).unwrap();
+        let chunks: i64 = store
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM experiment_chunks WHERE client = 'a'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(chunks, 2);
+        drop(store);
+        // Los datos se recuperan al volver a abrir la base
+        let store = SqliteStore::open(&path).unwrap();
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 2);
+        check_snapshot(&loaded[0].1, 1, &[0.5]);
+        assert_eq!(loaded[1].0 .0, "a");
+        check_snapshot(&loaded[1].1, 3, &data);
+        assert_eq!(loaded[1].1.manifest.as_ref().unwrap().seed, 3);
+        // El experimento de un cliente se lee sin leer los de los demás
+        check_snapshot(&store.get(&client).unwrap().unwrap(), 3, &data);
+        assert!(store.get(&Client("b".into())).unwrap().is_none());
+        store.remove(&client).unwrap();
+        assert!(store.get(&client).unwrap().is_none());
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded[0].0.is_anonymous());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_sqlite_migration() {
+        // Base de una versión anterior, con el experimento en un BLOB
+        let path = temp_path("legacy.db");
+        let _ = std::fs::remove_file(&path);
+        let data = vec![0.1f64, 0.2, 0.3];
+        let snapshot = bincode::serialize(&(4u64, uniform(), data.clone())).unwrap();
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE datasets (
+                client TEXT PRIMARY KEY,
+                timestamp INTEGER NOT NULL,
+                snapshot BLOB NOT NULL
+            );",
+        )
+        .unwrap();
+        conn.execute("INSERT INTO datasets VALUES ('', 0, ?1)", params![snapshot]).unwrap();
+        drop(conn);
+        let store = SqliteStore::open(&path).unwrap();
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        check_snapshot(&loaded[0].1, 4, &data);
+        assert!(loaded[0].1.manifest.is_none());
+        drop(store);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_persistence_order() {
+        let path = temp_path("order.bin");
+        let store = Persistence::new(Box::new(FileStore { path: path.clone() }));
+        let (client, metadata) = (Client::anonymous(), Metadata::default());
+        let save = |ticket, seed, x: f64| {
+            store.save(ticket, &client, &manifest(seed, None), &metadata, &Samples::from(vec![x]))
+        };
+        let (old, new) = (store.ticket(&client), store.ticket(&client));
+        save(new, 2, 0.2).unwrap();
+        // Un guardado pedido antes que otro ya escrito se descarta
+        save(old, 1, 0.1).unwrap();
+        check_snapshot(&store.load().unwrap()[0].1, 2, &[0.2]);
+        // Un guardado pedido antes de borrar no vuelve a crear el archivo
+        let pending = store.ticket(&client);
+        store.remove(&client).unwrap();
+        save(pending, 3, 0.3).unwrap();
+        assert!(!path.exists());
+        save(store.ticket(&client), 4, 0.4).unwrap();
+        check_snapshot(&store.load().unwrap()[0].1, 4, &[0.4]);
+        store.remove(&client).unwrap();
+    }
+}
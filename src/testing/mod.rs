@@ -0,0 +1,181 @@
+//! Secuencias fijas de cada distribución con cada generador, para verificar
+//! que los cambios en la generación (por ejemplo, caminos SIMD o en
+//! paralelo) mantengan los valores idénticos bit a bit. Se compila con el
+//! feature `testing` y en las pruebas de la librería
+
+use crate::dist::exponential::Exponential;
+use crate::dist::gamma::Gamma;
+use crate::dist::normal::{Algorithm, Normal};
+use crate::dist::poisson::Poisson;
+use crate::dist::table::TabulatedCdf;
+use crate::dist::uniform::Uniform;
+use crate::rng::{fill_chunked, LinearCongruentialGenerator, Random, UniformGenerator};
+use crate::rng::PARALLEL_THRESHOLD;
+
+/// Semilla de todas las secuencias
+pub const FIXTURE_SEED: u64 = 42;
+
+/// Cantidad de valores de cada secuencia, salvo la generada por bloques
+pub const FIXTURE_LEN: usize = 256;
+
+/// Cantidad de valores de la secuencia generada por bloques, que supera el
+/// umbral para usar un subflujo por bloque
+pub const CHUNKED_FIXTURE_LEN: usize = PARALLEL_THRESHOLD + 1000;
+
+/// Huella de cada secuencia, por nombre, calculada con `fingerprint`. Si
+/// un cambio la modifica a propósito, la generación ya no es reproducible
+/// con la misma semilla y hay que actualizar la versión. Las funciones
+/// matemáticas de std pueden diferir en el último bit entre plataformas;
+/// las huellas son las de x86_64 Linux
+pub const FINGERPRINTS: &[(&str, u64)] = &[
+    ("uniform/xoshiro", 0xc14c1120379bf822),
+    ("normal-box-muller/xoshiro", 0x62366dba23f130bd),
+    ("normal-convolution/xoshiro", 0xe79479df93cf42ad),
+    ("exponential/xoshiro", 0x05ce43f7a80c13eb),
+    ("poisson/xoshiro", 0xae8886fc166325d0),
+    ("poisson-large/xoshiro", 0xfa406cf523889215),
+    ("gamma/xoshiro", 0x6494bfb09dbca951),
+    ("gamma-small-shape/xoshiro", 0x8494f342ff475a99),
+    ("tabulated/xoshiro", 0x196cf49caf807caa),
+    ("uniform/lcg", 0xe6fca690ec2a29f0),
+    ("normal-box-muller/lcg", 0x10af5a59848d08fb),
+    ("normal-convolution/lcg", 0x380470d94ed0276d),
+    ("exponential/lcg", 0xb624315b6537e33d),
+    ("poisson/lcg", 0x5dd8c090742d97af),
+    ("poisson-large/lcg", 0x3f4b62d1ca2bb1e0),
+    ("gamma/lcg", 0xa82617b4ce690cd5),
+    ("gamma-small-shape/lcg", 0x587d3155376ec364),
+    ("tabulated/lcg", 0x3de4c6d83e3168c6),
+    ("uniform-chunked/xoshiro", 0xfcdc6a7575e4863d),
+];
+
+/// Secuencia fija de una distribución con un generador
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fixture {
+    /// Nombre de la secuencia, distribución/generador, por ejemplo
+    /// "normal-box-muller/lcg"
+    pub name: String,
+    /// Valores generados con `FIXTURE_SEED`, en orden
+    pub values: Vec<f64>,
+}
+
+/// Función que genera un valor de una distribución con el generador indicado
+type Sampler = Box<dyn FnMut(&mut dyn Random) -> f64>;
+
+// Función privada, distribuciones de las secuencias, por nombre, con
+// parámetros que recorren los distintos métodos de generación
+fn samplers() -> Vec<(&'static str, Sampler)> {
+    let uniform = Uniform::builder().lower(2f64).upper(5f64).build().unwrap();
+    let box_muller = Normal { algorithm: Algorithm::BoxMuller, mean: 10f64, sd: 2f64 };
+    let convolution = Normal { algorithm: Algorithm::Convolution, ..box_muller.clone() };
+    let exponential = Exponential::builder().lambda(1.5).build().unwrap();
+    let poisson = Poisson::builder().lambda(4f64).build().unwrap();
+    let poisson_large = Poisson::builder().lambda(800f64).build().unwrap();
+    let gamma = Gamma::builder().shape(2.5).scale(2f64).build().unwrap();
+    let gamma_small = Gamma::builder().shape(0.5).build().unwrap();
+    let table = TabulatedCdf::new(vec![(0f64, 0f64), (1f64, 0.3), (3f64, 1f64)]).unwrap();
+    let mut box_muller = box_muller.sampler();
+    let mut convolution = convolution.sampler();
+    let mut gamma = gamma.sampler();
+    let mut gamma_small = gamma_small.sampler();
+    vec![
+        ("uniform", Box::new(move |rng| uniform.next(rng))),
+        ("normal-box-muller", Box::new(move |rng| box_muller.next(rng))),
+        ("normal-convolution", Box::new(move |rng| convolution.next(rng))),
+        ("exponential", Box::new(move |rng| exponential.next(rng))),
+        ("poisson", Box::new(move |rng| poisson.next(rng))),
+        ("poisson-large", Box::new(move |rng| poisson_large.next(rng))),
+        ("gamma", Box::new(move |rng| gamma.next(rng))),
+        ("gamma-small-shape", Box::new(move |rng| gamma_small.next(rng))),
+        ("tabulated", Box::new(move |rng| table.next(rng))),
+    ]
+}
+
+/// Genera todas las secuencias fijas: cada distribución con xoshiro256++ y
+/// con el congruencial de `LinearCongruentialGenerator::with_seed`, y la
+/// Uniforme en [0, 1) generada por bloques con `rng::fill_chunked`
+pub fn fixtures() -> Vec<Fixture> {
+    let mut res = Vec::new();
+    for generator in ["xoshiro", "lcg"] {
+        for (name, mut sampler) in samplers() {
+            let mut rng: Box<dyn Random> = match generator {
+                "xoshiro" => Box::new(UniformGenerator::with_seed(FIXTURE_SEED)),
+                _ => Box::new(LinearCongruentialGenerator::with_seed(FIXTURE_SEED)),
+            };
+            let values = (0..FIXTURE_LEN).map(|_| sampler(rng.as_mut())).collect();
+            res.push(Fixture { name: format!("{}/{}", name, generator), values });
+        }
+    }
+    let mut values = vec![0f64; CHUNKED_FIXTURE_LEN];
+    fill_chunked(FIXTURE_SEED, &mut values, |rng: &mut UniformGenerator, chunk: &mut [f64]| {
+        chunk.iter_mut().for_each(|x| *x = rng.next())
+    });
+    res.push(Fixture { name: "uniform-chunked/xoshiro".into(), values });
+    res
+}
+
+/// Huella de una secuencia: FNV-1a de 64 bits de los bits de cada valor, de
+/// modo que cualquier diferencia, aunque sea en el último bit, la cambia
+///
+/// # Argumentos
+///
+/// * `values` valores de la secuencia
+pub fn fingerprint(values: &[f64]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in values.iter().flat_map(|x| x.to_bits().to_le_bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Verifica que todas las secuencias fijas tengan la huella registrada en
+/// `FINGERPRINTS`. Devuelve un error con las secuencias que cambiaron
+pub fn check_fixtures() -> Result<(), String> {
+    let changed: Vec<String> = fixtures()
+        .into_iter()
+        .filter_map(|fixture| {
+            let expected = FINGERPRINTS.iter().find(|(name, _)| *name == fixture.name);
+            let actual = fingerprint(&fixture.values);
+            match expected {
+                Some(&(_, expected)) if expected == actual => None,
+                Some(&(_, expected)) => Some(format!(
+                    "{}: {:#018x} en lugar de {:#018x}",
+                    fixture.name, actual, expected
+                )),
+                None => Some(format!("{}: sin huella registrada ({:#018x})", fixture.name, actual)),
+            }
+        })
+        .collect();
+    if changed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("cambiaron las secuencias fijas:\n{}", changed.join("\n")))
+    }
+}
+
+/// Verifica que dos secuencias sean idénticas bit a bit, indicando la
+/// primera posición distinta
+///
+/// # Panics
+///
+/// Si las longitudes o algún valor difieren, comparando los bits (NaN es
+/// igual a sí mismo y 0 distinto de -0)
+///
+/// # Argumentos
+///
+/// * `expected` valores esperados
+/// * `actual` valores obtenidos
+pub fn assert_bit_identical(expected: &[f64], actual: &[f64]) {
+    assert_eq!(expected.len(), actual.len(), "las secuencias tienen distinta longitud");
+    if let Some(i) = (0..expected.len()).find(|&i| expected[i].to_bits() != actual[i].to_bits()) {
+        panic!(
+            "las secuencias difieren en la posición {}: {:?} ({:#018x}) y {:?} ({:#018x})",
+            i,
+            expected[i],
+            expected[i].to_bits(),
+            actual[i],
+            actual[i].to_bits()
+        );
+    }
+}
@@ -0,0 +1,350 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Cantidad de spans terminados que se guardan para `/last-run`
+pub const RECENT_SPANS: usize = 50;
+
+/// Nombres de los spans que se guardan: la generación de valores y el
+/// cálculo de estadísticas
+pub const RECORDED_SPANS: [&str; 2] = ["generation", "statistics"];
+
+/// Avance, en puntos porcentuales, entre dos eventos de progreso de una
+/// generación
+pub const PROGRESS_STEP: f64 = 10f64;
+
+/// Tiempo máximo entre dos eventos de progreso de una generación
+pub const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Span terminado, con sus campos, su duración y la memoria que ocupó
+#[derive(Serialize, Clone)]
+pub struct SpanRecord {
+    /// Nombre del span, "generation" o "statistics"
+    pub name: &'static str,
+    /// Campos registrados en el span, por ejemplo la distribución y n
+    pub fields: BTreeMap<&'static str, Value>,
+    /// Momento en que empezó, en milisegundos desde 1970
+    pub started_at: u64,
+    /// Duración, desde que se creó hasta que se cerró, en milisegundos
+    pub duration_ms: f64,
+    /// Diferencia de la memoria residente del proceso entre el cierre y el
+    /// inicio, en bytes. Incluye lo que reservaron otras peticiones al mismo
+    /// tiempo; None si no se puede leer (fuera de Linux)
+    pub memory_delta: Option<i64>,
+}
+
+/// Avance de una generación según su último evento de progreso
+#[derive(Serialize, Clone, Default)]
+pub struct ProgressReport {
+    /// Cantidad de valores generados, incluidos los que se descartan
+    pub done: u64,
+    /// Cantidad total de valores a generar
+    pub total: u64,
+    /// Porcentaje generado
+    pub percent: f64,
+    /// Valores generados por segundo desde el inicio
+    pub rate: f64,
+    /// Segundos que faltan para terminar a ese ritmo
+    pub eta_seconds: f64,
+    /// Momento del evento, en milisegundos desde 1970. Si no cambia en
+    /// mucho más que `PROGRESS_INTERVAL`, la generación está detenida
+    pub updated_at: u64,
+}
+
+/// Span todavía abierto, con su último evento de progreso
+#[derive(Serialize, Clone)]
+pub struct ActiveSpan {
+    /// Nombre del span, "generation" o "statistics"
+    pub name: &'static str,
+    /// Campos registrados en el span, por ejemplo la distribución y n
+    pub fields: BTreeMap<&'static str, Value>,
+    /// Momento en que empezó, en milisegundos desde 1970
+    pub started_at: u64,
+    /// Tiempo transcurrido desde que empezó, en milisegundos
+    pub elapsed_ms: f64,
+    /// Último avance informado, None si todavía no hubo eventos de progreso
+    pub progress: Option<ProgressReport>,
+}
+
+/// Últimos spans terminados y spans abiertos, compartidos entre la capa de
+/// tracing que los registra y los handlers que los devuelven
+#[derive(Default)]
+pub struct SpanLog {
+    spans: Mutex<VecDeque<SpanRecord>>,
+    active: Mutex<BTreeMap<u64, (Instant, ActiveSpan)>>,
+}
+
+impl SpanLog {
+    /// Spans guardados, el más reciente primero
+    pub fn recent(&self) -> Vec<SpanRecord> {
+        self.spans.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    /// Spans abiertos, el más antiguo primero
+    pub fn active(&self) -> Vec<ActiveSpan> {
+        let mut active: Vec<(Instant, ActiveSpan)> =
+            self.active.lock().unwrap().values().cloned().collect();
+        active.sort_by_key(|(start, _)| *start);
+        active
+            .into_iter()
+            .map(|(start, span)| ActiveSpan {
+                elapsed_ms: start.elapsed().as_secs_f64() * 1000f64,
+                ..span
+            })
+            .collect()
+    }
+
+    // Función privada, agrega un span quitando el más antiguo si no hay lugar
+    fn push(&self, record: SpanRecord) {
+        let mut spans = self.spans.lock().unwrap();
+        if spans.len() == RECENT_SPANS {
+            spans.pop_front();
+        }
+        spans.push_back(record);
+    }
+}
+
+/// Capa de tracing que mide los spans de `RECORDED_SPANS` y los guarda en
+/// un `SpanLog` al cerrarse
+pub struct RecentSpans {
+    log: Arc<SpanLog>,
+}
+
+impl RecentSpans {
+    /// Crea la capa, que guarda los spans en el registro indicado
+    ///
+    /// # Argumentos
+    ///
+    /// * `log` registro de los spans terminados
+    pub fn new(log: Arc<SpanLog>) -> Self {
+        RecentSpans { log }
+    }
+}
+
+/// Estado de un span abierto, guardado en sus extensiones
+struct Timing {
+    fields: BTreeMap<&'static str, Value>,
+    started_at: u64,
+    start: Instant,
+    memory: Option<i64>,
+}
+
+/// Visitante que copia los campos de un span; los números y booleanos se
+/// mantienen, el resto se guarda como texto
+struct FieldVisitor<'a>(&'a mut BTreeMap<&'static str, Value>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name(), format!("{:?}", value).into());
+    }
+}
+
+impl<S> Layer<S> for RecentSpans
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !RECORDED_SPANS.contains(&attrs.metadata().name()) {
+            return;
+        }
+        let Some(span) = ctx.span(id) else { return };
+        let mut fields = BTreeMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        let (started_at, start) = (now_millis(), Instant::now());
+        let name = span.name();
+        let active = ActiveSpan {
+            name,
+            fields: fields.clone(),
+            started_at,
+            elapsed_ms: 0f64,
+            progress: None,
+        };
+        self.log.active.lock().unwrap().insert(id.into_u64(), (start, active));
+        span.extensions_mut().insert(Timing {
+            fields,
+            started_at,
+            start,
+            memory: resident_memory(),
+        });
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.event_span(event) else { return };
+        let mut report = ProgressReport::default();
+        let mut visitor = ProgressVisitor { report: &mut report, found: false };
+        event.record(&mut visitor);
+        if !visitor.found {
+            return;
+        }
+        report.updated_at = now_millis();
+        let mut active = self.log.active.lock().unwrap();
+        if let Some((_, active)) = active.get_mut(&span.id().into_u64()) {
+            active.progress = Some(report);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut extensions = span.extensions_mut();
+        if let Some(timing) = extensions.get_mut::<Timing>() {
+            values.record(&mut FieldVisitor(&mut timing.fields));
+            if let Some((_, active)) = self.log.active.lock().unwrap().get_mut(&id.into_u64()) {
+                active.fields = timing.fields.clone();
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let Some(timing) = span.extensions_mut().remove::<Timing>() else { return };
+        self.log.active.lock().unwrap().remove(&id.into_u64());
+        let memory_delta = timing.memory.zip(resident_memory()).map(|(start, end)| end - start);
+        self.log.push(SpanRecord {
+            name: span.name(),
+            fields: timing.fields,
+            started_at: timing.started_at,
+            duration_ms: timing.start.elapsed().as_secs_f64() * 1000f64,
+            memory_delta,
+        });
+    }
+}
+
+/// Visitante que lee los campos de un evento de progreso (ver `Progress`)
+struct ProgressVisitor<'a> {
+    report: &'a mut ProgressReport,
+    /// Si el evento tiene los campos de un evento de progreso
+    found: bool,
+}
+
+impl Visit for ProgressVisitor<'_> {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            "done" => self.report.done = value,
+            "total" => {
+                self.report.total = value;
+                self.found = true;
+            }
+            _ => {}
+        }
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        match field.name() {
+            "percent" => self.report.percent = value,
+            "rate" => self.report.rate = value,
+            "eta_seconds" => self.report.eta_seconds = value,
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, _: &Field, _: &dyn fmt::Debug) {}
+}
+
+/// Avance de una generación por bloques. Emite un evento de tracing de
+/// nivel info en el span que estaba activo al crearlo, con la cantidad de valores
+/// generados, el porcentaje, el ritmo y el tiempo estimado para terminar,
+/// cada `PROGRESS_STEP` puntos porcentuales o cada `PROGRESS_INTERVAL`, lo
+/// que ocurra primero. `RecentSpans` guarda el último evento de cada span
+/// abierto para `/last-run/active`
+pub struct Progress {
+    span: tracing::Span,
+    total: u64,
+    done: AtomicU64,
+    start: Instant,
+    /// Momento y porcentaje del último evento
+    last: Mutex<(Instant, f64)>,
+}
+
+impl Progress {
+    /// Crea el avance de una generación en el span actual
+    ///
+    /// # Argumentos
+    ///
+    /// * `total` cantidad de valores a generar
+    pub fn new(total: u64) -> Self {
+        let start = Instant::now();
+        Progress {
+            span: tracing::Span::current(),
+            total,
+            done: AtomicU64::new(0),
+            start,
+            last: Mutex::new((start, 0f64)),
+        }
+    }
+
+    /// Suma valores generados, emitiendo un evento si corresponde. Una
+    /// generación que termina en un solo paso no emite eventos. Se puede
+    /// llamar desde varios hilos a la vez
+    ///
+    /// # Argumentos
+    ///
+    /// * `values` cantidad de valores que se terminaron de generar
+    pub fn advance(&self, values: u64) {
+        let done = self.done.fetch_add(values, Ordering::Relaxed) + values;
+        if done == values && done >= self.total {
+            return;
+        }
+        let percent = done as f64 * 100f64 / self.total.max(1) as f64;
+        let mut last = self.last.lock().unwrap();
+        if percent < last.1 + PROGRESS_STEP && last.0.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        *last = (Instant::now(), percent);
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let rate = if elapsed > 0f64 { done as f64 / elapsed } else { 0f64 };
+        let remaining = self.total.saturating_sub(done) as f64;
+        let eta_seconds = if rate > 0f64 { remaining / rate } else { 0f64 };
+        tracing::info!(
+            parent: &self.span,
+            done,
+            total = self.total,
+            percent,
+            rate,
+            eta_seconds,
+            "generation progress"
+        );
+    }
+}
+
+// Función privada, momento actual en milisegundos desde 1970
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+// Función privada, memoria residente del proceso en bytes, leída de
+// /proc/self/status. None si no existe
+fn resident_memory() -> Option<i64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: i64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
@@ -0,0 +1,399 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::chunked::{self, SampleChunks};
+use crate::dist::{Distribution, DistributionLimits};
+use crate::float::Float;
+use crate::stats::descriptive::summary;
+use crate::stats::{expected_from_cdf, Truncated};
+
+/// Operación que se aplica a cada valor de una muestra para derivar otra
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+    /// factor · x + shift. Los que no se indican dejan el valor igual
+    Scale {
+        #[serde(default = "unit_factor")]
+        factor: f64,
+        #[serde(default)]
+        shift: f64,
+    },
+    /// Logaritmo natural, para muestras de valores positivos
+    Log,
+    /// Exponencial, eˣ
+    Exp,
+    /// Valor absoluto
+    Abs,
+    /// Puntajes z, (x - media) / desvío, con la media y el desvío de la
+    /// muestra (ver `Operation::resolve`)
+    Standardize,
+    /// Lleva los valores menores a lower a lower, y los mayores a upper a
+    /// upper. Sin uno de los límites, ese lado no se recorta
+    Clip {
+        #[serde(default)]
+        lower: Option<f64>,
+        #[serde(default)]
+        upper: Option<f64>,
+    },
+    /// Redondea a la cantidad de decimales indicada, como los valores de
+    /// las tablas de los libros, con 4 decimales
+    Round { decimals: u8 },
+}
+
+/// Mayor cantidad de decimales de Operation::Round: con más, el redondeo
+/// no cambia los valores de f64
+pub const MAX_DECIMALS: u8 = 15;
+
+// Función privada, factor por defecto de Operation::Scale
+fn unit_factor() -> f64 {
+    1f64
+}
+
+impl Operation {
+    /// Verifica los parámetros: factor y shift finitos, con factor distinto
+    /// de 0, y límites finitos y en orden, con al menos uno
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Operation::Scale { factor, shift } => {
+                if !factor.is_finite() || !shift.is_finite() {
+                    return Err("factor y shift deben ser finitos".into());
+                }
+                if factor == 0f64 {
+                    return Err("factor no puede ser 0".into());
+                }
+                Ok(())
+            }
+            Operation::Clip { lower: None, upper: None } => {
+                Err("debe indicarse lower, upper o ambos".into())
+            }
+            Operation::Clip { lower, upper } => {
+                if lower.is_some_and(|x| !x.is_finite()) || upper.is_some_and(|x| !x.is_finite())
+                {
+                    return Err("lower y upper deben ser finitos".into());
+                }
+                if let (Some(lower), Some(upper)) = (lower, upper) {
+                    if lower >= upper {
+                        return Err("lower debe ser menor a upper".into());
+                    }
+                }
+                Ok(())
+            }
+            Operation::Round { decimals } if decimals > MAX_DECIMALS => {
+                Err(format!("decimals debe ser a lo sumo {}", MAX_DECIMALS))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Devuelve la operación con los parámetros que dependen de la muestra
+    /// ya calculados: Standardize pasa a ser Scale con factor 1 / desvío y
+    /// shift -media / desvío. Las demás no cambian. Devuelve un error si
+    /// la muestra no tiene al menos 2 valores distintos para estandarizar
+    ///
+    /// # Argumentos
+    ///
+    /// * `nums` valores de la muestra
+    pub fn resolve<T: Float, S: SampleChunks<T> + ?Sized>(
+        self,
+        nums: &S,
+    ) -> Result<Operation, String> {
+        if self != Operation::Standardize {
+            return Ok(self);
+        }
+        let summary = summary(nums);
+        if summary.count < 2 || summary.variance <= 0f64 {
+            return Err("para estandarizar se necesitan al menos 2 valores distintos".into());
+        }
+        let sd = summary.variance.sqrt();
+        Ok(Operation::Scale { factor: 1f64 / sd, shift: -summary.mean / sd })
+    }
+
+    /// Aplica la operación a un valor. Standardize debe estar resuelta (ver
+    /// `Operation::resolve`); si no, deja el valor igual
+    ///
+    /// # Argumentos
+    ///
+    /// * `x` valor a transformar
+    pub fn apply(&self, x: f64) -> f64 {
+        match *self {
+            Operation::Scale { factor, shift } => factor * x + shift,
+            Operation::Log => x.ln(),
+            Operation::Exp => x.exp(),
+            Operation::Abs => x.abs(),
+            Operation::Standardize => x,
+            Operation::Clip { lower, upper } => {
+                let x = lower.map_or(x, |lower| x.max(lower));
+                upper.map_or(x, |upper| x.min(upper))
+            }
+            Operation::Round { decimals } => {
+                // Sumar 0 convierte en 0 el -0 de los valores negativos que
+                // redondean a 0
+                let factor = resolution(decimals);
+                (x * factor).round() / factor + 0f64
+            }
+        }
+    }
+}
+
+// Función privada, cantidad de valores redondeados por unidad, 10^decimals
+fn resolution(decimals: u8) -> f64 {
+    10f64.powi(decimals as i32)
+}
+
+/// Muestra derivada de otra por una operación
+pub struct Transformation {
+    /// Valores transformados, en el orden de la muestra original
+    pub values: Vec<f64>,
+    /// Operación aplicada, resuelta (ver `Operation::resolve`)
+    pub operation: Operation,
+}
+
+/// Aplica la operación a cada valor de la muestra, en orden. Devuelve un
+/// error si la operación no es válida o si algún valor transformado no es
+/// finito, como el logaritmo de un valor que no es positivo
+///
+/// # Argumentos
+///
+/// * `nums` valores de la muestra
+/// * `operation` operación a aplicar
+pub fn transform<T: Float, S: SampleChunks<T> + ?Sized>(
+    nums: &S,
+    operation: Operation,
+) -> Result<Transformation, String> {
+    operation.validate()?;
+    let operation = operation.resolve(nums)?;
+    let mut values = Vec::with_capacity(nums.len());
+    for x in chunked::values(nums) {
+        let x = x.to_f64();
+        let y = operation.apply(x);
+        if !y.is_finite() {
+            return Err(format!("la operación no da un valor finito para {}", x));
+        }
+        values.push(y);
+    }
+    Ok(Transformation { values, operation })
+}
+
+/// Distribución de una variable transformada por una operación resuelta
+/// (ver `Operation::resolve`), para probar la muestra derivada contra la
+/// distribución de la original: P(g(X) <= y) a partir de la función
+/// acumulada de X
+pub struct TransformedDistribution {
+    dist: Arc<Box<dyn Distribution + Send + Sync>>,
+    operation: Operation,
+}
+
+impl TransformedDistribution {
+    /// Crea la distribución de la variable transformada
+    ///
+    /// # Argumentos
+    ///
+    /// * `dist` distribución de la variable original
+    /// * `operation` operación resuelta aplicada a la variable
+    pub fn new(dist: Arc<Box<dyn Distribution + Send + Sync>>, operation: Operation) -> Self {
+        TransformedDistribution { dist, operation }
+    }
+}
+
+impl Distribution for TransformedDistribution {
+    fn get_expected(&self, intervals: usize, lower: f64, upper: f64) -> Vec<f64> {
+        expected_from_cdf(self, intervals, lower, upper)
+    }
+
+    fn get_degrees(&self, intervals: usize) -> usize {
+        self.dist.get_degrees(intervals)
+    }
+
+    fn get_intervals(&self, limits: DistributionLimits) -> DistributionLimits {
+        let Operation::Round { decimals } = self.operation else {
+            // Los límites que fija la distribución original no valen para la
+            // transformada: se usan los de la muestra
+            return limits;
+        };
+        let limits = self.dist.get_intervals(limits);
+        if self.dist.is_discrete() {
+            return limits;
+        }
+        // Los valores redondeados son múltiplos de 1 / factor: los límites
+        // caen a mitad de camino entre dos de ellos, y cada intervalo tiene
+        // la misma cantidad de valores posibles, para que ninguno caiga en
+        // un límite ni los intervalos cuenten distinta cantidad de valores
+        let factor = resolution(decimals);
+        let first = (limits.lower * factor).round();
+        let values = (limits.upper * factor).round() - first + 1f64;
+        let per_interval = (values / limits.intervals.max(1) as f64).ceil();
+        let intervals = (values / per_interval).ceil();
+        let lower = (first - 0.5) / factor;
+        DistributionLimits {
+            lower,
+            upper: lower + intervals * per_interval / factor,
+            intervals: intervals as usize,
+        }
+    }
+
+    fn intervals_reason(&self) -> Option<&'static str> {
+        match self.operation {
+            Operation::Round { .. } if self.dist.is_discrete() => self.dist.intervals_reason(),
+            Operation::Round { .. } => Some(
+                "los valores están redondeados: los límites caen entre dos valores posibles y \
+                 cada intervalo abarca la misma cantidad de ellos",
+            ),
+            _ => None,
+        }
+    }
+
+    fn quantile(&self, p: f64) -> f64 {
+        match self.operation {
+            Operation::Scale { factor, shift } if factor < 0f64 => {
+                factor * self.dist.quantile(1f64 - p) + shift
+            }
+            Operation::Abs => {
+                // |X| <= m con probabilidad p para algún m menor al mayor
+                // módulo de los cuantiles (1 - p) / 2 y (1 + p) / 2 de X
+                let left = self.dist.quantile((1f64 - p) / 2f64).abs();
+                let right = self.dist.quantile((1f64 + p) / 2f64).abs();
+                let (mut lower, mut upper) = (0f64, left.max(right));
+                for _ in 0..200 {
+                    let middle = (lower + upper) / 2f64;
+                    if self.cdf(middle) < p {
+                        lower = middle;
+                    } else {
+                        upper = middle;
+                    }
+                    if upper - lower <= 1e-14 * upper {
+                        break;
+                    }
+                }
+                (lower + upper) / 2f64
+            }
+            operation => operation.apply(self.dist.quantile(p)),
+        }
+    }
+
+    fn cdf(&self, y: f64) -> f64 {
+        match self.operation {
+            Operation::Scale { factor, shift } => {
+                let x = (y - shift) / factor;
+                if factor > 0f64 { self.dist.cdf(x) } else { 1f64 - self.dist.cdf(x.next_down()) }
+            }
+            Operation::Log => self.dist.cdf(y.exp()),
+            Operation::Exp if y <= 0f64 => 0f64,
+            Operation::Exp => self.dist.cdf(y.ln()),
+            Operation::Abs if y < 0f64 => 0f64,
+            Operation::Abs => self.dist.cdf(y) - self.dist.cdf((-y).next_down()),
+            Operation::Standardize => self.dist.cdf(y),
+            Operation::Round { decimals } => {
+                // El mayor valor redondeado que no supera y es k / factor, y
+                // redondean a él o a uno menor los valores menores a
+                // (k + 0.5) / factor
+                let factor = resolution(decimals);
+                let k = (y * factor + 1e-9).floor();
+                self.dist.cdf((k + 0.5) / factor)
+            }
+            Operation::Clip { lower, upper } => {
+                if lower.is_some_and(|lower| y < lower) {
+                    0f64
+                } else if upper.is_some_and(|upper| y >= upper) {
+                    1f64
+                } else {
+                    self.dist.cdf(y)
+                }
+            }
+        }
+    }
+
+    fn is_discrete(&self) -> bool {
+        self.dist.is_discrete()
+    }
+}
+
+/// Distribución de una variable truncada a un rango, la de los valores que
+/// quedan al descartar los que caen fuera y volver a generarlos:
+/// P(X <= x | lower <= X <= upper)
+pub struct TruncatedDistribution {
+    dist: Arc<Box<dyn Distribution + Send + Sync>>,
+    lower: Option<f64>,
+    upper: Option<f64>,
+}
+
+impl TruncatedDistribution {
+    /// Crea la distribución truncada
+    ///
+    /// # Argumentos
+    ///
+    /// * `dist` distribución de la variable sin truncar
+    /// * `lower` menor valor admitido, None para no truncar por debajo
+    /// * `upper` mayor valor admitido, None para no truncar por encima
+    pub fn new(
+        dist: Arc<Box<dyn Distribution + Send + Sync>>,
+        lower: Option<f64>,
+        upper: Option<f64>,
+    ) -> Self {
+        TruncatedDistribution { dist, lower, upper }
+    }
+
+    /// Probabilidad de que un valor de la distribución sin truncar caiga
+    /// dentro del rango, la proporción de valores que se aceptan
+    pub fn acceptance(&self) -> f64 {
+        let (below, above) = self.truncated().mass();
+        (above - below).max(0f64)
+    }
+
+    // Función privada, la distribución truncada de las estadísticas. El
+    // límite inferior se admite, por lo que la probabilidad que se quita es
+    // la de los valores menores, P(X < lower), que en las discretas no es
+    // P(X <= lower)
+    fn truncated(&self) -> Truncated<'_> {
+        Truncated {
+            dist: self.dist.as_ref().as_ref(),
+            lower: self.lower.map(f64::next_down),
+            upper: self.upper,
+        }
+    }
+}
+
+impl Distribution for TruncatedDistribution {
+    fn get_expected(&self, intervals: usize, lower: f64, upper: f64) -> Vec<f64> {
+        expected_from_cdf(self, intervals, lower, upper)
+    }
+
+    fn get_degrees(&self, intervals: usize) -> usize {
+        self.dist.get_degrees(intervals)
+    }
+
+    fn get_intervals(&self, limits: DistributionLimits) -> DistributionLimits {
+        let limits = self.dist.get_intervals(limits);
+        if self.dist.is_discrete() {
+            return limits;
+        }
+        // Los intervalos no pasan de los límites del rango, fuera de los que
+        // la probabilidad es 0
+        let lower = self.lower.map_or(limits.lower, |lower| limits.lower.max(lower));
+        let upper = self.upper.map_or(limits.upper, |upper| limits.upper.min(upper));
+        if lower >= upper {
+            return limits;
+        }
+        DistributionLimits { lower, upper, ..limits }
+    }
+
+    fn intervals_reason(&self) -> Option<&'static str> {
+        if self.dist.is_discrete() {
+            return self.dist.intervals_reason();
+        }
+        Some("los valores están truncados: los intervalos no pasan de min ni de max")
+    }
+
+    fn quantile(&self, p: f64) -> f64 {
+        self.truncated().quantile(p)
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        if self.lower.is_some_and(|lower| x < lower) {
+            return 0f64;
+        }
+        self.truncated().cdf(x)
+    }
+
+    fn is_discrete(&self) -> bool {
+        self.dist.is_discrete()
+    }
+}
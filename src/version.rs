@@ -0,0 +1,39 @@
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::HeaderValue;
+use axum::response::Response;
+use std::convert::Infallible;
+
+/// Versión de la API por la que llega la petición. Las rutas sin versión
+/// (`/api/...`) se mantienen por compatibilidad con el front end actual
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum ApiVersion {
+    /// Rutas sin versión, obsoletas
+    #[default]
+    Legacy,
+    /// Rutas bajo `/api/v1`
+    V1,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ApiVersion {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts.extensions.get::<ApiVersion>().copied().unwrap_or_default())
+    }
+}
+
+/// Marca las respuestas de las rutas sin versión como obsoletas, indicando
+/// que se debe usar `/api/v1`
+///
+/// # Argumentos
+///
+/// * `response` respuesta del handler
+pub async fn deprecated(mut response: Response) -> Response {
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    headers.insert("link", HeaderValue::from_static("</api/v1>; rel=\"successor-version\""));
+    response
+}